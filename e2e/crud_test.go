@@ -136,12 +136,12 @@ var _ = Describe("Apify CRUD Operations", func() {
 			Expect(err).NotTo(HaveOccurred())
 			defer resp.Body.Close()
 
-			Expect(resp.StatusCode).To(Equal(http.StatusOK))
+			Expect(resp.StatusCode).To(Equal(http.StatusCreated))
 
 			var result map[string]interface{}
 			err = json.NewDecoder(resp.Body).Decode(&result)
 			Expect(err).NotTo(HaveOccurred())
-			Expect(result["affected_rows"]).To(BeNumerically(">=", 1))
+			Expect(result["name"]).To(Equal("Test Item"))
 		})
 
 		It("should list the created item", func() {
@@ -258,7 +258,7 @@ var _ = Describe("Apify CRUD Operations", func() {
 			Expect(err).NotTo(HaveOccurred())
 			defer resp.Body.Close()
 
-			Expect(resp.StatusCode).To(Equal(http.StatusOK))
+			Expect(resp.StatusCode).To(Equal(http.StatusCreated))
 		})
 
 		It("should list multiple items", func() {
@@ -287,7 +287,7 @@ var _ = Describe("Apify CRUD Operations", func() {
 			Expect(err).NotTo(HaveOccurred())
 			defer resp.Body.Close()
 
-			Expect(resp.StatusCode).To(Equal(http.StatusOK))
+			Expect(resp.StatusCode).To(Equal(http.StatusNoContent))
 		})
 
 		It("should return 404 for deleted item", func() {
@@ -331,7 +331,7 @@ var _ = Describe("Apify CRUD Operations", func() {
 			Expect(err).NotTo(HaveOccurred())
 			defer resp.Body.Close()
 
-			Expect(resp.StatusCode).To(Equal(http.StatusOK))
+			Expect(resp.StatusCode).To(Equal(http.StatusCreated))
 		})
 	})
 
@@ -356,7 +356,7 @@ var _ = Describe("Apify CRUD Operations", func() {
 			Expect(err).NotTo(HaveOccurred())
 			defer resp.Body.Close()
 
-			Expect(resp.StatusCode).To(Equal(http.StatusOK))
+			Expect(resp.StatusCode).To(Equal(http.StatusCreated))
 		})
 	})
 })
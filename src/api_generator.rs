@@ -20,15 +20,62 @@ pub struct RoutePattern {
     pub methods: Vec<String>,
     pub operation_type: OperationType,
     pub table_name: String,
+    pub pagination: PaginationStyle,
+    /// Longest relation path accepted in `include`, from `x-include-max-depth`
+    pub include_max_depth: usize,
+    /// Status of a successful single-record delete, from `x-delete-status` (204 or 200)
+    pub delete_status: u16,
+}
+
+/// `include` depth allowed when neither the operation nor the spec sets `x-include-max-depth`
+pub const DEFAULT_INCLUDE_MAX_DEPTH: usize = 3;
+
+/// Deletes answer `204 No Content` unless `x-delete-status: 200` asks for a body
+pub const DEFAULT_DELETE_STATUS: u16 = 204;
+
+/// Shape of list responses, chosen with the `x-pagination` extension
+/// on the list operation or at the root of the API spec
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaginationStyle {
+    /// Bare JSON array (default)
+    #[default]
+    None,
+    /// `{data, total, limit, offset, next}` object
+    Envelope,
+    /// Bare JSON array with `X-Total-Count` and `Link` headers
+    Headers,
+    /// `{data, limit, next_cursor}` object, paged by keyset `cursor`
+    Cursor,
+}
+
+impl PaginationStyle {
+    fn from_extension(value: Option<&Value>) -> Option<Self> {
+        match value?.as_str()? {
+            "envelope" => Some(Self::Envelope),
+            "headers" => Some(Self::Headers),
+            "cursor" => Some(Self::Cursor),
+            "none" => Some(Self::None),
+            other => {
+                tracing::warn!("Unknown x-pagination style '{}', using none", other);
+                Some(Self::None)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum OperationType {
-    List,   // GET /table
-    Get,    // GET /table/{id}
-    Create, // POST /table
-    Update, // PUT /table/{id}
-    Delete, // DELETE /table/{id}
+    List,       // GET /table
+    Get,        // GET /table/{id}
+    Create,     // POST /table
+    Replace,    // PUT /table/{id}
+    Update,     // PATCH /table/{id}
+    Delete,     // DELETE /table/{id}
+    Upsert,     // PUT /table
+    BulkUpdate, // PATCH /table?filters
+    BulkDelete, // DELETE /table?filters
+    Restore,    // POST /table/{id}/restore (soft-delete tables)
+    Aggregate,  // GET /table/_aggregate
 }
 
 impl APIGenerator {
@@ -37,6 +84,10 @@ impl APIGenerator {
         schemas: Vec<TableSchema>,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let route_patterns = Self::build_route_patterns(&spec)?;
+        let mut spec = spec;
+        Self::describe_pagination(&mut spec, &route_patterns);
+        Self::describe_aggregates(&mut spec, &route_patterns, &schemas);
+        Self::describe_errors(&mut spec, &route_patterns);
 
         for pattern in &route_patterns {
             tracing::debug!(
@@ -88,6 +139,34 @@ impl APIGenerator {
                             let operation_type = Self::determine_operation_type(method, path);
                             let regex = Self::build_regex_from_openapi_path(path)?;
                             let param_names = Self::extract_param_names_from_openapi(path);
+                            let pagination =
+                                PaginationStyle::from_extension(op_obj.get("x-pagination"))
+                                    .or_else(|| {
+                                        PaginationStyle::from_extension(spec.get("x-pagination"))
+                                    })
+                                    .unwrap_or_default();
+                            let include_max_depth = op_obj
+                                .get("x-include-max-depth")
+                                .or_else(|| spec.get("x-include-max-depth"))
+                                .and_then(Value::as_u64)
+                                .map(|depth| depth as usize)
+                                .unwrap_or(DEFAULT_INCLUDE_MAX_DEPTH);
+                            let delete_status = match op_obj
+                                .get("x-delete-status")
+                                .or_else(|| spec.get("x-delete-status"))
+                                .and_then(Value::as_u64)
+                            {
+                                Some(status @ (200 | 204)) => status as u16,
+                                Some(other) => {
+                                    tracing::warn!(
+                                        "Unsupported x-delete-status {}, using {}",
+                                        other,
+                                        DEFAULT_DELETE_STATUS
+                                    );
+                                    DEFAULT_DELETE_STATUS
+                                }
+                                None => DEFAULT_DELETE_STATUS,
+                            };
 
                             patterns.push(RoutePattern {
                                 path_pattern: path.to_string(),
@@ -96,6 +175,9 @@ impl APIGenerator {
                                 methods: vec![method.to_uppercase()],
                                 operation_type,
                                 table_name,
+                                pagination,
+                                include_max_depth,
+                                delete_status,
                             });
                         }
                     }
@@ -106,6 +188,272 @@ impl APIGenerator {
         Ok(patterns)
     }
 
+    /// Document the paginated response shape of list operations in the served spec
+    fn describe_pagination(spec: &mut Value, patterns: &[RoutePattern]) {
+        for pattern in patterns {
+            if !matches!(pattern.operation_type, OperationType::List)
+                || pattern.pagination == PaginationStyle::None
+            {
+                continue;
+            }
+            let Some(ok) = spec
+                .pointer_mut(&format!(
+                    "/paths/{}/get/responses",
+                    pattern.path_pattern.replace('~', "~0").replace('/', "~1")
+                ))
+                .and_then(|r| r.as_object_mut())
+                .map(|r| {
+                    r.entry("200")
+                        .or_insert_with(|| serde_json::json!({"description": "OK"}))
+                })
+                .and_then(|r| r.as_object_mut())
+            else {
+                continue;
+            };
+
+            match pattern.pagination {
+                PaginationStyle::Envelope | PaginationStyle::Cursor => {
+                    let content = ok
+                        .entry("content")
+                        .or_insert_with(|| serde_json::json!({}))
+                        .as_object_mut();
+                    let Some(content) = content else { continue };
+                    let media = content
+                        .entry("application/json")
+                        .or_insert_with(|| serde_json::json!({}));
+                    let items = media
+                        .get("schema")
+                        .and_then(|s| s.get("items"))
+                        .cloned()
+                        .unwrap_or_else(|| serde_json::json!({"type": "object"}));
+                    media["schema"] = if pattern.pagination == PaginationStyle::Cursor {
+                        serde_json::json!({
+                            "type": "object",
+                            "properties": {
+                                "data": { "type": "array", "items": items },
+                                "limit": { "type": "integer", "nullable": true },
+                                "next_cursor": { "type": "string", "nullable": true, "description": "Value of the `cursor` parameter for the next page" }
+                            },
+                            "required": ["data", "next_cursor"]
+                        })
+                    } else {
+                        serde_json::json!({
+                            "type": "object",
+                            "properties": {
+                                "data": { "type": "array", "items": items },
+                                "total": { "type": "integer", "description": "Number of records matching the filters" },
+                                "limit": { "type": "integer", "nullable": true },
+                                "offset": { "type": "integer" },
+                                "next": { "type": "string", "nullable": true, "description": "URL of the next page" }
+                            },
+                            "required": ["data", "total", "offset"]
+                        })
+                    };
+                }
+                PaginationStyle::Headers => {
+                    let headers = ok
+                        .entry("headers")
+                        .or_insert_with(|| serde_json::json!({}))
+                        .as_object_mut();
+                    let Some(headers) = headers else { continue };
+                    headers.insert(
+                        "X-Total-Count".to_string(),
+                        serde_json::json!({
+                            "description": "Number of records matching the filters",
+                            "schema": { "type": "integer" }
+                        }),
+                    );
+                    headers.insert(
+                        "Link".to_string(),
+                        serde_json::json!({
+                            "description": "RFC 8288 link to the next page (rel=\"next\")",
+                            "schema": { "type": "string" }
+                        }),
+                    );
+                }
+                PaginationStyle::None => {}
+            }
+        }
+    }
+
+    /// Document the parameters and the result rows of aggregate operations
+    fn describe_aggregates(spec: &mut Value, patterns: &[RoutePattern], schemas: &[TableSchema]) {
+        for pattern in patterns {
+            if !matches!(pattern.operation_type, OperationType::Aggregate) {
+                continue;
+            }
+            let Some(op) = spec
+                .pointer_mut(&format!(
+                    "/paths/{}/get",
+                    pattern.path_pattern.replace('~', "~0").replace('/', "~1")
+                ))
+                .and_then(|op| op.as_object_mut())
+            else {
+                continue;
+            };
+            let columns = schemas
+                .iter()
+                .find(|s| s.table_name == pattern.table_name)
+                .map(|s| {
+                    s.columns
+                        .iter()
+                        .map(|c| c.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+
+            let documented = [
+                ("group_by", "Comma-separated columns to group by"),
+                (
+                    "count",
+                    "Columns to count non-null values of, or `*` to count rows",
+                ),
+                ("sum", "Numeric columns to sum"),
+                ("avg", "Numeric columns to average"),
+                ("min", "Columns to take the minimum of"),
+                ("max", "Columns to take the maximum of"),
+            ];
+            if let Some(parameters) = op
+                .entry("parameters")
+                .or_insert_with(|| serde_json::json!([]))
+                .as_array_mut()
+            {
+                for (name, description) in documented {
+                    if parameters
+                        .iter()
+                        .any(|p| p.get("name").and_then(Value::as_str) == Some(name))
+                    {
+                        continue;
+                    }
+                    let description = if columns.is_empty() {
+                        description.to_string()
+                    } else {
+                        format!("{}. Columns: {}", description, columns)
+                    };
+                    parameters.push(serde_json::json!({
+                        "name": name,
+                        "in": "query",
+                        "required": false,
+                        "description": description,
+                        "schema": { "type": "string" }
+                    }));
+                }
+            }
+
+            let Some(ok) = op
+                .entry("responses")
+                .or_insert_with(|| serde_json::json!({}))
+                .as_object_mut()
+                .map(|r| {
+                    r.entry("200")
+                        .or_insert_with(|| serde_json::json!({"description": "OK"}))
+                })
+                .and_then(|r| r.as_object_mut())
+            else {
+                continue;
+            };
+            ok.entry("content").or_insert_with(|| {
+                serde_json::json!({
+                    "application/json": {
+                        "schema": {
+                            "type": "array",
+                            "description": "One row per group, ordered by the group_by columns",
+                            "items": {
+                                "type": "object",
+                                "description": "The group_by columns, then one member per aggregate: `count` for `count=*`, `<function>_<column>` otherwise (e.g. `sum_total`)",
+                                "additionalProperties": true
+                            }
+                        },
+                        "example": [{ "status": "paid", "count": 3, "sum_total": 120.5 }]
+                    }
+                })
+            });
+        }
+    }
+
+    /// Declare the `application/problem+json` error responses of every operation: the
+    /// statuses the CRUD handler answers with for its operation type, and a `default`
+    fn describe_errors(spec: &mut Value, patterns: &[RoutePattern]) {
+        let Some(root) = spec.as_object_mut() else {
+            return;
+        };
+        let components = root
+            .entry("components")
+            .or_insert_with(|| serde_json::json!({}));
+        if let Some(components) = components.as_object_mut()
+            && let Some(schemas) = components
+                .entry("schemas")
+                .or_insert_with(|| serde_json::json!({}))
+                .as_object_mut()
+        {
+            schemas
+                .entry("Problem")
+                .or_insert_with(crate::problem::openapi_schema);
+        }
+
+        let Some(paths) = root.get_mut("paths").and_then(|p| p.as_object_mut()) else {
+            return;
+        };
+        for (path, item) in paths.iter_mut() {
+            let Some(item) = item.as_object_mut() else {
+                continue;
+            };
+            for (method, op) in item.iter_mut() {
+                let Some(responses) = op
+                    .as_object_mut()
+                    .and_then(|op| op.get_mut("responses"))
+                    .and_then(|r| r.as_object_mut())
+                else {
+                    continue;
+                };
+                let operation_type = patterns
+                    .iter()
+                    .find(|p| {
+                        &p.path_pattern == path
+                            && p.methods.iter().any(|m| m.eq_ignore_ascii_case(method))
+                    })
+                    .map(|p| &p.operation_type);
+                let statuses: &[u16] = match operation_type {
+                    Some(OperationType::List | OperationType::Aggregate) => &[400],
+                    Some(OperationType::Get) => &[400, 404],
+                    Some(OperationType::Create | OperationType::Upsert) => &[400, 409, 422],
+                    Some(OperationType::Update | OperationType::Replace) => {
+                        &[400, 404, 409, 412, 422]
+                    }
+                    Some(OperationType::Delete) => &[400, 404, 409, 412],
+                    Some(OperationType::BulkUpdate) => &[400, 409, 422],
+                    Some(OperationType::BulkDelete) => &[400, 409],
+                    Some(OperationType::Restore) => &[404, 409],
+                    None => &[],
+                };
+                for status in statuses {
+                    let reason = crate::hyper::StatusCode::from_u16(*status)
+                        .ok()
+                        .and_then(|s| s.canonical_reason())
+                        .unwrap_or("Error");
+                    responses
+                        .entry(status.to_string())
+                        .or_insert_with(|| Self::problem_response(reason));
+                }
+                responses
+                    .entry("default")
+                    .or_insert_with(|| Self::problem_response("Error"));
+            }
+        }
+    }
+
+    fn problem_response(description: &str) -> Value {
+        serde_json::json!({
+            "description": description,
+            "content": {
+                crate::problem::PROBLEM_MEDIA_TYPE: {
+                    "schema": { "$ref": "#/components/schemas/Problem" }
+                }
+            }
+        })
+    }
+
     fn resolve_table_name_from_schema(
         spec: &Value,
         op_obj: &serde_json::Map<String, Value>,
@@ -188,6 +536,9 @@ impl APIGenerator {
 
     fn determine_operation_type(method: &str, path: &str) -> OperationType {
         match method.to_lowercase().as_str() {
+            "get" if !path.contains('{') && path.ends_with("/_aggregate") => {
+                OperationType::Aggregate
+            }
             "get" => {
                 if path.contains('{') {
                     OperationType::Get
@@ -195,9 +546,14 @@ impl APIGenerator {
                     OperationType::List
                 }
             }
+            "post" if path.contains('{') && path.ends_with("/restore") => OperationType::Restore,
             "post" => OperationType::Create,
-            "put" | "patch" => OperationType::Update,
-            "delete" => OperationType::Delete,
+            "put" if path.contains('{') => OperationType::Replace,
+            "patch" if path.contains('{') => OperationType::Update,
+            "put" => OperationType::Upsert,
+            "patch" => OperationType::BulkUpdate,
+            "delete" if path.contains('{') => OperationType::Delete,
+            "delete" => OperationType::BulkDelete,
             _ => OperationType::List,
         }
     }
@@ -244,17 +600,22 @@ impl APIGenerator {
     pub fn match_operation(&self, method: &str, path: &str) -> Option<RoutePattern> {
         let method_upper = method.to_uppercase();
         tracing::debug!("match_operation called for {} {}", method_upper, path);
-        let operation = self.route_patterns.iter().find(|pattern| {
-            let matched = pattern.regex.is_match(path) && pattern.methods.contains(&method_upper);
-            if matched {
-                tracing::debug!(
-                    "Matched pattern: {} for path: {}",
-                    pattern.path_pattern,
-                    path
-                );
-            }
-            matched
-        });
+        // Literal segments win over parameters, so `/orders/_aggregate` is not
+        // taken for the record `/orders/{id}`
+        let operation = self
+            .route_patterns
+            .iter()
+            .filter(|pattern| {
+                pattern.regex.is_match(path) && pattern.methods.contains(&method_upper)
+            })
+            .min_by_key(|pattern| pattern.param_names.len());
+        if let Some(pattern) = operation {
+            tracing::debug!(
+                "Matched pattern: {} for path: {}",
+                pattern.path_pattern,
+                path
+            );
+        }
         operation.cloned()
     }
 
@@ -299,6 +660,91 @@ mod tests {
         assert_eq!(APIGenerator::extract_table_name("/api/v1/users"), "api");
     }
 
+    #[test]
+    fn test_pagination_style_and_docs() {
+        let spec = serde_json::json!({
+            "x-pagination": "headers",
+            "paths": {
+                "/users": { "get": { "responses": { "200": { "description": "ok" } } } },
+                "/orders": {
+                    "get": {
+                        "x-pagination": "envelope",
+                        "responses": { "200": { "description": "ok" } }
+                    }
+                },
+                "/orders/{id}": {
+                    "delete": {
+                        "x-delete-status": 200,
+                        "responses": { "200": { "description": "ok" } }
+                    }
+                }
+            }
+        });
+        let generator = APIGenerator::new(spec, vec![]).unwrap();
+        let users = generator.match_operation("GET", "/users").unwrap();
+        let orders = generator.match_operation("GET", "/orders").unwrap();
+        assert_eq!(users.pagination, PaginationStyle::Headers);
+        assert_eq!(orders.pagination, PaginationStyle::Envelope);
+        assert_eq!(users.delete_status, DEFAULT_DELETE_STATUS);
+        let delete = generator.match_operation("DELETE", "/orders/7").unwrap();
+        assert_eq!(delete.delete_status, 200);
+
+        let doc = generator.get_spec();
+        assert!(
+            doc["paths"]["/users"]["get"]["responses"]["200"]["headers"]["X-Total-Count"]
+                .is_object()
+        );
+        assert_eq!(
+            doc["paths"]["/orders"]["get"]["responses"]["200"]["content"]["application/json"]["schema"]
+                ["properties"]["data"]["type"],
+            "array"
+        );
+
+        // Error responses are declared with the problem details schema
+        assert!(doc["components"]["schemas"]["Problem"].is_object());
+        let responses = &doc["paths"]["/orders/{id}"]["delete"]["responses"];
+        assert_eq!(
+            responses["412"]["content"]["application/problem+json"]["schema"]["$ref"],
+            "#/components/schemas/Problem"
+        );
+        assert!(responses["404"].is_object());
+        assert!(responses["default"].is_object());
+        assert_eq!(responses["200"]["description"], "ok");
+    }
+
+    #[test]
+    fn test_aggregate_routes_and_docs() {
+        let ok = serde_json::json!({ "200": { "description": "ok" } });
+        let spec = serde_json::json!({
+            "paths": {
+                "/orders/{id}": { "get": { "responses": ok } },
+                "/orders/_aggregate": { "get": { "responses": ok } }
+            }
+        });
+        let generator = APIGenerator::new(spec, vec![]).unwrap();
+        let pattern = generator
+            .match_operation("GET", "/orders/_aggregate")
+            .unwrap();
+        assert!(matches!(pattern.operation_type, OperationType::Aggregate));
+        assert_eq!(pattern.table_name, "orders");
+        let get = generator.match_operation("GET", "/orders/7").unwrap();
+        assert!(matches!(get.operation_type, OperationType::Get));
+
+        let op = &generator.get_spec()["paths"]["/orders/_aggregate"]["get"];
+        let names: Vec<&str> = op["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|p| p["name"].as_str())
+            .collect();
+        assert_eq!(names, ["group_by", "count", "sum", "avg", "min", "max"]);
+        assert_eq!(
+            op["responses"]["200"]["content"]["application/json"]["schema"]["type"],
+            "array"
+        );
+        assert!(op["responses"]["400"].is_object());
+    }
+
     #[test]
     fn test_determine_operation_type() {
         assert!(matches!(
@@ -315,12 +761,32 @@ mod tests {
         ));
         assert!(matches!(
             APIGenerator::determine_operation_type("put", "/users/{id}"),
+            OperationType::Replace
+        ));
+        assert!(matches!(
+            APIGenerator::determine_operation_type("patch", "/users/{id}"),
             OperationType::Update
         ));
         assert!(matches!(
             APIGenerator::determine_operation_type("delete", "/users/{id}"),
             OperationType::Delete
         ));
+        assert!(matches!(
+            APIGenerator::determine_operation_type("put", "/users"),
+            OperationType::Upsert
+        ));
+        assert!(matches!(
+            APIGenerator::determine_operation_type("patch", "/users"),
+            OperationType::BulkUpdate
+        ));
+        assert!(matches!(
+            APIGenerator::determine_operation_type("delete", "/users"),
+            OperationType::BulkDelete
+        ));
+        assert!(matches!(
+            APIGenerator::determine_operation_type("post", "/users/{id}/restore"),
+            OperationType::Restore
+        ));
     }
 
     #[test]
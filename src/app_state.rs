@@ -98,109 +98,113 @@ impl AppState {
                     "Building CRUD handler"
                 );
 
-                // Determine which datasource to use (from first API config or first available)
-                let datasource_name = config
-                    .openapi_configs
-                    .first()
-                    .and_then(|c| c.datasource.clone())
-                    .or_else(|| ds_map.keys().next().cloned())
-                    .ok_or("No datasource specified and none available")?;
-
-                let ds = ds_map.get(&datasource_name).ok_or_else(|| {
-                    format!("Datasource '{}' not found in config", datasource_name)
-                })?;
-
-                tracing::debug!(
-                    datasource_name = %datasource_name,
-                    driver = %ds.driver,
-                    "Selected datasource for AppState"
-                );
-
-                // Build database URL
-                let url = match ds.driver.as_str() {
-                    "postgres" | "postgresql" => {
-                        format!(
-                            "postgres://{}:{}@{}:{}/{}",
-                            ds.user.as_deref().unwrap_or("postgres"),
-                            ds.password.as_deref().unwrap_or(""),
-                            ds.host.as_deref().unwrap_or("localhost"),
-                            ds.port.unwrap_or(5432),
-                            ds.database
-                        )
-                    }
-                    "sqlite" => {
-                        let path = &ds.database;
-                        if path == ":memory:" {
-                            "sqlite::memory:".to_string()
-                        } else {
-                            format!("sqlite:{}", path)
-                        }
-                    }
-                    _ => return Err(format!("Unsupported database driver: {}", ds.driver).into()),
-                };
-
-                tracing::debug!(
-                    url = %url,
-                    max_size = ds.max_pool_size.unwrap_or(10),
-                    "Constructed database URL"
-                );
-
-                let max_size = ds.max_pool_size.unwrap_or(10) as u32;
-                let db_cfg = crate::database::DatabaseRuntimeConfig {
-                    driver: ds.driver.clone(),
-                    url,
-                    max_size,
-                };
+                let mut all_schemas = Vec::new();
+                let mut default_db: Option<DatabaseManager> = None;
+                let mut route_databases: HashMap<String, DatabaseManager> = HashMap::new();
+                // Datasource serving each path and table, to refuse APIs that collide
+                let mut path_owners: HashMap<String, String> = HashMap::new();
+                let mut table_owners: HashMap<String, String> = HashMap::new();
+
+                for (datasource_name, api_configs) in
+                    group_apis_by_datasource(&config.openapi_configs, ds_map)?
+                {
+                    let ds = ds_map.get(&datasource_name).ok_or_else(|| {
+                        format!("Datasource '{}' not found in config", datasource_name)
+                    })?;
+
+                    tracing::debug!(
+                        datasource_name = %datasource_name,
+                        driver = %ds.driver,
+                        "Selected datasource for AppState"
+                    );
 
-                let db_manager = DatabaseManager::new(db_cfg).await?;
+                    let db_manager = connect_datasource(ds).await?;
 
-                // Extract table schemas from all OpenAPI specs
-                let mut all_schemas = Vec::new();
-                tracing::info!(
-                    config_count = config.openapi_configs.len(),
-                    "Extracting schemas from OpenAPI configs"
-                );
-                for (i, api_config) in config.openapi_configs.iter().enumerate() {
-                    tracing::info!(index = i, "Extracting from OpenAPI config");
+                    // Extract table schemas from the OpenAPI specs bound to this datasource
+                    let mut ds_schemas = Vec::new();
+                    tracing::info!(
+                        datasource = %datasource_name,
+                        config_count = api_configs.len(),
+                        "Extracting schemas from OpenAPI configs"
+                    );
+                    for (i, api_config) in api_configs.iter().enumerate() {
+                        tracing::info!(index = i, "Extracting from OpenAPI config");
 
-                    match SchemaGenerator::extract_schemas_from_openapi(
-                        &api_config.config.openapi.spec,
-                    ) {
-                        Ok(schemas) => {
-                            tracing::info!(
-                                index = i,
-                                schema_count = schemas.len(),
-                                "Found schemas"
-                            );
-                            for schema in &schemas {
+                        match SchemaGenerator::extract_schemas_from_openapi(
+                            &api_config.config.openapi.spec,
+                        ) {
+                            Ok(schemas) => {
                                 tracing::info!(
-                                    table = %schema.table_name,
-                                    columns = schema.columns.len(),
-                                    relations = schema.relations.len(),
-                                    "Schema details"
+                                    index = i,
+                                    schema_count = schemas.len(),
+                                    "Found schemas"
                                 );
+                                for schema in &schemas {
+                                    tracing::info!(
+                                        table = %schema.table_name,
+                                        columns = schema.columns.len(),
+                                        relations = schema.relations.len(),
+                                        "Schema details"
+                                    );
+                                }
+                                ds_schemas.extend(schemas);
+                            }
+                            Err(e) => {
+                                tracing::error!(index = i, error = %e, "ERROR extracting schemas");
+                                return Err(e);
                             }
-                            all_schemas.extend(schemas);
                         }
-                        Err(e) => {
-                            tracing::error!(index = i, error = %e, "ERROR extracting schemas");
-                            return Err(e);
+
+                        // Route every path of this API to the datasource's backend
+                        if let Some(paths_obj) = api_config
+                            .config
+                            .openapi
+                            .spec
+                            .get("paths")
+                            .and_then(|v| v.as_object())
+                        {
+                            for path_key in paths_obj.keys() {
+                                claim(&mut path_owners, "Path", path_key, &datasource_name)?;
+                                route_databases.insert(path_key.clone(), db_manager.clone());
+                            }
                         }
                     }
-                }
 
-                // Always initialize schema if schemas are defined (removed opt-in gating)
-                if !all_schemas.is_empty() {
-                    tracing::info!(
-                        datasource = %datasource_name,
-                        schema_count = all_schemas.len(),
-                        "Initializing database with table schemas"
-                    );
-                    tracing::info!("Calling db_manager.initialize_schema...");
-                    db_manager.initialize_schema(all_schemas.clone()).await?;
-                    tracing::info!("Database initialization complete");
-                } else {
-                    tracing::warn!("No table schemas found in OpenAPI configurations");
+                    for schema in &ds_schemas {
+                        claim(
+                            &mut table_owners,
+                            "Table",
+                            &schema.table_name,
+                            &datasource_name,
+                        )?;
+                    }
+
+                    // Always initialize schema if schemas are defined (removed opt-in gating)
+                    if !ds_schemas.is_empty() {
+                        tracing::info!(
+                            datasource = %datasource_name,
+                            schema_count = ds_schemas.len(),
+                            "Initializing database with table schemas"
+                        );
+                        crate::migration::migrate_datasource(
+                            &db_manager,
+                            &datasource_name,
+                            ds.migrations.unwrap_or_default(),
+                            ds_schemas.clone(),
+                        )
+                        .await?;
+                        tracing::info!(datasource = %datasource_name, "Database initialization complete");
+                    } else {
+                        tracing::warn!(
+                            datasource = %datasource_name,
+                            "No table schemas found in OpenAPI configurations"
+                        );
+                    }
+
+                    all_schemas.extend(ds_schemas);
+                    if default_db.is_none() {
+                        default_db = Some(db_manager);
+                    }
                 }
 
                 // Merge all OpenAPI specs into one - deep merge for paths
@@ -209,14 +213,26 @@ impl AppState {
 
                 for api_config in &config.openapi_configs {
                     if let Some(spec_obj) = api_config.config.openapi.spec.as_object() {
+                        // API-wide list response shape, pushed down to the GET operations
+                        // so it survives merging with other APIs
+                        let pagination = spec_obj.get("x-pagination");
                         for (key, value) in spec_obj {
                             if key == "paths" {
                                 if let Some(paths_obj) = value.as_object() {
                                     for (path_key, path_value) in paths_obj {
-                                        merged_paths.insert(path_key.clone(), path_value.clone());
+                                        let mut path_value = path_value.clone();
+                                        if let Some(style) = pagination
+                                            && let Some(op) = path_value
+                                                .get_mut("get")
+                                                .and_then(|o| o.as_object_mut())
+                                        {
+                                            op.entry("x-pagination")
+                                                .or_insert_with(|| style.clone());
+                                        }
+                                        merged_paths.insert(path_key.clone(), path_value);
                                     }
                                 }
-                            } else {
+                            } else if key != "x-pagination" {
                                 merged_spec.insert(key.clone(), value.clone());
                             }
                         }
@@ -233,7 +249,12 @@ impl AppState {
 
                 let merged_value = serde_json::Value::Object(merged_spec);
                 let api_generator = APIGenerator::new(merged_value.clone(), all_schemas)?;
-                Some(Arc::new(CRUDHandler::new(db_manager, api_generator)))
+                let default_db = default_db.ok_or("No datasource specified and none available")?;
+                Some(Arc::new(CRUDHandler::with_route_databases(
+                    default_db,
+                    route_databases,
+                    api_generator,
+                )))
             } else {
                 None
             }
@@ -438,6 +459,136 @@ impl AppState {
     }
 }
 
+/// Name of a datasource and the APIs stored in it
+pub(crate) type DatasourceApis<'a> = (String, Vec<&'a OpenApiStateConfig>);
+
+/// Group APIs by datasource, in order of first use, so each backend only receives
+/// its own tables. APIs without a datasource use the only configured one, or the
+/// one named `default` when there are several.
+pub(crate) fn group_apis_by_datasource<'a>(
+    openapi_configs: &'a [OpenApiStateConfig],
+    ds_map: &HashMap<String, DatabaseSettings>,
+) -> Result<Vec<DatasourceApis<'a>>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut groups: Vec<DatasourceApis> = Vec::new();
+    for api_config in openapi_configs {
+        let datasource_name = match &api_config.datasource {
+            Some(name) => name.clone(),
+            None => default_datasource(ds_map)?,
+        };
+        match groups.iter_mut().find(|(name, _)| *name == datasource_name) {
+            Some((_, apis)) => apis.push(api_config),
+            None => groups.push((datasource_name, vec![api_config])),
+        }
+    }
+    Ok(groups)
+}
+
+/// Datasource of APIs that do not name one
+fn default_datasource(
+    ds_map: &HashMap<String, DatabaseSettings>,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    if ds_map.len() == 1 {
+        return Ok(ds_map.keys().next().cloned().unwrap_or_default());
+    }
+    if ds_map.contains_key("default") {
+        return Ok("default".to_string());
+    }
+    if ds_map.is_empty() {
+        return Err("No datasource specified and none available".into());
+    }
+    let mut names: Vec<&str> = ds_map.keys().map(String::as_str).collect();
+    names.sort();
+    Err(format!(
+        "An API does not name its datasource and several are configured ({}); \
+         set `datasource` on the API or name one of them `default`",
+        names.join(", ")
+    )
+    .into())
+}
+
+/// Open a connection pool to a configured datasource
+pub(crate) async fn connect_datasource(
+    ds: &DatabaseSettings,
+) -> Result<DatabaseManager, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(DatabaseManager::new(datasource_config(ds)?).await?)
+}
+
+/// Runtime connection settings of a configured datasource
+pub(crate) fn datasource_config(
+    ds: &DatabaseSettings,
+) -> Result<crate::database::DatabaseRuntimeConfig, Box<dyn std::error::Error + Send + Sync>> {
+    let url = build_datasource_url(ds)?;
+    tracing::debug!(
+        url = %url,
+        max_size = ds.max_pool_size.unwrap_or(10),
+        "Constructed database URL"
+    );
+    Ok(crate::database::DatabaseRuntimeConfig {
+        driver: ds.driver.clone(),
+        url,
+        max_size: ds.max_pool_size.unwrap_or(10) as u32,
+    })
+}
+
+/// Record that `datasource` serves a path or table, refusing one already served
+/// by another datasource: requests for it could only be routed to one of them
+fn claim(
+    owners: &mut HashMap<String, String>,
+    kind: &str,
+    name: &str,
+    datasource: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match owners.get(name) {
+        Some(owner) if owner != datasource => Err(format!(
+            "{} '{}' is declared by APIs of datasources '{}' and '{}'; serve it from one datasource",
+            kind, name, owner, datasource
+        )
+        .into()),
+        _ => {
+            owners.insert(name.to_string(), datasource.to_string());
+            Ok(())
+        }
+    }
+}
+
+/// Build the sqlx connection URL for a configured datasource
+fn build_datasource_url(
+    ds: &DatabaseSettings,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let url = match ds.driver.as_str() {
+        "postgres" | "postgresql" => {
+            format!(
+                "postgres://{}:{}@{}:{}/{}",
+                ds.user.as_deref().unwrap_or("postgres"),
+                ds.password.as_deref().unwrap_or(""),
+                ds.host.as_deref().unwrap_or("localhost"),
+                ds.port.unwrap_or(5432),
+                ds.database
+            )
+        }
+        "mysql" | "mariadb" => {
+            format!(
+                "mysql://{}:{}@{}:{}/{}",
+                ds.user.as_deref().unwrap_or("root"),
+                ds.password.as_deref().unwrap_or(""),
+                ds.host.as_deref().unwrap_or("localhost"),
+                ds.port.unwrap_or(3306),
+                ds.database
+            )
+        }
+        "sqlite" => {
+            let path = &ds.database;
+            if path == ":memory:" {
+                "sqlite::memory:".to_string()
+            } else {
+                format!("sqlite:{}", path)
+            }
+        }
+        _ => return Err(format!("Unsupported database driver: {}", ds.driver).into()),
+    };
+    Ok(url)
+}
+
 /// Helper to apply ModulesConfig into a ModuleRegistry
 fn apply_modules_cfg(
     mut reg: crate::modules::ModuleRegistry,
@@ -3,11 +3,12 @@
 use apify::{
     app_state::OpenApiStateConfig,
     config::{Config, OpenAPIConfig},
+    migration::{MigrationPlan, TableAction, apply_migrations, plan_migrations},
     modules::metrics::init_metrics,
     server::{ServerContext, start_docs_server, start_listener},
     startup::{RuntimeInitData, build_runtime, init_database, setup_logging},
 };
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::Path;
 use std::thread;
 
@@ -26,6 +27,23 @@ struct Cli {
     /// Enable Data Plane (default is true, unless --control-plane is set and this is not explicitly set)
     #[arg(long)]
     data_plane: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Migrate the datasource schemas to the configured APIs and exit
+    Migrate {
+        /// Only print the statements and their risk, without changing the databases
+        #[arg(long)]
+        plan: bool,
+
+        /// Print the plan as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -49,6 +67,10 @@ fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let config_path = Path::new(&cli.config);
     let config_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
 
+    if let Some(Command::Migrate { plan, json }) = cli.command {
+        return migrate(&config, config_dir, plan, json);
+    }
+
     // Setup logging
     let (tracing_enabled, otlp_endpoint, log_level) = setup_logging(&config)?;
 
@@ -563,6 +585,101 @@ fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     }
 }
 
+/// `apify migrate`: print the migration plan of every datasource used by the APIs of
+/// the config file and the control plane, then apply it unless `plan_only` is set
+fn migrate(
+    config: &Config,
+    config_dir: &Path,
+    plan_only: bool,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let rt = build_runtime()?;
+    rt.block_on(async {
+        let mut openapi_configs = Vec::new();
+        for api_config in config.apis.iter().flatten() {
+            let api_path = config_dir.join(&api_config.path);
+            openapi_configs.push(OpenApiStateConfig {
+                config: OpenAPIConfig::from_file(&api_path.to_string_lossy())?,
+                modules: None,
+                datasource: api_config.datasource.clone(),
+                access_log: None,
+                listeners: api_config.listeners.clone(),
+            });
+        }
+        let mut datasources = config.datasource.clone().unwrap_or_default();
+
+        // APIs and datasources registered through the Control Plane
+        if config.control_plane.is_some() {
+            let db = init_database(config).await?;
+            match apify::control_plane::load_api_configs(&db).await {
+                Ok(db_apis) => {
+                    let mut db_apis: Vec<_> = db_apis.into_iter().collect();
+                    db_apis.sort_by(|a, b| a.0.cmp(&b.0));
+                    openapi_configs.extend(db_apis.into_iter().map(|(_, c)| c));
+                }
+                Err(e) => eprintln!("Skipping API configs from Metadata DB: {}", e),
+            }
+            match apify::control_plane::load_datasources(&db).await {
+                Ok(db_ds) => datasources.extend(db_ds.unwrap_or_default()),
+                Err(e) => eprintln!("Skipping Datasources from Metadata DB: {}", e),
+            }
+        }
+
+        let plans = plan_migrations(&datasources, &openapi_configs).await?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&plans)?);
+        } else {
+            for plan in &plans {
+                print_plan(plan);
+            }
+        }
+        if plan_only || !plans.iter().any(MigrationPlan::has_changes) {
+            return Ok(());
+        }
+        apply_migrations(&datasources, &openapi_configs, &plans).await?;
+        eprintln!("Migrations applied");
+        Ok(())
+    })
+}
+
+fn print_plan(plan: &MigrationPlan) {
+    if !plan.has_changes() {
+        println!(
+            "Datasource '{}' ({}): up to date",
+            plan.datasource, plan.driver
+        );
+        return;
+    }
+    println!(
+        "Datasource '{}' ({}): {}",
+        plan.datasource,
+        plan.driver,
+        plan.risk.as_str()
+    );
+    let mut unchanged = 0;
+    for table in &plan.tables {
+        if table.action == TableAction::Unchanged {
+            unchanged += 1;
+            continue;
+        }
+        println!(
+            "  {}: {} ({})",
+            table.table,
+            table.action.as_str(),
+            table.risk.as_str()
+        );
+        if let Some(error) = &table.error {
+            println!("    error: {}", error);
+        }
+        for sql in &table.statements {
+            println!("    {};", sql.replace('\n', "\n    "));
+        }
+    }
+    if unchanged > 0 {
+        println!("  {} tables unchanged", unchanged);
+    }
+}
+
 /// Start metrics HTTP server
 fn start_metrics_server(
     port: u16,
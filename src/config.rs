@@ -144,6 +144,20 @@ pub struct DatabaseSettings {
     pub database: String,
     pub ssl_mode: Option<String>,
     pub max_pool_size: Option<usize>,
+    pub migrations: Option<MigrationMode>, // "auto", "manual" or "off" (default: "auto")
+}
+
+/// How the tables of a datasource are brought in line with its API specs at startup
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationMode {
+    /// Apply pending migrations
+    #[default]
+    Auto,
+    /// Refuse to start while migrations are pending; apply them with `apify migrate`
+    Manual,
+    /// Leave the schema alone
+    Off,
 }
 
 /// OpenAPI configuration structure
@@ -5,6 +5,8 @@ use crate::config::DatabaseSettings;
 use crate::config::{ModulesConfig, OpenAPIConfig};
 use crate::database::DatabaseManager;
 use crate::database::DatabaseRuntimeConfig;
+use crate::migration::migrate_datasource;
+use crate::problem::{Problem, error_response};
 use http_body_util::Full;
 use hyper::body::Bytes;
 use hyper::{Response, StatusCode};
@@ -100,9 +102,7 @@ pub async fn handle_apis_request(
                     .await?;
 
                 if records.is_empty() {
-                    Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?)
+                    Ok(error_response(StatusCode::NOT_FOUND, "API not found"))
                 } else {
                     let json = serde_json::to_string(&records[0])?;
                     Ok(Response::builder()
@@ -142,9 +142,7 @@ pub async fn handle_apis_request(
                     .await?;
 
                 if existing.is_empty() {
-                    return Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?);
+                    return Ok(error_response(StatusCode::NOT_FOUND, "API not found"));
                 }
 
                 let body_bytes = http_body_util::BodyExt::collect(body).await?.to_bytes();
@@ -249,9 +247,7 @@ pub async fn handle_apis_request(
                         serde_json::json!({"id": id}).to_string(),
                     )))?)
             } else {
-                Ok(Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Full::new(Bytes::from("Missing API ID")))?)
+                Ok(error_response(StatusCode::BAD_REQUEST, "Missing API ID"))
             }
         }
         hyper::Method::DELETE => {
@@ -271,9 +267,7 @@ pub async fn handle_apis_request(
                     .await?;
 
                 if existing.is_empty() {
-                    return Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?);
+                    return Ok(error_response(StatusCode::NOT_FOUND, "API not found"));
                 }
 
                 db.delete("_meta_api_configs", where_clause).await?;
@@ -282,9 +276,7 @@ pub async fn handle_apis_request(
                     .status(StatusCode::NO_CONTENT)
                     .body(Full::new(Bytes::from("")))?)
             } else {
-                Ok(Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Full::new(Bytes::from("Missing API ID")))?)
+                Ok(error_response(StatusCode::BAD_REQUEST, "Missing API ID"))
             }
         }
         hyper::Method::POST => {
@@ -436,6 +428,15 @@ pub async fn handle_apis_request(
                                 ds_settings.port.unwrap_or(5432),
                                 ds_settings.database
                             )
+                        } else if matches!(ds_settings.driver.as_str(), "mysql" | "mariadb") {
+                            format!(
+                                "mysql://{}:{}@{}:{}/{}",
+                                ds_settings.user.unwrap_or_default(),
+                                ds_settings.password.unwrap_or_default(),
+                                ds_settings.host.unwrap_or("localhost".to_string()),
+                                ds_settings.port.unwrap_or(3306),
+                                ds_settings.database
+                            )
                         } else {
                             format!("sqlite:{}", ds_settings.database)
                         };
@@ -446,8 +447,11 @@ pub async fn handle_apis_request(
                             max_size: ds_settings.max_pool_size.unwrap_or(20) as u32,
                         };
 
+                        let mode = ds_settings.migrations.unwrap_or_default();
                         let target_db = DatabaseManager::new(config).await?;
-                        if let Err(e) = target_db.initialize_schema(schemas.clone()).await {
+                        if let Err(e) =
+                            migrate_datasource(&target_db, ds_name, mode, schemas.clone()).await
+                        {
                             let msg = e.to_string();
                             // If tables already exist for this API, treat it as a no-op for updates.
                             if !msg.contains("exists") {
@@ -456,7 +460,7 @@ pub async fn handle_apis_request(
                                     name,
                                     e
                                 );
-                                return Err(Box::new(e));
+                                return Err(e);
                             }
                             tracing::info!("Tables already exist for API '{}', continuing", name);
                         }
@@ -509,8 +513,6 @@ pub async fn handle_apis_request(
                     serde_json::json!({"id": id}).to_string(),
                 )))?)
         }
-        _ => Ok(Response::builder()
-            .status(StatusCode::METHOD_NOT_ALLOWED)
-            .body(Full::new(Bytes::from("Method Not Allowed")))?),
+        _ => Ok(Problem::new(StatusCode::METHOD_NOT_ALLOWED).into_response()),
     }
 }
@@ -1,6 +1,7 @@
 use super::models::AuthConfigRecord;
 use crate::config::Authenticator;
 use crate::database::DatabaseManager;
+use crate::problem::{Problem, error_response};
 use http_body_util::Full;
 use hyper::body::Bytes;
 use hyper::{Response, StatusCode};
@@ -60,9 +61,10 @@ pub async fn handle_auth_request(
                     .await?;
 
                 if records.is_empty() {
-                    Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?)
+                    Ok(error_response(
+                        StatusCode::NOT_FOUND,
+                        "Auth config not found",
+                    ))
                 } else {
                     let json = serde_json::to_string(&records[0])?;
                     Ok(Response::builder()
@@ -109,9 +111,10 @@ pub async fn handle_auth_request(
                     .await?;
 
                 if existing.is_empty() {
-                    return Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?);
+                    return Ok(error_response(
+                        StatusCode::NOT_FOUND,
+                        "Auth config not found",
+                    ));
                 }
 
                 // Check if another auth config with the same name exists (excluding current)
@@ -133,14 +136,10 @@ pub async fn handle_auth_request(
                         };
 
                         if existing_name == auth_name {
-                            return Ok(Response::builder()
-                                        .status(StatusCode::CONFLICT)
-                                        .header("Content-Type", "application/json")
-                                        .body(Full::new(Bytes::from(
-                                            serde_json::json!({
-                                                "error": format!("Auth config with name '{}' already exists", auth_name)
-                                            }).to_string(),
-                                        )))?);
+                            return Ok(error_response(
+                                StatusCode::CONFLICT,
+                                &format!("Auth config with name '{}' already exists", auth_name),
+                            ));
                         }
                     }
                 }
@@ -166,9 +165,10 @@ pub async fn handle_auth_request(
                         serde_json::json!({"id": id}).to_string(),
                     )))?)
             } else {
-                Ok(Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Full::new(Bytes::from("Missing auth config ID")))?)
+                Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    "Missing auth config ID",
+                ))
             }
         }
         hyper::Method::DELETE => {
@@ -188,9 +188,10 @@ pub async fn handle_auth_request(
                     .await?;
 
                 if existing.is_empty() {
-                    return Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?);
+                    return Ok(error_response(
+                        StatusCode::NOT_FOUND,
+                        "Auth config not found",
+                    ));
                 }
 
                 db.delete("_meta_auth_configs", where_clause).await?;
@@ -199,9 +200,10 @@ pub async fn handle_auth_request(
                     .status(StatusCode::NO_CONTENT)
                     .body(Full::new(Bytes::from("")))?)
             } else {
-                Ok(Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Full::new(Bytes::from("Missing auth config ID")))?)
+                Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    "Missing auth config ID",
+                ))
             }
         }
         hyper::Method::POST => {
@@ -231,14 +233,10 @@ pub async fn handle_auth_request(
                     };
 
                     if existing_name == auth_name {
-                        return Ok(Response::builder()
-                                .status(StatusCode::CONFLICT)
-                                .header("Content-Type", "application/json")
-                                .body(Full::new(Bytes::from(
-                                    serde_json::json!({
-                                        "error": format!("Auth config with name '{}' already exists", auth_name)
-                                    }).to_string(),
-                                )))?);
+                        return Ok(error_response(
+                            StatusCode::CONFLICT,
+                            &format!("Auth config with name '{}' already exists", auth_name),
+                        ));
                     }
                 }
             }
@@ -271,8 +269,6 @@ pub async fn handle_auth_request(
                     serde_json::json!({"id": id}).to_string(),
                 )))?)
         }
-        _ => Ok(Response::builder()
-            .status(StatusCode::METHOD_NOT_ALLOWED)
-            .body(Full::new(Bytes::from("Method Not Allowed")))?),
+        _ => Ok(Problem::new(StatusCode::METHOD_NOT_ALLOWED).into_response()),
     }
 }
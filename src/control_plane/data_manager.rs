@@ -1,4 +1,5 @@
 use crate::database::{DatabaseError, DatabaseManager, DatabaseRuntimeConfig};
+use crate::problem::{Problem, error_response};
 use http_body_util::Full;
 use hyper::body::Bytes;
 use hyper::{Request, Response, StatusCode};
@@ -30,6 +31,14 @@ fn make_db_url(settings: &crate::config::DatabaseSettings) -> String {
             settings.port.unwrap_or(5432),
             settings.database
         ),
+        "mysql" | "mariadb" => format!(
+            "mysql://{}:{}@{}:{}/{}",
+            settings.user.as_deref().unwrap_or(""),
+            settings.password.as_deref().unwrap_or(""),
+            settings.host.as_deref().unwrap_or("localhost"),
+            settings.port.unwrap_or(3306),
+            settings.database
+        ),
         _ => {
             if settings.database.starts_with("sqlite:") {
                 settings.database.clone()
@@ -128,27 +137,25 @@ pub async fn handle_data_manager_request(
     // Index:       0        1       2          3
 
     if segments.len() < 4 {
-        return Ok(Response::builder()
-            .status(StatusCode::BAD_REQUEST)
-            .body(Full::new(Bytes::from("Missing datasource name")))?);
+        return Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "Missing datasource name",
+        ));
     }
 
     let datasource_name = segments[3];
     let user_db = match get_user_datasource(cp_db, cache, datasource_name).await {
         Ok(db) => db,
         Err(e) => {
-            let error_json = json!({ "error": e.to_string() });
-            return Ok(Response::builder()
-                .status(StatusCode::BAD_REQUEST) // Or NOT_FOUND
-                .header("Content-Type", "application/json")
-                .body(Full::new(Bytes::from(error_json.to_string())))?);
+            return Ok(error_response(StatusCode::BAD_REQUEST, &e.to_string()));
         }
     };
 
     if segments.len() == 4 {
-        return Ok(Response::builder()
-            .status(StatusCode::BAD_REQUEST)
-            .body(Full::new(Bytes::from("Missing resource/table name")))?);
+        return Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "Missing resource/table name",
+        ));
     }
 
     // /apify/admin/data/{ds}/tables -> List tables (Not implemented widely in modules yet, hard to do generically without specific backend support logic, but let's assume 'schema' module calls)
@@ -170,9 +177,10 @@ pub async fn handle_data_manager_request(
     // /apify/admin/data/{ds}/schema/{table}
     if resource == "schema" {
         if segments.len() < 6 {
-            return Ok(Response::builder()
-                .status(StatusCode::BAD_REQUEST)
-                .body(Full::new(Bytes::from("Missing table name")))?);
+            return Ok(error_response(
+                StatusCode::BAD_REQUEST,
+                "Missing table name",
+            ));
         }
         let table_name = segments[5];
         let schema = user_db.get_table_schema(table_name).await?;
@@ -207,8 +215,33 @@ pub async fn handle_data_manager_request(
             where_clause = Some(map);
         }
 
-        // TODO: Columns selection
-        let columns = None;
+        let columns = match payload.get("columns").and_then(|v| v.as_array()) {
+            Some(requested) => {
+                let schema = user_db.get_table_schema(table_name).await?;
+                let mut columns = Vec::new();
+                for col in requested {
+                    let name = col.as_str().unwrap_or_default();
+                    // Column names end up in the SQL text, so only known columns are accepted
+                    let known = schema.as_ref().and_then(|s| {
+                        s.columns
+                            .iter()
+                            .find(|c| c.name.eq_ignore_ascii_case(name))
+                            .map(|c| c.name.clone())
+                    });
+                    match known {
+                        Some(column) => columns.push(column),
+                        None => {
+                            return Ok(error_response(
+                                StatusCode::BAD_REQUEST,
+                                &format!("Unknown column '{}'", name),
+                            ));
+                        }
+                    }
+                }
+                Some(columns)
+            }
+            None => None,
+        };
 
         let rows = user_db
             .select(table_name, columns, where_clause, limit, offset)
@@ -236,32 +269,22 @@ pub async fn handle_data_manager_request(
             .header("Content-Type", "application/json")
             .body(Full::new(Bytes::from(result.to_string())))?);
     }
-    // PUT /apify/admin/data/{ds}/{table}/{id} -> Update
-    // Need to handle ID. Schema might not be 'id'.
-    // NOTE: This simple implementation assumes 'id' in URL maps to a where clause.
-    // Ideally we should look up PK, but for now allow passing filters in body or Assume PK is id?
-    // Let's try to support parsing ID from URL.
+    // PUT /apify/admin/data/{ds}/{table}/{key...} -> Update
+    // The key segments fill the table's primary key columns in order
     if method == hyper::Method::PUT {
         if segments.len() < 6 {
-            return Ok(Response::builder()
-                .status(StatusCode::BAD_REQUEST)
-                .body(Full::new(Bytes::from("Missing ID")))?);
+            return Ok(error_response(StatusCode::BAD_REQUEST, "Missing ID"));
         }
-        let id = segments[5];
+        let where_clause = match primary_key_where(&user_db, table_name, &segments[5..]).await? {
+            Ok(where_clause) => where_clause,
+            Err(message) => {
+                return Ok(error_response(StatusCode::BAD_REQUEST, &message));
+            }
+        };
 
         let body_bytes = http_body_util::BodyExt::collect(body).await?.to_bytes();
         let data: HashMap<String, Value> = serde_json::from_slice(&body_bytes)?;
 
-        // Construct where clause assuming PK is "id"
-        // TODO: Make this flexible
-        let mut where_clause = HashMap::new();
-        // Try parsing ID as number if possible, else string
-        if let Ok(num) = id.parse::<i64>() {
-            where_clause.insert("id".to_string(), json!(num));
-        } else {
-            where_clause.insert("id".to_string(), json!(id));
-        }
-
         let result = user_db
             .update(table_name, data, where_clause)
             .await
@@ -272,21 +295,17 @@ pub async fn handle_data_manager_request(
             .body(Full::new(Bytes::from(result.to_string())))?);
     }
 
-    // DELETE /apify/admin/data/{ds}/{table}/{id}
+    // DELETE /apify/admin/data/{ds}/{table}/{key...}
     if method == hyper::Method::DELETE {
         if segments.len() < 6 {
-            return Ok(Response::builder()
-                .status(StatusCode::BAD_REQUEST)
-                .body(Full::new(Bytes::from("Missing ID")))?);
-        }
-        let id = segments[5];
-
-        let mut where_clause = HashMap::new();
-        if let Ok(num) = id.parse::<i64>() {
-            where_clause.insert("id".to_string(), json!(num));
-        } else {
-            where_clause.insert("id".to_string(), json!(id));
+            return Ok(error_response(StatusCode::BAD_REQUEST, "Missing ID"));
         }
+        let where_clause = match primary_key_where(&user_db, table_name, &segments[5..]).await? {
+            Ok(where_clause) => where_clause,
+            Err(message) => {
+                return Ok(error_response(StatusCode::BAD_REQUEST, &message));
+            }
+        };
 
         user_db
             .delete(table_name, where_clause)
@@ -297,7 +316,37 @@ pub async fn handle_data_manager_request(
             .body(Full::new(Bytes::from("")))?);
     }
 
-    Ok(Response::builder()
-        .status(StatusCode::NOT_FOUND)
-        .body(Full::new(Bytes::from("Not Found")))?)
+    Ok(Problem::new(StatusCode::NOT_FOUND).into_response())
+}
+
+/// Where clause addressing one row by its primary key, from the key segments of the URL.
+/// Tables without a declared primary key are keyed on `id`.
+async fn primary_key_where(
+    user_db: &DatabaseManager,
+    table: &str,
+    segments: &[&str],
+) -> Result<Result<HashMap<String, Value>, String>, DatabaseError> {
+    let primary_key = user_db
+        .get_table_schema(table)
+        .await?
+        .map(|schema| schema.primary_key())
+        .unwrap_or_else(|| vec!["id".to_string()]);
+    if segments.len() != primary_key.len() {
+        return Ok(Err(format!(
+            "Expected {} key segment(s) for the primary key ({}) of {}",
+            primary_key.len(),
+            primary_key.join(", "),
+            table
+        )));
+    }
+
+    // Numbers when they parse, strings otherwise
+    Ok(Ok(primary_key
+        .into_iter()
+        .zip(segments)
+        .map(|(column, segment)| match segment.parse::<i64>() {
+            Ok(num) => (column, json!(num)),
+            Err(_) => (column, json!(segment)),
+        })
+        .collect()))
 }
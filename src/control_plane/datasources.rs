@@ -1,6 +1,7 @@
 use super::models::DatasourceConfigRecord;
 use crate::config::DatabaseSettings;
 use crate::database::DatabaseManager;
+use crate::problem::{Problem, error_response};
 use http_body_util::Full;
 use hyper::body::Bytes;
 use hyper::{Response, StatusCode};
@@ -90,9 +91,10 @@ pub async fn handle_datasources_request(
                     .await?;
 
                 if records.is_empty() {
-                    Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?)
+                    Ok(error_response(
+                        StatusCode::NOT_FOUND,
+                        "Datasource not found",
+                    ))
                 } else {
                     let record = transform_record(records[0].clone());
                     let json = serde_json::to_string(&record)?;
@@ -180,9 +182,10 @@ pub async fn handle_datasources_request(
                     .await?;
 
                 if existing.is_empty() {
-                    return Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?);
+                    return Ok(error_response(
+                        StatusCode::NOT_FOUND,
+                        "Datasource not found",
+                    ));
                 }
 
                 // Check if another datasource with the same name exists (excluding current)
@@ -197,15 +200,10 @@ pub async fn handle_datasources_request(
                     if let Some(record_id) = record.get("id").and_then(|v| v.as_str())
                         && record_id != id
                     {
-                        return Ok(Response::builder()
-                                .status(StatusCode::CONFLICT)
-                                .header("Content-Type", "application/json")
-                                .body(Full::new(Bytes::from(
-                                    serde_json::json!({
-                                        "error": format!("Datasource with name '{}' already exists", name)
-                                    })
-                                    .to_string(),
-                                )))?);
+                        return Ok(error_response(
+                            StatusCode::CONFLICT,
+                            &format!("Datasource with name '{}' already exists", name),
+                        ));
                     }
                 }
 
@@ -235,9 +233,10 @@ pub async fn handle_datasources_request(
                         serde_json::json!({"id": id}).to_string(),
                     )))?)
             } else {
-                Ok(Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Full::new(Bytes::from("Missing datasource ID")))?)
+                Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    "Missing datasource ID",
+                ))
             }
         }
         hyper::Method::DELETE => {
@@ -257,9 +256,10 @@ pub async fn handle_datasources_request(
                     .await?;
 
                 if existing.is_empty() {
-                    return Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?);
+                    return Ok(error_response(
+                        StatusCode::NOT_FOUND,
+                        "Datasource not found",
+                    ));
                 }
 
                 // Check if datasource is used by any API
@@ -277,14 +277,13 @@ pub async fn handle_datasources_request(
                                 .get("name")
                                 .and_then(|v| v.as_str())
                                 .unwrap_or("Unknown");
-                            return Ok(Response::builder()
-                                    .status(StatusCode::CONFLICT)
-                                    .header("Content-Type", "application/json")
-                                    .body(Full::new(Bytes::from(
-                                        serde_json::json!({
-                                            "error": format!("Datasource is used by API '{}'. Please delete the API first.", api_name)
-                                        }).to_string(),
-                                    )))?);
+                            return Ok(error_response(
+                                StatusCode::CONFLICT,
+                                &format!(
+                                    "Datasource is used by API '{}'. Please delete the API first.",
+                                    api_name
+                                ),
+                            ));
                         }
                     }
                 }
@@ -295,9 +294,10 @@ pub async fn handle_datasources_request(
                     .status(StatusCode::NO_CONTENT)
                     .body(Full::new(Bytes::from("")))?)
             } else {
-                Ok(Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Full::new(Bytes::from("Missing datasource ID")))?)
+                Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    "Missing datasource ID",
+                ))
             }
         }
         hyper::Method::POST => {
@@ -355,15 +355,10 @@ pub async fn handle_datasources_request(
                 .await?;
 
             if !existing.is_empty() {
-                return Ok(Response::builder()
-                    .status(StatusCode::CONFLICT)
-                    .header("Content-Type", "application/json")
-                    .body(Full::new(Bytes::from(
-                        serde_json::json!({
-                            "error": format!("Datasource with name '{}' already exists", name)
-                        })
-                        .to_string(),
-                    )))?);
+                return Ok(error_response(
+                    StatusCode::CONFLICT,
+                    &format!("Datasource with name '{}' already exists", name),
+                ));
             }
 
             // Validate config
@@ -394,8 +389,6 @@ pub async fn handle_datasources_request(
                     serde_json::json!({"id": id}).to_string(),
                 )))?)
         }
-        _ => Ok(Response::builder()
-            .status(StatusCode::METHOD_NOT_ALLOWED)
-            .body(Full::new(Bytes::from("Method Not Allowed")))?),
+        _ => Ok(Problem::new(StatusCode::METHOD_NOT_ALLOWED).into_response()),
     }
 }
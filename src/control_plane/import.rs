@@ -1,4 +1,5 @@
 use crate::database::DatabaseManager;
+use crate::problem::Problem;
 use http_body_util::Full;
 use hyper::body::Bytes;
 use hyper::{Response, StatusCode};
@@ -13,9 +14,7 @@ pub async fn handle_import_request(
     let method = parts.method;
 
     if method != hyper::Method::POST {
-        return Ok(Response::builder()
-            .status(StatusCode::METHOD_NOT_ALLOWED)
-            .body(Full::new(Bytes::from("Method Not Allowed")))?);
+        return Ok(Problem::new(StatusCode::METHOD_NOT_ALLOWED).into_response());
     }
 
     let body_bytes = http_body_util::BodyExt::collect(body).await?.to_bytes();
@@ -1,4 +1,5 @@
 use crate::database::DatabaseManager;
+use crate::problem::{Problem, error_response};
 use http_body_util::Full;
 use hyper::body::Bytes;
 use hyper::{Response, StatusCode};
@@ -80,9 +81,7 @@ pub async fn handle_listeners_request(
                     .await?;
 
                 if records.is_empty() {
-                    Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?)
+                    Ok(error_response(StatusCode::NOT_FOUND, "Listener not found"))
                 } else {
                     let record = transform_record(records[0].clone());
                     let json = serde_json::to_string(&record)?;
@@ -112,9 +111,10 @@ pub async fn handle_listeners_request(
                         Ok(c) => c,
                         Err(e) => {
                             tracing::error!("Failed to deserialize ListenerConfig: {}", e);
-                            return Ok(Response::builder()
-                                .status(StatusCode::BAD_REQUEST)
-                                .body(Full::new(Bytes::from(format!("Invalid config: {}", e))))?);
+                            return Ok(error_response(
+                                StatusCode::BAD_REQUEST,
+                                &format!("Invalid config: {}", e),
+                            ));
                         }
                     };
 
@@ -133,9 +133,7 @@ pub async fn handle_listeners_request(
                     .await?;
 
                 if existing.is_empty() {
-                    return Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?);
+                    return Ok(error_response(StatusCode::NOT_FOUND, "Listener not found"));
                 }
 
                 // Check for port conflicts with other listeners
@@ -161,17 +159,13 @@ pub async fn handle_listeners_request(
                             || existing_config.ip == config.ip;
 
                         if conflict {
-                            return Ok(Response::builder()
-                                        .status(StatusCode::CONFLICT)
-                                        .header("Content-Type", "application/json")
-                                        .body(Full::new(Bytes::from(
-                                            serde_json::json!({
-                                                "error": format!(
-                                                    "Listener port {} conflicts with existing listener {}:{}",
-                                                    config.port, existing_config.ip, existing_config.port
-                                                )
-                                            }).to_string(),
-                                        )))?);
+                            return Ok(error_response(
+                                StatusCode::CONFLICT,
+                                &format!(
+                                    "Listener port {} conflicts with existing listener {}:{}",
+                                    config.port, existing_config.ip, existing_config.port
+                                ),
+                            ));
                         }
                     }
                 }
@@ -206,9 +200,10 @@ pub async fn handle_listeners_request(
                         serde_json::json!({"id": id}).to_string(),
                     )))?)
             } else {
-                Ok(Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Full::new(Bytes::from("Missing listener ID")))?)
+                Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    "Missing listener ID",
+                ))
             }
         }
         hyper::Method::DELETE => {
@@ -228,9 +223,7 @@ pub async fn handle_listeners_request(
                     .await?;
 
                 if existing.is_empty() {
-                    return Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Full::new(Bytes::from("Not Found")))?);
+                    return Ok(error_response(StatusCode::NOT_FOUND, "Listener not found"));
                 }
 
                 // Extract listener name if available
@@ -261,15 +254,13 @@ pub async fn handle_listeners_request(
                                 .get("name")
                                 .and_then(|v| v.as_str())
                                 .unwrap_or("Unknown");
-                            return Ok(Response::builder()
-                                    .status(StatusCode::CONFLICT)
-                                    .header("Content-Type", "application/json")
-                                    .body(Full::new(Bytes::from(
-                                        serde_json::json!({
-                                            "error": format!("Listener is used by API '{}'. Please delete the API first.", api_name)
-                                        })
-                                        .to_string(),
-                                    )))?);
+                            return Ok(error_response(
+                                StatusCode::CONFLICT,
+                                &format!(
+                                    "Listener is used by API '{}'. Please delete the API first.",
+                                    api_name
+                                ),
+                            ));
                         }
                     }
                 }
@@ -280,9 +271,10 @@ pub async fn handle_listeners_request(
                     .status(StatusCode::NO_CONTENT)
                     .body(Full::new(Bytes::from("")))?)
             } else {
-                Ok(Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Full::new(Bytes::from("Missing listener ID")))?)
+                Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    "Missing listener ID",
+                ))
             }
         }
         hyper::Method::POST => {
@@ -325,17 +317,13 @@ pub async fn handle_listeners_request(
                         };
 
                         if conflict {
-                            return Ok(Response::builder()
-                                    .status(StatusCode::CONFLICT)
-                                    .header("Content-Type", "application/json")
-                                    .body(Full::new(Bytes::from(
-                                        serde_json::json!({
-                                            "error": format!(
-                                                "Listener port {} conflicts with existing listener {}:{}",
-                                                config.port, existing_config.ip, existing_config.port
-                                            )
-                                        }).to_string(),
-                                    )))?);
+                            return Ok(error_response(
+                                StatusCode::CONFLICT,
+                                &format!(
+                                    "Listener port {} conflicts with existing listener {}:{}",
+                                    config.port, existing_config.ip, existing_config.port
+                                ),
+                            ));
                         }
                     }
                 }
@@ -373,8 +361,6 @@ pub async fn handle_listeners_request(
                     serde_json::json!({"id": id}).to_string(),
                 )))?)
         }
-        _ => Ok(Response::builder()
-            .status(StatusCode::METHOD_NOT_ALLOWED)
-            .body(Full::new(Bytes::from("Method Not Allowed")))?),
+        _ => Ok(Problem::new(StatusCode::METHOD_NOT_ALLOWED).into_response()),
     }
 }
@@ -0,0 +1,104 @@
+use super::apis::load_api_configs;
+use super::datasources::load_datasources;
+use crate::app_state::OpenApiStateConfig;
+use crate::config::{OpenAPIConfig, OpenAPISettings};
+use crate::database::DatabaseManager;
+use crate::migration::plan_migrations;
+use crate::problem::{Problem, error_response};
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::{Response, StatusCode};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Schema changes computed without changing the databases:
+///
+/// - `GET /apify/admin/migrations/plan` for the registered APIs, or for one with `?api=<name>`
+/// - `POST /apify/admin/migrations/plan` for an API about to be registered, given its
+///   `spec` and `datasource_name` as in `POST /apify/admin/apis`
+pub async fn handle_migrations_request(
+    req: hyper::Request<hyper::body::Incoming>,
+    db: &DatabaseManager,
+) -> Result<Response<Full<Bytes>>, Box<dyn std::error::Error + Send + Sync>> {
+    let (parts, body) = req.into_parts();
+    if parts.uri.path() != "/apify/admin/migrations/plan" {
+        return Ok(Problem::new(StatusCode::NOT_FOUND).into_response());
+    }
+    let datasources = load_datasources(db).await?.unwrap_or_default();
+
+    let api_configs = match parts.method {
+        hyper::Method::GET => {
+            let params: HashMap<String, String> =
+                form_urlencoded::parse(parts.uri.query().unwrap_or_default().as_bytes())
+                    .into_owned()
+                    .collect();
+            let mut api_configs: Vec<_> = load_api_configs(db).await?.into_iter().collect();
+            api_configs.sort_by(|a, b| a.0.cmp(&b.0));
+            if let Some(api) = params.get("api") {
+                api_configs.retain(|(name, _)| name == api);
+                if api_configs.is_empty() {
+                    return Ok(error_response(StatusCode::NOT_FOUND, "API not found"));
+                }
+            }
+            api_configs.into_iter().map(|(_, c)| c).collect()
+        }
+        hyper::Method::POST => {
+            let body_bytes = http_body_util::BodyExt::collect(body).await?.to_bytes();
+            let payload: Value = match serde_json::from_slice(&body_bytes) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    return Ok(error_response(
+                        StatusCode::BAD_REQUEST,
+                        &format!("Invalid JSON body: {}", e),
+                    ));
+                }
+            };
+            let spec = match payload.get("spec") {
+                Some(Value::String(content)) => {
+                    match serde_json::from_str(content).or_else(|_| serde_yaml::from_str(content)) {
+                        Ok(spec) => spec,
+                        Err(e) => {
+                            return Ok(error_response(
+                                StatusCode::BAD_REQUEST,
+                                &format!("Failed to parse spec as JSON or YAML: {}", e),
+                            ));
+                        }
+                    }
+                }
+                Some(spec) => spec.clone(),
+                None => return Ok(error_response(StatusCode::BAD_REQUEST, "Missing spec")),
+            };
+            let Some(datasource) = payload.get("datasource_name").and_then(|v| v.as_str()) else {
+                return Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    "Missing datasource_name",
+                ));
+            };
+            if !datasources.contains_key(datasource) {
+                return Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Datasource '{}' not found", datasource),
+                ));
+            }
+            vec![OpenApiStateConfig {
+                config: OpenAPIConfig {
+                    openapi: OpenAPISettings {
+                        spec,
+                        validation: None,
+                    },
+                },
+                modules: None,
+                datasource: Some(datasource.to_string()),
+                access_log: None,
+                listeners: None,
+            }]
+        }
+        _ => return Ok(Problem::new(StatusCode::METHOD_NOT_ALLOWED).into_response()),
+    };
+
+    let plans = plan_migrations(&datasources, &api_configs).await?;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .body(Full::new(Bytes::from(serde_json::to_string(&plans)?)))?)
+}
@@ -4,6 +4,7 @@ pub mod data_manager;
 pub mod datasources;
 pub mod import;
 pub mod listeners;
+pub mod migrations;
 pub mod models;
 pub mod schemas;
 pub mod server;
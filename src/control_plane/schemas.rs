@@ -1,4 +1,4 @@
-use crate::schema_generator::{ColumnDefinition, IndexDefinition, TableSchema};
+use crate::schema_generator::{ColumnDefinition, DropPolicy, IndexDefinition, TableSchema};
 
 pub fn get_metadata_schemas() -> Vec<TableSchema> {
     vec![
@@ -14,6 +14,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "name".to_string(),
@@ -24,6 +25,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "version".to_string(),
@@ -34,6 +36,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "spec".to_string(),
@@ -44,6 +47,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "datasource_name".to_string(),
@@ -54,6 +58,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "modules_config".to_string(),
@@ -64,6 +69,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "listeners".to_string(),
@@ -74,6 +80,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "created_at".to_string(),
@@ -84,6 +91,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "updated_at".to_string(),
@@ -94,6 +102,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
             ],
             indexes: vec![IndexDefinition {
@@ -102,6 +111,14 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                 unique: true,
             }],
             relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
         },
         TableSchema {
             table_name: "_meta_datasources".to_string(),
@@ -115,6 +132,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "name".to_string(),
@@ -125,6 +143,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "type".to_string(),
@@ -135,6 +154,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "config".to_string(),
@@ -145,6 +165,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "updated_at".to_string(),
@@ -155,10 +176,19 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
             ],
             indexes: vec![],
             relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
         },
         TableSchema {
             table_name: "_meta_auth_configs".to_string(),
@@ -172,6 +202,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "config".to_string(),
@@ -182,6 +213,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "updated_at".to_string(),
@@ -192,10 +224,19 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
             ],
             indexes: vec![],
             relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
         },
         TableSchema {
             table_name: "_meta_listeners".to_string(),
@@ -209,6 +250,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "port".to_string(),
@@ -219,6 +261,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "config".to_string(),
@@ -229,6 +272,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "created_at".to_string(),
@@ -239,6 +283,7 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "updated_at".to_string(),
@@ -249,10 +294,19 @@ pub fn get_metadata_schemas() -> Vec<TableSchema> {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
             ],
             indexes: vec![],
             relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
         },
     ]
 }
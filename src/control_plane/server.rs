@@ -1,5 +1,7 @@
 use crate::database::DatabaseManager;
+use crate::problem::{Problem, RequestId, error_response, stamp_response};
 use http_body_util::Full;
+use hyper::StatusCode;
 use hyper::body::Bytes;
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
@@ -15,6 +17,7 @@ use super::data_manager::{DbCache, create_db_cache, handle_data_manager_request}
 use super::datasources::handle_datasources_request;
 use super::import::handle_import_request;
 use super::listeners::handle_listeners_request;
+use super::migrations::handle_migrations_request;
 
 /// Serve static files from admin dashboard
 async fn serve_static_file(
@@ -34,9 +37,7 @@ async fn serve_static_file(
 
     // Security check: prevent directory traversal
     if !full_path.starts_with(&static_dir) {
-        return Ok(hyper::Response::builder()
-            .status(hyper::StatusCode::FORBIDDEN)
-            .body(Full::new(Bytes::from("Forbidden")))?);
+        return Ok(Problem::new(StatusCode::FORBIDDEN).into_response());
     }
 
     // Read file
@@ -65,9 +66,7 @@ async fn serve_static_file(
                 }
             }
 
-            Ok(hyper::Response::builder()
-                .status(hyper::StatusCode::NOT_FOUND)
-                .body(Full::new(Bytes::from("Not Found")))?)
+            Ok(Problem::new(StatusCode::NOT_FOUND).into_response())
         }
     }
 }
@@ -110,9 +109,10 @@ pub async fn handle_control_plane_request(
 
         if !authorized {
             tracing::warn!("Unauthorized access attempt to Control Plane");
-            return Ok(hyper::Response::builder()
-                .status(hyper::StatusCode::UNAUTHORIZED)
-                .body(Full::new(Bytes::from("Unauthorized")))?);
+            return Ok(error_response(
+                StatusCode::UNAUTHORIZED,
+                "Missing or invalid X-API-KEY header",
+            ));
         }
     }
 
@@ -133,12 +133,12 @@ pub async fn handle_control_plane_request(
         handle_data_manager_request(req, db, cache).await
     } else if path.starts_with("/apify/admin/auth") {
         handle_auth_request(req, db).await
+    } else if path.starts_with("/apify/admin/migrations") {
+        handle_migrations_request(req, db).await
     } else if path == "/apify/admin/import" {
         handle_import_request(req, db).await
     } else {
-        Ok(hyper::Response::builder()
-            .status(hyper::StatusCode::NOT_FOUND)
-            .body(Full::new(Bytes::from("Not Found")))?)
+        Ok(Problem::new(StatusCode::NOT_FOUND).into_response())
     }
 }
 
@@ -170,20 +170,21 @@ pub async fn start_control_plane_server(
                         let config = config_clone.clone();
                         let cache = cache_clone.clone();
                         async move {
-                            match handle_control_plane_request(req, &db, &config, &cache).await {
-                                Ok(res) => Ok::<_, hyper::Error>(res),
+                            let path = req.uri().path().to_string();
+                            let request_id = RequestId::from_headers(req.headers());
+                            let res = match handle_control_plane_request(req, &db, &config, &cache)
+                                .await
+                            {
+                                Ok(res) => res,
                                 Err(e) => {
                                     tracing::error!("Internal server error: {}", e);
-                                    let res = hyper::Response::builder()
-                                        .status(hyper::StatusCode::INTERNAL_SERVER_ERROR)
-                                        .body(Full::new(Bytes::from(format!(
-                                            "Internal Server Error: {}",
-                                            e
-                                        ))))
-                                        .unwrap();
-                                    Ok(res)
+                                    error_response(
+                                        StatusCode::INTERNAL_SERVER_ERROR,
+                                        &e.to_string(),
+                                    )
                                 }
-                            }
+                            };
+                            Ok::<_, hyper::Error>(stamp_response(res, &path, &request_id).await)
                         }
                     }),
                 )
@@ -1,19 +1,62 @@
 //! CRUD operation handlers
 
-use crate::api_generator::{APIGenerator, OperationType, RoutePattern};
-use crate::database::DatabaseManager;
+use crate::api_generator::{APIGenerator, OperationType, PaginationStyle, RoutePattern};
+use crate::database::{
+    AggregateQuery, BulkInsert, DatabaseManager, Filter, FilterOperator, Keyset, SelectQuery,
+    SortKey,
+};
 use crate::modules::ConsumerIdentity;
 use crate::phases::RequestContext;
+use crate::query::FieldSelection;
+use crate::schema_generator::{ColumnDefinition, OnDelete, RelationDefinition, TableSchema};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use sqlx::types::chrono::Utc;
 use std::collections::HashMap;
 
+/// How an update body applies to the stored record
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UpdateMode {
+    /// PUT: the body is the whole record
+    Replace,
+    /// PATCH with a JSON (merge patch) body: only the given fields change
+    Merge,
+    /// PATCH with a JSON Patch document
+    JsonPatch,
+}
+
+impl UpdateMode {
+    fn for_request(pattern: &RoutePattern, ctx: &RequestContext) -> Self {
+        if matches!(pattern.operation_type, OperationType::Replace) {
+            return Self::Replace;
+        }
+        let is_json_patch = ctx
+            .headers
+            .get(hyper::header::CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .and_then(|ct| ct.split(';').next())
+            .is_some_and(|ct| {
+                ct.trim()
+                    .eq_ignore_ascii_case(crate::patch::JSON_PATCH_MEDIA_TYPE)
+            });
+        if is_json_patch {
+            Self::JsonPatch
+        } else {
+            Self::Merge
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum CRUDError {
     DatabaseError(crate::database::DatabaseError),
     ValidationError(String),
     NotFoundError(String),
     InvalidParameterError(String),
+    PreconditionFailedError(String),
+    ConflictError(String),
 }
 
 impl std::fmt::Display for CRUDError {
@@ -23,6 +66,8 @@ impl std::fmt::Display for CRUDError {
             CRUDError::ValidationError(err) => write!(f, "Validation error: {err}"),
             CRUDError::NotFoundError(err) => write!(f, "Not found: {err}"),
             CRUDError::InvalidParameterError(err) => write!(f, "Invalid parameter: {err}"),
+            CRUDError::PreconditionFailedError(err) => write!(f, "Precondition failed: {err}"),
+            CRUDError::ConflictError(err) => write!(f, "Conflict: {err}"),
         }
     }
 }
@@ -35,9 +80,19 @@ impl From<crate::database::DatabaseError> for CRUDError {
     }
 }
 
+/// Maximum number of keys bound in one `IN (...)` when loading relations
+const RELATION_BATCH_SIZE: usize = 500;
+
+/// Page size of cursor-paginated lists requested without a `limit`
+const DEFAULT_CURSOR_LIMIT: u32 = 50;
+
+/// Largest page of a cursor-paginated list; greater limits are lowered to it
+const MAX_CURSOR_LIMIT: u32 = 1000;
+
 #[derive(Debug)]
 pub struct CRUDHandler {
     db_manager: DatabaseManager,
+    route_databases: HashMap<String, DatabaseManager>, // path_pattern -> datasource backend
     pub api_generator: APIGenerator,
 }
 
@@ -57,10 +112,32 @@ impl CRUDHandler {
     pub fn new(db_manager: DatabaseManager, api_generator: APIGenerator) -> Self {
         Self {
             db_manager,
+            route_databases: HashMap::new(),
+            api_generator,
+        }
+    }
+
+    /// Create a handler whose routes are served by different datasources.
+    /// Routes missing from `route_databases` fall back to `db_manager`.
+    pub fn with_route_databases(
+        db_manager: DatabaseManager,
+        route_databases: HashMap<String, DatabaseManager>,
+        api_generator: APIGenerator,
+    ) -> Self {
+        Self {
+            db_manager,
+            route_databases,
             api_generator,
         }
     }
 
+    /// Resolve the database backing a matched route pattern
+    fn database_for(&self, pattern: &RoutePattern) -> &DatabaseManager {
+        self.route_databases
+            .get(&pattern.path_pattern)
+            .unwrap_or(&self.db_manager)
+    }
+
     /// Handle CRUD operations based on route pattern
     pub async fn handle_request(
         &self,
@@ -79,19 +156,92 @@ impl CRUDHandler {
                 CRUDError::NotFoundError(format!("No matching route for {} {}", method, path))
             })?;
 
+        let db = self.database_for(&pattern);
+
         match pattern.operation_type {
-            OperationType::List => self.handle_list(&pattern, query_params).await,
-            OperationType::Get => self.handle_get(&pattern, path_params).await,
-            OperationType::Create => self.handle_create(&pattern, body, ctx).await,
-            OperationType::Update => self.handle_update(&pattern, path_params, body, ctx).await,
-            OperationType::Delete => self.handle_delete(&pattern, path_params).await,
+            OperationType::List => self.handle_list(db, &pattern, path, query_params).await,
+            OperationType::Aggregate => self.handle_aggregate(db, &pattern, query_params).await,
+            OperationType::Get => {
+                self.handle_get(db, &pattern, path_params, query_params)
+                    .await
+            }
+            // Writes touch the record and its nested relations; run them atomically
+            OperationType::Create | OperationType::Upsert => {
+                let on_conflict = self.conflict_columns(&pattern, &query_params)?;
+                let tx = db.begin().await?;
+                let result = match (body, on_conflict) {
+                    (Some(Value::Array(items)), on_conflict) => {
+                        self.handle_bulk_create(&tx, &pattern, items, on_conflict, ctx)
+                            .await
+                    }
+                    (Some(item), Some(on_conflict)) => {
+                        self.handle_upsert(&tx, &pattern, item, on_conflict, ctx)
+                            .await
+                    }
+                    (body, _) => self.handle_create(&tx, &pattern, body, ctx).await,
+                };
+                Self::finish_transaction(tx, result).await
+            }
+            OperationType::Replace | OperationType::Update => {
+                let mode = UpdateMode::for_request(&pattern, ctx);
+                let tx = db.begin().await?;
+                let result = self
+                    .handle_update(&tx, &pattern, path_params, body, mode, ctx)
+                    .await;
+                Self::finish_transaction(tx, result).await
+            }
+            OperationType::Delete => {
+                let tx = db.begin().await?;
+                let result = self.handle_delete(&tx, &pattern, path_params, ctx).await;
+                Self::finish_transaction(tx, result).await
+            }
+            OperationType::BulkUpdate => {
+                let tx = db.begin().await?;
+                let result = self
+                    .handle_bulk_update(&tx, &pattern, query_params, body, ctx)
+                    .await;
+                Self::finish_transaction(tx, result).await
+            }
+            OperationType::BulkDelete => {
+                let tx = db.begin().await?;
+                let result = self
+                    .handle_bulk_delete(&tx, &pattern, query_params, ctx)
+                    .await;
+                Self::finish_transaction(tx, result).await
+            }
+            OperationType::Restore => {
+                let tx = db.begin().await?;
+                let result = self.handle_restore(&tx, &pattern, path_params).await;
+                Self::finish_transaction(tx, result).await
+            }
+        }
+    }
+
+    /// Commit the transaction of a successful write, roll it back otherwise
+    async fn finish_transaction(
+        tx: DatabaseManager,
+        result: Result<Value, CRUDError>,
+    ) -> Result<Value, CRUDError> {
+        match result {
+            Ok(value) => {
+                tx.commit().await?;
+                Ok(value)
+            }
+            Err(err) => {
+                if let Err(rollback_err) = tx.rollback().await {
+                    tracing::error!(error = %rollback_err, "Failed to roll back transaction");
+                }
+                Err(err)
+            }
         }
     }
 
     /// Handle GET /table (list all records)
     async fn handle_list(
         &self,
+        db: &DatabaseManager,
         pattern: &RoutePattern,
+        path: &str,
         query_params: HashMap<String, String>,
     ) -> Result<Value, CRUDError> {
         let table = &pattern.table_name;
@@ -104,70 +254,238 @@ impl CRUDHandler {
             .get("offset")
             .and_then(|s| s.parse::<u32>().ok());
 
-        // Extract filter parameters (exclude pagination params)
-        let mut where_clause = HashMap::new();
-        for (key, value) in query_params {
-            if key != "limit" && key != "offset" {
-                where_clause.insert(key, Value::String(value));
-            }
+        // Compile the remaining parameters into filter conditions and ordering
+        let table_schema = self.api_generator.get_table_schema(table);
+        let mut filters = crate::query::parse_filters(&query_params, table_schema)
+            .map_err(CRUDError::InvalidParameterError)?;
+        if !Self::with_deleted(&query_params)? {
+            filters.extend(self.live_filter(table));
         }
+        let search =
+            crate::query::parse_search(query_params.get("q").map(|s| s.as_str()), table_schema)
+                .map_err(CRUDError::InvalidParameterError)?;
+        let fields = self.parse_fields(pattern, &query_params)?;
+        let mut columns = Self::projection(table_schema, &fields);
+
+        let cursor_paged = pattern.pagination == PaginationStyle::Cursor;
+        // Cursor pages are always bounded, so following cursors never loads the whole table
+        let limit = if cursor_paged {
+            Some(
+                limit
+                    .unwrap_or(DEFAULT_CURSOR_LIMIT)
+                    .clamp(1, MAX_CURSOR_LIMIT),
+            )
+        } else {
+            limit
+        };
+        let (sort, keyset, scope) = if cursor_paged {
+            if query_params.contains_key("sort") || query_params.contains_key("offset") {
+                return Err(CRUDError::InvalidParameterError(
+                    "sort and offset cannot be combined with cursor pagination".to_string(),
+                ));
+            }
+            // Keyset pagination: fixed ordering, rows after the cursor position
+            let (key_columns, descending) = crate::query::keyset_order(table_schema)
+                .map_err(CRUDError::InvalidParameterError)?;
+            let scope = crate::query::cursor_scope(table, &filters, search.as_ref());
+            let keyset = query_params
+                .get("cursor")
+                .map(|cursor| {
+                    crate::query::decode_cursor(cursor, &scope, key_columns.len()).map(|values| {
+                        Keyset {
+                            columns: key_columns.clone(),
+                            values,
+                            descending,
+                        }
+                    })
+                })
+                .transpose()
+                .map_err(CRUDError::InvalidParameterError)?;
+            if let Some(columns) = columns.as_mut() {
+                for column in &key_columns {
+                    if !columns.contains(column) {
+                        columns.push(column.clone());
+                    }
+                }
+            }
+            let sort = key_columns
+                .iter()
+                .map(|column| SortKey {
+                    column: column.clone(),
+                    descending,
+                })
+                .collect();
+            (sort, keyset, Some((key_columns, scope)))
+        } else {
+            if query_params.contains_key("cursor") {
+                return Err(CRUDError::InvalidParameterError(
+                    "cursor pagination is not enabled for this endpoint".to_string(),
+                ));
+            }
+            let sort = crate::query::list_sort(
+                query_params.get("sort").map(|s| s.as_str()),
+                table_schema,
+                search.as_ref(),
+            )
+            .map_err(CRUDError::InvalidParameterError)?;
+            (sort, None, None)
+        };
 
-        let results = self
-            .db_manager
-            .select(
+        let mut results = db
+            .select_query(
                 table,
-                None,
-                if where_clause.is_empty() {
-                    None
-                } else {
-                    Some(where_clause)
+                SelectQuery {
+                    columns,
+                    filters: filters.clone(),
+                    search: search.clone(),
+                    keyset,
+                    sort,
+                    // One extra row tells whether another cursor page follows
+                    limit: limit.map(|l| if cursor_paged { l.saturating_add(1) } else { l }),
+                    offset,
                 },
-                limit,
-                offset,
             )
             .await?;
+        let has_more = cursor_paged && limit.is_some_and(|l| results.len() > l as usize);
+        if has_more {
+            results.truncate(limit.unwrap_or_default() as usize);
+        }
 
-        // Load nested relations for each record
-        let table_schema = self.api_generator.get_table_schema(table);
-        if let Some(schema) = table_schema
-            && !schema.relations.is_empty()
-        {
-            let mut enriched_results = Vec::new();
-            for record in results {
-                let normalized = self.normalize_record_casing(table, record);
-                let enriched = self.load_relations_for_record(table, normalized).await?;
-                enriched_results.push(enriched);
+        // Normalize records and load nested relations for the whole page at once
+        let mut records: Vec<Value> = results
+            .into_iter()
+            .map(|record| self.normalize_record_casing(table, record))
+            .collect();
+        let next_cursor = match (&scope, records.last()) {
+            (Some((key_columns, scope)), Some(last)) if has_more => {
+                let values: Vec<Value> = key_columns
+                    .iter()
+                    .map(|c| last.get(c).cloned().unwrap_or(Value::Null))
+                    .collect();
+                Some(crate::query::encode_cursor(&values, scope))
             }
-            return Ok(Value::Array(enriched_results));
+            _ => None,
+        };
+        self.load_relations(db, table, &mut records, &fields)
+            .await?;
+        for record in &mut records {
+            fields.retain_root(record);
         }
 
-        // Normalize results even if no relations
-        let normalized_results = results
-            .into_iter()
-            .map(|r| self.normalize_record_casing(table, r))
+        match pattern.pagination {
+            PaginationStyle::None => return Ok(Value::Array(records)),
+            PaginationStyle::Cursor => {
+                return Ok(serde_json::json!({
+                    "data": records,
+                    "limit": limit,
+                    "next_cursor": next_cursor,
+                }));
+            }
+            PaginationStyle::Envelope | PaginationStyle::Headers => {}
+        }
+
+        // Paginated shape: count every matching row and link the following page
+        let total = db.count_search(table, filters, search).await?;
+        let offset = offset.unwrap_or(0);
+        let next = limit
+            .filter(|l| u64::from(offset) + u64::from(*l) < total)
+            .map(|l| Self::page_url(path, &query_params, offset + l));
+        Ok(serde_json::json!({
+            "data": records,
+            "total": total,
+            "limit": limit,
+            "offset": offset,
+            "next": next,
+        }))
+    }
+
+    /// Handle GET /table/_aggregate: counts, sums, averages and extremes of the rows
+    /// matching the list filters, one row per group
+    async fn handle_aggregate(
+        &self,
+        db: &DatabaseManager,
+        pattern: &RoutePattern,
+        query_params: HashMap<String, String>,
+    ) -> Result<Value, CRUDError> {
+        let table = &pattern.table_name;
+        let table_schema = self.api_generator.get_table_schema(table);
+        let (group_by, aggregates) = crate::query::parse_aggregates(&query_params, table_schema)
+            .map_err(CRUDError::InvalidParameterError)?;
+
+        let filter_params: HashMap<String, String> = query_params
+            .iter()
+            .filter(|(key, _)| !crate::query::AGGREGATE_PARAMS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
             .collect();
+        let mut filters = crate::query::parse_filters(&filter_params, table_schema)
+            .map_err(CRUDError::InvalidParameterError)?;
+        if !Self::with_deleted(&query_params)? {
+            filters.extend(self.live_filter(table));
+        }
+        let search =
+            crate::query::parse_search(query_params.get("q").map(|s| s.as_str()), table_schema)
+                .map_err(CRUDError::InvalidParameterError)?;
+
+        let aliases: Vec<String> = aggregates.iter().map(|a| a.alias()).collect();
+        let rows = db
+            .aggregate(
+                table,
+                AggregateQuery {
+                    group_by,
+                    aggregates,
+                    filters,
+                    search,
+                },
+            )
+            .await?;
+        Ok(Value::Array(
+            rows.into_iter()
+                .map(|row| {
+                    let mut row = self.normalize_record_casing(table, row);
+                    // Postgres folds the unquoted aliases to lower case
+                    if let Value::Object(map) = &mut row {
+                        for alias in &aliases {
+                            if !map.contains_key(alias)
+                                && let Some(value) = map.remove(&alias.to_lowercase())
+                            {
+                                map.insert(alias.clone(), value);
+                            }
+                        }
+                    }
+                    row
+                })
+                .collect(),
+        ))
+    }
 
-        Ok(Value::Array(normalized_results))
+    /// URL of the list page starting at `offset`, keeping the other query parameters
+    fn page_url(path: &str, query_params: &HashMap<String, String>, offset: u32) -> String {
+        let mut keys: Vec<&String> = query_params.keys().filter(|k| *k != "offset").collect();
+        keys.sort();
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        for key in keys {
+            query.append_pair(key, &query_params[key]);
+        }
+        query.append_pair("offset", &offset.to_string());
+        format!("{}?{}", path, query.finish())
     }
 
     /// Handle GET /table/{id} (get single record)
     async fn handle_get(
         &self,
+        db: &DatabaseManager,
         pattern: &RoutePattern,
         path_params: HashMap<String, String>,
+        query_params: HashMap<String, String>,
     ) -> Result<Value, CRUDError> {
         let table = &pattern.table_name;
+        let fields = self.parse_fields(pattern, &query_params)?;
 
-        // Use the first path parameter as the primary key
-        let id_param = path_params
-            .keys()
-            .next()
-            .ok_or_else(|| CRUDError::InvalidParameterError("No ID parameter found".to_string()))?;
-        let id_value = path_params.get(id_param).ok_or_else(|| {
-            CRUDError::InvalidParameterError("ID parameter value not found".to_string())
-        })?;
-
-        let id_json = Self::coerce_string_to_json_value(id_value);
+        let key = self.record_key(pattern, &path_params)?;
+        let mut where_clause: HashMap<String, Value> = key.iter().cloned().collect();
+        if !Self::with_deleted(&query_params)? {
+            self.exclude_deleted(table, &mut where_clause);
+        }
 
         // Check if this table has relations
         let table_schema = self.api_generator.get_table_schema(table);
@@ -175,28 +493,40 @@ impl CRUDHandler {
             && !schema.relations.is_empty()
         {
             // Use fetch_with_relations to get record with nested data
-            return self.fetch_with_relations(table, id_json).await;
+            let mut record = self
+                .fetch_with_relations(db, table, where_clause, &fields)
+                .await?;
+            fields.retain_root(&mut record);
+            return Ok(record);
         }
 
         // No relations, use regular select
-        let mut where_clause = HashMap::new();
-        where_clause.insert(id_param.clone(), id_json);
-
-        let results = self
-            .db_manager
-            .select(table, None, Some(where_clause), Some(1), None)
+        let results = db
+            .select(
+                table,
+                Self::projection(table_schema, &fields),
+                Some(where_clause),
+                Some(1),
+                None,
+            )
             .await?;
 
         let record = results.into_iter().next().ok_or_else(|| {
-            CRUDError::NotFoundError(format!("Record with {} = {} not found", id_param, id_value))
+            CRUDError::NotFoundError(format!(
+                "Record with {} not found",
+                Self::describe_key(&key)
+            ))
         })?;
 
-        Ok(self.normalize_record_casing(table, record))
+        let mut record = self.normalize_record_casing(table, record);
+        fields.retain_root(&mut record);
+        Ok(record)
     }
 
     /// Handle POST /table (create new record)
     async fn handle_create(
         &self,
+        db: &DatabaseManager,
         pattern: &RoutePattern,
         body: Option<Value>,
         ctx: &RequestContext,
@@ -219,6 +549,7 @@ impl CRUDHandler {
         let table_schema = self.api_generator.get_table_schema(table);
         let mut nested_relations: Vec<(String, Vec<Value>)> = Vec::new();
         let mut nested_single_relations: Vec<(String, Value)> = Vec::new(); // For hasOne
+        let mut many_relations: Vec<(String, Vec<Value>)> = Vec::new(); // For belongsToMany
 
         if let Some(schema) = table_schema {
             tracing::debug!(
@@ -261,30 +592,17 @@ impl CRUDHandler {
                         // The foreign key should already be in the data
                         data_map.remove(&relation.field_name);
                     }
-                    _ => {}
-                }
-            }
-
-            // Inject audit fields for create operation
-            let now = Utc::now().to_rfc3339();
-            for col in &schema.columns {
-                if col.auto_field {
-                    match col.name.as_str() {
-                        "createdBy" | "updatedBy" | "created_by" | "updated_by" => {
-                            if let Some(identity) = ctx.extensions.get::<ConsumerIdentity>() {
-                                data_map
-                                    .insert(col.name.clone(), Value::String(identity.name.clone()));
-                            }
-                        }
-                        "createdAt" | "updatedAt" | "created_at" | "updated_at" => {
-                            if !data_map.contains_key(&col.name) {
-                                data_map.insert(col.name.clone(), Value::String(now.clone()));
-                            }
+                    crate::schema_generator::RelationType::BelongsToMany => {
+                        // Ids or objects to link through the join table
+                        if let Some(Value::Array(items)) = data_map.remove(&relation.field_name) {
+                            many_relations.push((relation.field_name.clone(), items));
                         }
-                        _ => {}
                     }
                 }
             }
+
+            // Inject audit fields for create operation
+            self.apply_create_audit_fields(table, &mut data_map, ctx);
         }
 
         // Convert serde_json::Map to HashMap<String, Value>
@@ -294,7 +612,8 @@ impl CRUDHandler {
         }
 
         // Insert main record
-        let result = self.db_manager.insert(table, data_hashmap).await?;
+        let submitted = data_hashmap.clone();
+        let result = db.insert(table, data_hashmap).await?;
 
         // Normalize the returned record
         let result = if let Value::Object(mut map) = result {
@@ -307,7 +626,9 @@ impl CRUDHandler {
             result
         };
 
-        let has_nested_data = !nested_relations.is_empty() || !nested_single_relations.is_empty();
+        let has_nested_data = !nested_relations.is_empty()
+            || !nested_single_relations.is_empty()
+            || !many_relations.is_empty();
 
         tracing::info!(
             table = %table,
@@ -317,14 +638,16 @@ impl CRUDHandler {
             "Record inserted"
         );
 
-        // Get the inserted ID for nested relations
-        let inserted_id = result.get("id").or_else(|| result.get("last_insert_rowid"));
+        // Get the inserted key for nested relations
+        let inserted_key = self.inserted_key(table, &submitted, &result);
 
         // Handle hasMany nested relations
-        if !nested_relations.is_empty() || !nested_single_relations.is_empty() {
-            if let Some(parent_id) = inserted_id {
-                tracing::info!(parent_id = ?parent_id, "Processing nested relations");
+        if has_nested_data {
+            if let Some(parent_key) = &inserted_key {
+                tracing::info!(parent_key = ?parent_key, "Processing nested relations");
                 let schema = self.api_generator.get_table_schema(table).unwrap();
+                // Children reference the parent's local key, which may not be its primary key
+                let parent = self.find_record(db, table, parent_key).await?;
 
                 // Process hasMany relations (arrays)
                 for (field_name, items) in nested_relations {
@@ -340,6 +663,8 @@ impl CRUDHandler {
                             "Inserting hasMany nested items"
                         );
 
+                        let parent_id = self.local_value(table, relation, &parent)?;
+
                         // Insert each nested item
                         for mut item in items {
                             if let Value::Object(ref mut item_map) = item {
@@ -347,55 +672,19 @@ impl CRUDHandler {
                                 item_map.insert(relation.foreign_key.clone(), parent_id.clone());
 
                                 // Inject audit fields
-                                if let Some(target_schema) =
-                                    self.api_generator.get_table_schema(&relation.target_table)
-                                {
-                                    let now = Utc::now().to_rfc3339();
-                                    for col in &target_schema.columns {
-                                        if col.auto_field {
-                                            if matches!(
-                                                col.name.as_str(),
-                                                "createdBy"
-                                                    | "updatedBy"
-                                                    | "created_by"
-                                                    | "updated_by"
-                                            ) {
-                                                if let Some(identity) =
-                                                    ctx.extensions.get::<ConsumerIdentity>()
-                                                {
-                                                    item_map.insert(
-                                                        col.name.clone(),
-                                                        Value::String(identity.name.clone()),
-                                                    );
-                                                }
-                                            } else if matches!(
-                                                col.name.as_str(),
-                                                "createdAt"
-                                                    | "updatedAt"
-                                                    | "created_at"
-                                                    | "updated_at"
-                                            ) {
-                                                // Only inject if not already present
-                                                if !item_map.contains_key(&col.name) {
-                                                    item_map.insert(
-                                                        col.name.clone(),
-                                                        Value::String(now.clone()),
-                                                    );
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
+                                self.apply_create_audit_fields(
+                                    &relation.target_table,
+                                    item_map,
+                                    ctx,
+                                );
 
                                 let mut item_hashmap = HashMap::new();
                                 for (k, v) in item_map.iter() {
                                     item_hashmap.insert(k.clone(), v.clone());
                                 }
 
-                                let item_result = self
-                                    .db_manager
-                                    .insert(&relation.target_table, item_hashmap)
-                                    .await?;
+                                let item_result =
+                                    db.insert(&relation.target_table, item_hashmap).await?;
                                 tracing::info!(item_result = ?item_result, "Nested item inserted");
                             }
                         }
@@ -416,78 +705,288 @@ impl CRUDHandler {
 
                         if let Value::Object(mut item_map) = item {
                             // Inject foreign key
-                            item_map.insert(relation.foreign_key.clone(), parent_id.clone());
+                            let parent_id = self.local_value(table, relation, &parent)?;
+                            item_map.insert(relation.foreign_key.clone(), parent_id);
 
                             // Inject audit fields
-                            if let Some(target_schema) =
-                                self.api_generator.get_table_schema(&relation.target_table)
-                            {
-                                let now = Utc::now().to_rfc3339();
-                                for col in &target_schema.columns {
-                                    if col.auto_field {
-                                        if matches!(
-                                            col.name.as_str(),
-                                            "createdBy" | "updatedBy" | "created_by" | "updated_by"
-                                        ) {
-                                            if let Some(identity) =
-                                                ctx.extensions.get::<ConsumerIdentity>()
-                                            {
-                                                item_map.insert(
-                                                    col.name.clone(),
-                                                    Value::String(identity.name.clone()),
-                                                );
-                                            }
-                                        } else if matches!(
-                                            col.name.as_str(),
-                                            "createdAt" | "updatedAt" | "created_at" | "updated_at"
-                                        ) {
-                                            // Only inject if not already present
-                                            if !item_map.contains_key(&col.name) {
-                                                item_map.insert(
-                                                    col.name.clone(),
-                                                    Value::String(now.clone()),
-                                                );
-                                            }
-                                        }
-                                    }
-                                }
-                            }
+                            self.apply_create_audit_fields(
+                                &relation.target_table,
+                                &mut item_map,
+                                ctx,
+                            );
 
                             let mut item_hashmap = HashMap::new();
                             for (k, v) in item_map.iter() {
                                 item_hashmap.insert(k.clone(), v.clone());
                             }
 
-                            let item_result = self
-                                .db_manager
-                                .insert(&relation.target_table, item_hashmap)
-                                .await?;
+                            let item_result =
+                                db.insert(&relation.target_table, item_hashmap).await?;
                             tracing::info!(item_result = ?item_result, "HasOne object inserted");
                         }
                     }
                 }
 
-                // Re-fetch the record with nested data to return complete result
-                return self.fetch_with_relations(table, parent_id.clone()).await;
+                // Process belongsToMany relations (join table rows)
+                for (field_name, items) in many_relations {
+                    if let Some(relation) =
+                        schema.relations.iter().find(|r| r.field_name == field_name)
+                    {
+                        let parent_id = self.local_value(table, relation, &parent)?;
+                        self.sync_join_rows(db, relation, &parent_id, items, ctx)
+                            .await?;
+                    }
+                }
             } else {
                 tracing::warn!(
-                    "No parent ID found in insert result, cannot process nested relations"
+                    "No parent key found in insert result, cannot process nested relations"
                 );
             }
         }
 
-        Ok(result)
+        // Respond with the stored record as a GET returns it, whichever the backend
+        match inserted_key {
+            Some(key) => {
+                self.fetch_with_relations(db, table, key, &FieldSelection::default())
+                    .await
+            }
+            None => Ok(result
+                .get("record")
+                .cloned()
+                .unwrap_or_else(|| Value::Object(submitted.into_iter().collect()))),
+        }
     }
 
-    /// Fetch a record with its related data
-    async fn fetch_with_relations(&self, table: &str, id: Value) -> Result<Value, CRUDError> {
-        let mut where_clause = HashMap::new();
-        where_clause.insert("id".to_string(), id);
+    /// Handle POST /table with an array body, and upserts. Rows are written with
+    /// multi-row INSERTs, or one at a time when they carry nested relations.
+    async fn handle_bulk_create(
+        &self,
+        db: &DatabaseManager,
+        pattern: &RoutePattern,
+        items: Vec<Value>,
+        on_conflict: Option<Vec<String>>,
+        ctx: &RequestContext,
+    ) -> Result<Value, CRUDError> {
+        let table = &pattern.table_name;
+        let table_schema = self.api_generator.get_table_schema(table);
+
+        let mut rows = Vec::with_capacity(items.len());
+        for item in items {
+            let Value::Object(map) = item else {
+                return Err(CRUDError::ValidationError(
+                    "Each record must be a JSON object".to_string(),
+                ));
+            };
+            rows.push(map);
+        }
+
+        // Nested writes need the key of each parent, so those rows take the single-record path
+        let nested_fields: Vec<&str> = table_schema
+            .map(|schema| {
+                schema
+                    .relations
+                    .iter()
+                    .filter(|r| {
+                        !matches!(
+                            r.relation_type,
+                            crate::schema_generator::RelationType::BelongsTo
+                        )
+                    })
+                    .map(|r| r.field_name.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if rows
+            .iter()
+            .any(|row| nested_fields.iter().any(|field| row.contains_key(*field)))
+        {
+            if on_conflict.is_some() {
+                return Err(CRUDError::ValidationError(
+                    "Nested relations cannot be combined with on_conflict".to_string(),
+                ));
+            }
+            let mut created = 0u64;
+            for row in rows {
+                self.handle_create(db, pattern, Some(Value::Object(row)), ctx)
+                    .await?;
+                created += 1;
+            }
+            return Ok(
+                serde_json::json!({"message": "Records inserted", "affected_rows": created}),
+            );
+        }
+
+        // The version is never taken from the client: new rows get the column default
+        // and updated rows have it incremented
+        let version_column = self.version_column(table);
+        // Rows are grouped by their columns so that omitted columns keep their defaults
+        let mut groups: Vec<(Vec<String>, Vec<Vec<Value>>)> = Vec::new();
+        for mut row in rows {
+            if let Some(schema) = table_schema {
+                for relation in &schema.relations {
+                    row.remove(&relation.field_name);
+                }
+            }
+            self.apply_create_audit_fields(table, &mut row, ctx);
+            let mut row = row
+                .into_iter()
+                .map(|(column, value)| Ok((self.insert_column(table, &column)?, value)))
+                .collect::<Result<Vec<(String, Value)>, CRUDError>>()?;
+            row.retain(|(column, _)| Some(column) != version_column.as_ref());
+            row.sort_by(|(a, _), (b, _)| a.cmp(b));
+            let (columns, values): (Vec<String>, Vec<Value>) = row.into_iter().unzip();
+            match groups.iter_mut().find(|(c, _)| *c == columns) {
+                Some((_, rows)) => rows.push(values),
+                None => groups.push((columns, vec![values])),
+            }
+        }
+
+        let mut affected_rows = 0;
+        for (columns, rows) in groups {
+            // Conflicting rows keep their key and creation audit fields
+            let update_columns = match &on_conflict {
+                Some(conflict) => columns
+                    .iter()
+                    .filter(|c| !conflict.contains(c))
+                    .filter(|c| {
+                        !matches!(
+                            c.as_str(),
+                            "createdAt" | "created_at" | "createdBy" | "created_by"
+                        )
+                    })
+                    .cloned()
+                    .collect(),
+                None => Vec::new(),
+            };
+            affected_rows += db
+                .insert_many(
+                    table,
+                    BulkInsert {
+                        columns,
+                        rows,
+                        on_conflict: on_conflict.clone(),
+                        update_columns,
+                        increments: version_column.iter().cloned().collect(),
+                    },
+                )
+                .await?;
+        }
+
+        let message = if on_conflict.is_some() {
+            "Records upserted"
+        } else {
+            "Records inserted"
+        };
+        Ok(serde_json::json!({"message": message, "affected_rows": affected_rows}))
+    }
 
-        let results = self
-            .db_manager
-            .select(table, None, Some(where_clause), Some(1), None)
+    /// Handle an upsert of a single object, returning the stored record
+    async fn handle_upsert(
+        &self,
+        db: &DatabaseManager,
+        pattern: &RoutePattern,
+        item: Value,
+        on_conflict: Vec<String>,
+        ctx: &RequestContext,
+    ) -> Result<Value, CRUDError> {
+        let key: Option<HashMap<String, Value>> = on_conflict
+            .iter()
+            .map(|column| {
+                item.get(column)
+                    .filter(|v| !v.is_null())
+                    .map(|v| (column.clone(), v.clone()))
+            })
+            .collect();
+        let result = self
+            .handle_bulk_create(db, pattern, vec![item], Some(on_conflict), ctx)
             .await?;
+        // Without the conflict values (e.g. a generated id) the row cannot be looked up
+        match key {
+            Some(key) => self.find_record(db, &pattern.table_name, &key).await,
+            None => Ok(result),
+        }
+    }
+
+    /// Conflict target of an upsert: the `on_conflict=a,b` columns, else the primary
+    /// key for `PUT /table`. They must form the primary key or a unique constraint.
+    fn conflict_columns(
+        &self,
+        pattern: &RoutePattern,
+        query_params: &HashMap<String, String>,
+    ) -> Result<Option<Vec<String>>, CRUDError> {
+        let table = &pattern.table_name;
+        let requested: Vec<String> = match query_params.get("on_conflict") {
+            Some(raw) => raw
+                .split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(String::from)
+                .collect(),
+            None if matches!(pattern.operation_type, OperationType::Upsert) => {
+                self.primary_key(table)
+            }
+            None => return Ok(None),
+        };
+        if requested.is_empty() {
+            return Err(CRUDError::InvalidParameterError(
+                "on_conflict requires at least one column".to_string(),
+            ));
+        }
+
+        let columns = requested
+            .iter()
+            .map(|name| self.insert_column(table, name))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CRUDError::InvalidParameterError(e.to_string()))?;
+        let Some(schema) = self.api_generator.get_table_schema(table) else {
+            return Ok(Some(columns));
+        };
+        let same_columns =
+            |key: &[String]| key.len() == columns.len() && key.iter().all(|k| columns.contains(k));
+        let unique = same_columns(&schema.primary_key())
+            || (columns.len() == 1
+                && schema
+                    .columns
+                    .iter()
+                    .any(|c| c.unique && c.name == columns[0]))
+            || schema
+                .indexes
+                .iter()
+                .any(|index| index.unique && same_columns(&index.columns));
+        if !unique {
+            return Err(CRUDError::InvalidParameterError(format!(
+                "on_conflict columns ({}) must be the primary key or a unique constraint of {}",
+                columns.join(", "),
+                table
+            )));
+        }
+        Ok(Some(columns))
+    }
+
+    /// Column of `table` named in a bulk write, as declared in the schema
+    fn insert_column(&self, table: &str, name: &str) -> Result<String, CRUDError> {
+        match self.api_generator.get_table_schema(table) {
+            Some(schema) => schema
+                .columns
+                .iter()
+                .find(|c| c.name.eq_ignore_ascii_case(name))
+                .map(|c| c.name.clone()),
+            // Column names end up in the SQL text
+            None => crate::query::is_identifier(name).then(|| name.to_string()),
+        }
+        .ok_or_else(|| CRUDError::ValidationError(format!("Unknown column '{}'", name)))
+    }
+
+    /// Fetch a record with its related data
+    async fn fetch_with_relations(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        key: HashMap<String, Value>,
+        fields: &FieldSelection,
+    ) -> Result<Value, CRUDError> {
+        let columns = Self::projection(self.api_generator.get_table_schema(table), fields);
+        let results = db.select(table, columns, Some(key), Some(1), None).await?;
 
         let record = results
             .into_iter()
@@ -497,124 +996,348 @@ impl CRUDHandler {
         let normalized = self.normalize_record_casing(table, record);
 
         // Load relations
-        self.load_relations_for_record(table, normalized).await
+        self.load_relations_for_record(db, table, normalized, fields)
+            .await
     }
 
-    /// Load all relations for a single record
-    async fn load_relations_for_record(
+    /// Fetch the full row with the given key, without relations
+    async fn find_record(
         &self,
+        db: &DatabaseManager,
         table: &str,
-        mut record: Value,
+        key: &HashMap<String, Value>,
     ) -> Result<Value, CRUDError> {
-        let table_schema = match self.api_generator.get_table_schema(table) {
-            Some(schema) => schema,
-            None => return Ok(record),
-        };
+        let results = db
+            .select(table, None, Some(key.clone()), Some(1), None)
+            .await?;
+        let record = results.into_iter().next().ok_or_else(|| {
+            let mut key: Vec<(String, Value)> = key.clone().into_iter().collect();
+            key.sort_by(|(a, _), (b, _)| a.cmp(b));
+            CRUDError::NotFoundError(format!(
+                "Record with {} not found",
+                Self::describe_key(&key)
+            ))
+        })?;
+        Ok(self.normalize_record_casing(table, record))
+    }
 
-        if table_schema.relations.is_empty() {
-            return Ok(record);
-        }
+    /// Value of a record's local key for `relation`, referenced by the related rows.
+    /// A record without one has no related rows, so NULL is an error rather than a match.
+    fn local_value(
+        &self,
+        table: &str,
+        relation: &RelationDefinition,
+        record: &Value,
+    ) -> Result<Value, CRUDError> {
+        let local_key = self.local_key(table, relation);
+        record
+            .get(&local_key)
+            .filter(|value| !value.is_null())
+            .cloned()
+            .ok_or_else(|| {
+                CRUDError::ValidationError(format!(
+                    "Record has no {} for relation '{}'",
+                    local_key, relation.field_name
+                ))
+            })
+    }
 
-        let record_obj = match record.as_object_mut() {
-            Some(obj) => obj,
-            None => return Ok(record),
-        };
+    /// Load all relations for a single record
+    async fn load_relations_for_record(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        record: Value,
+        fields: &FieldSelection,
+    ) -> Result<Value, CRUDError> {
+        let mut records = [record];
+        self.load_relations(db, table, &mut records, fields).await?;
+        let [record] = records;
+        Ok(record)
+    }
 
-        let record_id = match record_obj.get("id") {
-            Some(id) => id.clone(),
-            None => return Ok(record),
+    /// Load the relations of a batch of records with one query per relation
+    /// (`WHERE key IN (...)`), then stitch the related rows into each record
+    async fn load_relations(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        records: &mut [Value],
+        fields: &FieldSelection,
+    ) -> Result<(), CRUDError> {
+        let Some(table_schema) = self.api_generator.get_table_schema(table) else {
+            return Ok(());
         };
+        if table_schema.relations.is_empty() || records.is_empty() {
+            return Ok(());
+        }
 
         for relation in &table_schema.relations {
+            if !fields.includes_relation(&relation.field_name) {
+                continue;
+            }
+            // Related rows get their own fieldset and nested includes
+            let nested = fields.nested(&relation.field_name);
+            let columns = Self::projection(
+                self.api_generator.get_table_schema(&relation.target_table),
+                &nested,
+            );
+            let local_key = self.local_key(table, relation);
+            let local_values: Vec<Value> = records
+                .iter()
+                .filter_map(|record| record.get(&local_key))
+                .filter(|value| !value.is_null())
+                .cloned()
+                .collect();
+            let target_key = self.primary_key(&relation.target_table).remove(0);
             match relation.relation_type {
-                crate::schema_generator::RelationType::HasMany => {
-                    // Query child records
-                    let mut where_clause = HashMap::new();
-                    where_clause.insert(relation.foreign_key.clone(), record_id.clone());
-
+                crate::schema_generator::RelationType::HasMany
+                | crate::schema_generator::RelationType::HasOne => {
+                    // Child rows of every record, grouped by their foreign key
+                    let children = self
+                        .select_related(
+                            db,
+                            &relation.target_table,
+                            &relation.foreign_key,
+                            local_values,
+                            columns,
+                        )
+                        .await?;
                     let children = self
-                        .db_manager
-                        .select(&relation.target_table, None, Some(where_clause), None, None)
-                        .await
-                        .unwrap_or_else(|_| Vec::new());
+                        .expand_related(db, &relation.target_table, children, &nested)
+                        .await?;
+                    let mut by_parent: HashMap<String, Vec<Value>> = HashMap::new();
+                    for (key, child) in children {
+                        by_parent.entry(key).or_default().push(child);
+                    }
 
-                    let normalized_children: Vec<Value> = children
+                    for record in records.iter_mut() {
+                        let Some(id) = record.get(&local_key).map(Self::key_string) else {
+                            continue;
+                        };
+                        let children = by_parent.get(&id).cloned().unwrap_or_default();
+                        let value = match relation.relation_type {
+                            crate::schema_generator::RelationType::HasMany => {
+                                Value::Array(children)
+                            }
+                            _ => children.into_iter().next().unwrap_or(Value::Null),
+                        };
+                        if let Some(obj) = record.as_object_mut() {
+                            obj.insert(relation.field_name.clone(), value);
+                        }
+                    }
+                }
+                crate::schema_generator::RelationType::BelongsTo => {
+                    // Parents referenced by the records' foreign keys
+                    let foreign_ids: Vec<Value> = records
+                        .iter()
+                        .filter_map(|record| record.get(&relation.foreign_key))
+                        .filter(|id| !id.is_null())
+                        .cloned()
+                        .collect();
+                    let parents = self
+                        .select_related(
+                            db,
+                            &relation.target_table,
+                            &target_key,
+                            foreign_ids,
+                            columns,
+                        )
+                        .await?;
+                    let parents: HashMap<String, Value> = self
+                        .expand_related(db, &relation.target_table, parents, &nested)
+                        .await?
                         .into_iter()
-                        .map(|c| self.normalize_record_casing(&relation.target_table, c))
                         .collect();
 
-                    record_obj.insert(
-                        relation.field_name.clone(),
-                        Value::Array(normalized_children),
-                    );
+                    for record in records.iter_mut() {
+                        let Some(obj) = record.as_object_mut() else {
+                            continue;
+                        };
+                        if let Some(foreign_id) = obj.get(&relation.foreign_key) {
+                            let parent = parents
+                                .get(&Self::key_string(foreign_id))
+                                .cloned()
+                                .unwrap_or(Value::Null);
+                            obj.insert(relation.field_name.clone(), parent);
+                        }
+                    }
                 }
-                crate::schema_generator::RelationType::HasOne => {
-                    // Query single related record
-                    let mut where_clause = HashMap::new();
-                    where_clause.insert(relation.foreign_key.clone(), record_id.clone());
-
-                    let related = self
-                        .db_manager
-                        .select(
+                crate::schema_generator::RelationType::BelongsToMany => {
+                    let (Some(through), Some(other_key)) = (&relation.through, &relation.other_key)
+                    else {
+                        continue;
+                    };
+                    // Join rows of every record, then the linked targets
+                    let links = self
+                        .select_related(
+                            db,
+                            through,
+                            &relation.foreign_key,
+                            local_values,
+                            Some(vec![other_key.clone()]),
+                        )
+                        .await?;
+                    let target_ids: Vec<Value> = links
+                        .iter()
+                        .filter_map(|(_, link)| link.get(other_key).cloned())
+                        .collect();
+                    let targets = self
+                        .select_related(
+                            db,
                             &relation.target_table,
-                            None,
-                            Some(where_clause),
-                            Some(1),
-                            None,
+                            &target_key,
+                            target_ids,
+                            columns,
                         )
-                        .await
-                        .unwrap_or_else(|_| Vec::new());
+                        .await?;
+                    let targets: HashMap<String, Value> = self
+                        .expand_related(db, &relation.target_table, targets, &nested)
+                        .await?
+                        .into_iter()
+                        .collect();
+                    let mut by_parent: HashMap<String, Vec<(String, Value)>> = HashMap::new();
+                    for (key, link) in links {
+                        if let Some(target_id) = link.get(other_key).map(Self::key_string)
+                            && let Some(target) = targets.get(&target_id)
+                        {
+                            by_parent
+                                .entry(key)
+                                .or_default()
+                                .push((target_id, target.clone()));
+                        }
+                    }
 
-                    if let Some(related_record) = related.into_iter().next() {
-                        let normalized =
-                            self.normalize_record_casing(&relation.target_table, related_record);
-                        record_obj.insert(relation.field_name.clone(), normalized);
-                    } else {
-                        record_obj.insert(relation.field_name.clone(), Value::Null);
+                    for record in records.iter_mut() {
+                        let Some(id) = record.get(&local_key).map(Self::key_string) else {
+                            continue;
+                        };
+                        let mut related = by_parent.remove(&id).unwrap_or_default();
+                        related.sort_by(|(a, _), (b, _)| Self::compare_keys(a, b));
+                        let related = related.into_iter().map(|(_, target)| target).collect();
+                        if let Some(obj) = record.as_object_mut() {
+                            obj.insert(relation.field_name.clone(), Value::Array(related));
+                        }
                     }
                 }
-                crate::schema_generator::RelationType::BelongsTo => {
-                    // Get foreign key value from current record
-                    if let Some(foreign_id) = record_obj.get(&relation.foreign_key) {
-                        let mut where_clause = HashMap::new();
-                        where_clause.insert("id".to_string(), foreign_id.clone());
-
-                        let parent = self
-                            .db_manager
-                            .select(
-                                &relation.target_table,
-                                None,
-                                Some(where_clause),
-                                Some(1),
-                                None,
-                            )
-                            .await
-                            .unwrap_or_else(|_| Vec::new());
-
-                        if let Some(parent_record) = parent.into_iter().next() {
-                            let normalized =
-                                self.normalize_record_casing(&relation.target_table, parent_record);
-                            record_obj.insert(relation.field_name.clone(), normalized);
-                        } else {
-                            record_obj.insert(relation.field_name.clone(), Value::Null);
-                        }
-                    }
-                }
-                _ => {
-                    // BelongsToMany not implemented yet
-                }
             }
         }
 
-        Ok(record)
+        Ok(())
+    }
+
+    /// Load the included relations of related rows, then trim them to their fieldset
+    async fn expand_related(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        rows: Vec<(String, Value)>,
+        fields: &FieldSelection,
+    ) -> Result<Vec<(String, Value)>, CRUDError> {
+        let (keys, mut rows): (Vec<String>, Vec<Value>) = rows.into_iter().unzip();
+        Box::pin(self.load_relations(db, table, &mut rows, fields)).await?;
+        for row in &mut rows {
+            fields.retain_root(row);
+        }
+        Ok(keys.into_iter().zip(rows).collect())
+    }
+
+    /// Rows of `table` whose `key` column is one of `keys`, paired with that key.
+    /// `key` is selected even when `columns` leaves it out, but only returned if requested.
+    async fn select_related(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        key: &str,
+        keys: Vec<Value>,
+        columns: Option<Vec<String>>,
+    ) -> Result<Vec<(String, Value)>, CRUDError> {
+        let mut seen = std::collections::HashSet::new();
+        let keys: Vec<Value> = keys
+            .into_iter()
+            .filter(|k| seen.insert(Self::key_string(k)))
+            .collect();
+        let strip_key = columns
+            .as_ref()
+            .is_some_and(|c| !c.iter().any(|c| c.eq_ignore_ascii_case(key)));
+        let columns = columns.map(|mut c| {
+            if strip_key {
+                c.push(key.to_string());
+            }
+            c
+        });
+
+        // Stable order, matching what the per-record lookups returned
+        let sort: Vec<SortKey> = self
+            .primary_key(table)
+            .into_iter()
+            .filter(|column| self.has_column(table, column))
+            .map(|column| SortKey {
+                column,
+                descending: false,
+            })
+            .collect();
+
+        let mut related = Vec::new();
+        for chunk in keys.chunks(RELATION_BATCH_SIZE) {
+            let rows = db
+                .select_query(
+                    table,
+                    SelectQuery {
+                        columns: columns.clone(),
+                        filters: std::iter::once(Filter {
+                            column: key.to_string(),
+                            operator: FilterOperator::In,
+                            value: Value::Array(chunk.to_vec()),
+                        })
+                        .chain(self.live_filter(table))
+                        .collect(),
+                        sort: sort.clone(),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            for row in rows {
+                let mut row = self.normalize_record_casing(table, row);
+                let Some(obj) = row.as_object_mut() else {
+                    continue;
+                };
+                let Some(value) = (if strip_key {
+                    obj.remove(key)
+                } else {
+                    obj.get(key).cloned()
+                }) else {
+                    continue;
+                };
+                related.push((Self::key_string(&value), row));
+            }
+        }
+        Ok(related)
+    }
+
+    /// Whether `table` is known to have `column`
+    fn has_column(&self, table: &str, column: &str) -> bool {
+        self.api_generator
+            .get_table_schema(table)
+            .is_some_and(|schema| schema.columns.iter().any(|c| c.name == column))
+    }
+
+    /// Order keys numerically when both are numbers, as strings otherwise
+    fn compare_keys(a: &str, b: &str) -> std::cmp::Ordering {
+        match (a.parse::<i64>(), b.parse::<i64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            _ => a.cmp(b),
+        }
     }
 
     /// Handle PUT/PATCH /table/{id} (update record)
     async fn handle_update(
         &self,
+        db: &DatabaseManager,
         pattern: &RoutePattern,
         path_params: HashMap<String, String>,
         body: Option<Value>,
+        mode: UpdateMode,
         ctx: &RequestContext,
     ) -> Result<Value, CRUDError> {
         let table = &pattern.table_name;
@@ -622,8 +1345,28 @@ impl CRUDHandler {
         let data =
             body.ok_or_else(|| CRUDError::ValidationError("Request body is required".to_string()))?;
 
-        let mut data_map = match data {
-            Value::Object(map) => map,
+        let key = self.record_key(pattern, &path_params)?;
+        let mut where_clause: HashMap<String, Value> = key.iter().cloned().collect();
+        self.exclude_deleted(table, &mut where_clause);
+        let version_guard = self.check_if_match(db, table, &where_clause, ctx).await?;
+        let table_schema = self.api_generator.get_table_schema(table);
+
+        let mut data_map = match (mode, data) {
+            (UpdateMode::JsonPatch, patch) => {
+                let current = self
+                    .fetch_with_relations(
+                        db,
+                        table,
+                        where_clause.clone(),
+                        &FieldSelection::default(),
+                    )
+                    .await?;
+                let mut patched = current.clone();
+                crate::patch::apply_json_patch(&mut patched, &patch)
+                    .map_err(|e| CRUDError::ValidationError(format!("JSON Patch failed: {}", e)))?;
+                Self::patched_fields(table_schema, &current, patched)?
+            }
+            (_, Value::Object(map)) => map,
             _ => {
                 return Err(CRUDError::ValidationError(
                     "Request body must be a JSON object".to_string(),
@@ -631,20 +1374,9 @@ impl CRUDHandler {
             }
         };
 
-        // Get the record ID
-        let id_param = path_params
-            .keys()
-            .next()
-            .ok_or_else(|| CRUDError::InvalidParameterError("No ID parameter found".to_string()))?;
-        let id_value = path_params.get(id_param).ok_or_else(|| {
-            CRUDError::InvalidParameterError("ID parameter value not found".to_string())
-        })?;
-        let record_id = Self::coerce_string_to_json_value(id_value);
-
         // Extract nested relations before processing main record
-        let table_schema = self.api_generator.get_table_schema(table);
         let mut nested_relations: Vec<(String, Vec<Value>)> = Vec::new();
-        let mut nested_single_relations: Vec<(String, Value)> = Vec::new();
+        let mut many_relations: Vec<(String, Vec<Value>)> = Vec::new();
 
         if let Some(schema) = &table_schema {
             for relation in &schema.relations {
@@ -662,227 +1394,705 @@ impl CRUDHandler {
                         }
                     }
                     crate::schema_generator::RelationType::HasOne => {
-                        if let Some(nested_data) = data_map.remove(&relation.field_name)
-                            && let Value::Object(_) = nested_data
-                        {
-                            tracing::info!(
-                                relation = %relation.field_name,
-                                "Extracted hasOne relation for update"
-                            );
-                            nested_single_relations
-                                .push((relation.field_name.clone(), nested_data));
-                        }
+                        // `null` removes the child
+                        let item = match data_map.remove(&relation.field_name) {
+                            Some(Value::Null) => vec![],
+                            Some(item @ Value::Object(_)) => vec![item],
+                            _ => continue,
+                        };
+                        tracing::info!(
+                            relation = %relation.field_name,
+                            "Extracted hasOne relation for update"
+                        );
+                        nested_relations.push((relation.field_name.clone(), item));
                     }
                     crate::schema_generator::RelationType::BelongsTo => {
                         // For belongsTo, just remove the nested object
                         data_map.remove(&relation.field_name);
                     }
-                    _ => {}
+                    crate::schema_generator::RelationType::BelongsToMany => {
+                        if let Some(Value::Array(items)) = data_map.remove(&relation.field_name) {
+                            many_relations.push((relation.field_name.clone(), items));
+                        }
+                    }
                 }
             }
         }
 
-        // Inject audit fields for update operation
-        if let Some(schema) = &table_schema {
-            let now = Utc::now().to_rfc3339();
-            for col in &schema.columns {
-                if col.auto_field {
-                    if matches!(col.name.as_str(), "updatedBy" | "updated_by") {
-                        if let Some(identity) = ctx.extensions.get::<ConsumerIdentity>() {
-                            data_map.insert(col.name.clone(), Value::String(identity.name.clone()));
-                        }
-                    } else if matches!(col.name.as_str(), "updatedAt" | "updated_at")
-                        && !data_map.contains_key(&col.name)
-                    {
-                        data_map.insert(col.name.clone(), Value::String(now.clone()));
-                    }
-                }
+        match mode {
+            UpdateMode::Replace => self.reset_omitted_columns(table, &mut data_map)?,
+            UpdateMode::Merge => {
+                self.merge_json_columns(db, table, &where_clause, &mut data_map)
+                    .await?
             }
+            UpdateMode::JsonPatch => {}
         }
 
+        // Inject audit fields for update operation
+        self.apply_update_audit_fields(table, &mut data_map, ctx);
+
         // Convert serde_json::Map to HashMap<String, Value>
         let mut data_hashmap = HashMap::new();
         for (key, value) in data_map {
             data_hashmap.insert(key, value);
         }
+        // The version is only ever incremented by the update itself
+        let version_column = self.version_column(table);
+        if let Some(column) = &version_column {
+            data_hashmap.remove(column);
+        }
 
-        let mut where_clause = HashMap::new();
-        where_clause.insert(id_param.clone(), record_id.clone());
+        // The key the record has after the update, should the body change it
+        let mut updated_key = where_clause.clone();
+        for (column, value) in updated_key.iter_mut() {
+            if let Some(new_value) = data_hashmap.get(column) {
+                *value = new_value.clone();
+            }
+        }
 
-        // Update main record
-        let result = self
-            .db_manager
-            .update(table, data_hashmap, where_clause)
-            .await?;
+        // Update main record (a body holding only relations leaves it untouched)
+        let has_nested_data = !nested_relations.is_empty() || !many_relations.is_empty();
+        let result = if data_hashmap.is_empty() && version_column.is_none() {
+            // Nothing to write, but the record must still exist
+            self.find_record(db, table, &where_clause).await?;
+            serde_json::json!({"message": "Record updated", "affected_rows": 0})
+        } else {
+            let guarded = version_guard.is_some();
+            let mut filters: Vec<Filter> = where_clause
+                .into_iter()
+                .map(|(column, value)| Filter::eq(column, value))
+                .collect();
+            filters.extend(version_guard);
+            let result = self.update_rows(db, table, data_hashmap, filters).await?;
+            if guarded && result.get("affected_rows").and_then(Value::as_u64) == Some(0) {
+                return Err(CRUDError::PreconditionFailedError(
+                    "The record was modified concurrently".to_string(),
+                ));
+            }
+            result
+        };
 
         // Handle nested relation updates
-        if (!nested_relations.is_empty() || !nested_single_relations.is_empty())
-            && let Some(schema) = table_schema
-        {
-            // Process hasMany relations (replace all children)
-            for (field_name, new_items) in nested_relations {
+        if has_nested_data && let Some(schema) = table_schema {
+            let parent = self.find_record(db, table, &updated_key).await?;
+
+            // Children listed by id are updated in place, the others inserted.
+            // A merge leaves unlisted children alone; a replacement deletes them.
+            for (field_name, items) in nested_relations {
                 if let Some(relation) = schema.relations.iter().find(|r| r.field_name == field_name)
                 {
                     tracing::info!(
                         relation_field = %field_name,
                         target_table = %relation.target_table,
-                        new_item_count = new_items.len(),
-                        "Updating hasMany relation"
+                        item_count = items.len(),
+                        "Updating child relation"
                     );
+                    let record_id = self.local_value(table, relation, &parent)?;
+                    let prune = !matches!(mode, UpdateMode::Merge)
+                        || matches!(
+                            relation.relation_type,
+                            crate::schema_generator::RelationType::HasOne
+                        );
+                    self.sync_children(db, relation, &record_id, items, prune, ctx)
+                        .await?;
+                }
+            }
 
-                    // Delete existing child records
-                    let mut child_where = HashMap::new();
-                    child_where.insert(relation.foreign_key.clone(), record_id.clone());
-                    let deleted = self
-                        .db_manager
-                        .delete(&relation.target_table, child_where)
-                        .await
-                        .unwrap_or(0);
+            // Process belongsToMany relations (replace join rows)
+            for (field_name, items) in many_relations {
+                if let Some(relation) = schema.relations.iter().find(|r| r.field_name == field_name)
+                {
+                    let record_id = self.local_value(table, relation, &parent)?;
+                    self.sync_join_rows(db, relation, &record_id, items, ctx)
+                        .await?;
+                }
+            }
+        }
 
-                    tracing::info!(deleted_count = deleted, "Deleted old child records");
+        Ok(result)
+    }
 
-                    // Insert new child records
-                    for mut item in new_items {
-                        if let Value::Object(ref mut item_map) = item {
-                            item_map.insert(relation.foreign_key.clone(), record_id.clone());
+    /// Fields of a JSON-patched record that differ from the stored one.
+    /// Removed columns become NULL and removed relations empty.
+    fn patched_fields(
+        schema: Option<&TableSchema>,
+        current: &Value,
+        patched: Value,
+    ) -> Result<serde_json::Map<String, Value>, CRUDError> {
+        let Value::Object(mut patched) = patched else {
+            return Err(CRUDError::ValidationError(
+                "JSON Patch must leave the record an object".to_string(),
+            ));
+        };
+        let empty = serde_json::Map::new();
+        let current = current.as_object().unwrap_or(&empty);
+        for (field, value) in current {
+            if patched.get(field) == Some(value) {
+                patched.remove(field);
+            } else if !patched.contains_key(field) {
+                let relation = schema
+                    .and_then(|s| s.relations.iter().find(|r| &r.field_name == field))
+                    .map(|r| &r.relation_type);
+                let cleared = match relation {
+                    Some(
+                        crate::schema_generator::RelationType::HasMany
+                        | crate::schema_generator::RelationType::BelongsToMany,
+                    ) => Value::Array(vec![]),
+                    _ => Value::Null,
+                };
+                patched.insert(field.clone(), cleared);
+            }
+        }
+        Ok(patched)
+    }
 
-                            // Inject audit fields
-                            if let Some(target_schema) =
-                                self.api_generator.get_table_schema(&relation.target_table)
-                            {
-                                let now = Utc::now().to_rfc3339();
-                                for col in &target_schema.columns {
-                                    if col.auto_field {
-                                        if matches!(
-                                            col.name.as_str(),
-                                            "createdBy" | "updatedBy" | "created_by" | "updated_by"
-                                        ) {
-                                            if let Some(identity) =
-                                                ctx.extensions.get::<ConsumerIdentity>()
-                                            {
-                                                item_map.insert(
-                                                    col.name.clone(),
-                                                    Value::String(identity.name.clone()),
-                                                );
-                                            }
-                                        } else if matches!(
-                                            col.name.as_str(),
-                                            "createdAt" | "updatedAt" | "created_at" | "updated_at"
-                                        ) && !item_map.contains_key(&col.name)
-                                        {
-                                            item_map.insert(
-                                                col.name.clone(),
-                                                Value::String(now.clone()),
-                                            );
-                                        }
-                                    }
-                                }
-                            }
+    /// Columns a PUT body leaves out go back to their default (or NULL).
+    /// Keys, autoincrement and audit columns are kept.
+    fn reset_omitted_columns(
+        &self,
+        table: &str,
+        data_map: &mut serde_json::Map<String, Value>,
+    ) -> Result<(), CRUDError> {
+        let Some(schema) = self.api_generator.get_table_schema(table) else {
+            return Ok(());
+        };
+        for col in &schema.columns {
+            if col.primary_key
+                || col.auto_increment
+                || col.auto_field
+                || data_map.contains_key(&col.name)
+            {
+                continue;
+            }
+            if !col.nullable && col.default_value.is_none() {
+                return Err(CRUDError::ValidationError(format!(
+                    "Missing required column '{}'",
+                    col.name
+                )));
+            }
+            data_map.insert(col.name.clone(), Self::default_value(col));
+        }
+        Ok(())
+    }
 
-                            let mut item_hashmap = HashMap::new();
-                            for (k, v) in item_map.iter() {
-                                item_hashmap.insert(k.clone(), v.clone());
-                            }
+    /// Value of a column's SQL default, as far as it can be written back through an update
+    fn default_value(col: &ColumnDefinition) -> Value {
+        let Some(default) = col.default_value.as_deref().map(str::trim) else {
+            return Value::Null;
+        };
+        if default.eq_ignore_ascii_case("null") {
+            return Value::Null;
+        }
+        if default.eq_ignore_ascii_case("current_timestamp")
+            || default.eq_ignore_ascii_case("now()")
+        {
+            return Value::String(Utc::now().to_rfc3339());
+        }
+        if let Some(text) = default
+            .strip_prefix('\'')
+            .and_then(|d| d.strip_suffix('\''))
+        {
+            return Value::String(text.replace("''", "'"));
+        }
+        serde_json::from_str::<Value>(&default.to_ascii_lowercase())
+            .ok()
+            .filter(|v| v.is_number() || v.is_boolean())
+            .unwrap_or_else(|| Value::String(default.to_string()))
+    }
 
-                            self.db_manager
-                                .insert(&relation.target_table, item_hashmap)
-                                .await?;
-                        }
+    /// Merge object values into JSON columns instead of overwriting them (RFC 7396)
+    async fn merge_json_columns(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        key: &HashMap<String, Value>,
+        data_map: &mut serde_json::Map<String, Value>,
+    ) -> Result<(), CRUDError> {
+        if !data_map.values().any(Value::is_object) {
+            return Ok(());
+        }
+        let current = self.find_record(db, table, key).await?;
+        for (column, value) in data_map.iter_mut() {
+            if !value.is_object() {
+                continue;
+            }
+            // JSON columns come back as text from drivers without a JSON type
+            let mut stored = match current.get(column) {
+                Some(Value::String(text)) => serde_json::from_str(text).unwrap_or(Value::Null),
+                Some(stored) => stored.clone(),
+                None => continue,
+            };
+            crate::patch::merge_patch(&mut stored, value);
+            *value = stored;
+        }
+        Ok(())
+    }
+
+    /// Bring the hasMany/hasOne children of a record in line with `items`: items carrying
+    /// the key of an existing child update it, the others are inserted, and with `prune`
+    /// children missing from `items` are deleted
+    async fn sync_children(
+        &self,
+        db: &DatabaseManager,
+        relation: &RelationDefinition,
+        parent_id: &Value,
+        items: Vec<Value>,
+        prune: bool,
+        ctx: &RequestContext,
+    ) -> Result<(), CRUDError> {
+        let target = &relation.target_table;
+        let target_schema = self.api_generator.get_table_schema(target);
+        let target_key = self.primary_key(target);
+        let child_key = |record: &serde_json::Map<String, Value>| -> Option<Vec<String>> {
+            target_key
+                .iter()
+                .map(|col| {
+                    record
+                        .get(col)
+                        .filter(|v| !v.is_null())
+                        .map(Self::key_string)
+                })
+                .collect()
+        };
+
+        let existing = db
+            .select_query(
+                target,
+                SelectQuery {
+                    columns: Some(target_key.clone()),
+                    filters: std::iter::once(Filter::eq(
+                        relation.foreign_key.clone(),
+                        parent_id.clone(),
+                    ))
+                    .chain(self.live_filter(target))
+                    .collect(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let existing: Vec<(Vec<String>, HashMap<String, Value>)> = existing
+            .into_iter()
+            .filter_map(|row| {
+                let row = row.as_object()?.clone();
+                let key = child_key(&row)?;
+                Some((key, row.into_iter().collect()))
+            })
+            .collect();
+        let single = matches!(
+            relation.relation_type,
+            crate::schema_generator::RelationType::HasOne
+        );
+
+        let mut kept = Vec::new();
+        for item in items {
+            let Value::Object(mut item_map) = item else {
+                return Err(CRUDError::ValidationError(format!(
+                    "'{}' items must be objects",
+                    relation.field_name
+                )));
+            };
+            // Relations of the child (as returned by a GET) are not columns
+            if let Some(schema) = target_schema {
+                for nested in &schema.relations {
+                    item_map.remove(&nested.field_name);
+                }
+            }
+            item_map.insert(relation.foreign_key.clone(), parent_id.clone());
+
+            // A hasOne child without a key is the existing child
+            let key = child_key(&item_map).or_else(|| {
+                single
+                    .then(|| existing.first().map(|(key, _)| key.clone()))
+                    .flatten()
+            });
+            match existing.iter().find(|(k, _)| Some(k) == key.as_ref()) {
+                Some((key, where_clause)) => {
+                    for col in &target_key {
+                        item_map.remove(col);
                     }
+                    self.apply_update_audit_fields(target, &mut item_map, ctx);
+                    let data: HashMap<String, Value> = item_map.into_iter().collect();
+                    let filters = where_clause
+                        .iter()
+                        .map(|(column, value)| Filter::eq(column.clone(), value.clone()))
+                        .collect();
+                    self.update_rows(db, target, data, filters).await?;
+                    kept.push(key.clone());
+                }
+                None => {
+                    self.apply_create_audit_fields(target, &mut item_map, ctx);
+                    let data: HashMap<String, Value> = item_map.into_iter().collect();
+                    db.insert(target, data).await?;
                 }
             }
+        }
 
-            // Process hasOne relations (replace child)
-            for (field_name, new_item) in nested_single_relations {
-                if let Some(relation) = schema.relations.iter().find(|r| r.field_name == field_name)
+        if prune {
+            // Children of soft-delete tables are stamped rather than removed
+            let deleted_at = Utc::now().to_rfc3339();
+            for (key, where_clause) in existing {
+                if kept.contains(&key) {
+                    continue;
+                }
+                if self.soft_delete_column(target).is_some() {
+                    let filters = where_clause
+                        .into_iter()
+                        .map(|(column, value)| Filter::eq(column, value))
+                        .collect();
+                    self.soft_delete(db, target, filters, &deleted_at, ctx)
+                        .await?;
+                } else {
+                    db.delete(target, where_clause).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the join rows of a belongsToMany relation with links to `items`:
+    /// ids of existing target records, or objects (inserted first when they have no id)
+    async fn sync_join_rows(
+        &self,
+        db: &DatabaseManager,
+        relation: &RelationDefinition,
+        parent_id: &Value,
+        items: Vec<Value>,
+        ctx: &RequestContext,
+    ) -> Result<(), CRUDError> {
+        let (Some(through), Some(other_key)) = (&relation.through, &relation.other_key) else {
+            return Err(CRUDError::ValidationError(format!(
+                "Relation '{}' has no join table",
+                relation.field_name
+            )));
+        };
+
+        let target_key = self.primary_key(&relation.target_table).remove(0);
+        let mut target_ids = Vec::new();
+        let mut linked_ids = Vec::new();
+        for item in items {
+            let id = match item {
+                Value::Object(mut item_map) => match item_map.get(&target_key) {
+                    Some(id) if !id.is_null() => {
+                        let id = id.clone();
+                        linked_ids.push(id.clone());
+                        id
+                    }
+                    _ => {
+                        self.apply_create_audit_fields(&relation.target_table, &mut item_map, ctx);
+                        let item: HashMap<String, Value> = item_map.into_iter().collect();
+                        let result = db.insert(&relation.target_table, item.clone()).await?;
+                        self.inserted_key(&relation.target_table, &item, &result)
+                            .and_then(|mut key| key.remove(&target_key))
+                            .ok_or_else(|| {
+                                CRUDError::ValidationError(format!(
+                                    "Could not link new '{}' record",
+                                    relation.field_name
+                                ))
+                            })?
+                    }
+                },
+                id @ (Value::Number(_) | Value::String(_)) => {
+                    linked_ids.push(id.clone());
+                    id
+                }
+                _ => {
+                    return Err(CRUDError::ValidationError(format!(
+                        "'{}' must be an array of ids or objects",
+                        relation.field_name
+                    )));
+                }
+            };
+            if !target_ids.contains(&id) {
+                target_ids.push(id);
+            }
+        }
+
+        // Linking to records that do not exist would leave dangling join rows
+        if !linked_ids.is_empty() {
+            let existing = db
+                .select_query(
+                    &relation.target_table,
+                    SelectQuery {
+                        columns: Some(vec![target_key.clone()]),
+                        filters: vec![Filter {
+                            column: target_key.clone(),
+                            operator: FilterOperator::In,
+                            value: Value::Array(linked_ids.clone()),
+                        }],
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            let existing: Vec<String> = existing
+                .iter()
+                .filter_map(|r| r.get(&target_key))
+                .map(Self::key_string)
+                .collect();
+            let missing: Vec<String> = linked_ids
+                .iter()
+                .map(Self::key_string)
+                .filter(|id| !existing.contains(id))
+                .collect();
+            if !missing.is_empty() {
+                return Err(CRUDError::ValidationError(format!(
+                    "Unknown {} id(s): {}",
+                    relation.target_table,
+                    missing.join(", ")
+                )));
+            }
+        }
+
+        let mut where_clause = HashMap::new();
+        where_clause.insert(relation.foreign_key.clone(), parent_id.clone());
+        db.delete(through, where_clause).await?;
+        for id in target_ids {
+            let mut row = HashMap::new();
+            row.insert(relation.foreign_key.clone(), parent_id.clone());
+            row.insert(other_key.clone(), id);
+            db.insert(through, row).await?;
+        }
+        Ok(())
+    }
+
+    /// Key value as text, so `5` and `"5"` compare equal
+    fn key_string(value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(i), _) => i.to_string(),
+                (_, Some(u)) => u.to_string(),
+                _ => n.to_string(),
+            },
+            other => other.to_string(),
+        }
+    }
+
+    /// Fill the audit columns of a record about to be inserted into `table`
+    fn apply_create_audit_fields(
+        &self,
+        table: &str,
+        item_map: &mut serde_json::Map<String, Value>,
+        ctx: &RequestContext,
+    ) {
+        let Some(schema) = self.api_generator.get_table_schema(table) else {
+            return;
+        };
+        let now = Utc::now().to_rfc3339();
+        for col in schema.columns.iter().filter(|c| c.auto_field) {
+            match col.name.as_str() {
+                "createdBy" | "updatedBy" | "created_by" | "updated_by" => {
+                    if let Some(identity) = ctx.extensions.get::<ConsumerIdentity>() {
+                        item_map.insert(col.name.clone(), Value::String(identity.name.clone()));
+                    }
+                }
+                "createdAt" | "updatedAt" | "created_at" | "updated_at"
+                    if !item_map.contains_key(&col.name) =>
                 {
-                    tracing::info!(
-                        relation_field = %field_name,
-                        target_table = %relation.target_table,
-                        "Updating hasOne relation"
-                    );
+                    item_map.insert(col.name.clone(), Value::String(now.clone()));
+                }
+                _ => {}
+            }
+        }
+    }
 
-                    // Delete existing child record
-                    let mut child_where = HashMap::new();
-                    child_where.insert(relation.foreign_key.clone(), record_id.clone());
-                    self.db_manager
-                        .delete(&relation.target_table, child_where)
-                        .await
-                        .ok();
-
-                    // Insert new child record
-                    if let Value::Object(mut item_map) = new_item {
-                        item_map.insert(relation.foreign_key.clone(), record_id.clone());
-
-                        // Inject audit fields
-                        if let Some(target_schema) =
-                            self.api_generator.get_table_schema(&relation.target_table)
-                        {
-                            let now = Utc::now().to_rfc3339();
-                            for col in &target_schema.columns {
-                                if col.auto_field {
-                                    if matches!(
-                                        col.name.as_str(),
-                                        "createdBy" | "updatedBy" | "created_by" | "updated_by"
-                                    ) {
-                                        if let Some(identity) =
-                                            ctx.extensions.get::<ConsumerIdentity>()
-                                        {
-                                            item_map.insert(
-                                                col.name.clone(),
-                                                Value::String(identity.name.clone()),
-                                            );
-                                        }
-                                    } else if matches!(
-                                        col.name.as_str(),
-                                        "createdAt" | "updatedAt" | "created_at" | "updated_at"
-                                    ) {
-                                        // Only inject if not already present
-                                        if !item_map.contains_key(&col.name) {
-                                            item_map.insert(
-                                                col.name.clone(),
-                                                Value::String(now.clone()),
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                        }
+    /// Handle PATCH /table?filters (update every matching record)
+    async fn handle_bulk_update(
+        &self,
+        db: &DatabaseManager,
+        pattern: &RoutePattern,
+        query_params: HashMap<String, String>,
+        body: Option<Value>,
+        ctx: &RequestContext,
+    ) -> Result<Value, CRUDError> {
+        let table = &pattern.table_name;
+        let filters = self.bulk_filters(table, &query_params)?;
 
-                        let mut item_hashmap = HashMap::new();
-                        for (k, v) in item_map.iter() {
-                            item_hashmap.insert(k.clone(), v.clone());
-                        }
+        let mut data_map = match body {
+            Some(Value::Object(map)) => map,
+            Some(_) => {
+                return Err(CRUDError::ValidationError(
+                    "Request body must be a JSON object".to_string(),
+                ));
+            }
+            None => {
+                return Err(CRUDError::ValidationError(
+                    "Request body is required".to_string(),
+                ));
+            }
+        };
+        if let Some(schema) = self.api_generator.get_table_schema(table)
+            && let Some(relation) = schema
+                .relations
+                .iter()
+                .find(|r| data_map.contains_key(&r.field_name))
+        {
+            return Err(CRUDError::ValidationError(format!(
+                "Relation '{}' cannot be updated in bulk",
+                relation.field_name
+            )));
+        }
+        self.apply_update_audit_fields(table, &mut data_map, ctx);
 
-                        self.db_manager
-                            .insert(&relation.target_table, item_hashmap)
-                            .await?;
+        let data = data_map
+            .into_iter()
+            .map(|(column, value)| Ok((self.insert_column(table, &column)?, value)))
+            .collect::<Result<HashMap<String, Value>, CRUDError>>()?;
+        self.update_rows(db, table, data, filters).await
+    }
+
+    /// Handle DELETE /table?filters, cascading to the children of every matching record
+    async fn handle_bulk_delete(
+        &self,
+        db: &DatabaseManager,
+        pattern: &RoutePattern,
+        query_params: HashMap<String, String>,
+        ctx: &RequestContext,
+    ) -> Result<Value, CRUDError> {
+        let table = &pattern.table_name;
+        let filters = self.bulk_filters(table, &query_params)?;
+
+        if self.soft_delete_column(table).is_some() {
+            let deleted_at = Utc::now().to_rfc3339();
+            let affected_rows = self
+                .soft_delete(db, table, filters, &deleted_at, ctx)
+                .await?;
+            return Ok(
+                serde_json::json!({"message": "Records deleted", "affected_rows": affected_rows}),
+            );
+        }
+
+        if let Some(schema) = self.api_generator.get_table_schema(table)
+            && !schema.relations.is_empty()
+        {
+            let records: Vec<Value> = db
+                .select_query(
+                    table,
+                    SelectQuery {
+                        filters: filters.clone(),
+                        ..Default::default()
+                    },
+                )
+                .await?
+                .into_iter()
+                .map(|record| self.normalize_record_casing(table, record))
+                .collect();
+            for relation in &schema.relations {
+                // Join rows go for belongsToMany; the linked records are shared
+                let target = match relation.relation_type {
+                    crate::schema_generator::RelationType::HasMany
+                    | crate::schema_generator::RelationType::HasOne => &relation.target_table,
+                    crate::schema_generator::RelationType::BelongsToMany => {
+                        match &relation.through {
+                            Some(through) => through,
+                            None => continue,
+                        }
                     }
+                    crate::schema_generator::RelationType::BelongsTo => continue,
+                };
+                let local_values: Vec<Value> = records
+                    .iter()
+                    .filter_map(|record| self.local_value(table, relation, record).ok())
+                    .collect();
+                for chunk in local_values.chunks(RELATION_BATCH_SIZE) {
+                    self.delete_related(db, table, target, relation, chunk.to_vec(), ctx)
+                        .await?;
                 }
             }
         }
 
-        Ok(result)
+        let affected_rows = db.delete_query(table, filters).await?;
+        Ok(serde_json::json!({"message": "Records deleted", "affected_rows": affected_rows}))
+    }
+
+    /// Filters of a bulk write. At least one is required, so that a bare
+    /// `PATCH /table` or `DELETE /table` cannot touch every row.
+    fn bulk_filters(
+        &self,
+        table: &str,
+        query_params: &HashMap<String, String>,
+    ) -> Result<Vec<Filter>, CRUDError> {
+        let filters =
+            crate::query::parse_filters(query_params, self.api_generator.get_table_schema(table))
+                .map_err(CRUDError::InvalidParameterError)?;
+        if filters.is_empty() {
+            return Err(CRUDError::InvalidParameterError(
+                "Bulk updates and deletes require at least one filter".to_string(),
+            ));
+        }
+        Ok(filters.into_iter().chain(self.live_filter(table)).collect())
+    }
+
+    /// Fill the audit columns of a record about to be updated in `table`
+    fn apply_update_audit_fields(
+        &self,
+        table: &str,
+        data_map: &mut serde_json::Map<String, Value>,
+        ctx: &RequestContext,
+    ) {
+        let Some(schema) = self.api_generator.get_table_schema(table) else {
+            return;
+        };
+        let now = Utc::now().to_rfc3339();
+        for col in schema.columns.iter().filter(|c| c.auto_field) {
+            match col.name.as_str() {
+                "updatedBy" | "updated_by" => {
+                    if let Some(identity) = ctx.extensions.get::<ConsumerIdentity>() {
+                        data_map.insert(col.name.clone(), Value::String(identity.name.clone()));
+                    }
+                }
+                "updatedAt" | "updated_at" if !data_map.contains_key(&col.name) => {
+                    data_map.insert(col.name.clone(), Value::String(now.clone()));
+                }
+                _ => {}
+            }
+        }
     }
 
     /// Handle DELETE /table/{id} (delete record)
     async fn handle_delete(
         &self,
+        db: &DatabaseManager,
         pattern: &RoutePattern,
         path_params: HashMap<String, String>,
+        ctx: &RequestContext,
     ) -> Result<Value, CRUDError> {
         let table = &pattern.table_name;
 
-        // Use the first path parameter as the primary key for WHERE clause
-        let id_param = path_params
-            .keys()
-            .next()
-            .ok_or_else(|| CRUDError::InvalidParameterError("No ID parameter found".to_string()))?;
-        let id_value = path_params.get(id_param).ok_or_else(|| {
-            CRUDError::InvalidParameterError("ID parameter value not found".to_string())
-        })?;
+        let key = self.record_key(pattern, &path_params)?;
+        let where_clause: HashMap<String, Value> = key.iter().cloned().collect();
+        let mut live_where = where_clause.clone();
+        self.exclude_deleted(table, &mut live_where);
+        let version_guard = self.check_if_match(db, table, &live_where, ctx).await?;
+        let guarded = version_guard.is_some();
+        let precondition_failed = || {
+            CRUDError::PreconditionFailedError("The record was modified concurrently".to_string())
+        };
 
-        let id_json = Self::coerce_string_to_json_value(id_value);
+        // Soft-delete tables keep the row (and its children) and stamp it instead
+        if self.soft_delete_column(table).is_some() {
+            let mut filters: Vec<Filter> = where_clause
+                .into_iter()
+                .map(|(column, value)| Filter::eq(column, value))
+                .collect();
+            filters.extend(version_guard);
+            let deleted_at = Utc::now().to_rfc3339();
+            let affected_rows = self
+                .soft_delete(db, table, filters, &deleted_at, ctx)
+                .await?;
+            if affected_rows == 0 && guarded {
+                return Err(precondition_failed());
+            }
+            if affected_rows == 0 {
+                return Err(CRUDError::NotFoundError(format!(
+                    "Record with {} not found",
+                    Self::describe_key(&key)
+                )));
+            }
+            return Ok(serde_json::json!({
+                "message": "Record deleted successfully",
+                "affected_rows": affected_rows
+            }));
+        }
 
         // Check if this table has relations that need cascading delete
         let table_schema = self.api_generator.get_table_schema(table);
@@ -891,24 +2101,31 @@ impl CRUDHandler {
         {
             tracing::info!(
                 table = %table,
-                id = ?id_json,
+                key = ?key,
                 relation_count = schema.relations.len(),
                 "Checking for cascade delete"
             );
+            // Children reference the record's local keys
+            let record = self.find_record(db, table, &where_clause).await?;
 
-            // Delete child records for hasMany and hasOne relations
+            // Apply each relation's onDelete action to its child records
             for relation in &schema.relations {
+                let Ok(local_value) = self.local_value(table, relation, &record) else {
+                    continue;
+                };
                 match relation.relation_type {
                     crate::schema_generator::RelationType::HasMany
                     | crate::schema_generator::RelationType::HasOne => {
-                        let mut child_where = HashMap::new();
-                        child_where.insert(relation.foreign_key.clone(), id_json.clone());
-
                         let deleted_count = self
-                            .db_manager
-                            .delete(&relation.target_table, child_where)
-                            .await
-                            .unwrap_or(0);
+                            .delete_related(
+                                db,
+                                table,
+                                &relation.target_table,
+                                relation,
+                                vec![local_value],
+                                ctx,
+                            )
+                            .await?;
 
                         if deleted_count > 0 {
                             tracing::info!(
@@ -919,22 +2136,41 @@ impl CRUDHandler {
                             );
                         }
                     }
-                    _ => {
-                        // belongsTo doesn't require cascade delete
+                    crate::schema_generator::RelationType::BelongsToMany => {
+                        // Only the join rows go; the linked records are shared
+                        if let Some(through) = &relation.through {
+                            self.delete_related(
+                                db,
+                                table,
+                                through,
+                                relation,
+                                vec![local_value],
+                                ctx,
+                            )
+                            .await?;
+                        }
+                    }
+                    crate::schema_generator::RelationType::BelongsTo => {
+                        // The record is the child; its parent is unaffected
                     }
                 }
             }
         }
 
-        let mut where_clause = HashMap::new();
-        where_clause.insert(id_param.clone(), id_json);
-
-        let affected_rows = self.db_manager.delete(table, where_clause).await?;
+        let mut filters: Vec<Filter> = where_clause
+            .into_iter()
+            .map(|(column, value)| Filter::eq(column, value))
+            .collect();
+        filters.extend(version_guard);
+        let affected_rows = db.delete_query(table, filters).await?;
 
+        if affected_rows == 0 && guarded {
+            return Err(precondition_failed());
+        }
         if affected_rows == 0 {
             return Err(CRUDError::NotFoundError(format!(
-                "Record with {} = {} not found",
-                id_param, id_value
+                "Record with {} not found",
+                Self::describe_key(&key)
             )));
         }
 
@@ -952,7 +2188,602 @@ impl CRUDHandler {
         Ok(Value::Object(response.into_iter().collect()))
     }
 
-    /// Normalize record keys to match schema casing
+    /// Handle POST /table/{id}/restore (undo a soft delete, with the children deleted along)
+    async fn handle_restore(
+        &self,
+        db: &DatabaseManager,
+        pattern: &RoutePattern,
+        path_params: HashMap<String, String>,
+    ) -> Result<Value, CRUDError> {
+        let table = &pattern.table_name;
+        let column = self.soft_delete_column(table).ok_or_else(|| {
+            CRUDError::InvalidParameterError(format!("Table '{}' has no softDelete column", table))
+        })?;
+
+        let key = self.record_key(pattern, &path_params)?;
+        let where_clause: HashMap<String, Value> = key.iter().cloned().collect();
+        let record = self.find_record(db, table, &where_clause).await?;
+        let Some(deleted_at) = record.get(&column).filter(|v| !v.is_null()).cloned() else {
+            return Err(CRUDError::NotFoundError(format!(
+                "Deleted record with {} not found",
+                Self::describe_key(&key)
+            )));
+        };
+
+        let filters = where_clause
+            .iter()
+            .map(|(column, value)| Filter::eq(column.clone(), value.clone()))
+            .collect();
+        self.restore(db, table, filters, &deleted_at).await?;
+        self.fetch_with_relations(db, table, where_clause, &FieldSelection::default())
+            .await
+    }
+
+    /// Soft-delete column of a table (`softDelete` in its schema)
+    fn soft_delete_column(&self, table: &str) -> Option<String> {
+        self.api_generator
+            .get_table_schema(table)
+            .and_then(|schema| schema.soft_delete.clone())
+    }
+
+    /// `with_deleted=true` makes lists and fetches include soft-deleted rows
+    fn with_deleted(query_params: &HashMap<String, String>) -> Result<bool, CRUDError> {
+        match query_params.get("with_deleted").map(String::as_str) {
+            None | Some("false") => Ok(false),
+            Some("true") => Ok(true),
+            Some(other) => Err(CRUDError::InvalidParameterError(format!(
+                "with_deleted must be true or false, got '{}'",
+                other
+            ))),
+        }
+    }
+
+    /// Condition hiding the soft-deleted rows of a table
+    fn live_filter(&self, table: &str) -> Option<Filter> {
+        self.soft_delete_column(table)
+            .map(|column| Filter::eq(column, Value::Null))
+    }
+
+    /// Restrict a key lookup to rows that are not soft-deleted
+    fn exclude_deleted(&self, table: &str, where_clause: &mut HashMap<String, Value>) {
+        if let Some(column) = self.soft_delete_column(table) {
+            where_clause.insert(column, Value::Null);
+        }
+    }
+
+    /// Version column of a table (`versionColumn` in its schema)
+    fn version_column(&self, table: &str) -> Option<String> {
+        self.api_generator
+            .get_table_schema(table)
+            .and_then(|schema| schema.version_column.clone())
+    }
+
+    /// Update the rows matching `filters`, incrementing the table's version column
+    async fn update_rows(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        data: HashMap<String, Value>,
+        filters: Vec<Filter>,
+    ) -> Result<Value, CRUDError> {
+        let version_column = self.version_column(table);
+        Ok(db
+            .update_versioned(table, data, filters, version_column.as_deref())
+            .await?)
+    }
+
+    /// Strong ETag of a stored record: its version on tables with a `versionColumn`,
+    /// otherwise a hash of its columns. Relations do not contribute. `None` when the
+    /// record lacks some of the columns, e.g. when fetched with `fields`.
+    pub fn record_etag(&self, table: &str, record: &Value) -> Option<String> {
+        let schema = self.api_generator.get_table_schema(table);
+        if let Some(column) = schema.and_then(|s| s.version_column.as_ref()) {
+            return match record.get(column)? {
+                Value::Number(version) => Some(format!("\"{}\"", version)),
+                Value::String(version) => Some(format!("\"{}\"", version)),
+                _ => None,
+            };
+        }
+        let columns = match schema {
+            Some(schema) => schema
+                .columns
+                .iter()
+                .map(|col| Some((col.name.clone(), record.get(&col.name)?.clone())))
+                .collect::<Option<serde_json::Map<String, Value>>>()?,
+            None => record.as_object()?.clone(),
+        };
+        Some(Self::content_etag(&Value::Object(columns)))
+    }
+
+    /// Strong ETag derived from the JSON serialization of a value
+    pub fn content_etag(value: &Value) -> String {
+        let digest = Sha256::digest(value.to_string().as_bytes());
+        format!("\"{}\"", URL_SAFE_NO_PAD.encode(&digest[..16]))
+    }
+
+    /// Whether an `If-Match`/`If-None-Match` header value lists `etag`. `*` matches
+    /// any current record; weak validators (`W/"..."`) compare by their opaque tag.
+    pub fn etag_matches(header: &str, etag: Option<&str>) -> bool {
+        let Some(etag) = etag else {
+            return false;
+        };
+        let etag = etag.trim_start_matches("W/");
+        header
+            .split(',')
+            .map(str::trim)
+            .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+    }
+
+    /// Check the `If-Match` header of a write against the current record. On tables with
+    /// a `versionColumn` the matched version is returned as an extra condition for the
+    /// write, so an update landing between the check and the write is detected as well.
+    async fn check_if_match(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        key: &HashMap<String, Value>,
+        ctx: &RequestContext,
+    ) -> Result<Option<Filter>, CRUDError> {
+        let Some(header) = ctx.headers.get(hyper::header::IF_MATCH) else {
+            return Ok(None);
+        };
+        let header = header.to_str().map_err(|_| {
+            CRUDError::InvalidParameterError("If-Match header is not valid text".to_string())
+        })?;
+        let record = self.find_record(db, table, key).await?;
+        if !Self::etag_matches(header, self.record_etag(table, &record).as_deref()) {
+            return Err(CRUDError::PreconditionFailedError(format!(
+                "If-Match {} does not match the current record",
+                header
+            )));
+        }
+        Ok(self.version_column(table).and_then(|column| {
+            let version = record.get(&column)?.clone();
+            Some(Filter::eq(column, version))
+        }))
+    }
+
+    /// Stamp the live rows matching `filters` as deleted and apply the `onDelete` action
+    /// of their hasMany/hasOne relations: cascades stamp the children of soft-delete
+    /// tables and leave the children of other tables alone, so deleting through the API
+    /// never removes rows. Live restricted children refuse the delete.
+    async fn soft_delete(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        mut filters: Vec<Filter>,
+        deleted_at: &str,
+        ctx: &RequestContext,
+    ) -> Result<u64, CRUDError> {
+        let Some(column) = self.soft_delete_column(table) else {
+            return Ok(0);
+        };
+        filters.push(Filter::eq(column.clone(), Value::Null));
+
+        for (relation, local_values) in self.child_keys(db, table, &filters).await? {
+            let child = &relation.target_table;
+            let action = self.delete_action(table, child, &relation);
+            for chunk in local_values.chunks(RELATION_BATCH_SIZE) {
+                let mut child_filters = vec![Filter {
+                    column: relation.foreign_key.clone(),
+                    operator: FilterOperator::In,
+                    value: Value::Array(chunk.to_vec()),
+                }];
+                match action {
+                    OnDelete::Cascade if self.soft_delete_column(child).is_some() => {
+                        Box::pin(self.soft_delete(db, child, child_filters, deleted_at, ctx))
+                            .await?;
+                    }
+                    OnDelete::Cascade => {}
+                    OnDelete::SetNull => {
+                        let data = HashMap::from([(relation.foreign_key.clone(), Value::Null)]);
+                        self.update_rows(db, child, data, child_filters).await?;
+                    }
+                    OnDelete::Restrict => {
+                        if let Some(column) = self.soft_delete_column(child) {
+                            child_filters.push(Filter::eq(column, Value::Null));
+                        }
+                        let live = db
+                            .select_query(
+                                child,
+                                SelectQuery {
+                                    filters: child_filters,
+                                    limit: Some(1),
+                                    ..Default::default()
+                                },
+                            )
+                            .await?;
+                        if !live.is_empty() {
+                            return Err(CRUDError::ConflictError(format!(
+                                "Records of '{}' still reference the records of '{}' to delete",
+                                child, table
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut data = HashMap::new();
+        data.insert(column, Value::String(deleted_at.to_string()));
+        if let Some(schema) = self.api_generator.get_table_schema(table)
+            && let Some(identity) = ctx.extensions.get::<ConsumerIdentity>()
+        {
+            for col in &schema.columns {
+                if matches!(col.name.as_str(), "deletedBy" | "deleted_by") {
+                    data.insert(col.name.clone(), Value::String(identity.name.clone()));
+                }
+            }
+        }
+        let result = self.update_rows(db, table, data, filters).await?;
+        Ok(result
+            .get("affected_rows")
+            .and_then(Value::as_u64)
+            .unwrap_or(0))
+    }
+
+    /// Clear the deletion stamp of the rows matching `filters`, and of their
+    /// hasMany/hasOne children that were deleted together with them
+    async fn restore(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        mut filters: Vec<Filter>,
+        deleted_at: &Value,
+    ) -> Result<(), CRUDError> {
+        let Some(column) = self.soft_delete_column(table) else {
+            return Ok(());
+        };
+        filters.push(Filter::eq(column.clone(), deleted_at.clone()));
+
+        for (relation, local_values) in self.child_keys(db, table, &filters).await? {
+            let child = &relation.target_table;
+            if self.soft_delete_column(child).is_none()
+                || self.delete_action(table, child, &relation) != OnDelete::Cascade
+            {
+                continue;
+            }
+            for chunk in local_values.chunks(RELATION_BATCH_SIZE) {
+                let child_filters = vec![Filter {
+                    column: relation.foreign_key.clone(),
+                    operator: FilterOperator::In,
+                    value: Value::Array(chunk.to_vec()),
+                }];
+                Box::pin(self.restore(db, &relation.target_table, child_filters, deleted_at))
+                    .await?;
+            }
+        }
+
+        let mut data = HashMap::new();
+        data.insert(column, Value::Null);
+        if let Some(schema) = self.api_generator.get_table_schema(table) {
+            for col in &schema.columns {
+                if matches!(col.name.as_str(), "deletedBy" | "deleted_by") {
+                    data.insert(col.name.clone(), Value::Null);
+                }
+            }
+        }
+        self.update_rows(db, table, data, filters).await?;
+        Ok(())
+    }
+
+    /// hasMany/hasOne relations of `table`, with the local key values of the rows
+    /// matching `filters`
+    async fn child_keys(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        filters: &[Filter],
+    ) -> Result<Vec<(RelationDefinition, Vec<Value>)>, CRUDError> {
+        let Some(schema) = self.api_generator.get_table_schema(table) else {
+            return Ok(vec![]);
+        };
+        let relations: Vec<&RelationDefinition> = schema
+            .relations
+            .iter()
+            .filter(|r| {
+                matches!(
+                    r.relation_type,
+                    crate::schema_generator::RelationType::HasMany
+                        | crate::schema_generator::RelationType::HasOne
+                )
+            })
+            .collect();
+        if relations.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let records: Vec<Value> = db
+            .select_query(
+                table,
+                SelectQuery {
+                    filters: filters.to_vec(),
+                    ..Default::default()
+                },
+            )
+            .await?
+            .into_iter()
+            .map(|record| self.normalize_record_casing(table, record))
+            .collect();
+        Ok(relations
+            .into_iter()
+            .map(|relation| {
+                let local_values = records
+                    .iter()
+                    .filter_map(|record| self.local_value(table, relation, record).ok())
+                    .collect();
+                (relation.clone(), local_values)
+            })
+            .collect())
+    }
+
+    /// Primary key columns of a table (`id` for tables without a schema)
+    fn primary_key(&self, table: &str) -> Vec<String> {
+        self.api_generator
+            .get_table_schema(table)
+            .map(TableSchema::primary_key)
+            .unwrap_or_else(|| vec!["id".to_string()])
+    }
+
+    /// Column of `table` referenced by a hasMany/hasOne/belongsToMany relation:
+    /// its `local_key`, else the table's primary key
+    fn local_key(&self, table: &str, relation: &RelationDefinition) -> String {
+        relation
+            .local_key
+            .clone()
+            .unwrap_or_else(|| self.primary_key(table).remove(0))
+    }
+
+    /// What deleting a record of `table` does to the rows of `child` related to it:
+    /// the action of the child's foreign key when one is declared, else the relation's.
+    /// Soft-delete tables get restricting constraints in place of cascading ones, so
+    /// their restriction defers to the relation.
+    fn delete_action(&self, table: &str, child: &str, relation: &RelationDefinition) -> OnDelete {
+        let schema = self.api_generator.get_table_schema(child);
+        let foreign_key = schema.and_then(|schema| {
+            schema
+                .foreign_keys
+                .iter()
+                .find(|fk| fk.column == relation.foreign_key && fk.references_table == table)
+        });
+        match foreign_key.map(|fk| fk.on_delete) {
+            Some(OnDelete::Restrict) if schema.is_some_and(|s| s.soft_delete.is_some()) => {
+                relation.delete_action()
+            }
+            Some(action) => action,
+            None => relation.delete_action(),
+        }
+    }
+
+    /// Apply the `onDelete` action of a relation to the rows of `child` whose
+    /// foreign key is one of `values`. Cascades into soft-delete tables stamp the
+    /// rows instead of removing them. Restricted rows are left for the database
+    /// to refuse the delete.
+    async fn delete_related(
+        &self,
+        db: &DatabaseManager,
+        table: &str,
+        child: &str,
+        relation: &RelationDefinition,
+        values: Vec<Value>,
+        ctx: &RequestContext,
+    ) -> Result<u64, CRUDError> {
+        let filter = Filter {
+            column: relation.foreign_key.clone(),
+            operator: FilterOperator::In,
+            value: Value::Array(values),
+        };
+        match self.delete_action(table, child, relation) {
+            OnDelete::Cascade if self.soft_delete_column(child).is_some() => {
+                let deleted_at = Utc::now().to_rfc3339();
+                self.soft_delete(db, child, vec![filter], &deleted_at, ctx)
+                    .await
+            }
+            OnDelete::Cascade => Ok(db.delete_query(child, vec![filter]).await?),
+            OnDelete::SetNull => {
+                let data = HashMap::from([(relation.foreign_key.clone(), Value::Null)]);
+                db.update_query(child, data, vec![filter]).await?;
+                Ok(0)
+            }
+            OnDelete::Restrict => Ok(0),
+        }
+    }
+
+    /// Key columns and values of the record addressed by a route. Path parameters named
+    /// after columns are matched by name; otherwise they fill the primary key in path order
+    fn record_key(
+        &self,
+        pattern: &RoutePattern,
+        path_params: &HashMap<String, String>,
+    ) -> Result<Vec<(String, Value)>, CRUDError> {
+        let mut params: Vec<(&String, &String)> = pattern
+            .param_names
+            .iter()
+            .filter_map(|name| path_params.get_key_value(name))
+            .collect();
+        if params.is_empty() {
+            params = path_params.iter().collect();
+            params.sort();
+        }
+        if params.is_empty() {
+            return Err(CRUDError::InvalidParameterError(
+                "No ID parameter found".to_string(),
+            ));
+        }
+
+        let table = &pattern.table_name;
+        let Some(schema) = self.api_generator.get_table_schema(table) else {
+            return Ok(params
+                .into_iter()
+                .map(|(name, raw)| (name.clone(), Self::coerce_string_to_json_value(raw)))
+                .collect());
+        };
+        let column = |name: &str| {
+            schema
+                .columns
+                .iter()
+                .find(|c| c.name.eq_ignore_ascii_case(name))
+        };
+        if params.iter().all(|(name, _)| column(name).is_some()) {
+            return Ok(params
+                .into_iter()
+                .filter_map(|(name, raw)| {
+                    column(name).map(|col| (col.name.clone(), Self::key_value(col, raw)))
+                })
+                .collect());
+        }
+
+        let primary_key = schema.primary_key();
+        if params.len() != primary_key.len() {
+            return Err(CRUDError::InvalidParameterError(format!(
+                "Expected {} path parameter(s) for the primary key ({}) of {}",
+                primary_key.len(),
+                primary_key.join(", "),
+                table
+            )));
+        }
+        Ok(primary_key
+            .into_iter()
+            .zip(params)
+            .map(|(name, (_, raw))| {
+                let value = match column(&name) {
+                    Some(col) => Self::key_value(col, raw),
+                    None => Self::coerce_string_to_json_value(raw),
+                };
+                (name, value)
+            })
+            .collect())
+    }
+
+    /// Path of the single-record GET route of `record`, for the `Location` of a create.
+    /// `None` when the table has no such route or the record lacks its key.
+    pub fn record_location(&self, pattern: &RoutePattern, record: &Value) -> Option<String> {
+        let route = self
+            .api_generator
+            .get_route_patterns()
+            .iter()
+            .find(|route| {
+                matches!(route.operation_type, OperationType::Get)
+                    && route.table_name == pattern.table_name
+                    && route.path_pattern.starts_with(&pattern.path_pattern)
+            })?;
+        // Parameters named after columns, otherwise the primary key in path order
+        let columns: Vec<String> = if route.param_names.iter().all(|n| record.get(n).is_some()) {
+            route.param_names.clone()
+        } else {
+            self.primary_key(&pattern.table_name)
+        };
+        if columns.len() != route.param_names.len() {
+            return None;
+        }
+        let mut location = route.path_pattern.clone();
+        for (param, column) in route.param_names.iter().zip(&columns) {
+            let value = record.get(column).filter(|v| !v.is_null())?;
+            let segment: String =
+                form_urlencoded::byte_serialize(Self::key_string(value).as_bytes()).collect();
+            location = location.replace(&format!("{{{}}}", param), &segment.replace('+', "%20"));
+        }
+        Some(location)
+    }
+
+    /// Path parameter converted for its key column: text keys stay strings
+    fn key_value(column: &ColumnDefinition, raw: &str) -> Value {
+        let column_type = column.column_type.to_lowercase();
+        let textual = ["text", "string", "char", "varchar", "uuid", "date", "time"]
+            .iter()
+            .any(|t| column_type.starts_with(t));
+        if textual {
+            Value::String(raw.to_string())
+        } else {
+            Self::coerce_string_to_json_value(raw)
+        }
+    }
+
+    /// `a = 1, b = x`, for messages about a record key
+    fn describe_key(key: &[(String, Value)]) -> String {
+        key.iter()
+            .map(|(column, value)| format!("{} = {}", column, Self::key_string(value)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Primary key of a freshly inserted row, from the submitted data or the insert result
+    fn inserted_key(
+        &self,
+        table: &str,
+        data: &HashMap<String, Value>,
+        result: &Value,
+    ) -> Option<HashMap<String, Value>> {
+        let primary_key = self.primary_key(table);
+        let single = primary_key.len() == 1;
+        let mut key = HashMap::new();
+        for column in primary_key {
+            let value = data
+                .get(&column)
+                .or_else(|| result.get("record").and_then(|r| r.get(&column)))
+                .or_else(|| {
+                    single
+                        .then(|| result.get("id").or_else(|| result.get("last_insert_rowid")))
+                        .flatten()
+                })
+                .filter(|v| !v.is_null())?
+                .clone();
+            key.insert(column, value);
+        }
+        Some(key)
+    }
+
+    /// Parse the sparse fieldset and `include` parameters of a request against the table schema
+    fn parse_fields(
+        &self,
+        pattern: &RoutePattern,
+        query_params: &HashMap<String, String>,
+    ) -> Result<FieldSelection, CRUDError> {
+        let schema = self.api_generator.get_table_schema(&pattern.table_name);
+        let target_schema = |target: &str| self.api_generator.get_table_schema(target);
+        let mut fields = crate::query::parse_fields(query_params, schema, target_schema)
+            .map_err(CRUDError::InvalidParameterError)?;
+        if let Some(raw) = query_params.get("include") {
+            let include =
+                crate::query::parse_include(raw, schema, target_schema, pattern.include_max_depth)
+                    .map_err(CRUDError::InvalidParameterError)?;
+            fields.include = Some(include);
+        }
+        Ok(fields)
+    }
+
+    /// SQL projection for a sparse fieldset: the requested columns plus the keys
+    /// that relation loading needs (trimmed from the response by `retain_root`)
+    fn projection(schema: Option<&TableSchema>, fields: &FieldSelection) -> Option<Vec<String>> {
+        let root = fields.root.as_ref()?;
+        let Some(schema) = schema else {
+            return Some(root.clone());
+        };
+        let mut columns: Vec<String> = root
+            .iter()
+            .filter(|f| !schema.relations.iter().any(|r| &r.field_name == *f))
+            .cloned()
+            .collect();
+        for relation in &schema.relations {
+            if !fields.includes_relation(&relation.field_name) {
+                continue;
+            }
+            let key = match relation.relation_type {
+                crate::schema_generator::RelationType::BelongsTo => relation.foreign_key.clone(),
+                _ => relation
+                    .local_key
+                    .clone()
+                    .unwrap_or_else(|| schema.primary_key().remove(0)),
+            };
+            if !columns.iter().any(|c| c.eq_ignore_ascii_case(&key)) {
+                columns.push(key);
+            }
+        }
+        Some(columns)
+    }
+
+    /// Normalize record keys to match schema casing, and the 0/1 of boolean columns
+    /// to JSON booleans: SQLite stores them as integers and reports no column type
     fn normalize_record_casing(&self, table: &str, mut record: Value) -> Value {
         let schema = match self.api_generator.get_table_schema(table) {
             Some(s) => s,
@@ -980,6 +2811,17 @@ impl CRUDHandler {
                     map.insert(new_key, val);
                 }
             }
+
+            for col in &schema.columns {
+                if matches!(
+                    col.column_type.to_ascii_lowercase().as_str(),
+                    "boolean" | "bool"
+                ) && let Some(value) = map.get_mut(&col.name)
+                    && let Some(n) = value.as_i64()
+                {
+                    *value = Value::Bool(n != 0);
+                }
+            }
         }
         record
     }
@@ -1,5 +1,6 @@
 //! Database facade delegating to backend implementations in modules/.
 
+use crate::migration::TablePlan;
 use crate::modules::metrics::DbMetrics;
 use crate::schema_generator::TableSchema;
 use serde_json::Value;
@@ -25,9 +26,291 @@ impl std::fmt::Display for DatabaseError {
 
 impl std::error::Error for DatabaseError {}
 
+/// Kind of integrity constraint rejected by the database
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstraintKind {
+    Unique,
+    ForeignKey,
+    NotNull,
+    Check,
+}
+
+impl ConstraintKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConstraintKind::Unique => "unique",
+            ConstraintKind::ForeignKey => "foreign_key",
+            ConstraintKind::NotNull => "not_null",
+            ConstraintKind::Check => "check",
+        }
+    }
+}
+
+/// A write rejected by an integrity constraint, as reported by the driver
+#[derive(Clone, Debug)]
+pub struct ConstraintViolation {
+    pub kind: ConstraintKind,
+    /// Constraint name, where the backend reports it (Postgres)
+    pub constraint: Option<String>,
+    pub message: String,
+}
+
+impl DatabaseError {
+    /// The violated constraint, when the query failed on one
+    pub fn constraint_violation(&self) -> Option<ConstraintViolation> {
+        let DatabaseError::QueryError(sqlx::Error::Database(err)) = self else {
+            return None;
+        };
+        let kind = match err.kind() {
+            sqlx::error::ErrorKind::UniqueViolation => ConstraintKind::Unique,
+            sqlx::error::ErrorKind::ForeignKeyViolation => ConstraintKind::ForeignKey,
+            sqlx::error::ErrorKind::NotNullViolation => ConstraintKind::NotNull,
+            sqlx::error::ErrorKind::CheckViolation => ConstraintKind::Check,
+            _ => return None,
+        };
+        Some(ConstraintViolation {
+            kind,
+            constraint: err.constraint().map(str::to_string),
+            message: err.message().to_string(),
+        })
+    }
+}
+
+/// Comparison operator of a single filter condition
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterOperator {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Like,
+    NotLike,
+    ILike,
+    NotILike,
+    In,
+    NotIn,
+    IsNull,
+    IsNotNull,
+}
+
+impl FilterOperator {
+    /// Parse the operator name used in query parameters (`age[gte]=18`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "eq" => Some(Self::Eq),
+            "ne" | "neq" => Some(Self::Ne),
+            "gt" => Some(Self::Gt),
+            "gte" => Some(Self::Gte),
+            "lt" => Some(Self::Lt),
+            "lte" => Some(Self::Lte),
+            "like" => Some(Self::Like),
+            "ilike" => Some(Self::ILike),
+            "in" => Some(Self::In),
+            "is" => Some(Self::IsNull),
+            _ => None,
+        }
+    }
+
+    /// The operator matching the complement of this one
+    pub fn negate(self) -> Self {
+        match self {
+            Self::Eq => Self::Ne,
+            Self::Ne => Self::Eq,
+            Self::Gt => Self::Lte,
+            Self::Gte => Self::Lt,
+            Self::Lt => Self::Gte,
+            Self::Lte => Self::Gt,
+            Self::Like => Self::NotLike,
+            Self::NotLike => Self::Like,
+            Self::ILike => Self::NotILike,
+            Self::NotILike => Self::ILike,
+            Self::In => Self::NotIn,
+            Self::NotIn => Self::In,
+            Self::IsNull => Self::IsNotNull,
+            Self::IsNotNull => Self::IsNull,
+        }
+    }
+
+    /// SQL for binary comparison operators (None for list and null checks)
+    pub fn comparison_sql(self) -> Option<&'static str> {
+        match self {
+            Self::Eq => Some(" = "),
+            Self::Ne => Some(" <> "),
+            Self::Gt => Some(" > "),
+            Self::Gte => Some(" >= "),
+            Self::Lt => Some(" < "),
+            Self::Lte => Some(" <= "),
+            _ => None,
+        }
+    }
+}
+
+/// A single `column <op> value` condition; `value` is an array for `In`/`NotIn`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter {
+    pub column: String,
+    pub operator: FilterOperator,
+    pub value: Value,
+}
+
+impl Filter {
+    pub fn eq(column: impl Into<String>, value: Value) -> Self {
+        Self {
+            column: column.into(),
+            operator: FilterOperator::Eq,
+            value,
+        }
+    }
+}
+
+/// Full-text condition of a list query (`q=`): rows whose `columns` contain every
+/// term, matched as a word prefix, through the table's search index
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextSearch {
+    pub columns: Vec<String>,
+    pub terms: Vec<String>,
+}
+
+impl TextSearch {
+    /// Split a search string into terms: runs of letters and digits, as the
+    /// search indexes tokenize text
+    pub fn new(columns: Vec<String>, query: &str) -> Self {
+        Self {
+            columns,
+            terms: query
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|t| !t.is_empty())
+                .map(|t| t.to_lowercase())
+                .collect(),
+        }
+    }
+}
+
+/// Sort key ranking rows by how well they match the `TextSearch` of the query
+pub const RELEVANCE_SORT: &str = "relevance";
+
+/// One `ORDER BY` term
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortKey {
+    pub column: String,
+    pub descending: bool,
+}
+
+/// Function computing one value of an aggregation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    pub const ALL: [Self; 5] = [Self::Count, Self::Sum, Self::Avg, Self::Min, Self::Max];
+
+    /// Name of the function as a query parameter (`sum=total`)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::Sum => "sum",
+            Self::Avg => "avg",
+            Self::Min => "min",
+            Self::Max => "max",
+        }
+    }
+
+    pub fn sql(self) -> &'static str {
+        match self {
+            Self::Count => "COUNT",
+            Self::Sum => "SUM",
+            Self::Avg => "AVG",
+            Self::Min => "MIN",
+            Self::Max => "MAX",
+        }
+    }
+}
+
+/// One value computed per group, e.g. `SUM(total) AS sum_total`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    /// Column aggregated; `None` counts rows (`COUNT(*)`)
+    pub column: Option<String>,
+    /// Whether the column holds integers, so backends keep sums integral
+    pub integer: bool,
+}
+
+impl Aggregate {
+    /// Key of the value in result rows: `count` for `COUNT(*)`, else `sum_total` etc.
+    pub fn alias(&self) -> String {
+        match &self.column {
+            Some(column) => format!("{}_{}", self.function.name(), column),
+            None => self.function.name().to_string(),
+        }
+    }
+    /// SQL computing the value, e.g. `SUM(total)`
+    pub fn expression(&self) -> String {
+        format!(
+            "{}({})",
+            self.function.sql(),
+            self.column.as_deref().unwrap_or("*")
+        )
+    }
+}
+
+/// Options of an aggregation issued through a backend: one row per distinct
+/// combination of `group_by` values (a single row without grouping), ordered by them
+#[derive(Clone, Debug, Default)]
+pub struct AggregateQuery {
+    pub group_by: Vec<String>,
+    pub aggregates: Vec<Aggregate>,
+    pub filters: Vec<Filter>,
+    pub search: Option<TextSearch>,
+}
+
+/// Keyset pagination bound: rows strictly after `values` in `columns` order,
+/// i.e. `(a, b) > (x, y)` (or `<` when descending)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyset {
+    pub columns: Vec<String>,
+    pub values: Vec<Value>,
+    pub descending: bool,
+}
+
+/// Options of a SELECT issued through a backend
+#[derive(Clone, Debug, Default)]
+pub struct SelectQuery {
+    pub columns: Option<Vec<String>>,
+    pub filters: Vec<Filter>,
+    pub search: Option<TextSearch>,
+    pub keyset: Option<Keyset>,
+    pub sort: Vec<SortKey>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// Rows written by one multi-row INSERT, optionally as an upsert
+#[derive(Clone, Debug, Default)]
+pub struct BulkInsert {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    /// Unique columns whose conflicts turn the insert into an update
+    pub on_conflict: Option<Vec<String>>,
+    /// Columns overwritten on conflict; conflicting rows are skipped when empty
+    pub update_columns: Vec<String>,
+    /// Columns incremented when a conflicting row is updated, i.e. the version column
+    pub increments: Vec<String>,
+}
+
+/// Upper bound on bind parameters per statement, below every backend's limit
+const MAX_BIND_PARAMS: usize = 30_000;
+
 #[derive(Clone, Debug)]
 pub struct DatabaseRuntimeConfig {
-    pub driver: String, // "sqlite" or "postgres"
+    pub driver: String, // "sqlite", "postgres" or "mysql"
     pub url: String,
     pub max_size: u32,
 }
@@ -38,6 +321,8 @@ impl DatabaseRuntimeConfig {
         if let Ok(mut url) = std::env::var("APIFY_DB_URL") {
             let driver = if url.starts_with("postgres://") {
                 "postgres".to_string()
+            } else if url.starts_with("mysql://") || url.starts_with("mariadb://") {
+                "mysql".to_string()
             } else {
                 "sqlite".to_string()
             };
@@ -65,18 +350,107 @@ impl DatabaseRuntimeConfig {
     }
 }
 
+/// Transaction shared by the backend handle returned from `DatabaseBackend::begin`.
+/// Dropping the last handle without committing rolls the transaction back.
+pub struct SharedTransaction<DB: sqlx::Database>(
+    Arc<tokio::sync::Mutex<Option<sqlx::Transaction<'static, DB>>>>,
+);
+
+impl<DB: sqlx::Database> SharedTransaction<DB> {
+    pub fn new(tx: sqlx::Transaction<'static, DB>) -> Self {
+        Self(Arc::new(tokio::sync::Mutex::new(Some(tx))))
+    }
+
+    /// Lock the transaction for one statement; holds `None` once it has been finished
+    pub async fn lock(
+        &self,
+    ) -> tokio::sync::MutexGuard<'_, Option<sqlx::Transaction<'static, DB>>> {
+        self.0.lock().await
+    }
+
+    pub async fn commit(&self) -> Result<(), DatabaseError> {
+        match self.0.lock().await.take() {
+            Some(tx) => tx.commit().await.map_err(DatabaseError::QueryError),
+            None => Err(DatabaseError::QueryError(transaction_finished())),
+        }
+    }
+
+    pub async fn rollback(&self) -> Result<(), DatabaseError> {
+        match self.0.lock().await.take() {
+            Some(tx) => tx.rollback().await.map_err(DatabaseError::QueryError),
+            None => Err(DatabaseError::QueryError(transaction_finished())),
+        }
+    }
+}
+
+impl<DB: sqlx::Database> Clone for SharedTransaction<DB> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<DB: sqlx::Database> std::fmt::Debug for SharedTransaction<DB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedTransaction")
+    }
+}
+
+/// Error for statements issued on a transaction after commit or rollback
+pub fn transaction_finished() -> sqlx::Error {
+    sqlx::Error::Protocol("transaction already finished".to_string())
+}
+
+/// Error returned by `begin` on a handle that is already inside a transaction
+pub fn nested_transaction() -> DatabaseError {
+    DatabaseError::ValidationError("Nested transactions are not supported".to_string())
+}
+
+/// Error returned by `commit`/`rollback` on a handle outside a transaction
+pub fn no_transaction() -> DatabaseError {
+    DatabaseError::ValidationError("No transaction in progress".to_string())
+}
+
+/// Future resolving to a backend bound to a new transaction
+pub type BeginFuture<'a> = core::pin::Pin<
+    Box<
+        dyn core::future::Future<Output = Result<Arc<dyn DatabaseBackend>, DatabaseError>>
+            + Send
+            + 'a,
+    >,
+>;
+
 pub trait DatabaseBackend: Send + Sync {
     fn initialize_schema<'a>(
         &'a self,
         table_schemas: Vec<TableSchema>,
     ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>;
+    /// Compute the statements `initialize_schema` would run for each table, without running them
+    fn plan_schema<'a>(
+        &'a self,
+        table_schemas: Vec<TableSchema>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Vec<TablePlan>, DatabaseError>> + Send + 'a>,
+    >;
     fn select<'a>(
         &'a self,
         table: &'a str,
-        columns: Option<Vec<String>>,
-        where_clause: Option<HashMap<String, Value>>,
-        limit: Option<u32>,
-        offset: Option<u32>,
+        query: SelectQuery,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Vec<Value>, DatabaseError>> + Send + 'a>,
+    >;
+    fn count<'a>(
+        &'a self,
+        table: &'a str,
+        filters: Vec<Filter>,
+        search: Option<TextSearch>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
+    >;
+    /// Compute the aggregates of the rows matching the query, one row per group
+    fn aggregate<'a>(
+        &'a self,
+        table: &'a str,
+        query: AggregateQuery,
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<Vec<Value>, DatabaseError>> + Send + 'a>,
     >;
@@ -87,18 +461,29 @@ pub trait DatabaseBackend: Send + Sync {
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<Value, DatabaseError>> + Send + 'a>,
     >;
+    /// Insert many rows in one statement, returning the number of rows written
+    fn insert_many<'a>(
+        &'a self,
+        table: &'a str,
+        insert: BulkInsert,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
+    >;
+    /// UPDATE the rows matching `filters`, setting `data` and adding one to each
+    /// of the `increments` columns
     fn update<'a>(
         &'a self,
         table: &'a str,
         data: HashMap<String, Value>,
-        where_clause: HashMap<String, Value>,
+        filters: Vec<Filter>,
+        increments: Vec<String>,
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<Value, DatabaseError>> + Send + 'a>,
     >;
     fn delete<'a>(
         &'a self,
         table: &'a str,
-        where_clause: HashMap<String, Value>,
+        filters: Vec<Filter>,
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
     >;
@@ -117,6 +502,16 @@ pub trait DatabaseBackend: Send + Sync {
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<Vec<String>, DatabaseError>> + Send + 'a>,
     >;
+    /// Start a transaction; every operation on the returned backend runs inside it
+    fn begin<'a>(&'a self) -> BeginFuture<'a>;
+    /// Commit the transaction of a backend returned by `begin`
+    fn commit<'a>(
+        &'a self,
+    ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>;
+    /// Roll back the transaction of a backend returned by `begin`
+    fn rollback<'a>(
+        &'a self,
+    ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>;
 }
 
 #[derive(Clone)]
@@ -134,15 +529,32 @@ impl std::fmt::Debug for DatabaseManager {
 
 impl DatabaseManager {
     pub async fn new(config: DatabaseRuntimeConfig) -> Result<Self, DatabaseError> {
+        Self::open(config, true).await
+    }
+
+    /// Connect to an existing database: a missing SQLite file is an error rather than created
+    pub async fn connect_existing(config: DatabaseRuntimeConfig) -> Result<Self, DatabaseError> {
+        Self::open(config, false).await
+    }
+
+    async fn open(config: DatabaseRuntimeConfig, create: bool) -> Result<Self, DatabaseError> {
         let backend: Arc<dyn DatabaseBackend> = match config.driver.as_str() {
             "postgres" | "postgresql" => {
                 let b = crate::modules::postgres::PostgresBackend::connect(config).await?;
                 Arc::new(b)
             }
-            _ => {
+            "mysql" | "mariadb" => {
+                let b = crate::modules::mysql::MySqlBackend::connect(config).await?;
+                Arc::new(b)
+            }
+            _ if create => {
                 let b = crate::modules::sqlite::SqliteBackend::connect(config).await?;
                 Arc::new(b)
             }
+            _ => {
+                let b = crate::modules::sqlite::SqliteBackend::connect_existing(config).await?;
+                Arc::new(b)
+            }
         };
         Ok(Self { backend })
     }
@@ -152,7 +564,19 @@ impl DatabaseManager {
         &self,
         table_schemas: Vec<TableSchema>,
     ) -> Result<(), DatabaseError> {
-        self.backend.initialize_schema(table_schemas).await
+        self.backend
+            .initialize_schema(crate::migration::dependency_order(table_schemas))
+            .await
+    }
+
+    /// Plan the migration of the database to the table schemas without applying it
+    pub async fn plan_schema(
+        &self,
+        table_schemas: Vec<TableSchema>,
+    ) -> Result<Vec<TablePlan>, DatabaseError> {
+        self.backend
+            .plan_schema(crate::migration::dependency_order(table_schemas))
+            .await
     }
 
     pub async fn select(
@@ -162,12 +586,65 @@ impl DatabaseManager {
         where_clause: Option<HashMap<String, Value>>,
         limit: Option<u32>,
         offset: Option<u32>,
+    ) -> Result<Vec<Value>, DatabaseError> {
+        let filters = where_clause
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(k, v)| Filter::eq(k, v))
+            .collect();
+        self.select_query(
+            table,
+            SelectQuery {
+                columns,
+                filters,
+                limit,
+                offset,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Select rows matching arbitrary filter conditions
+    pub async fn select_query(
+        &self,
+        table: &str,
+        query: SelectQuery,
     ) -> Result<Vec<Value>, DatabaseError> {
         let metrics = DbMetrics::new("select", table);
-        let result = self
-            .backend
-            .select(table, columns, where_clause, limit, offset)
-            .await;
+        let result = self.backend.select(table, query).await;
+        let status = if result.is_ok() { "success" } else { "error" };
+        metrics.record(status);
+        result
+    }
+
+    /// Count rows matching the filter conditions
+    pub async fn count(&self, table: &str, filters: Vec<Filter>) -> Result<u64, DatabaseError> {
+        self.count_search(table, filters, None).await
+    }
+
+    /// Count the rows matching `filters` and, when given, the full-text `search`
+    pub async fn count_search(
+        &self,
+        table: &str,
+        filters: Vec<Filter>,
+        search: Option<TextSearch>,
+    ) -> Result<u64, DatabaseError> {
+        let metrics = DbMetrics::new("count", table);
+        let result = self.backend.count(table, filters, search).await;
+        let status = if result.is_ok() { "success" } else { "error" };
+        metrics.record(status);
+        result
+    }
+
+    /// Compute counts, sums and other aggregates, optionally per group
+    pub async fn aggregate(
+        &self,
+        table: &str,
+        query: AggregateQuery,
+    ) -> Result<Vec<Value>, DatabaseError> {
+        let metrics = DbMetrics::new("aggregate", table);
+        let result = self.backend.aggregate(table, query).await;
         let status = if result.is_ok() { "success" } else { "error" };
         metrics.record(status);
         result
@@ -190,24 +667,81 @@ impl DatabaseManager {
         result
     }
 
+    /// Insert many rows, batched so that no statement exceeds the bind parameter limit
+    pub async fn insert_many(&self, table: &str, insert: BulkInsert) -> Result<u64, DatabaseError> {
+        if insert.columns.is_empty() {
+            return Err(DatabaseError::ValidationError(
+                "No data provided for insert".to_string(),
+            ));
+        }
+        if let Some(row) = insert.rows.iter().find(|r| r.len() != insert.columns.len()) {
+            return Err(DatabaseError::ValidationError(format!(
+                "Expected {} values per row, got {}",
+                insert.columns.len(),
+                row.len()
+            )));
+        }
+        let batch_size = (MAX_BIND_PARAMS / insert.columns.len()).max(1);
+        let mut affected = 0;
+        for rows in insert.rows.chunks(batch_size) {
+            let batch = BulkInsert {
+                rows: rows.to_vec(),
+                ..insert.clone()
+            };
+            let metrics = DbMetrics::new("insert", table);
+            let result = self.backend.insert_many(table, batch).await;
+            let status = if result.is_ok() { "success" } else { "error" };
+            metrics.record(status);
+            affected += result?;
+        }
+        Ok(affected)
+    }
+
     pub async fn update(
         &self,
         table: &str,
         data: HashMap<String, Value>,
         where_clause: HashMap<String, Value>,
     ) -> Result<Value, DatabaseError> {
-        if data.is_empty() {
+        let filters = where_clause
+            .into_iter()
+            .map(|(k, v)| Filter::eq(k, v))
+            .collect();
+        self.update_query(table, data, filters).await
+    }
+
+    /// Update every row matching the filter conditions
+    pub async fn update_query(
+        &self,
+        table: &str,
+        data: HashMap<String, Value>,
+        filters: Vec<Filter>,
+    ) -> Result<Value, DatabaseError> {
+        self.update_versioned(table, data, filters, None).await
+    }
+
+    /// Update every row matching the filter conditions, incrementing the version
+    /// column in the same statement
+    pub async fn update_versioned(
+        &self,
+        table: &str,
+        data: HashMap<String, Value>,
+        filters: Vec<Filter>,
+        version_column: Option<&str>,
+    ) -> Result<Value, DatabaseError> {
+        if data.is_empty() && version_column.is_none() {
             return Err(DatabaseError::ValidationError(
                 "No data provided for update".to_string(),
             ));
         }
-        if where_clause.is_empty() {
+        if filters.is_empty() {
             return Err(DatabaseError::ValidationError(
                 "WHERE clause is required for update".to_string(),
             ));
         }
+        let increments = version_column.map(str::to_string).into_iter().collect();
         let metrics = DbMetrics::new("update", table);
-        let result = self.backend.update(table, data, where_clause).await;
+        let result = self.backend.update(table, data, filters, increments).await;
         let status = if result.is_ok() { "success" } else { "error" };
         metrics.record(status);
         result
@@ -217,9 +751,22 @@ impl DatabaseManager {
         &self,
         table: &str,
         where_clause: HashMap<String, Value>,
+    ) -> Result<u64, DatabaseError> {
+        let filters = where_clause
+            .into_iter()
+            .map(|(k, v)| Filter::eq(k, v))
+            .collect();
+        self.delete_query(table, filters).await
+    }
+
+    /// Delete every row matching the filter conditions
+    pub async fn delete_query(
+        &self,
+        table: &str,
+        filters: Vec<Filter>,
     ) -> Result<u64, DatabaseError> {
         let metrics = DbMetrics::new("delete", table);
-        let result = self.backend.delete(table, where_clause).await;
+        let result = self.backend.delete(table, filters).await;
         let status = if result.is_ok() { "success" } else { "error" };
         metrics.record(status);
         result
@@ -235,4 +782,19 @@ impl DatabaseManager {
     pub async fn list_tables(&self) -> Result<Vec<String>, DatabaseError> {
         self.backend.list_tables().await
     }
+
+    /// Begin a transaction. Operations on the returned manager run inside it
+    /// until `commit` or `rollback`; dropping it uncommitted rolls back.
+    pub async fn begin(&self) -> Result<DatabaseManager, DatabaseError> {
+        let backend = self.backend.begin().await?;
+        Ok(Self { backend })
+    }
+
+    pub async fn commit(&self) -> Result<(), DatabaseError> {
+        self.backend.commit().await
+    }
+
+    pub async fn rollback(&self) -> Result<(), DatabaseError> {
+        self.backend.rollback().await
+    }
 }
@@ -1,12 +1,15 @@
 //! HTTP request handling logic
 
+use super::api_generator::{OperationType, PaginationStyle};
 use super::app_state::AppState;
-use super::crud_handler::CRUDError;
+use super::crud_handler::{CRUDError, CRUDHandler};
+use super::database::{ConstraintKind, ConstraintViolation};
 use super::hyper::{Request, Response, StatusCode};
 use super::{Arc, http_body_util::Full, hyper::body::Bytes};
-use crate::modules::ModuleOutcome;
 use crate::modules::metrics::RequestMetrics;
+use crate::modules::{ModuleOutcome, error_response};
 use crate::phases::{Phase, RequestContext};
+use crate::problem::{Problem, RequestId};
 use arc_swap::ArcSwap;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -40,9 +43,18 @@ pub async fn handle_request(
     );
     ctx.extensions = parts.extensions; // carry over existing request extensions
     ctx.query_params = extract_query_params(parts.uri.query());
+    let request_id = RequestId::from_headers(&ctx.headers);
+    ctx.extensions.insert(request_id.clone());
 
     // Inner handler that returns response
-    let response = handle_request_inner(&mut ctx, body_stream, state.clone()).await?;
+    let response = match handle_request_inner(&mut ctx, body_stream, state.clone()).await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::error!("Internal server error: {}", e);
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string())
+        }
+    };
+    let response = crate::problem::stamp_response(response, &path, &request_id).await;
 
     // Record metrics before returning
     metrics.record(response.status().as_u16());
@@ -114,21 +126,17 @@ async fn handle_request_inner(
                 .await;
             } else {
                 tracing::error!("Data Manager Cache is missing in AppState");
-                return Ok(Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .body(Full::new(Bytes::from(
-                        "Internal Server Error: Data Manager Cache Missing",
-                    )))
-                    .unwrap());
+                return Ok(error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Data Manager Cache Missing",
+                ));
             }
         } else {
             tracing::error!("Control Plane DB is present but Config is missing in AppState");
-            return Ok(Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Full::new(Bytes::from(
-                    "Internal Server Error: CP Config Missing",
-                )))
-                .unwrap());
+            return Ok(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "CP Config Missing",
+            ));
         }
     }
 
@@ -146,10 +154,7 @@ async fn handle_request_inner(
                         ctx.json_body = Some(value);
                     }
                     Err(_) => {
-                        return Ok(create_error_response(
-                            StatusCode::BAD_REQUEST,
-                            "Invalid JSON body",
-                        ));
+                        return Ok(error_response(StatusCode::BAD_REQUEST, "Invalid JSON body"));
                     }
                 }
             }
@@ -209,7 +214,7 @@ async fn handle_request_inner(
                 }
                 ModuleOutcome::Error(e) => {
                     tracing::error!("BodyParse Module error: {e}");
-                    return Ok(create_error_response(
+                    return Ok(error_response(
                         StatusCode::INTERNAL_SERVER_ERROR,
                         "Module error",
                     ));
@@ -228,7 +233,7 @@ async fn handle_request_inner(
                 }
                 ModuleOutcome::Error(e) => {
                     tracing::error!("Module error: {e}");
-                    return Ok(create_error_response(
+                    return Ok(error_response(
                         StatusCode::INTERNAL_SERVER_ERROR,
                         "Module error",
                     ));
@@ -238,7 +243,7 @@ async fn handle_request_inner(
 
         // Phase: Data (CRUD execution)
         tracing::debug!("Executing CRUD for {} {}", method, ctx.path);
-        match crud_handler
+        let etag = match crud_handler
             .handle_request(
                 method.as_str(),
                 &ctx.path,
@@ -251,25 +256,60 @@ async fn handle_request_inner(
         {
             Ok(result) => {
                 tracing::debug!("CRUD success for {} {}", method, ctx.path);
+                // Validators for conditional requests: records by version or column
+                // hash, lists by their content
+                let etag =
+                    ctx.matched_route
+                        .as_ref()
+                        .and_then(|pattern| match pattern.operation_type {
+                            OperationType::Get => Some(
+                                crud_handler
+                                    .record_etag(&pattern.table_name, &result)
+                                    .unwrap_or_else(|| CRUDHandler::content_etag(&result)),
+                            ),
+                            OperationType::List | OperationType::Aggregate => {
+                                Some(CRUDHandler::content_etag(&result))
+                            }
+                            _ => None,
+                        });
+                if let Some(tag) = &etag
+                    && let Some(header) = ctx.headers.get(hyper::header::IF_NONE_MATCH)
+                    && let Ok(header) = header.to_str()
+                    && CRUDHandler::etag_matches(header, Some(tag))
+                {
+                    let mut response = create_empty_response(StatusCode::NOT_MODIFIED);
+                    if let Ok(value) = tag.parse() {
+                        response.headers_mut().insert(hyper::header::ETAG, value);
+                    }
+                    return Ok(response);
+                }
                 ctx.result_json = Some(result);
+                etag
             }
             Err(CRUDError::NotFoundError(_)) => {
-                return Ok(create_error_response(
-                    StatusCode::NOT_FOUND,
-                    "Resource not found",
-                ));
+                return Ok(error_response(StatusCode::NOT_FOUND, "Resource not found"));
             }
             Err(CRUDError::ValidationError(msg)) => {
                 tracing::warn!("Validation Error: {}", msg);
-                return Ok(create_error_response(StatusCode::BAD_REQUEST, &msg));
+                return Ok(error_response(StatusCode::BAD_REQUEST, &msg));
             }
             Err(CRUDError::InvalidParameterError(msg)) => {
                 tracing::warn!("Invalid Parameter Error: {}", msg);
-                return Ok(create_error_response(StatusCode::BAD_REQUEST, &msg));
+                return Ok(error_response(StatusCode::BAD_REQUEST, &msg));
+            }
+            Err(CRUDError::PreconditionFailedError(msg)) => {
+                return Ok(error_response(StatusCode::PRECONDITION_FAILED, &msg));
+            }
+            Err(CRUDError::ConflictError(msg)) => {
+                return Ok(error_response(StatusCode::CONFLICT, &msg));
             }
             Err(CRUDError::DatabaseError(e)) => {
+                if let Some(violation) = e.constraint_violation() {
+                    tracing::warn!("Constraint violation: {}", violation.message);
+                    return Ok(create_constraint_error_response(&violation));
+                }
                 tracing::error!("Database error: {:?}", e);
-                return Ok(create_error_response(
+                return Ok(error_response(
                     StatusCode::INTERNAL_SERVER_ERROR,
                     &format!("Database error: {}", e),
                 ));
@@ -278,14 +318,65 @@ async fn handle_request_inner(
 
         // Phase: Response (serialize JSON)
         if let Some(ref val) = ctx.result_json {
+            // Header pagination: the body stays a bare array, totals move to headers
+            if let Some(pattern) = &ctx.matched_route
+                && pattern.pagination == PaginationStyle::Headers
+                && let Some(data) = val.get("data")
+            {
+                let json_response = serde_json::to_string(data)
+                    .map_err(|e| format!("Failed to serialize response: {}", e))?;
+                let mut response = create_json_response(StatusCode::OK, json_response);
+                let headers = response.headers_mut();
+                if let Some(tag) = etag.and_then(|tag| tag.parse().ok()) {
+                    headers.insert(hyper::header::ETAG, tag);
+                }
+                if let Some(total) = val.get("total").and_then(|t| t.as_u64()) {
+                    headers.insert("X-Total-Count", total.into());
+                }
+                if let Some(next) = val.get("next").and_then(|n| n.as_str())
+                    && let Ok(link) = format!("<{}>; rel=\"next\"", next).parse()
+                {
+                    headers.insert(hyper::header::LINK, link);
+                }
+                return Ok(response);
+            }
+
+            // Single creates answer 201 with the new record's URL; upserts (`on_conflict`)
+            // stay 200. Deletes have nothing to return unless `x-delete-status: 200`.
+            let mut status = StatusCode::OK;
+            let mut location = None;
+            if let Some(pattern) = &ctx.matched_route {
+                match pattern.operation_type {
+                    OperationType::Create if !ctx.query_params.contains_key("on_conflict") => {
+                        status = StatusCode::CREATED;
+                        if ctx.json_body.as_ref().is_some_and(Value::is_object) {
+                            location = crud_handler.record_location(pattern, val);
+                        }
+                    }
+                    OperationType::Delete if pattern.delete_status == 204 => {
+                        return Ok(create_empty_response(StatusCode::NO_CONTENT));
+                    }
+                    _ => {}
+                }
+            }
+
             let json_response = serde_json::to_string(val)
                 .map_err(|e| format!("Failed to serialize response: {}", e))?;
 
-            return Ok(create_json_response(StatusCode::OK, json_response));
+            let mut response = create_json_response(status, json_response);
+            if let Some(tag) = etag.and_then(|tag| tag.parse().ok()) {
+                response.headers_mut().insert(hyper::header::ETAG, tag);
+            }
+            if let Some(location) = location.and_then(|l| l.parse().ok()) {
+                response
+                    .headers_mut()
+                    .insert(hyper::header::LOCATION, location);
+            }
+            return Ok(response);
         }
 
         // Should not reach here
-        Ok(create_error_response(
+        Ok(error_response(
             StatusCode::INTERNAL_SERVER_ERROR,
             "Empty response",
         ))
@@ -310,10 +401,9 @@ async fn handle_request_inner(
 fn extract_query_params(query: Option<&str>) -> HashMap<String, String> {
     let mut params = HashMap::new();
     if let Some(query_str) = query {
-        for pair in query_str.split('&') {
-            if let Some((key, value)) = pair.split_once('=') {
-                params.insert(key.to_string(), value.to_string());
-            }
+        // Percent-decode so filters like `age%5Bgte%5D=18` reach the handler as `age[gte]`
+        for (key, value) in form_urlencoded::parse(query_str.as_bytes()) {
+            params.insert(key.into_owned(), value.into_owned());
         }
     }
     params
@@ -333,12 +423,35 @@ fn create_json_response(status: StatusCode, body: String) -> Response<Full<Bytes
         })
 }
 
-/// Create an error response
-fn create_error_response(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
-    let error_body = serde_json::json!({
-        "error": message,
-        "status": status.as_u16()
-    });
+/// Create a response without a body
+fn create_empty_response(status: StatusCode) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::new()));
+    *response.status_mut() = status;
+    response
+}
 
-    create_json_response(status, error_body.to_string())
+/// Create the response to a write rejected by a database constraint: duplicates and
+/// dangling references conflict with stored data (409), missing or out-of-range values
+/// are unprocessable (422)
+fn create_constraint_error_response(violation: &ConstraintViolation) -> Response<Full<Bytes>> {
+    let (status, message) = match violation.kind {
+        ConstraintKind::Unique => (StatusCode::CONFLICT, "Unique constraint violated"),
+        ConstraintKind::ForeignKey => (StatusCode::CONFLICT, "Foreign key constraint violated"),
+        ConstraintKind::NotNull => (StatusCode::UNPROCESSABLE_ENTITY, "Required value missing"),
+        ConstraintKind::Check => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Check constraint violated",
+        ),
+    };
+    Problem::new(status)
+        .with_detail(message)
+        .with_extension(
+            "constraint",
+            serde_json::json!({
+                "type": violation.kind.as_str(),
+                "name": violation.constraint,
+                "detail": violation.message
+            }),
+        )
+        .into_response()
 }
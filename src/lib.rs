@@ -5,8 +5,12 @@ pub mod control_plane;
 pub mod crud_handler;
 pub mod database;
 pub mod handler;
+pub mod migration;
 pub mod modules;
+pub mod patch;
 pub mod phases;
+pub mod problem;
+pub mod query;
 pub mod schema_generator;
 pub mod server;
 pub mod startup;
@@ -0,0 +1,568 @@
+//! Schema migration plans: the statements bringing a datasource in line with its
+//! API specs, computed without applying them so they can be reviewed first
+
+use crate::app_state::{
+    OpenApiStateConfig, connect_datasource, datasource_config, group_apis_by_datasource,
+};
+use crate::config::{DatabaseSettings, MigrationMode};
+use crate::database::{DatabaseError, DatabaseManager};
+use crate::schema_generator::{ColumnDefinition, DropPolicy, SchemaGenerator, TableSchema};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Table recording each migration applied, or refused, by `initialize_schema`
+pub const HISTORY_TABLE: &str = "_apify_migrations";
+
+/// How much a migration can disturb existing data, from least to most
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationRisk {
+    /// Nothing to do
+    None,
+    /// New tables, columns and indexes, or constraints being relaxed
+    Additive,
+    /// Existing columns or tables rewritten in place; no data is lost but the
+    /// table may be locked and the statements can fail on existing rows
+    Rewrite,
+    /// Data is lost, or the change is refused as incompatible
+    Destructive,
+}
+
+impl MigrationRisk {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationRisk::None => "none",
+            MigrationRisk::Additive => "additive",
+            MigrationRisk::Rewrite => "rewrite",
+            MigrationRisk::Destructive => "destructive",
+        }
+    }
+}
+
+/// What a plan does to a table
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableAction {
+    Create,
+    Alter,
+    Unchanged,
+}
+
+impl TableAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TableAction::Create => "create",
+            TableAction::Alter => "alter",
+            TableAction::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// Ordered statements migrating one table
+#[derive(Clone, Debug, Serialize)]
+pub struct TablePlan {
+    pub table: String,
+    pub action: TableAction,
+    pub risk: MigrationRisk,
+    pub statements: Vec<String>,
+    /// Why the table cannot be migrated, e.g. an incompatible type change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TablePlan {
+    /// Plan bringing `current`, or a missing table when `None`, to `desired`
+    pub fn new(current: Option<&TableSchema>, desired: &TableSchema, driver: &str) -> Self {
+        let mut plan = Self {
+            table: desired.table_name.clone(),
+            action: TableAction::Unchanged,
+            risk: MigrationRisk::None,
+            statements: Vec::new(),
+            error: None,
+        };
+        let Some(current) = current else {
+            let sql = match driver {
+                "postgres" | "postgresql" => {
+                    SchemaGenerator::generate_create_table_sql_postgres(desired)
+                }
+                "mysql" | "mariadb" => SchemaGenerator::generate_create_table_sql_mysql(desired),
+                _ => SchemaGenerator::generate_create_table_sql_sqlite(desired),
+            };
+            plan.action = TableAction::Create;
+            plan.risk = MigrationRisk::Additive;
+            plan.statements = sql
+                .split(';')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect();
+            return plan;
+        };
+
+        match SchemaGenerator::generate_migration_sql(current, desired, driver) {
+            Ok(statements) if statements.is_empty() => {}
+            Ok(statements) => {
+                // Recreating a SQLite table leaves out the columns being dropped
+                let drops_columns = driver == "sqlite"
+                    && desired.drop_columns == DropPolicy::Drop
+                    && !SchemaGenerator::undeclared_columns(current, desired).is_empty();
+                plan.risk = if drops_columns {
+                    MigrationRisk::Destructive
+                } else {
+                    statements
+                        .iter()
+                        .map(|s| statement_risk(s))
+                        .max()
+                        .unwrap_or(MigrationRisk::None)
+                };
+                plan.action = TableAction::Alter;
+                plan.statements = statements;
+            }
+            Err(e) => {
+                plan.action = TableAction::Alter;
+                plan.risk = MigrationRisk::Destructive;
+                plan.error = Some(e);
+            }
+        }
+        plan
+    }
+
+    /// Append the statements syncing the table's indexes. Building a new index is
+    /// additive, while replacing an existing one rebuilds it.
+    pub fn push_index_statements(&mut self, statements: Vec<String>, replaces_existing: bool) {
+        if statements.is_empty() {
+            return;
+        }
+        let risk = if replaces_existing {
+            MigrationRisk::Rewrite
+        } else {
+            MigrationRisk::Additive
+        };
+        if self.action == TableAction::Unchanged {
+            self.action = TableAction::Alter;
+        }
+        self.risk = self.risk.max(risk);
+        self.statements.extend(statements);
+    }
+}
+
+/// Refuse to apply plans when any table cannot be migrated, before anything is changed
+pub fn ensure_applicable(plans: &[TablePlan]) -> Result<(), DatabaseError> {
+    match plans.iter().find_map(|p| p.error.as_ref()) {
+        Some(error) => Err(DatabaseError::ValidationError(error.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Risk of a single `ALTER` statement
+fn statement_risk(sql: &str) -> MigrationRisk {
+    let sql = sql.trim().to_uppercase();
+    if sql.starts_with("CREATE ")
+        || sql.contains(" ADD COLUMN ")
+        || sql.ends_with(" DROP NOT NULL")
+        || sql.ends_with(" DROP DEFAULT")
+        || sql.contains(" SET DEFAULT ")
+        || sql.contains(" DROP CONSTRAINT ")
+        || sql.contains(" DROP FOREIGN KEY ")
+    {
+        MigrationRisk::Additive
+    } else if sql.contains(" DROP COLUMN ") {
+        MigrationRisk::Destructive
+    } else {
+        MigrationRisk::Rewrite
+    }
+}
+
+/// Schema of the migration history table
+pub fn history_schema() -> TableSchema {
+    let column = |name: &str, column_type: &str, nullable: bool| ColumnDefinition {
+        name: name.to_string(),
+        column_type: column_type.to_string(),
+        nullable,
+        primary_key: name == "id",
+        unique: false,
+        auto_increment: name == "id",
+        default_value: None,
+        auto_field: false,
+        renamed_from: None,
+    };
+    TableSchema {
+        table_name: HISTORY_TABLE.to_string(),
+        columns: vec![
+            column("id", "INTEGER", false),
+            column("spec_hash", "TEXT", false),
+            column("statements", "TEXT", false), // JSON array
+            column("duration_ms", "INTEGER", false),
+            column("outcome", "TEXT", false),
+            column("error", "TEXT", true),
+            column("applied_at", "INTEGER", false), // Timestamp
+        ],
+        indexes: vec![],
+        relations: vec![],
+        default_sort: None,
+        cursor_column: None,
+        soft_delete: None,
+        version_column: None,
+        search_columns: vec![],
+        drop_columns: DropPolicy::Keep,
+        foreign_keys: vec![],
+        checks: vec![],
+    }
+}
+
+/// Hash of the table schemas a database is migrated to, identifying the spec version
+pub fn spec_hash(table_schemas: &[TableSchema]) -> String {
+    let json = serde_json::to_string(table_schemas).unwrap_or_default();
+    Sha256::digest(json.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Order tables so that every table comes after the tables its foreign keys
+/// reference, keeping the given order otherwise. Tables in a reference cycle keep
+/// their relative order.
+pub fn dependency_order(mut table_schemas: Vec<TableSchema>) -> Vec<TableSchema> {
+    let mut ordered: Vec<TableSchema> = Vec::with_capacity(table_schemas.len());
+    while !table_schemas.is_empty() {
+        let ready = table_schemas
+            .iter()
+            .position(|schema| {
+                schema.foreign_keys.iter().all(|fk| {
+                    fk.references_table == schema.table_name
+                        || !table_schemas
+                            .iter()
+                            .any(|other| other.table_name == fk.references_table)
+                })
+            })
+            .unwrap_or(0);
+        ordered.push(table_schemas.remove(ready));
+    }
+    ordered
+}
+
+/// A migration in progress, recorded in the history table once it ends
+pub struct MigrationRun {
+    spec_hash: String,
+    statements: Vec<String>,
+    started: Instant,
+    /// Why a table cannot be migrated, refusing the whole run before anything is changed
+    refusal: Option<String>,
+}
+
+impl MigrationRun {
+    /// Start applying `plans`, or `None` when they leave the database unchanged
+    pub fn start(spec_hash: String, plans: &[TablePlan]) -> Option<Self> {
+        if plans.iter().all(|p| p.action == TableAction::Unchanged) {
+            return None;
+        }
+        Some(Self {
+            spec_hash,
+            statements: plans.iter().flat_map(|p| p.statements.clone()).collect(),
+            started: Instant::now(),
+            refusal: plans.iter().find_map(|p| p.error.clone()),
+        })
+    }
+
+    /// History row of the migration: `applied`, `refused` when a table could not be
+    /// migrated and nothing was run, or `failed` when applying the statements failed
+    pub fn finish(self, result: &Result<(), DatabaseError>) -> HashMap<String, Value> {
+        let (outcome, error) = match (result, self.refusal) {
+            (Ok(()), _) => ("applied", Value::Null),
+            (Err(_), Some(refusal)) => ("refused", Value::String(refusal)),
+            (Err(e), None) => ("failed", Value::String(e.to_string())),
+        };
+        let applied_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut row = HashMap::new();
+        row.insert("spec_hash".to_string(), Value::String(self.spec_hash));
+        row.insert(
+            "statements".to_string(),
+            Value::String(serde_json::to_string(&self.statements).unwrap_or_default()),
+        );
+        row.insert(
+            "duration_ms".to_string(),
+            Value::from(self.started.elapsed().as_millis() as u64),
+        );
+        row.insert("outcome".to_string(), Value::String(outcome.to_string()));
+        row.insert("error".to_string(), error);
+        row.insert("applied_at".to_string(), Value::from(applied_at));
+        row
+    }
+}
+
+/// Migration plan of one datasource
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrationPlan {
+    pub datasource: String,
+    pub driver: String,
+    /// Highest risk of the table plans
+    pub risk: MigrationRisk,
+    pub tables: Vec<TablePlan>,
+}
+
+impl MigrationPlan {
+    pub fn new(datasource: String, driver: String, tables: Vec<TablePlan>) -> Self {
+        let risk = tables
+            .iter()
+            .map(|t| t.risk)
+            .max()
+            .unwrap_or(MigrationRisk::None);
+        Self {
+            datasource,
+            driver,
+            risk,
+            tables,
+        }
+    }
+
+    /// Whether applying the plan would run any statement
+    pub fn has_changes(&self) -> bool {
+        self.tables
+            .iter()
+            .any(|t| t.action != TableAction::Unchanged)
+    }
+}
+
+/// Name and settings of a datasource, and the table schemas of the APIs stored in it
+type DatasourceSchemas<'a> = (String, &'a DatabaseSettings, Vec<TableSchema>);
+
+/// Table schemas of the given APIs per datasource, in the order the datasources are
+/// initialized at startup
+fn schemas_by_datasource<'a>(
+    datasources: &'a HashMap<String, DatabaseSettings>,
+    openapi_configs: &[OpenApiStateConfig],
+) -> Result<Vec<DatasourceSchemas<'a>>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut groups = Vec::new();
+    for (name, api_configs) in group_apis_by_datasource(openapi_configs, datasources)? {
+        let ds = datasources
+            .get(&name)
+            .ok_or_else(|| format!("Datasource '{}' not found in config", name))?;
+        let mut schemas = Vec::new();
+        for api_config in api_configs {
+            schemas.extend(SchemaGenerator::extract_schemas_from_openapi(
+                &api_config.config.openapi.spec,
+            )?);
+        }
+        groups.push((name, ds, schemas));
+    }
+    Ok(groups)
+}
+
+/// Plan the migrations of every datasource used by the given APIs. Nothing is
+/// written to the databases.
+pub async fn plan_migrations(
+    datasources: &HashMap<String, DatabaseSettings>,
+    openapi_configs: &[OpenApiStateConfig],
+) -> Result<Vec<MigrationPlan>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut plans = Vec::new();
+    for (name, ds, schemas) in schemas_by_datasource(datasources, openapi_configs)? {
+        if ds.migrations == Some(MigrationMode::Off) {
+            continue;
+        }
+        // Planning must not create a SQLite file; one that does not exist yet is empty
+        let mut config = datasource_config(ds)?;
+        if config.driver == "sqlite"
+            && let Some(path) = config.url.strip_prefix("sqlite:")
+            && path != ":memory:"
+            && !std::path::Path::new(path).exists()
+        {
+            config.url = "sqlite::memory:".to_string();
+        }
+        let db = DatabaseManager::connect_existing(config).await?;
+        let tables = db.plan_schema(schemas).await?;
+        plans.push(MigrationPlan::new(name, ds.driver.clone(), tables));
+    }
+    Ok(plans)
+}
+
+/// Apply the plans computed by `plan_migrations` to every datasource used by the given APIs,
+/// including those in `manual` mode. Datasources with migrations `off` are left alone.
+/// Nothing is applied when a datasource no longer plans the same statements, e.g. because
+/// its schema changed after the plans were printed.
+pub async fn apply_migrations(
+    datasources: &HashMap<String, DatabaseSettings>,
+    openapi_configs: &[OpenApiStateConfig],
+    plans: &[MigrationPlan],
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut pending = Vec::new();
+    for (name, ds, schemas) in schemas_by_datasource(datasources, openapi_configs)? {
+        if schemas.is_empty() || ds.migrations == Some(MigrationMode::Off) {
+            continue;
+        }
+        let db = connect_datasource(ds).await?;
+        let current = db.plan_schema(schemas.clone()).await?;
+        let planned = plans.iter().find(|p| p.datasource == name);
+        if planned.map(|p| plan_statements(&p.tables)) != Some(plan_statements(&current)) {
+            return Err(format!(
+                "The migration plan of datasource '{}' changed since it was computed; \
+                 review it again with `apify migrate --plan`",
+                name
+            )
+            .into());
+        }
+        pending.push((db, schemas));
+    }
+    for (db, schemas) in pending {
+        db.initialize_schema(schemas).await?;
+    }
+    Ok(())
+}
+
+/// Statements of each table plan, to tell whether two plans would run the same migration
+fn plan_statements(tables: &[TablePlan]) -> Vec<(&str, &[String])> {
+    tables
+        .iter()
+        .map(|t| (t.table.as_str(), t.statements.as_slice()))
+        .collect()
+}
+
+/// Bring a datasource in line with its table schemas as its migration mode says:
+/// apply the migrations (`auto`), refuse to continue while any are pending (`manual`)
+/// or do nothing (`off`)
+pub async fn migrate_datasource(
+    db: &DatabaseManager,
+    datasource: &str,
+    mode: MigrationMode,
+    table_schemas: Vec<TableSchema>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match mode {
+        MigrationMode::Auto => Ok(db.initialize_schema(table_schemas).await?),
+        MigrationMode::Manual => {
+            let pending: Vec<String> = db
+                .plan_schema(table_schemas)
+                .await?
+                .into_iter()
+                .filter(|p| p.action != TableAction::Unchanged)
+                .map(|p| p.table)
+                .collect();
+            if pending.is_empty() {
+                return Ok(());
+            }
+            Err(format!(
+                "Datasource '{}' has pending migrations for tables {}; review them with \
+                 `apify migrate --plan` and apply them with `apify migrate`",
+                datasource,
+                pending.join(", ")
+            )
+            .into())
+        }
+        MigrationMode::Off => {
+            tracing::info!(datasource = %datasource, "Schema migrations are off");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, column_type: &str, nullable: bool) -> ColumnDefinition {
+        ColumnDefinition {
+            name: name.to_string(),
+            column_type: column_type.to_string(),
+            nullable,
+            primary_key: name == "id",
+            unique: false,
+            auto_increment: false,
+            default_value: None,
+            auto_field: false,
+            renamed_from: None,
+        }
+    }
+
+    fn table(columns: Vec<ColumnDefinition>) -> TableSchema {
+        TableSchema {
+            table_name: "users".to_string(),
+            columns,
+            indexes: vec![],
+            relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: vec![],
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
+        }
+    }
+
+    #[test]
+    fn plans_are_classified_by_risk() {
+        let current = table(vec![
+            column("id", "INTEGER", false),
+            column("name", "TEXT", true),
+        ]);
+
+        let plan = TablePlan::new(None, &current, "sqlite");
+        assert_eq!(plan.action, TableAction::Create);
+        assert_eq!(plan.risk, MigrationRisk::Additive);
+        assert!(plan.statements[0].starts_with("CREATE TABLE"));
+
+        let plan = TablePlan::new(Some(&current), &current, "postgres");
+        assert_eq!(plan.action, TableAction::Unchanged);
+        assert_eq!(plan.risk, MigrationRisk::None);
+
+        let mut added = current.clone();
+        added.columns.push(column("email", "TEXT", true));
+        let plan = TablePlan::new(Some(&current), &added, "postgres");
+        assert_eq!(plan.risk, MigrationRisk::Additive);
+        assert_eq!(plan.statements.len(), 1);
+
+        let mut required = current.clone();
+        required.columns[1].nullable = false;
+        let plan = TablePlan::new(Some(&current), &required, "postgres");
+        assert_eq!(plan.risk, MigrationRisk::Rewrite);
+        assert_eq!(
+            plan.statements,
+            vec!["ALTER TABLE users ALTER COLUMN name SET NOT NULL"]
+        );
+        // SQLite recreates the table, copying every column
+        let plan = TablePlan::new(Some(&current), &required, "sqlite");
+        assert_eq!(plan.risk, MigrationRisk::Rewrite);
+
+        // Columns removed from the spec are kept unless dropped explicitly
+        let mut dropped = table(vec![column("id", "INTEGER", false)]);
+        let plan = TablePlan::new(Some(&current), &dropped, "sqlite");
+        assert_eq!(plan.risk, MigrationRisk::None);
+        let plan = TablePlan::new(Some(&current), &dropped, "postgres");
+        assert_eq!(plan.risk, MigrationRisk::None);
+        dropped.drop_columns = DropPolicy::Drop;
+        let plan = TablePlan::new(Some(&current), &dropped, "sqlite");
+        assert_eq!(plan.risk, MigrationRisk::Destructive);
+        let plan = TablePlan::new(Some(&current), &dropped, "postgres");
+        assert_eq!(plan.risk, MigrationRisk::Destructive);
+        assert_eq!(plan.statements, vec!["ALTER TABLE users DROP COLUMN name"]);
+
+        let mut retyped = current.clone();
+        retyped.columns[1].column_type = "INTEGER".to_string();
+        let plan = TablePlan::new(Some(&current), &retyped, "postgres");
+        assert_eq!(plan.risk, MigrationRisk::Destructive);
+        assert!(plan.statements.is_empty());
+        assert!(plan.error.unwrap().contains("Incompatible type change"));
+
+        let mut indexed = TablePlan::new(Some(&current), &current, "sqlite");
+        indexed.push_index_statements(vec!["CREATE INDEX i ON users (name)".to_string()], false);
+        assert_eq!(indexed.action, TableAction::Alter);
+        assert_eq!(indexed.risk, MigrationRisk::Additive);
+
+        let plan = MigrationPlan::new(
+            "main".to_string(),
+            "sqlite".to_string(),
+            vec![indexed, TablePlan::new(Some(&current), &dropped, "sqlite")],
+        );
+        assert_eq!(plan.risk, MigrationRisk::Destructive);
+        assert!(plan.has_changes());
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["risk"], "destructive");
+        assert_eq!(json["tables"][0]["action"], "alter");
+        assert!(json["tables"][0].get("error").is_none());
+    }
+}
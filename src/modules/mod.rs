@@ -2,14 +2,15 @@
 
 use crate::app_state::AppState;
 use crate::http_body_util::Full;
+use crate::hyper::Response;
 use crate::hyper::body::Bytes;
-use crate::hyper::{Response, StatusCode};
 use crate::phases::{Phase, RequestContext};
 use std::error::Error;
 use std::sync::Arc;
 
 pub mod key_auth;
 pub mod metrics;
+pub mod mysql;
 pub mod oauth;
 pub mod openapi_docs;
 pub mod postgres;
@@ -78,11 +79,4 @@ impl Default for ModuleRegistry {
 }
 
 /// Helper for building error response bodies
-pub fn error_response(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
-    let body = serde_json::json!({"error": message, "status": status.as_u16()}).to_string();
-    Response::builder()
-        .status(status)
-        .header("Content-Type", "application/json")
-        .body(Full::new(Bytes::from(body)))
-        .unwrap()
-}
+pub use crate::problem::error_response;
@@ -0,0 +1,1178 @@
+//! MySQL/MariaDB backend implementation for DatabaseBackend
+
+use serde_json::{Value, json};
+use sqlx::mysql::{MySql, MySqlArguments, MySqlPool, MySqlPoolOptions, MySqlQueryResult, MySqlRow};
+use sqlx::types::chrono;
+use sqlx::{Column, QueryBuilder, Row, TypeInfo};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::database::{
+    Aggregate, AggregateFunction, AggregateQuery, BeginFuture, BulkInsert, DatabaseBackend,
+    DatabaseError, DatabaseRuntimeConfig, Filter, FilterOperator, Keyset, RELEVANCE_SORT,
+    SelectQuery, SharedTransaction, SortKey, TextSearch, nested_transaction, no_transaction,
+    transaction_finished,
+};
+use crate::migration::{
+    HISTORY_TABLE, MigrationRun, TablePlan, ensure_applicable, history_schema, spec_hash,
+};
+use crate::schema_generator::{
+    CheckDefinition, ColumnDefinition, DropPolicy, ForeignKeyDefinition, IndexDefinition, OnDelete,
+    SchemaGenerator, TableSchema,
+};
+
+/// Named lock serializing schema migrations between instances sharing a database
+const MIGRATION_LOCK: &str = "apify_schema_migration";
+
+type MySqlQuery<'q> = sqlx::query::Query<'q, MySql, MySqlArguments>;
+
+#[derive(Debug, Clone)]
+pub struct MySqlBackend {
+    pub pool: MySqlPool,
+    /// Open transaction this handle runs its statements in (see `begin`)
+    tx: Option<SharedTransaction<MySql>>,
+}
+
+impl MySqlBackend {
+    pub async fn connect(config: DatabaseRuntimeConfig) -> Result<Self, DatabaseError> {
+        let pool = MySqlPoolOptions::new()
+            .max_connections(config.max_size)
+            .acquire_timeout(std::time::Duration::from_secs(5))
+            .connect(&config.url)
+            .await
+            .map_err(DatabaseError::PoolError)?;
+
+        tracing::info!(
+            "Connected to MySQL. Max pool size: {}, Current Size: {}, Idle: {}",
+            config.max_size,
+            pool.size(),
+            pool.num_idle()
+        );
+
+        Ok(Self { pool, tx: None })
+    }
+
+    /// Run a statement inside the open transaction, or on the pool outside one
+    async fn fetch_all(&self, query: MySqlQuery<'_>) -> Result<Vec<MySqlRow>, sqlx::Error> {
+        match &self.tx {
+            Some(tx) => {
+                let mut guard = tx.lock().await;
+                let conn: &mut sqlx::MySqlConnection =
+                    guard.as_mut().ok_or_else(transaction_finished)?;
+                query.fetch_all(conn).await
+            }
+            None => query.fetch_all(&self.pool).await,
+        }
+    }
+
+    async fn fetch_one(&self, query: MySqlQuery<'_>) -> Result<MySqlRow, sqlx::Error> {
+        match &self.tx {
+            Some(tx) => {
+                let mut guard = tx.lock().await;
+                let conn: &mut sqlx::MySqlConnection =
+                    guard.as_mut().ok_or_else(transaction_finished)?;
+                query.fetch_one(conn).await
+            }
+            None => query.fetch_one(&self.pool).await,
+        }
+    }
+
+    async fn execute(&self, query: MySqlQuery<'_>) -> Result<MySqlQueryResult, sqlx::Error> {
+        match &self.tx {
+            Some(tx) => {
+                let mut guard = tx.lock().await;
+                let conn: &mut sqlx::MySqlConnection =
+                    guard.as_mut().ok_or_else(transaction_finished)?;
+                query.execute(conn).await
+            }
+            None => query.execute(&self.pool).await,
+        }
+    }
+    async fn do_get_table_schema(&self, table: &str) -> Result<Option<TableSchema>, DatabaseError> {
+        // information_schema text columns may be reported as binary strings, hence the casts
+        let query = r#"
+            SELECT
+                CAST(COLUMN_NAME AS CHAR) AS column_name,
+                CAST(DATA_TYPE AS CHAR) AS data_type,
+                CAST(COLUMN_TYPE AS CHAR) AS column_type,
+                CAST(IS_NULLABLE AS CHAR) AS is_nullable,
+                CAST(COLUMN_DEFAULT AS CHAR) AS column_default,
+                CAST(COLUMN_KEY AS CHAR) AS column_key,
+                CAST(EXTRA AS CHAR) AS extra
+            FROM information_schema.COLUMNS
+            WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+            ORDER BY ORDINAL_POSITION
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(table)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DatabaseError::QueryError)?;
+
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let mut columns = Vec::new();
+        for row in rows {
+            let name: String = row.get("column_name");
+            let data_type: String = row.get("data_type");
+            let column_type: String = row.get("column_type");
+            let is_nullable: String = row.get("is_nullable");
+            let column_default: Option<String> = row.get("column_default");
+            let column_key: String = row.get("column_key");
+            let extra: String = row.get("extra");
+
+            columns.push(ColumnDefinition {
+                name,
+                column_type: generic_type_mysql(&data_type, &column_type),
+                nullable: is_nullable == "YES",
+                primary_key: column_key == "PRI",
+                unique: column_key == "UNI",
+                auto_increment: extra.contains("auto_increment"),
+                // MariaDB reports a missing default as the literal NULL
+                default_value: column_default.filter(|d| !d.eq_ignore_ascii_case("NULL")),
+                auto_field: false,
+                renamed_from: None,
+            });
+        }
+
+        let index_rows = sqlx::query(
+            r#"
+            SELECT
+                CAST(INDEX_NAME AS CHAR) AS index_name,
+                CAST(COLUMN_NAME AS CHAR) AS column_name,
+                CAST(NON_UNIQUE AS SIGNED) AS non_unique
+            FROM information_schema.STATISTICS
+            WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND INDEX_NAME <> 'PRIMARY'
+            ORDER BY INDEX_NAME, SEQ_IN_INDEX
+            "#,
+        )
+        .bind(table)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::QueryError)?;
+        let mut indexes: Vec<IndexDefinition> = Vec::new();
+        for row in index_rows {
+            let name: String = row.get("index_name");
+            let column: Option<String> = row.get("column_name");
+            if indexes.last().is_none_or(|index| index.name != name) {
+                indexes.push(IndexDefinition {
+                    name,
+                    columns: Vec::new(),
+                    unique: row.get::<i64, _>("non_unique") == 0,
+                });
+            }
+            if let (Some(index), Some(column)) = (indexes.last_mut(), column) {
+                index.columns.push(column);
+            }
+        }
+
+        // Foreign keys, by their first column
+        let foreign_keys = sqlx::query(
+            r#"
+            SELECT
+                CAST(rc.CONSTRAINT_NAME AS CHAR) AS name,
+                CAST(k.COLUMN_NAME AS CHAR) AS column_name,
+                CAST(k.REFERENCED_TABLE_NAME AS CHAR) AS references_table,
+                CAST(k.REFERENCED_COLUMN_NAME AS CHAR) AS references_column,
+                CAST(rc.DELETE_RULE AS CHAR) AS on_delete
+            FROM information_schema.REFERENTIAL_CONSTRAINTS rc
+            JOIN information_schema.KEY_COLUMN_USAGE k
+                ON k.CONSTRAINT_SCHEMA = rc.CONSTRAINT_SCHEMA
+                AND k.CONSTRAINT_NAME = rc.CONSTRAINT_NAME
+                AND k.TABLE_NAME = rc.TABLE_NAME
+            WHERE rc.CONSTRAINT_SCHEMA = DATABASE() AND rc.TABLE_NAME = ?
+                AND k.ORDINAL_POSITION = 1
+            "#,
+        )
+        .bind(table)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::QueryError)?
+        .iter()
+        .map(|row| ForeignKeyDefinition {
+            name: row.get("name"),
+            column: row.get("column_name"),
+            references_table: row.get("references_table"),
+            references_column: row.get("references_column"),
+            on_delete: OnDelete::from_sql(&row.get::<String, _>("on_delete")),
+        })
+        .collect();
+
+        // CHECK constraints need MySQL 8.0.16 or MariaDB 10.2; older servers ignore them
+        let checks = sqlx::query(
+            r#"
+            SELECT
+                CAST(tc.CONSTRAINT_NAME AS CHAR) AS name,
+                CAST(cc.CHECK_CLAUSE AS CHAR) AS expression
+            FROM information_schema.TABLE_CONSTRAINTS tc
+            JOIN information_schema.CHECK_CONSTRAINTS cc
+                ON cc.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA
+                AND cc.CONSTRAINT_NAME = tc.CONSTRAINT_NAME
+            WHERE tc.TABLE_SCHEMA = DATABASE() AND tc.TABLE_NAME = ?
+                AND tc.CONSTRAINT_TYPE = 'CHECK'
+            "#,
+        )
+        .bind(table)
+        .fetch_all(&self.pool)
+        .await
+        .map(|rows| {
+            rows.iter()
+                .map(|row| CheckDefinition {
+                    name: row.get("name"),
+                    expression: row.get("expression"),
+                })
+                .collect()
+        })
+        .unwrap_or_else(|e| {
+            tracing::warn!(table = %table, error = %e, "Could not read CHECK constraints");
+            Vec::new()
+        });
+
+        Ok(Some(TableSchema {
+            table_name: table.to_string(),
+            columns,
+            indexes,
+            relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys,
+            checks,
+        }))
+    }
+
+    async fn do_initialize_schema(
+        &self,
+        table_schemas: Vec<TableSchema>,
+    ) -> Result<(), DatabaseError> {
+        // GET_LOCK is held by the connection, so keep one connection for the whole migration
+        let mut lock_conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(DatabaseError::PoolError)?;
+        // 1 once the lock is held, 0 on timeout and NULL on error
+        let locked: Option<i64> = sqlx::query_scalar("SELECT GET_LOCK(?, 60)")
+            .bind(MIGRATION_LOCK)
+            .fetch_one(&mut *lock_conn)
+            .await
+            .map_err(DatabaseError::QueryError)?;
+        if locked != Some(1) {
+            return Err(DatabaseError::ValidationError(format!(
+                "Could not acquire the migration lock '{}' within 60 seconds",
+                MIGRATION_LOCK
+            )));
+        }
+
+        let result = self.migrate_tables(table_schemas).await;
+
+        sqlx::query("SELECT RELEASE_LOCK(?)")
+            .bind(MIGRATION_LOCK)
+            .execute(&mut *lock_conn)
+            .await
+            .map_err(DatabaseError::QueryError)?;
+        result
+    }
+
+    async fn migrate_tables(&self, table_schemas: Vec<TableSchema>) -> Result<(), DatabaseError> {
+        let spec_hash = spec_hash(&table_schemas);
+        let plans = self.do_plan_schema(table_schemas).await?;
+        let Some(run) = MigrationRun::start(spec_hash, &plans) else {
+            return Ok(());
+        };
+        let result = self.apply_plans(plans).await;
+        if let Err(e) = self.record_migration(run.finish(&result)).await {
+            tracing::error!(error = %e, "Failed to record migration history");
+        }
+        result
+    }
+
+    async fn apply_plans(&self, plans: Vec<TablePlan>) -> Result<(), DatabaseError> {
+        ensure_applicable(&plans)?;
+        for plan in plans {
+            for sql in plan.statements {
+                tracing::info!(table = %plan.table, sql = %sql, "Executing migration SQL");
+                sqlx::raw_sql(&sql)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(DatabaseError::QueryError)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Append a row to the migration history, creating the table on first use
+    async fn record_migration(&self, row: HashMap<String, Value>) -> Result<(), DatabaseError> {
+        sqlx::raw_sql(&SchemaGenerator::generate_create_table_sql_mysql(
+            &history_schema(),
+        ))
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::QueryError)?;
+        self.do_insert(HISTORY_TABLE, row).await.map(|_| ())
+    }
+
+    async fn do_plan_schema(
+        &self,
+        table_schemas: Vec<TableSchema>,
+    ) -> Result<Vec<TablePlan>, DatabaseError> {
+        let mut plans = Vec::new();
+        for schema in table_schemas {
+            let current = self.do_get_table_schema(&schema.table_name).await?;
+            let mut plan = TablePlan::new(current.as_ref(), &schema, "mysql");
+            let (statements, replaces) = self.search_index_sql(&schema).await?;
+            plan.push_index_statements(statements, replaces);
+            plans.push(plan);
+        }
+        Ok(plans)
+    }
+
+    /// Statements creating, replacing or dropping the FULLTEXT index of a table to match
+    /// its `searchColumns`, and whether they replace an index built for other columns
+    async fn search_index_sql(
+        &self,
+        schema: &TableSchema,
+    ) -> Result<(Vec<String>, bool), DatabaseError> {
+        let index = SchemaGenerator::search_index_name(&schema.table_name);
+        let indexed: Vec<String> = sqlx::query_scalar(
+            "SELECT COLUMN_NAME FROM information_schema.STATISTICS \
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND INDEX_NAME = ? \
+             ORDER BY SEQ_IN_INDEX",
+        )
+        .bind(&schema.table_name)
+        .bind(&index)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::QueryError)?;
+
+        let mut statements = Vec::new();
+        let replaces = !indexed.is_empty() && indexed != schema.search_columns;
+        if replaces {
+            statements.push(format!("DROP INDEX {} ON {}", index, schema.table_name));
+        }
+        if !schema.search_columns.is_empty() && indexed != schema.search_columns {
+            statements.extend(SchemaGenerator::generate_search_sql_mysql(schema));
+        }
+        Ok((statements, replaces))
+    }
+
+    async fn do_select(
+        &self,
+        table: &str,
+        query: SelectQuery,
+    ) -> Result<Vec<Value>, DatabaseError> {
+        let mut qb = select_sql_mysql(table, &query);
+        let start = std::time::Instant::now();
+        let rows: Vec<MySqlRow> = self.fetch_all(qb.build()).await.map_err(|e| {
+            tracing::error!(
+                "MySQL select error on table {}: {:?}. Pool Size: {}, Idle: {}",
+                table,
+                e,
+                self.pool.size(),
+                self.pool.num_idle()
+            );
+            DatabaseError::QueryError(e)
+        })?;
+
+        let elapsed = start.elapsed();
+        if elapsed > std::time::Duration::from_millis(500) {
+            tracing::warn!(
+                "Slow MySQL select on table {} took {:?}. Pool Size: {}, Idle: {}",
+                table,
+                elapsed,
+                self.pool.size(),
+                self.pool.num_idle()
+            );
+        }
+
+        Ok(rows.iter().map(row_to_json_mysql).collect())
+    }
+
+    async fn do_aggregate(
+        &self,
+        table: &str,
+        query: AggregateQuery,
+    ) -> Result<Vec<Value>, DatabaseError> {
+        let mut columns = query.group_by.clone();
+        columns.extend(
+            query
+                .aggregates
+                .iter()
+                .map(|a| format!("{} AS {}", aggregate_sql_mysql(a), a.alias())),
+        );
+        let mut qb = QueryBuilder::<MySql>::new("SELECT ");
+        qb.push(columns.join(", ")).push(" FROM ").push(table);
+        push_filters_mysql(&mut qb, &query.filters);
+        if let Some(search) = &query.search {
+            push_search_mysql(&mut qb, search, !query.filters.is_empty());
+        }
+        if !query.group_by.is_empty() {
+            let groups = query.group_by.join(", ");
+            qb.push(" GROUP BY ").push(&groups);
+            qb.push(" ORDER BY ").push(&groups);
+        }
+        let rows = self.fetch_all(qb.build()).await.map_err(|e| {
+            tracing::error!("MySQL aggregate error on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+        Ok(rows.iter().map(row_to_json_mysql).collect())
+    }
+
+    async fn do_count(
+        &self,
+        table: &str,
+        filters: Vec<Filter>,
+        search: Option<TextSearch>,
+    ) -> Result<u64, DatabaseError> {
+        let mut qb = QueryBuilder::<MySql>::new("SELECT COUNT(*) FROM ");
+        qb.push(table);
+        push_filters_mysql(&mut qb, &filters);
+        if let Some(search) = &search {
+            push_search_mysql(&mut qb, search, !filters.is_empty());
+        }
+        let row = self.fetch_one(qb.build()).await.map_err(|e| {
+            tracing::error!("MySQL count error on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+        let count: i64 = row.try_get(0).map_err(DatabaseError::QueryError)?;
+        Ok(count.max(0) as u64)
+    }
+
+    async fn do_insert(
+        &self,
+        table: &str,
+        data: HashMap<String, Value>,
+    ) -> Result<Value, DatabaseError> {
+        if data.is_empty() {
+            return Err(DatabaseError::ValidationError(
+                "No data provided for insert".to_string(),
+            ));
+        }
+        let (cols, vals): (Vec<String>, Vec<Value>) = data.into_iter().unzip();
+        let mut qb = QueryBuilder::<MySql>::new("INSERT INTO ");
+        qb.push(table).push(" (");
+        qb.push(cols.join(", ")).push(") VALUES (");
+        for (i, (col, v)) in cols.iter().zip(&vals).enumerate() {
+            if i > 0 {
+                qb.push(", ");
+            }
+            push_value_mysql(&mut qb, col, v);
+        }
+        qb.push(")");
+
+        let res = self.execute(qb.build()).await.map_err(|e| {
+            tracing::error!("Insert query failed on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        // MySQL has no RETURNING; LAST_INSERT_ID is 0 unless an AUTO_INCREMENT value was
+        // generated, and explicit keys are taken from the inserted data by the caller
+        let id = match res.last_insert_id() {
+            0 => Value::Null,
+            id => json!(id),
+        };
+        Ok(json!({
+            "message": "Record inserted",
+            "affected_rows": res.rows_affected(),
+            "id": id
+        }))
+    }
+
+    async fn do_insert_many(&self, table: &str, insert: BulkInsert) -> Result<u64, DatabaseError> {
+        let mut qb = insert_many_sql_mysql(table, &insert);
+        let res = self.execute(qb.build()).await.map_err(|e| {
+            tracing::error!("Bulk insert failed on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+        Ok(res.rows_affected())
+    }
+
+    async fn do_update(
+        &self,
+        table: &str,
+        data: HashMap<String, Value>,
+        filters: Vec<Filter>,
+        increments: Vec<String>,
+    ) -> Result<Value, DatabaseError> {
+        if data.is_empty() && increments.is_empty() {
+            return Err(DatabaseError::ValidationError(
+                "No data provided for update".to_string(),
+            ));
+        }
+        if filters.is_empty() {
+            return Err(DatabaseError::ValidationError(
+                "WHERE clause is required for update".to_string(),
+            ));
+        }
+        let mut qb = QueryBuilder::<MySql>::new("UPDATE ");
+        qb.push(table).push(" SET ");
+        for (i, (k, v)) in data.iter().enumerate() {
+            if i > 0 {
+                qb.push(", ");
+            }
+            qb.push(k).push(" = ");
+            push_value_mysql(&mut qb, k, v);
+        }
+        for (i, column) in increments.iter().enumerate() {
+            if i > 0 || !data.is_empty() {
+                qb.push(", ");
+            }
+            qb.push(column).push(" = ").push(column).push(" + 1");
+        }
+        push_filters_mysql(&mut qb, &filters);
+
+        let res = self.execute(qb.build()).await.map_err(|e| {
+            tracing::error!("Update query failed on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(json!({"message": "Record updated", "affected_rows": res.rows_affected()}))
+    }
+
+    async fn do_delete(&self, table: &str, filters: Vec<Filter>) -> Result<u64, DatabaseError> {
+        if filters.is_empty() {
+            return Err(DatabaseError::ValidationError(
+                "WHERE clause is required for delete".to_string(),
+            ));
+        }
+        let mut qb = QueryBuilder::<MySql>::new("DELETE FROM ");
+        qb.push(table);
+        push_filters_mysql(&mut qb, &filters);
+        let res = self
+            .execute(qb.build())
+            .await
+            .map_err(DatabaseError::QueryError)?;
+        Ok(res.rows_affected())
+    }
+}
+
+impl DatabaseBackend for MySqlBackend {
+    fn initialize_schema<'a>(
+        &'a self,
+        table_schemas: Vec<TableSchema>,
+    ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>
+    {
+        Box::pin(async move { self.do_initialize_schema(table_schemas).await })
+    }
+
+    fn plan_schema<'a>(
+        &'a self,
+        table_schemas: Vec<TableSchema>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Vec<TablePlan>, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_plan_schema(table_schemas).await })
+    }
+
+    fn select<'a>(
+        &'a self,
+        table: &'a str,
+        query: SelectQuery,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Vec<Value>, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_select(table, query).await })
+    }
+    fn count<'a>(
+        &'a self,
+        table: &'a str,
+        filters: Vec<Filter>,
+        search: Option<TextSearch>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_count(table, filters, search).await })
+    }
+    fn aggregate<'a>(
+        &'a self,
+        table: &'a str,
+        query: AggregateQuery,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Vec<Value>, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_aggregate(table, query).await })
+    }
+    fn insert<'a>(
+        &'a self,
+        table: &'a str,
+        data: HashMap<String, Value>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Value, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_insert(table, data).await })
+    }
+    fn insert_many<'a>(
+        &'a self,
+        table: &'a str,
+        insert: BulkInsert,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_insert_many(table, insert).await })
+    }
+    fn update<'a>(
+        &'a self,
+        table: &'a str,
+        data: HashMap<String, Value>,
+        filters: Vec<Filter>,
+        increments: Vec<String>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Value, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_update(table, data, filters, increments).await })
+    }
+    fn delete<'a>(
+        &'a self,
+        table: &'a str,
+        filters: Vec<Filter>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_delete(table, filters).await })
+    }
+
+    fn get_table_schema<'a>(
+        &'a self,
+        table: &'a str,
+    ) -> core::pin::Pin<
+        Box<
+            dyn core::future::Future<Output = Result<Option<TableSchema>, DatabaseError>>
+                + Send
+                + 'a,
+        >,
+    > {
+        Box::pin(async move { self.do_get_table_schema(table).await })
+    }
+
+    fn list_tables<'a>(
+        &'a self,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Vec<String>, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move {
+            let query = "SELECT CAST(TABLE_NAME AS CHAR) AS table_name FROM information_schema.TABLES WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE'";
+            let rows = sqlx::query(query)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(DatabaseError::QueryError)?;
+
+            let tables: Vec<String> = rows.iter().map(|r| r.get("table_name")).collect();
+            Ok(tables)
+        })
+    }
+
+    fn begin<'a>(&'a self) -> BeginFuture<'a> {
+        Box::pin(async move {
+            if self.tx.is_some() {
+                return Err(nested_transaction());
+            }
+            let tx = self.pool.begin().await.map_err(DatabaseError::PoolError)?;
+            let backend: Arc<dyn DatabaseBackend> = Arc::new(Self {
+                tx: Some(SharedTransaction::new(tx)),
+                ..self.clone()
+            });
+            Ok(backend)
+        })
+    }
+
+    fn commit<'a>(
+        &'a self,
+    ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>
+    {
+        Box::pin(async move { self.tx.as_ref().ok_or_else(no_transaction)?.commit().await })
+    }
+
+    fn rollback<'a>(
+        &'a self,
+    ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>
+    {
+        Box::pin(async move {
+            self.tx
+                .as_ref()
+                .ok_or_else(no_transaction)?
+                .rollback()
+                .await
+        })
+    }
+}
+
+/// Map an introspected MySQL column type back to the generic names used in table schemas
+fn generic_type_mysql(data_type: &str, column_type: &str) -> String {
+    let column_type = column_type.to_ascii_lowercase();
+    // Lengths and precisions, as in varchar(50) or decimal(10,2)
+    let sized = || {
+        column_type
+            .split(' ')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    match data_type.to_ascii_lowercase().as_str() {
+        "tinyint" if column_type.starts_with("tinyint(1)") => "boolean".to_string(),
+        "tinyint" | "smallint" => "smallint".to_string(),
+        "mediumint" | "int" | "integer" => "integer".to_string(),
+        "bigint" => "bigint".to_string(),
+        "varchar" | "char" | "decimal" => sized(),
+        "numeric" => sized().replace("numeric", "decimal"),
+        "json" => "json".to_string(),
+        "tinytext" | "text" | "mediumtext" | "longtext" => "text".to_string(),
+        "float" | "double" | "real" => "double".to_string(),
+        "datetime" | "timestamp" => "datetime".to_string(),
+        "date" => "date".to_string(),
+        "time" => "time".to_string(),
+        "tinyblob" | "blob" | "mediumblob" | "longblob" | "binary" | "varbinary" => {
+            "blob".to_string()
+        }
+        _ => data_type.to_string(),
+    }
+}
+
+fn row_to_json_mysql(row: &MySqlRow) -> Value {
+    let mut obj = serde_json::Map::new();
+    for (i, col) in row.columns().iter().enumerate() {
+        let name = col.name().to_string();
+        // NULLs decode as Err for every type below
+        if row
+            .try_get_raw(i)
+            .map(|v| sqlx::ValueRef::is_null(&v))
+            .unwrap_or(true)
+        {
+            obj.insert(name, Value::Null);
+            continue;
+        }
+        // Integer decoders accept TINYINT(1) too, so dispatch on the reported type first
+        match col.type_info().name() {
+            "BOOLEAN" => {
+                if let Ok(v) = row.try_get::<bool, _>(i) {
+                    obj.insert(name, Value::Bool(v));
+                    continue;
+                }
+            }
+            "JSON" => {
+                if let Ok(v) = row.try_get::<Value, _>(i) {
+                    obj.insert(name, v);
+                    continue;
+                }
+            }
+            "DECIMAL" => {
+                // Decoded from its text form; DECIMAL has no lossless native mapping here
+                if let Ok(v) = row.try_get_unchecked::<String, _>(i) {
+                    let number = v
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                        .map(Value::Number)
+                        .unwrap_or(Value::String(v));
+                    obj.insert(name, number);
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        if let Ok(v) = row.try_get::<chrono::NaiveDateTime, _>(i) {
+            obj.insert(name, Value::String(v.to_string()));
+            continue;
+        }
+        if let Ok(v) = row.try_get::<chrono::NaiveDate, _>(i) {
+            obj.insert(name, Value::String(v.to_string()));
+            continue;
+        }
+        if let Ok(v) = row.try_get::<chrono::NaiveTime, _>(i) {
+            obj.insert(name, Value::String(v.to_string()));
+            continue;
+        }
+        if let Ok(v) = row.try_get::<String, _>(i) {
+            obj.insert(name, Value::String(v));
+            continue;
+        }
+        if let Ok(v) = row.try_get::<i64, _>(i) {
+            obj.insert(name, Value::Number(v.into()));
+            continue;
+        }
+        if let Ok(v) = row.try_get::<u64, _>(i) {
+            obj.insert(name, Value::Number(v.into()));
+            continue;
+        }
+        if let Ok(v) = row.try_get::<f64, _>(i) {
+            obj.insert(
+                name,
+                serde_json::Number::from_f64(v)
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null),
+            );
+            continue;
+        }
+        if let Ok(v) = row.try_get::<f32, _>(i) {
+            obj.insert(
+                name,
+                serde_json::Number::from_f64(v as f64)
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null),
+            );
+            continue;
+        }
+        obj.insert(name, Value::Null);
+    }
+    Value::Object(obj)
+}
+
+/// Aggregate expression; MySQL sums integers into DECIMAL, which is cast back so
+/// the sum stays an integer
+/// SELECT statement of a query
+fn select_sql_mysql(table: &str, query: &SelectQuery) -> QueryBuilder<'static, MySql> {
+    let mut qb = QueryBuilder::<MySql>::new("SELECT ");
+    match &query.columns {
+        Some(cols) if !cols.is_empty() => {
+            qb.push(cols.join(", "));
+        }
+        _ => {
+            qb.push("*");
+        }
+    }
+    qb.push(" FROM ").push(table);
+    push_filters_mysql(&mut qb, &query.filters);
+    if let Some(search) = &query.search {
+        push_search_mysql(&mut qb, search, !query.filters.is_empty());
+    }
+    if let Some(keyset) = &query.keyset {
+        push_keyset_mysql(
+            &mut qb,
+            keyset,
+            !query.filters.is_empty() || query.search.is_some(),
+        );
+    }
+    push_order_by(&mut qb, &query.sort, query.search.as_ref());
+    match (query.limit, query.offset) {
+        (Some(l), offset) => {
+            qb.push(" LIMIT ").push_bind(l as i64);
+            if let Some(o) = offset {
+                qb.push(" OFFSET ").push_bind(o as i64);
+            }
+        }
+        // MySQL has no OFFSET without LIMIT
+        (None, Some(o)) => {
+            qb.push(" LIMIT 18446744073709551615 OFFSET ")
+                .push_bind(o as i64);
+        }
+        (None, None) => {}
+    }
+    qb
+}
+
+/// Multi-row INSERT of a bulk insert, with its upsert clause
+fn insert_many_sql_mysql(table: &str, insert: &BulkInsert) -> QueryBuilder<'static, MySql> {
+    let mut qb = QueryBuilder::<MySql>::new("INSERT INTO ");
+    qb.push(table).push(" (");
+    qb.push(insert.columns.join(", ")).push(") VALUES ");
+    for (i, row) in insert.rows.iter().enumerate() {
+        qb.push(if i == 0 { "(" } else { ", (" });
+        for (j, (column, v)) in insert.columns.iter().zip(row).enumerate() {
+            if j > 0 {
+                qb.push(", ");
+            }
+            push_value_mysql(&mut qb, column, v);
+        }
+        qb.push(")");
+    }
+    // MySQL resolves conflicts on any unique key; the conflict columns only matter
+    // as a no-op assignment when nothing is to be updated
+    if let Some(conflict) = &insert.on_conflict {
+        let set: Vec<String> = if insert.update_columns.is_empty() {
+            conflict
+                .iter()
+                .take(1)
+                .map(|c| format!("{c} = {c}"))
+                .collect()
+        } else {
+            insert
+                .update_columns
+                .iter()
+                .map(|c| format!("{c} = VALUES({c})"))
+                .chain(insert.increments.iter().map(|c| format!("{c} = {c} + 1")))
+                .collect()
+        };
+        qb.push(format!(" ON DUPLICATE KEY UPDATE {}", set.join(", ")));
+    }
+    qb
+}
+
+fn aggregate_sql_mysql(aggregate: &Aggregate) -> String {
+    match aggregate.function {
+        AggregateFunction::Sum if aggregate.integer => {
+            format!("CAST({} AS SIGNED)", aggregate.expression())
+        }
+        _ => aggregate.expression(),
+    }
+}
+
+fn push_order_by(qb: &mut QueryBuilder<MySql>, sort: &[SortKey], search: Option<&TextSearch>) {
+    for (i, key) in sort.iter().enumerate() {
+        qb.push(if i == 0 { " ORDER BY " } else { ", " });
+        match search {
+            Some(search) if key.column == RELEVANCE_SORT => push_match_mysql(qb, search),
+            _ => {
+                qb.push(&key.column);
+            }
+        }
+        qb.push(if key.descending { " DESC" } else { " ASC" });
+    }
+}
+
+/// Restrict a query to the rows matching every search term
+fn push_search_mysql(qb: &mut QueryBuilder<MySql>, search: &TextSearch, has_where: bool) {
+    qb.push(if has_where { " AND " } else { " WHERE " });
+    push_match_mysql(qb, search);
+}
+
+/// `MATCH ... AGAINST` over the FULLTEXT index, requiring every term as a word
+/// prefix (`+ali* +smith*`); its value is the relevance score
+fn push_match_mysql(qb: &mut QueryBuilder<MySql>, search: &TextSearch) {
+    let terms = search
+        .terms
+        .iter()
+        .map(|t| format!("+{}*", t))
+        .collect::<Vec<_>>()
+        .join(" ");
+    qb.push(format!("MATCH({}) AGAINST (", search.columns.join(", ")));
+    qb.push_bind(terms);
+    qb.push(" IN BOOLEAN MODE)");
+}
+
+fn push_keyset_mysql(qb: &mut QueryBuilder<MySql>, keyset: &Keyset, has_where: bool) {
+    qb.push(if has_where { " AND (" } else { " WHERE (" });
+    qb.push(keyset.columns.join(", "));
+    qb.push(if keyset.descending { ") < (" } else { ") > (" });
+    for (i, (column, value)) in keyset.columns.iter().zip(&keyset.values).enumerate() {
+        if i > 0 {
+            qb.push(", ");
+        }
+        push_value_mysql(qb, column, value);
+    }
+    qb.push(")");
+}
+
+fn push_filters_mysql(qb: &mut QueryBuilder<MySql>, filters: &[Filter]) {
+    if filters.is_empty() {
+        return;
+    }
+    qb.push(" WHERE ");
+    for (i, filter) in filters.iter().enumerate() {
+        if i > 0 {
+            qb.push(" AND ");
+        }
+        let col = &filter.column;
+        match filter.operator {
+            FilterOperator::Eq if filter.value.is_null() => {
+                qb.push(format!("{} IS NULL", col));
+            }
+            FilterOperator::Ne if filter.value.is_null() => {
+                qb.push(format!("{} IS NOT NULL", col));
+            }
+            FilterOperator::IsNull => {
+                qb.push(format!("{} IS NULL", col));
+            }
+            FilterOperator::IsNotNull => {
+                qb.push(format!("{} IS NOT NULL", col));
+            }
+            // LIKE follows the column collation; ILIKE compares lowercased values instead
+            FilterOperator::Like => {
+                qb.push(format!("{} LIKE ", col));
+                push_value_mysql(qb, col, &filter.value);
+            }
+            FilterOperator::NotLike => {
+                qb.push(format!("{} NOT LIKE ", col));
+                push_value_mysql(qb, col, &filter.value);
+            }
+            FilterOperator::ILike => {
+                qb.push(format!("LOWER({}) LIKE LOWER(", col));
+                push_value_mysql(qb, col, &filter.value);
+                qb.push(")");
+            }
+            FilterOperator::NotILike => {
+                qb.push(format!("LOWER({}) NOT LIKE LOWER(", col));
+                push_value_mysql(qb, col, &filter.value);
+                qb.push(")");
+            }
+            FilterOperator::In | FilterOperator::NotIn => {
+                let items = match &filter.value {
+                    Value::Array(items) => items.clone(),
+                    other => vec![other.clone()],
+                };
+                if items.is_empty() {
+                    // Empty IN list matches nothing, empty NOT IN matches everything
+                    qb.push(if filter.operator == FilterOperator::In {
+                        "1 = 0"
+                    } else {
+                        "1 = 1"
+                    });
+                    continue;
+                }
+                qb.push(col);
+                qb.push(if filter.operator == FilterOperator::In {
+                    " IN ("
+                } else {
+                    " NOT IN ("
+                });
+                for (j, item) in items.iter().enumerate() {
+                    if j > 0 {
+                        qb.push(", ");
+                    }
+                    push_value_mysql(qb, col, item);
+                }
+                qb.push(")");
+            }
+            op => {
+                qb.push(col);
+                qb.push(op.comparison_sql().unwrap_or(" = "));
+                push_value_mysql(qb, col, &filter.value);
+            }
+        }
+    }
+}
+
+fn push_value_mysql(qb: &mut QueryBuilder<MySql>, column: &str, v: &Value) {
+    match v {
+        Value::Null => {
+            qb.push("NULL");
+        }
+        Value::Bool(b) => {
+            qb.push_bind(*b);
+        }
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                qb.push_bind(i);
+            } else if let Some(f) = n.as_f64() {
+                qb.push_bind(f);
+            } else {
+                qb.push_bind(n.to_string());
+            }
+        }
+        Value::String(s) => {
+            // DATETIME columns reject RFC 3339 offsets, so audit timestamps are bound as UTC
+            if (column.eq_ignore_ascii_case("createdAt")
+                || column.eq_ignore_ascii_case("updatedAt"))
+                && let Ok(ts) = chrono::DateTime::parse_from_rfc3339(s)
+            {
+                qb.push_bind(ts.naive_utc());
+            } else {
+                qb.push_bind(s.clone());
+            }
+        }
+        Value::Array(_) | Value::Object(_) => {
+            qb.push_bind(serde_json::to_string(v).unwrap_or_default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(column: &str, operator: FilterOperator, value: Value) -> Filter {
+        Filter {
+            column: column.to_string(),
+            operator,
+            value,
+        }
+    }
+
+    fn filters_sql(filters: &[Filter]) -> String {
+        let mut qb = QueryBuilder::<MySql>::new("SELECT * FROM t");
+        push_filters_mysql(&mut qb, filters);
+        qb.sql().to_string()
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!(filters_sql(&[]), "SELECT * FROM t");
+        assert_eq!(
+            filters_sql(&[
+                Filter::eq("a", json!(1)),
+                Filter::eq("b", Value::Null),
+                filter("c", FilterOperator::Ne, Value::Null),
+                filter("d", FilterOperator::Gte, json!(2)),
+            ]),
+            "SELECT * FROM t WHERE a = ? AND b IS NULL AND c IS NOT NULL AND d >= ?"
+        );
+        assert_eq!(
+            filters_sql(&[
+                filter("a", FilterOperator::In, json!([1, 2])),
+                filter("b", FilterOperator::NotIn, json!(["x"])),
+                filter("c", FilterOperator::ILike, json!("%x%")),
+            ]),
+            "SELECT * FROM t WHERE a IN (?, ?) AND b NOT IN (?) AND LOWER(c) LIKE LOWER(?)"
+        );
+        // Empty lists are resolved without binding anything
+        assert_eq!(
+            filters_sql(&[
+                filter("a", FilterOperator::In, json!([])),
+                filter("b", FilterOperator::NotIn, json!([])),
+            ]),
+            "SELECT * FROM t WHERE 1 = 0 AND 1 = 1"
+        );
+    }
+
+    #[test]
+    fn test_offset_without_limit() {
+        let query = |limit, offset| SelectQuery {
+            filters: vec![Filter::eq("a", json!(1))],
+            limit,
+            offset,
+            ..Default::default()
+        };
+        assert_eq!(
+            select_sql_mysql("t", &query(None, Some(10))).sql(),
+            "SELECT * FROM t WHERE a = ? LIMIT 18446744073709551615 OFFSET ?"
+        );
+        assert_eq!(
+            select_sql_mysql("t", &query(Some(5), Some(10))).sql(),
+            "SELECT * FROM t WHERE a = ? LIMIT ? OFFSET ?"
+        );
+        assert_eq!(
+            select_sql_mysql("t", &query(None, None)).sql(),
+            "SELECT * FROM t WHERE a = ?"
+        );
+    }
+
+    #[test]
+    fn test_on_duplicate_key() {
+        let insert = BulkInsert {
+            columns: vec!["sku".to_string(), "price".to_string()],
+            rows: vec![vec![json!("A"), json!(1)], vec![json!("B"), json!(2)]],
+            on_conflict: Some(vec!["sku".to_string()]),
+            update_columns: vec!["price".to_string()],
+            increments: vec!["version".to_string()],
+        };
+        assert_eq!(
+            insert_many_sql_mysql("products", &insert).sql(),
+            "INSERT INTO products (sku, price) VALUES (?, ?), (?, ?) \
+             ON DUPLICATE KEY UPDATE price = VALUES(price), version = version + 1"
+        );
+
+        // Nothing to update: conflicting rows are kept as they are
+        let skip = BulkInsert {
+            update_columns: vec![],
+            ..insert.clone()
+        };
+        assert_eq!(
+            insert_many_sql_mysql("products", &skip).sql(),
+            "INSERT INTO products (sku, price) VALUES (?, ?), (?, ?) \
+             ON DUPLICATE KEY UPDATE sku = sku"
+        );
+
+        let plain = BulkInsert {
+            on_conflict: None,
+            ..insert
+        };
+        assert_eq!(
+            insert_many_sql_mysql("products", &plain).sql(),
+            "INSERT INTO products (sku, price) VALUES (?, ?), (?, ?)"
+        );
+    }
+}
@@ -5,6 +5,7 @@ use crate::app_state::AppState;
 use crate::http_body_util::Full;
 use crate::hyper::body::Bytes;
 use crate::hyper::{Request, Response, StatusCode};
+use crate::problem::{Problem, error_response};
 use std::error::Error;
 use std::sync::Arc;
 
@@ -22,14 +23,10 @@ pub async fn handle_docs_request(
             let body = match serde_json::to_string(spec) {
                 Ok(s) => s,
                 Err(e) => {
-                    return Ok(Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .header("Content-Type", "application/json")
-                        .body(Full::new(Bytes::from(format!(
-                            r#"{{"error": "Failed to serialize OpenAPI spec: {}"}}"#,
-                            e
-                        ))))
-                        .map_err(|e| format!("Failed to build response: {}", e))?);
+                    return Ok(error_response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        &format!("Failed to serialize OpenAPI spec: {}", e),
+                    ));
                 }
             };
             return Ok(Response::builder()
@@ -130,9 +127,5 @@ pub async fn handle_docs_request(
     }
 
     // Fallback for unknown docs paths
-    Ok(Response::builder()
-        .status(StatusCode::NOT_FOUND)
-        .header("Content-Type", "application/json")
-        .body(Full::new(Bytes::from(r#"{"error": "Not Found"}"#)))
-        .map_err(|e| format!("Failed to build response: {}", e))?)
+    Ok(Problem::new(StatusCode::NOT_FOUND).into_response())
 }
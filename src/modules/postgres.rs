@@ -1,17 +1,42 @@
 //! Postgres backend implementation for DatabaseBackend
 
 use serde_json::{Value, json};
-use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
-use sqlx::types::chrono;
-use sqlx::{Column, Postgres, QueryBuilder, Row};
+use sqlx::postgres::{
+    PgArguments, PgPool, PgPoolOptions, PgQueryResult, PgRow, PgValueFormat, PgValueRef,
+};
+use sqlx::types::{BigDecimal, chrono};
+use sqlx::{Column, Postgres, QueryBuilder, Row, TypeInfo};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
 
-use crate::database::{DatabaseBackend, DatabaseError, DatabaseRuntimeConfig};
-use crate::schema_generator::{ColumnDefinition, SchemaGenerator, TableSchema};
+use crate::database::{
+    Aggregate, AggregateFunction, AggregateQuery, BeginFuture, BulkInsert, DatabaseBackend,
+    DatabaseError, DatabaseRuntimeConfig, Filter, FilterOperator, Keyset, RELEVANCE_SORT,
+    SelectQuery, SharedTransaction, SortKey, TextSearch, nested_transaction, no_transaction,
+    transaction_finished,
+};
+use crate::migration::{
+    HISTORY_TABLE, MigrationRun, TablePlan, ensure_applicable, history_schema, spec_hash,
+};
+use crate::schema_generator::SEARCH_CONFIG_POSTGRES;
+use crate::schema_generator::{
+    CheckDefinition, ColumnDefinition, DropPolicy, ForeignKeyDefinition, IndexDefinition, OnDelete,
+    SchemaGenerator, TableSchema,
+};
+
+type PgQuery<'q> = sqlx::query::Query<'q, Postgres, PgArguments>;
+
+/// Type each value bound as text is cast to, by lowercase column name (see `column_casts`)
+type Casts = HashMap<String, String>;
 
 #[derive(Debug, Clone)]
 pub struct PostgresBackend {
     pub pool: PgPool,
+    /// Open transaction this handle runs its statements in (see `begin`)
+    tx: Option<SharedTransaction<Postgres>>,
+    /// Column casts of each table, cleared when migrations change the tables
+    casts: Arc<StdMutex<HashMap<String, Arc<Casts>>>>,
 }
 
 impl PostgresBackend {
@@ -35,19 +60,323 @@ impl PostgresBackend {
             pool.num_idle()
         );
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            tx: None,
+            casts: Arc::new(StdMutex::new(HashMap::new())),
+        })
+    }
+
+    /// Types that text, array and object values written to or compared with the columns
+    /// of a table are cast to: parameters are sent as text, which Postgres does not turn
+    /// into timestamps, uuids, JSON documents and the like by itself
+    async fn column_casts(&self, table: &str) -> Result<Arc<Casts>, DatabaseError> {
+        if let Some(casts) = self.casts.lock().unwrap().get(table) {
+            return Ok(casts.clone());
+        }
+        // Looked up on the open transaction, which may have created the table
+        let rows = self
+            .fetch_all(
+                sqlx::query(
+                    "SELECT column_name, udt_name FROM information_schema.columns \
+                     WHERE table_name = $1 AND table_schema = current_schema()",
+                )
+                .bind(table),
+            )
+            .await
+            .map_err(DatabaseError::QueryError)?;
+        let casts: Arc<Casts> = Arc::new(
+            rows.iter()
+                .map(|row| {
+                    let column: String = row.get("column_name");
+                    (column.to_lowercase(), row.get::<String, _>("udt_name"))
+                })
+                .filter(|(_, udt)| !matches!(udt.as_str(), "text" | "varchar" | "bpchar" | "name"))
+                .collect(),
+        );
+        // Tables not created yet are looked up again
+        if !rows.is_empty() {
+            self.casts
+                .lock()
+                .unwrap()
+                .insert(table.to_string(), casts.clone());
+        }
+        Ok(casts)
+    }
+
+    /// Run a statement inside the open transaction, or on the pool outside one
+    async fn fetch_all(&self, query: PgQuery<'_>) -> Result<Vec<PgRow>, sqlx::Error> {
+        match &self.tx {
+            Some(tx) => {
+                let mut guard = tx.lock().await;
+                let conn: &mut sqlx::PgConnection =
+                    guard.as_mut().ok_or_else(transaction_finished)?;
+                query.fetch_all(conn).await
+            }
+            None => query.fetch_all(&self.pool).await,
+        }
+    }
+
+    async fn fetch_one(&self, query: PgQuery<'_>) -> Result<PgRow, sqlx::Error> {
+        match &self.tx {
+            Some(tx) => {
+                let mut guard = tx.lock().await;
+                let conn: &mut sqlx::PgConnection =
+                    guard.as_mut().ok_or_else(transaction_finished)?;
+                query.fetch_one(conn).await
+            }
+            None => query.fetch_one(&self.pool).await,
+        }
+    }
+
+    async fn execute(&self, query: PgQuery<'_>) -> Result<PgQueryResult, sqlx::Error> {
+        match &self.tx {
+            Some(tx) => {
+                let mut guard = tx.lock().await;
+                let conn: &mut sqlx::PgConnection =
+                    guard.as_mut().ok_or_else(transaction_finished)?;
+                query.execute(conn).await
+            }
+            None => query.execute(&self.pool).await,
+        }
+    }
+    async fn do_get_table_schema(&self, table: &str) -> Result<Option<TableSchema>, DatabaseError> {
+        let query = r#"
+            SELECT 
+                c.column_name, 
+                format_type(a.atttypid, a.atttypmod) AS data_type,
+                c.is_nullable, 
+                c.column_default,
+                EXISTS (
+                    SELECT 1
+                    FROM information_schema.key_column_usage kcu
+                    JOIN information_schema.table_constraints tc
+                        ON kcu.constraint_name = tc.constraint_name
+                        AND kcu.table_schema = tc.table_schema
+                        AND kcu.table_name = tc.table_name
+                    WHERE tc.constraint_type = 'PRIMARY KEY'
+                        AND kcu.table_schema = c.table_schema
+                        AND kcu.table_name = c.table_name
+                        AND kcu.column_name = c.column_name
+                ) as is_primary_key
+            FROM information_schema.columns c
+            JOIN pg_attribute a
+                ON a.attrelid = (quote_ident(c.table_schema) || '.' || quote_ident(c.table_name))::regclass
+                AND a.attname = c.column_name
+            WHERE c.table_name = $1 AND c.table_schema = current_schema()
+            ORDER BY c.ordinal_position
+        "#;
+
+        // Looked up on the open transaction, which may hold the migration lock
+        let rows = self
+            .fetch_all(sqlx::query(query).bind(table))
+            .await
+            .map_err(DatabaseError::QueryError)?;
+
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let mut columns = Vec::new();
+        for row in rows {
+            let name: String = row.get("column_name");
+            let data_type: String = row.get("data_type");
+            let is_nullable: String = row.get("is_nullable");
+            let column_default: Option<String> = row.get("column_default");
+            let is_primary_key: Option<bool> = row.get("is_primary_key");
+
+            columns.push(ColumnDefinition {
+                name,
+                column_type: generic_type_postgres(&data_type),
+                nullable: is_nullable == "YES",
+                primary_key: is_primary_key.unwrap_or(false),
+                unique: false, // TODO: Check unique constraints
+                auto_increment: column_default
+                    .as_ref()
+                    .map(|d| d.contains("nextval"))
+                    .unwrap_or(false),
+                default_value: column_default,
+                auto_field: false,
+                renamed_from: None,
+            });
+        }
+
+        // Indexes other than those of PRIMARY KEY and UNIQUE constraints
+        let index_query = sqlx::query(
+            r#"
+            SELECT i.relname AS index_name, ix.indisunique AS is_unique, a.attname AS column_name
+            FROM pg_index ix
+            JOIN pg_class t ON t.oid = ix.indrelid
+            JOIN pg_class i ON i.oid = ix.indexrelid
+            JOIN pg_namespace n ON n.oid = t.relnamespace
+            LEFT JOIN LATERAL unnest(ix.indkey::int2[]) WITH ORDINALITY AS k(attnum, ord) ON true
+            LEFT JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = k.attnum
+            WHERE t.relname = $1 AND n.nspname = current_schema()
+                AND NOT EXISTS (SELECT 1 FROM pg_constraint con WHERE con.conindid = ix.indexrelid)
+            ORDER BY i.relname, k.ord
+            "#,
+        )
+        .bind(table);
+        let index_rows = self
+            .fetch_all(index_query)
+            .await
+            .map_err(DatabaseError::QueryError)?;
+        let mut indexes: Vec<IndexDefinition> = Vec::new();
+        for row in index_rows {
+            let name: String = row.get("index_name");
+            let column: Option<String> = row.get("column_name");
+            if indexes.last().is_none_or(|index| index.name != name) {
+                indexes.push(IndexDefinition {
+                    name,
+                    columns: Vec::new(),
+                    unique: row.get("is_unique"),
+                });
+            }
+            if let (Some(index), Some(column)) = (indexes.last_mut(), column) {
+                index.columns.push(column);
+            }
+        }
+
+        // Single-column foreign keys
+        let foreign_key_query = sqlx::query(
+            r#"
+            SELECT con.conname AS name, a.attname AS column_name,
+                rt.relname AS references_table, ra.attname AS references_column,
+                con.confdeltype::text AS on_delete
+            FROM pg_constraint con
+            JOIN pg_class t ON t.oid = con.conrelid
+            JOIN pg_namespace n ON n.oid = t.relnamespace
+            JOIN pg_class rt ON rt.oid = con.confrelid
+            JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = con.conkey[1]
+            JOIN pg_attribute ra ON ra.attrelid = con.confrelid AND ra.attnum = con.confkey[1]
+            WHERE con.contype = 'f' AND t.relname = $1 AND n.nspname = current_schema()
+                AND array_length(con.conkey, 1) = 1
+            "#,
+        )
+        .bind(table);
+        let foreign_keys = self
+            .fetch_all(foreign_key_query)
+            .await
+            .map_err(DatabaseError::QueryError)?
+            .iter()
+            .map(|row| ForeignKeyDefinition {
+                name: row.get("name"),
+                column: row.get("column_name"),
+                references_table: row.get("references_table"),
+                references_column: row.get("references_column"),
+                on_delete: OnDelete::from_sql(&row.get::<String, _>("on_delete")),
+            })
+            .collect();
+
+        let check_query = sqlx::query(
+            r#"
+            SELECT con.conname AS name, pg_get_constraintdef(con.oid) AS expression
+            FROM pg_constraint con
+            JOIN pg_class t ON t.oid = con.conrelid
+            JOIN pg_namespace n ON n.oid = t.relnamespace
+            WHERE con.contype = 'c' AND t.relname = $1 AND n.nspname = current_schema()
+            "#,
+        )
+        .bind(table);
+        let checks = self
+            .fetch_all(check_query)
+            .await
+            .map_err(DatabaseError::QueryError)?
+            .iter()
+            .map(|row| {
+                let expression: String = row.get("expression");
+                CheckDefinition {
+                    name: row.get("name"),
+                    expression: expression
+                        .strip_prefix("CHECK ")
+                        .unwrap_or(&expression)
+                        .to_string(),
+                }
+            })
+            .collect();
+
+        Ok(Some(TableSchema {
+            table_name: table.to_string(),
+            columns,
+            indexes,
+            relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys,
+            checks,
+        }))
+    }
+
+    async fn migrate(&self, table_schemas: Vec<TableSchema>) -> Result<(), DatabaseError> {
+        let spec_hash = spec_hash(&table_schemas);
+        let plans = self.do_plan_schema(table_schemas).await?;
+        let Some(run) = MigrationRun::start(spec_hash, &plans) else {
+            return Ok(());
+        };
+        let result = self.apply_plans(plans).await;
+        if let Err(e) = self.record_migration(run.finish(&result)).await {
+            tracing::error!(error = %e, "Failed to record migration history");
+        }
+        result
+    }
+
+    /// Run the plans on the migration transaction, rolled back to a savepoint when a
+    /// statement fails
+    async fn apply_plans(&self, plans: Vec<TablePlan>) -> Result<(), DatabaseError> {
+        ensure_applicable(&plans)?;
+        let result = {
+            let mut guard = self.tx.as_ref().ok_or_else(no_transaction)?.lock().await;
+            let conn: &mut sqlx::PgConnection = guard
+                .as_mut()
+                .ok_or_else(|| DatabaseError::QueryError(transaction_finished()))?;
+            apply_statements(conn, plans).await
+        };
+        // Even a failed migration may have changed column types
+        self.casts.lock().unwrap().clear();
+        result
+    }
+
+    /// Append a row to the migration history, creating the table on first use
+    async fn record_migration(&self, row: HashMap<String, Value>) -> Result<(), DatabaseError> {
+        let create = SchemaGenerator::generate_create_table_sql_postgres(&history_schema());
+        {
+            let mut guard = self.tx.as_ref().ok_or_else(no_transaction)?.lock().await;
+            let conn: &mut sqlx::PgConnection = guard
+                .as_mut()
+                .ok_or_else(|| DatabaseError::QueryError(transaction_finished()))?;
+            sqlx::Executor::execute(conn, sqlx::raw_sql(&create))
+                .await
+                .map_err(DatabaseError::QueryError)?;
+        }
+        self.do_insert(HISTORY_TABLE, row).await.map(|_| ())
+    }
+
+    async fn do_plan_schema(
+        &self,
+        table_schemas: Vec<TableSchema>,
+    ) -> Result<Vec<TablePlan>, DatabaseError> {
+        let mut plans = Vec::new();
+        for schema in table_schemas {
+            let current = self.do_get_table_schema(&schema.table_name).await?;
+            let mut plan = TablePlan::new(current.as_ref(), &schema, "postgres");
+            let (statements, replaces) = search_index_sql(self, &schema).await?;
+            plan.push_index_statements(statements, replaces);
+            plans.push(plan);
+        }
+        Ok(plans)
     }
 
     async fn do_select(
         &self,
         table: &str,
-        columns: Option<Vec<String>>,
-        where_clause: Option<HashMap<String, Value>>,
-        limit: Option<u32>,
-        offset: Option<u32>,
+        query: SelectQuery,
     ) -> Result<Vec<Value>, DatabaseError> {
         let mut qb = QueryBuilder::<Postgres>::new("SELECT ");
-        if let Some(cols) = columns {
+        if let Some(cols) = query.columns {
             if !cols.is_empty() {
                 qb.push(cols.join(", "));
             } else {
@@ -57,13 +386,24 @@ impl PostgresBackend {
             qb.push("*");
         }
         qb.push(" FROM ").push(table);
-        if let Some(conds) = where_clause {
-            push_where_postgres(&mut qb, conds);
+        let casts = self.column_casts(table).await?;
+        push_filters_postgres(&mut qb, &query.filters, &casts);
+        if let Some(search) = &query.search {
+            push_search_postgres(&mut qb, search, !query.filters.is_empty());
+        }
+        if let Some(keyset) = &query.keyset {
+            push_keyset_postgres(
+                &mut qb,
+                keyset,
+                !query.filters.is_empty() || query.search.is_some(),
+                &casts,
+            );
         }
-        if let Some(l) = limit {
+        push_order_by(&mut qb, &query.sort, query.search.as_ref());
+        if let Some(l) = query.limit {
             qb.push(" LIMIT ").push_bind(l as i64);
         }
-        if let Some(o) = offset {
+        if let Some(o) = query.offset {
             qb.push(" OFFSET ").push_bind(o as i64);
         }
 
@@ -79,7 +419,7 @@ impl PostgresBackend {
         }
         let start = std::time::Instant::now();
 
-        let rows: Vec<PgRow> = qb.build().fetch_all(&self.pool).await.map_err(|e| {
+        let rows: Vec<PgRow> = self.fetch_all(qb.build()).await.map_err(|e| {
             tracing::error!(
                 "Postgres select error on table {}: {:?}. Pool Size: {}, Idle: {}",
                 table,
@@ -104,6 +444,56 @@ impl PostgresBackend {
         Ok(rows.into_iter().map(|r| row_to_json_postgres(&r)).collect())
     }
 
+    async fn do_aggregate(
+        &self,
+        table: &str,
+        query: AggregateQuery,
+    ) -> Result<Vec<Value>, DatabaseError> {
+        let mut columns = query.group_by.clone();
+        columns.extend(
+            query
+                .aggregates
+                .iter()
+                .map(|a| format!("{} AS {}", aggregate_sql_postgres(a), a.alias())),
+        );
+        let mut qb = QueryBuilder::<Postgres>::new("SELECT ");
+        qb.push(columns.join(", ")).push(" FROM ").push(table);
+        push_filters_postgres(&mut qb, &query.filters, &*self.column_casts(table).await?);
+        if let Some(search) = &query.search {
+            push_search_postgres(&mut qb, search, !query.filters.is_empty());
+        }
+        if !query.group_by.is_empty() {
+            let groups = query.group_by.join(", ");
+            qb.push(" GROUP BY ").push(&groups);
+            qb.push(" ORDER BY ").push(&groups);
+        }
+        let rows = self.fetch_all(qb.build()).await.map_err(|e| {
+            tracing::error!("Postgres aggregate error on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+        Ok(rows.into_iter().map(|r| row_to_json_postgres(&r)).collect())
+    }
+
+    async fn do_count(
+        &self,
+        table: &str,
+        filters: Vec<Filter>,
+        search: Option<TextSearch>,
+    ) -> Result<u64, DatabaseError> {
+        let mut qb = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM ");
+        qb.push(table);
+        push_filters_postgres(&mut qb, &filters, &*self.column_casts(table).await?);
+        if let Some(search) = &search {
+            push_search_postgres(&mut qb, search, !filters.is_empty());
+        }
+        let row = self.fetch_one(qb.build()).await.map_err(|e| {
+            tracing::error!("Postgres count error on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+        let count: i64 = row.try_get(0).map_err(DatabaseError::QueryError)?;
+        Ok(count.max(0) as u64)
+    }
+
     async fn do_insert(
         &self,
         table: &str,
@@ -122,6 +512,7 @@ impl PostgresBackend {
             values.push(v);
         }
 
+        let casts = self.column_casts(table).await?;
         let mut qb = QueryBuilder::<Postgres>::new("INSERT INTO ");
         qb.push(table).push(" (");
         qb.push(keys.join(", ")).push(") VALUES (");
@@ -149,17 +540,11 @@ impl PostgresBackend {
                 }
                 Value::String(s) => {
                     qb.push_bind(s);
-                    // Explicitly cast audit fields to TIMESTAMPTZ to avoid type mismatch errors
-                    // when Postgres expects a timestamp but receives a text parameter.
-                    let key = &keys[i];
-                    if key.eq_ignore_ascii_case("createdAt")
-                        || key.eq_ignore_ascii_case("updatedAt")
-                    {
-                        qb.push("::TIMESTAMPTZ");
-                    }
+                    push_cast(&mut qb, cast_of(&casts, &keys[i]));
                 }
                 Value::Array(_) | Value::Object(_) => {
                     qb.push_bind(serde_json::to_string(v).unwrap_or_default());
+                    push_cast(&mut qb, cast_of(&casts, &keys[i]));
                 }
             }
         }
@@ -177,7 +562,7 @@ impl PostgresBackend {
         }
         let start = std::time::Instant::now();
 
-        let row = qb.build().fetch_one(&self.pool).await.map_err(|e| {
+        let row = self.fetch_one(qb.build()).await.map_err(|e| {
             tracing::error!(
                 "Insert query failed on table {}: {:?}. Pool Size: {}, Idle: {}",
                 table,
@@ -212,22 +597,66 @@ impl PostgresBackend {
         }))
     }
 
+    async fn do_insert_many(&self, table: &str, insert: BulkInsert) -> Result<u64, DatabaseError> {
+        let casts = self.column_casts(table).await?;
+        let mut qb = QueryBuilder::<Postgres>::new("INSERT INTO ");
+        qb.push(table).push(" (");
+        qb.push(insert.columns.join(", ")).push(") VALUES ");
+        for (i, row) in insert.rows.iter().enumerate() {
+            qb.push(if i == 0 { "(" } else { ", (" });
+            for (j, (column, v)) in insert.columns.iter().zip(row).enumerate() {
+                if j > 0 {
+                    qb.push(", ");
+                }
+                push_value_postgres(&mut qb, v, cast_of(&casts, column));
+            }
+            qb.push(")");
+        }
+        if let Some(conflict) = &insert.on_conflict {
+            qb.push(format!(" ON CONFLICT ({}) ", conflict.join(", ")));
+            if insert.update_columns.is_empty() {
+                qb.push("DO NOTHING");
+            } else {
+                let set: Vec<String> = insert
+                    .update_columns
+                    .iter()
+                    .map(|c| format!("{c} = EXCLUDED.{c}"))
+                    .chain(
+                        insert
+                            .increments
+                            .iter()
+                            .map(|c| format!("{c} = {table}.{c} + 1")),
+                    )
+                    .collect();
+                qb.push(format!("DO UPDATE SET {}", set.join(", ")));
+            }
+        }
+
+        let res = self.execute(qb.build()).await.map_err(|e| {
+            tracing::error!("Bulk insert failed on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+        Ok(res.rows_affected())
+    }
+
     async fn do_update(
         &self,
         table: &str,
         data: HashMap<String, Value>,
-        where_clause: HashMap<String, Value>,
+        filters: Vec<Filter>,
+        increments: Vec<String>,
     ) -> Result<Value, DatabaseError> {
-        if data.is_empty() {
+        if data.is_empty() && increments.is_empty() {
             return Err(DatabaseError::ValidationError(
                 "No data provided for update".to_string(),
             ));
         }
-        if where_clause.is_empty() {
+        if filters.is_empty() {
             return Err(DatabaseError::ValidationError(
                 "WHERE clause is required for update".to_string(),
             ));
         }
+        let casts = self.column_casts(table).await?;
         let mut qb = QueryBuilder::<Postgres>::new("UPDATE ");
         qb.push(table).push(" SET ");
         let mut first = true;
@@ -255,50 +684,22 @@ impl PostgresBackend {
                 }
                 Value::String(s) => {
                     qb.push_bind(s);
-                    if k.eq_ignore_ascii_case("createdAt") || k.eq_ignore_ascii_case("updatedAt") {
-                        qb.push("::TIMESTAMPTZ");
-                    }
+                    push_cast(&mut qb, cast_of(&casts, &k));
                 }
                 Value::Array(_) | Value::Object(_) => {
                     qb.push_bind(serde_json::to_string(&v).unwrap_or_default());
+                    push_cast(&mut qb, cast_of(&casts, &k));
                 }
             }
         }
-        qb.push(" WHERE ");
-        let mut first = true;
-        for (k, v) in where_clause {
+        for column in &increments {
             if !first {
-                qb.push(" AND ");
+                qb.push(", ");
             }
             first = false;
-            qb.push(format!("{} = ", k));
-            match v {
-                Value::Null => {
-                    qb.push("NULL");
-                }
-                Value::Bool(b) => {
-                    qb.push_bind(b);
-                }
-                Value::Number(n) => {
-                    if let Some(i) = n.as_i64() {
-                        qb.push_bind(i);
-                    } else if let Some(f) = n.as_f64() {
-                        qb.push_bind(f);
-                    } else {
-                        qb.push_bind(n.to_string());
-                    }
-                }
-                Value::String(s) => {
-                    qb.push_bind(s);
-                    if k.eq_ignore_ascii_case("createdAt") || k.eq_ignore_ascii_case("updatedAt") {
-                        qb.push("::TIMESTAMPTZ");
-                    }
-                }
-                Value::Array(_) | Value::Object(_) => {
-                    qb.push_bind(serde_json::to_string(&v).unwrap_or_default());
-                }
-            }
+            qb.push(column).push(" = ").push(column).push(" + 1");
         }
+        push_filters_postgres(&mut qb, &filters, &casts);
 
         let pool_size = self.pool.size();
         let pool_idle = self.pool.num_idle();
@@ -312,7 +713,7 @@ impl PostgresBackend {
         }
         let start = std::time::Instant::now();
 
-        let res = qb.build().execute(&self.pool).await.map_err(|e| {
+        let res = self.execute(qb.build()).await.map_err(|e| {
             tracing::error!(
                 "Update query failed on table {}: {:?}. Pool Size: {}, Idle: {}",
                 table,
@@ -337,52 +738,17 @@ impl PostgresBackend {
         Ok(json!({"message": "Record updated", "affected_rows": res.rows_affected()}))
     }
 
-    async fn do_delete(
-        &self,
-        table: &str,
-        where_clause: HashMap<String, Value>,
-    ) -> Result<u64, DatabaseError> {
-        if where_clause.is_empty() {
+    async fn do_delete(&self, table: &str, filters: Vec<Filter>) -> Result<u64, DatabaseError> {
+        if filters.is_empty() {
             return Err(DatabaseError::ValidationError(
                 "WHERE clause is required for delete".to_string(),
             ));
         }
         let mut qb = QueryBuilder::<Postgres>::new("DELETE FROM ");
-        qb.push(table).push(" WHERE ");
-        let mut first = true;
-        for (k, v) in where_clause {
-            if !first {
-                qb.push(" AND ");
-            }
-            first = false;
-            qb.push(format!("{} = ", k));
-            match v {
-                Value::Null => {
-                    qb.push("NULL");
-                }
-                Value::Bool(b) => {
-                    qb.push_bind(b);
-                }
-                Value::Number(n) => {
-                    if let Some(i) = n.as_i64() {
-                        qb.push_bind(i);
-                    } else if let Some(f) = n.as_f64() {
-                        qb.push_bind(f);
-                    } else {
-                        qb.push_bind(n.to_string());
-                    }
-                }
-                Value::String(s) => {
-                    qb.push_bind(s);
-                }
-                Value::Array(_) | Value::Object(_) => {
-                    qb.push_bind(serde_json::to_string(&v).unwrap_or_default());
-                }
-            }
-        }
-        let res = qb
-            .build()
-            .execute(&self.pool)
+        qb.push(table);
+        push_filters_postgres(&mut qb, &filters, &*self.column_casts(table).await?);
+        let res = self
+            .execute(qb.build())
             .await
             .map_err(DatabaseError::QueryError)?;
         Ok(res.rows_affected())
@@ -395,128 +761,66 @@ impl DatabaseBackend for PostgresBackend {
         table_schemas: Vec<TableSchema>,
     ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>
     {
-        let pool = self.pool.clone();
         Box::pin(async move {
-            // Acquire a transaction-scoped advisory lock to ensure only one instance runs migrations.
-            // We hold this transaction open until the end.
-            let mut lock_tx = pool.begin().await.map_err(DatabaseError::PoolError)?;
-            sqlx::query("SELECT pg_advisory_xact_lock(123456789)")
-                .execute(&mut *lock_tx)
+            // A transaction-scoped advisory lock ensures only one instance runs migrations.
+            // Planning, the statements and the history row all run on that transaction,
+            // which is committed once at the end and releases the lock.
+            let tx = self.pool.begin().await.map_err(DatabaseError::PoolError)?;
+            let locked = Self {
+                tx: Some(SharedTransaction::new(tx)),
+                ..self.clone()
+            };
+            locked
+                .execute(sqlx::query("SELECT pg_advisory_xact_lock(123456789)"))
                 .await
                 .map_err(DatabaseError::QueryError)?;
 
-            for schema in table_schemas {
-                // Check if table exists
-                // We use the pool (fetch a new connection) for queries inside the locked section.
-                // ample pool size is required to avoid deadlock (lock_tx holds 1, we need 1 more).
-                let query = r#"
-                    SELECT 
-                        c.column_name, 
-                        c.data_type, 
-                        c.is_nullable, 
-                        c.column_default,
-                        CASE WHEN tc.constraint_type = 'PRIMARY KEY' THEN true ELSE false END as is_primary_key
-                    FROM information_schema.columns c
-                    LEFT JOIN information_schema.key_column_usage kcu 
-                        ON c.table_name = kcu.table_name 
-                        AND c.column_name = kcu.column_name
-                    LEFT JOIN information_schema.table_constraints tc 
-                        ON kcu.constraint_name = tc.constraint_name 
-                        AND kcu.table_name = tc.table_name 
-                        AND tc.constraint_type = 'PRIMARY KEY'
-                    WHERE c.table_name = $1 AND c.table_schema = current_schema()
-                "#;
-
-                let rows = sqlx::query(query)
-                    .bind(&schema.table_name)
-                    .fetch_all(&pool)
-                    .await
-                    .map_err(DatabaseError::QueryError)?;
-
-                let current_schema = if rows.is_empty() {
-                    None
-                } else {
-                    let mut columns = Vec::new();
-                    for row in rows {
-                        let name: String = row.get("column_name");
-                        let data_type: String = row.get("data_type");
-                        let is_nullable: String = row.get("is_nullable");
-                        let column_default: Option<String> = row.get("column_default");
-                        let is_primary_key: Option<bool> = row.get("is_primary_key");
-
-                        columns.push(ColumnDefinition {
-                            name,
-                            column_type: data_type,
-                            nullable: is_nullable == "YES",
-                            primary_key: is_primary_key.unwrap_or(false),
-                            unique: false,
-                            auto_increment: column_default
-                                .as_ref()
-                                .map(|d| d.contains("nextval"))
-                                .unwrap_or(false),
-                            default_value: column_default,
-                            auto_field: false,
-                        });
-                    }
-                    Some(TableSchema {
-                        table_name: schema.table_name.clone(),
-                        columns,
-                        indexes: vec![],
-                        relations: vec![],
-                    })
-                };
-
-                if let Some(current) = current_schema {
-                    // Table exists, migrate
-                    let migration_sqls =
-                        SchemaGenerator::generate_migration_sql(&current, &schema, "postgres")
-                            .map_err(DatabaseError::ValidationError)?;
-
-                    for sql in migration_sqls {
-                        tracing::info!(sql = %sql, "Executing migration SQL");
-                        sqlx::raw_sql(&sql)
-                            .execute(&pool)
-                            .await
-                            .map_err(DatabaseError::QueryError)?;
-                    }
-                } else {
-                    // Table does not exist, create
-                    let full_sql = SchemaGenerator::generate_create_table_sql_postgres(&schema);
-                    tracing::info!(sql = %full_sql, "Executing schema creation SQL");
-                    for stmt in full_sql.split(';') {
-                        let s = stmt.trim();
-                        if s.is_empty() {
-                            continue;
-                        }
-                        tracing::info!(statement = %s, "Executing SQL statement");
-                        sqlx::raw_sql(s)
-                            .execute(&pool)
-                            .await
-                            .map_err(DatabaseError::QueryError)?;
-                    }
-                }
-            }
-
-            // Release lock
-            lock_tx.commit().await.map_err(DatabaseError::QueryError)?;
-            Ok(())
+            let result = locked.migrate(table_schemas).await;
+            // A failed migration is rolled back to its savepoint, keeping its history row
+            let committed = match &locked.tx {
+                Some(tx) => tx.commit().await,
+                None => Err(no_transaction()),
+            };
+            result.and(committed)
         })
     }
 
+    fn plan_schema<'a>(
+        &'a self,
+        table_schemas: Vec<TableSchema>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Vec<TablePlan>, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_plan_schema(table_schemas).await })
+    }
+
     fn select<'a>(
         &'a self,
         table: &'a str,
-        columns: Option<Vec<String>>,
-        where_clause: Option<HashMap<String, Value>>,
-        limit: Option<u32>,
-        offset: Option<u32>,
+        query: SelectQuery,
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<Vec<Value>, DatabaseError>> + Send + 'a>,
     > {
-        Box::pin(async move {
-            self.do_select(table, columns, where_clause, limit, offset)
-                .await
-        })
+        Box::pin(async move { self.do_select(table, query).await })
+    }
+    fn count<'a>(
+        &'a self,
+        table: &'a str,
+        filters: Vec<Filter>,
+        search: Option<TextSearch>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_count(table, filters, search).await })
+    }
+    fn aggregate<'a>(
+        &'a self,
+        table: &'a str,
+        query: AggregateQuery,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Vec<Value>, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_aggregate(table, query).await })
     }
     fn insert<'a>(
         &'a self,
@@ -527,24 +831,34 @@ impl DatabaseBackend for PostgresBackend {
     > {
         Box::pin(async move { self.do_insert(table, data).await })
     }
+    fn insert_many<'a>(
+        &'a self,
+        table: &'a str,
+        insert: BulkInsert,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_insert_many(table, insert).await })
+    }
     fn update<'a>(
         &'a self,
         table: &'a str,
         data: HashMap<String, Value>,
-        where_clause: HashMap<String, Value>,
+        filters: Vec<Filter>,
+        increments: Vec<String>,
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<Value, DatabaseError>> + Send + 'a>,
     > {
-        Box::pin(async move { self.do_update(table, data, where_clause).await })
+        Box::pin(async move { self.do_update(table, data, filters, increments).await })
     }
     fn delete<'a>(
         &'a self,
         table: &'a str,
-        where_clause: HashMap<String, Value>,
+        filters: Vec<Filter>,
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
     > {
-        Box::pin(async move { self.do_delete(table, where_clause).await })
+        Box::pin(async move { self.do_delete(table, filters).await })
     }
 
     fn get_table_schema<'a>(
@@ -557,65 +871,7 @@ impl DatabaseBackend for PostgresBackend {
                 + 'a,
         >,
     > {
-        Box::pin(async move {
-            let query = r#"
-                SELECT 
-                    c.column_name, 
-                    c.data_type, 
-                    c.is_nullable, 
-                    c.column_default,
-                    CASE WHEN tc.constraint_type = 'PRIMARY KEY' THEN true ELSE false END as is_primary_key
-                FROM information_schema.columns c
-                LEFT JOIN information_schema.key_column_usage kcu 
-                    ON c.table_name = kcu.table_name 
-                    AND c.column_name = kcu.column_name
-                LEFT JOIN information_schema.table_constraints tc 
-                    ON kcu.constraint_name = tc.constraint_name 
-                    AND kcu.table_name = tc.table_name 
-                    AND tc.constraint_type = 'PRIMARY KEY'
-                WHERE c.table_name = $1 AND c.table_schema = current_schema()
-            "#;
-
-            let rows = sqlx::query(query)
-                .bind(table)
-                .fetch_all(&self.pool)
-                .await
-                .map_err(DatabaseError::QueryError)?;
-
-            if rows.is_empty() {
-                return Ok(None);
-            }
-
-            let mut columns = Vec::new();
-            for row in rows {
-                let name: String = row.get("column_name");
-                let data_type: String = row.get("data_type");
-                let is_nullable: String = row.get("is_nullable");
-                let column_default: Option<String> = row.get("column_default");
-                let is_primary_key: Option<bool> = row.get("is_primary_key");
-
-                columns.push(ColumnDefinition {
-                    name,
-                    column_type: data_type,
-                    nullable: is_nullable == "YES",
-                    primary_key: is_primary_key.unwrap_or(false),
-                    unique: false, // TODO: Check unique constraints
-                    auto_increment: column_default
-                        .as_ref()
-                        .map(|d| d.contains("nextval"))
-                        .unwrap_or(false),
-                    default_value: column_default,
-                    auto_field: false,
-                });
-            }
-
-            Ok(Some(TableSchema {
-                table_name: table.to_string(),
-                columns,
-                indexes: vec![],
-                relations: vec![],
-            }))
-        })
+        Box::pin(async move { self.do_get_table_schema(table).await })
     }
 
     fn list_tables<'a>(
@@ -634,6 +890,65 @@ impl DatabaseBackend for PostgresBackend {
             Ok(tables)
         })
     }
+
+    fn begin<'a>(&'a self) -> BeginFuture<'a> {
+        Box::pin(async move {
+            if self.tx.is_some() {
+                return Err(nested_transaction());
+            }
+            let tx = self.pool.begin().await.map_err(DatabaseError::PoolError)?;
+            let backend: Arc<dyn DatabaseBackend> = Arc::new(Self {
+                tx: Some(SharedTransaction::new(tx)),
+                ..self.clone()
+            });
+            Ok(backend)
+        })
+    }
+
+    fn commit<'a>(
+        &'a self,
+    ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>
+    {
+        Box::pin(async move { self.tx.as_ref().ok_or_else(no_transaction)?.commit().await })
+    }
+
+    fn rollback<'a>(
+        &'a self,
+    ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>
+    {
+        Box::pin(async move {
+            self.tx
+                .as_ref()
+                .ok_or_else(no_transaction)?
+                .rollback()
+                .await
+        })
+    }
+}
+
+/// Generic type of a column from its `format_type`, keeping lengths and precisions
+fn generic_type_postgres(data_type: &str) -> String {
+    let data_type = data_type.to_ascii_lowercase();
+    let (base, args) = match data_type.split_once('(') {
+        Some((base, rest)) => {
+            let (args, _) = rest.split_once(')').unwrap_or((rest, ""));
+            (base.trim().to_string(), Some(args.replace(' ', "")))
+        }
+        None => (data_type.clone(), None),
+    };
+    match (base.as_str(), args) {
+        ("character varying", Some(n)) => format!("varchar({})", n),
+        ("character varying", None) => "text".to_string(),
+        ("character", Some(n)) => format!("char({})", n),
+        ("character", None) => "char".to_string(),
+        ("numeric", Some(args)) => format!("numeric({})", args),
+        ("double precision", _) => "double".to_string(),
+        ("json" | "jsonb", _) => "json".to_string(),
+        ("bytea", _) => "blob".to_string(),
+        (base, _) if base.starts_with("timestamp") => "timestamp".to_string(),
+        (base, _) if base.starts_with("time") => "time".to_string(),
+        _ => data_type,
+    }
 }
 
 fn row_to_json_postgres(row: &PgRow) -> Value {
@@ -665,6 +980,23 @@ fn row_to_json_postgres(row: &PgRow) -> Value {
             obj.insert(name, Value::String(v.to_string()));
             continue;
         }
+        if let Ok(v) = row.try_get::<sqlx::types::Uuid, _>(i) {
+            obj.insert(name, Value::String(v.to_string()));
+            continue;
+        }
+        if col.type_info().name() == "NUMERIC" {
+            let value = match row.try_get::<Option<BigDecimal>, _>(i) {
+                Ok(v) => v.map(numeric_json_postgres).unwrap_or(Value::Null),
+                Err(_) => row
+                    .try_get_raw(i)
+                    .ok()
+                    .and_then(numeric_special_postgres)
+                    .map(|v| Value::String(v.to_string()))
+                    .unwrap_or(Value::Null),
+            };
+            obj.insert(name, value);
+            continue;
+        }
         if let Ok(v) = row.try_get::<String, _>(i) {
             obj.insert(name, Value::String(v));
             continue;
@@ -709,48 +1041,297 @@ fn row_to_json_postgres(row: &PgRow) -> Value {
     Value::Object(obj)
 }
 
-fn push_where_postgres(qb: &mut QueryBuilder<Postgres>, conds: HashMap<String, Value>) {
-    if conds.is_empty() {
+/// JSON form of a NUMERIC value: a number when it prints back as the same decimal,
+/// otherwise a string keeping every digit
+fn numeric_json_postgres(decimal: BigDecimal) -> Value {
+    if decimal.is_integer()
+        && let Ok(v) = decimal.with_scale(0).to_string().parse::<i64>()
+    {
+        return Value::Number(v.into());
+    }
+    decimal
+        .to_string()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.to_string().parse::<BigDecimal>().ok().as_ref() == Some(&decimal))
+        .and_then(serde_json::Number::from_f64)
+        .map(Value::Number)
+        .unwrap_or_else(|| Value::String(decimal.to_string()))
+}
+
+/// NaN and infinite NUMERIC values, which BigDecimal cannot hold
+fn numeric_special_postgres(value: PgValueRef<'_>) -> Option<&'static str> {
+    let sign = match value.format() {
+        PgValueFormat::Text => {
+            return ["NaN", "Infinity", "-Infinity"]
+                .into_iter()
+                .find(|special| value.as_str().ok() == Some(*special));
+        }
+        // Binary form: digit count, weight, then the sign word
+        PgValueFormat::Binary => value.as_bytes().ok()?.get(4..6)?,
+    };
+    match u16::from_be_bytes([sign[0], sign[1]]) {
+        0xC000 => Some("NaN"),
+        0xD000 => Some("Infinity"),
+        0xF000 => Some("-Infinity"),
+        _ => None,
+    }
+}
+
+/// Aggregate expression decoding to a JSON number: Postgres sums and averages are
+/// NUMERIC, so they are cast to BIGINT (sums of integers) or DOUBLE PRECISION
+fn aggregate_sql_postgres(aggregate: &Aggregate) -> String {
+    match aggregate.function {
+        AggregateFunction::Sum if aggregate.integer => {
+            format!("CAST({} AS BIGINT)", aggregate.expression())
+        }
+        AggregateFunction::Sum | AggregateFunction::Avg => {
+            format!("CAST({} AS DOUBLE PRECISION)", aggregate.expression())
+        }
+        _ => aggregate.expression(),
+    }
+}
+
+fn push_order_by(qb: &mut QueryBuilder<Postgres>, sort: &[SortKey], search: Option<&TextSearch>) {
+    for (i, key) in sort.iter().enumerate() {
+        qb.push(if i == 0 { " ORDER BY " } else { ", " });
+        match search {
+            Some(search) if key.column == RELEVANCE_SORT => {
+                qb.push("ts_rank(");
+                qb.push(SchemaGenerator::search_document_postgres(&search.columns));
+                qb.push(format!(", to_tsquery('{}', ", SEARCH_CONFIG_POSTGRES));
+                qb.push_bind(tsquery(search));
+                qb.push("))");
+            }
+            _ => {
+                qb.push(&key.column);
+            }
+        }
+        qb.push(if key.descending { " DESC" } else { " ASC" });
+    }
+}
+
+/// Restrict a query to the rows whose search document matches every term, using
+/// the expression of the GIN index so the index serves the query
+fn push_search_postgres(qb: &mut QueryBuilder<Postgres>, search: &TextSearch, has_where: bool) {
+    qb.push(if has_where { " AND " } else { " WHERE " });
+    qb.push(SchemaGenerator::search_document_postgres(&search.columns));
+    qb.push(format!(" @@ to_tsquery('{}', ", SEARCH_CONFIG_POSTGRES));
+    qb.push_bind(tsquery(search));
+    qb.push(")");
+}
+
+/// `tsquery` requiring every term as a word prefix: `'ali':* & 'smith':*`
+fn tsquery(search: &TextSearch) -> String {
+    search
+        .terms
+        .iter()
+        .map(|t| format!("'{}':*", t.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+/// Statements creating, replacing or dropping the search index of a table to match its
+/// `searchColumns`, and whether they replace an index built for other columns. The
+/// columns an index was built for are kept in its comment.
+/// Run the statements of a migration inside a savepoint of the open transaction, so
+/// that a failure leaves the transaction usable for the history row
+async fn apply_statements(
+    conn: &mut sqlx::PgConnection,
+    plans: Vec<TablePlan>,
+) -> Result<(), DatabaseError> {
+    sqlx::Executor::execute(&mut *conn, sqlx::raw_sql("SAVEPOINT apify_migration"))
+        .await
+        .map_err(DatabaseError::QueryError)?;
+    let mut result = Ok(());
+    'plans: for plan in plans {
+        for sql in plan.statements {
+            tracing::info!(table = %plan.table, sql = %sql, "Executing migration SQL");
+            if let Err(e) = sqlx::Executor::execute(&mut *conn, sqlx::raw_sql(&sql)).await {
+                result = Err(DatabaseError::QueryError(e));
+                break 'plans;
+            }
+        }
+    }
+    let end = if result.is_ok() {
+        "RELEASE SAVEPOINT apify_migration"
+    } else {
+        "ROLLBACK TO SAVEPOINT apify_migration; RELEASE SAVEPOINT apify_migration"
+    };
+    sqlx::Executor::execute(&mut *conn, sqlx::raw_sql(end))
+        .await
+        .map_err(DatabaseError::QueryError)?;
+    result
+}
+
+async fn search_index_sql(
+    backend: &PostgresBackend,
+    schema: &TableSchema,
+) -> Result<(Vec<String>, bool), DatabaseError> {
+    let index = SchemaGenerator::search_index_name(&schema.table_name);
+    let rows = backend
+        .fetch_all(
+            sqlx::query(
+                "SELECT obj_description(c.oid, 'pg_class') AS comment FROM pg_class c \
+                 WHERE c.relname = $1 AND c.relkind = 'i' \
+                 AND c.relnamespace = current_schema()::regnamespace",
+            )
+            .bind(&index),
+        )
+        .await
+        .map_err(DatabaseError::QueryError)?;
+    let indexed = rows
+        .first()
+        .map(|row| row.get::<Option<String>, _>("comment").unwrap_or_default());
+    let wanted = schema.search_columns.join(",");
+
+    let mut statements = Vec::new();
+    let replaces = indexed.as_ref().is_some_and(|columns| *columns != wanted);
+    if replaces {
+        statements.push(format!("DROP INDEX IF EXISTS {}", index));
+    }
+    if !schema.search_columns.is_empty() && indexed.as_ref() != Some(&wanted) {
+        statements.extend(SchemaGenerator::generate_search_sql_postgres(schema));
+    }
+    Ok((statements, replaces))
+}
+
+fn push_keyset_postgres(
+    qb: &mut QueryBuilder<Postgres>,
+    keyset: &Keyset,
+    has_where: bool,
+    casts: &Casts,
+) {
+    qb.push(if has_where { " AND (" } else { " WHERE (" });
+    qb.push(keyset.columns.join(", "));
+    qb.push(if keyset.descending { ") < (" } else { ") > (" });
+    for (i, (column, value)) in keyset.columns.iter().zip(&keyset.values).enumerate() {
+        if i > 0 {
+            qb.push(", ");
+        }
+        push_value_postgres(qb, value, cast_of(casts, column));
+    }
+    qb.push(")");
+}
+
+fn push_filters_postgres(qb: &mut QueryBuilder<Postgres>, filters: &[Filter], casts: &Casts) {
+    if filters.is_empty() {
         return;
     }
     qb.push(" WHERE ");
-    let mut first = true;
-    for (k, v) in conds {
-        if !first {
+    for (i, filter) in filters.iter().enumerate() {
+        if i > 0 {
             qb.push(" AND ");
         }
-        first = false;
-        qb.push(format!("{} = ", k));
-        match v {
-            Value::Null => {
-                qb.push("NULL");
+        let col = &filter.column;
+        match filter.operator {
+            FilterOperator::Eq if filter.value.is_null() => {
+                qb.push(format!("{} IS NULL", col));
+            }
+            FilterOperator::Ne if filter.value.is_null() => {
+                qb.push(format!("{} IS NOT NULL", col));
+            }
+            FilterOperator::IsNull => {
+                qb.push(format!("{} IS NULL", col));
+            }
+            FilterOperator::IsNotNull => {
+                qb.push(format!("{} IS NOT NULL", col));
+            }
+            // Pattern matching works on the text form so non-text columns can be searched too
+            FilterOperator::Like => {
+                qb.push(format!("{}::TEXT LIKE ", col));
+                push_value_postgres(qb, &filter.value, None);
+            }
+            FilterOperator::NotLike => {
+                qb.push(format!("{}::TEXT NOT LIKE ", col));
+                push_value_postgres(qb, &filter.value, None);
+            }
+            FilterOperator::ILike => {
+                qb.push(format!("{}::TEXT ILIKE ", col));
+                push_value_postgres(qb, &filter.value, None);
             }
-            Value::Bool(b) => {
-                qb.push_bind(b);
+            FilterOperator::NotILike => {
+                qb.push(format!("{}::TEXT NOT ILIKE ", col));
+                push_value_postgres(qb, &filter.value, None);
             }
-            Value::Number(n) => {
-                // Always bind numbers as f64 for consistency with REAL columns
-                if let Some(f) = n.as_f64() {
-                    qb.push_bind(f);
+            FilterOperator::In | FilterOperator::NotIn => {
+                let items = match &filter.value {
+                    Value::Array(items) => items.clone(),
+                    other => vec![other.clone()],
+                };
+                if items.is_empty() {
+                    // Empty IN list matches nothing, empty NOT IN matches everything
+                    qb.push(if filter.operator == FilterOperator::In {
+                        "1 = 0"
+                    } else {
+                        "1 = 1"
+                    });
+                    continue;
+                }
+                qb.push(col);
+                qb.push(if filter.operator == FilterOperator::In {
+                    " IN ("
                 } else {
-                    qb.push_bind(n.to_string());
+                    " NOT IN ("
+                });
+                let mut sep = qb.separated(", ");
+                for item in &items {
+                    push_bind_postgres(&mut sep, item, cast_of(casts, col));
                 }
+                qb.push(")");
             }
-            Value::String(s) => {
-                qb.push_bind(s);
-                if k.eq_ignore_ascii_case("createdAt") || k.eq_ignore_ascii_case("updatedAt") {
-                    qb.push("::TIMESTAMPTZ");
-                }
+            op => {
+                qb.push(col);
+                qb.push(op.comparison_sql().unwrap_or(" = "));
+                push_value_postgres(qb, &filter.value, cast_of(casts, col));
             }
-            Value::Array(_) | Value::Object(_) => {
-                qb.push_bind(serde_json::to_string(&v).unwrap_or_default());
+        }
+    }
+}
+
+/// Type values of a column are cast to, if any
+fn cast_of<'a>(casts: &'a Casts, column: &str) -> Option<&'a str> {
+    casts.get(&column.to_lowercase()).map(String::as_str)
+}
+
+fn push_cast(qb: &mut QueryBuilder<Postgres>, cast: Option<&str>) {
+    if let Some(cast) = cast {
+        qb.push("::").push(cast);
+    }
+}
+
+fn push_value_postgres(qb: &mut QueryBuilder<Postgres>, v: &Value, cast: Option<&str>) {
+    match v {
+        Value::Null => {
+            qb.push("NULL");
+        }
+        Value::Bool(b) => {
+            qb.push_bind(*b);
+        }
+        Value::Number(n) => {
+            // Always bind numbers as f64 for consistency with REAL columns
+            if let Some(f) = n.as_f64() {
+                qb.push_bind(f);
+            } else {
+                qb.push_bind(n.to_string());
             }
         }
+        Value::String(s) => {
+            qb.push_bind(s.clone());
+            push_cast(qb, cast);
+        }
+        Value::Array(_) | Value::Object(_) => {
+            qb.push_bind(serde_json::to_string(v).unwrap_or_default());
+            push_cast(qb, cast);
+        }
     }
 }
 
-#[allow(dead_code)]
-fn push_bind_postgres(sep: &mut sqlx::query_builder::Separated<'_, '_, Postgres, &str>, v: &Value) {
+fn push_bind_postgres(
+    sep: &mut sqlx::query_builder::Separated<'_, '_, Postgres, &str>,
+    v: &Value,
+    cast: Option<&str>,
+) {
     match v {
         Value::Null => {
             sep.push("NULL");
@@ -770,9 +1351,15 @@ fn push_bind_postgres(sep: &mut sqlx::query_builder::Separated<'_, '_, Postgres,
         }
         Value::String(s) => {
             sep.push_bind(s.clone());
+            if let Some(cast) = cast {
+                sep.push_unseparated(format!("::{}", cast));
+            }
         }
         Value::Array(_) | Value::Object(_) => {
             sep.push_bind(serde_json::to_string(v).unwrap_or_default());
+            if let Some(cast) = cast {
+                sep.push_unseparated(format!("::{}", cast));
+            }
         }
     }
 }
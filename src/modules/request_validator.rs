@@ -5,7 +5,8 @@ use crate::app_state::AppState;
 use crate::hyper::StatusCode;
 use crate::modules::{Module, ModuleOutcome, error_response};
 use crate::phases::{Phase, RequestContext};
-use jsonschema::JSONSchema;
+use crate::problem::{FieldError, Problem};
+use jsonschema::{JSONSchema, ValidationError, error::ValidationErrorKind};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -40,6 +41,9 @@ struct ParameterValidator {
 
 struct RouteValidators {
     body_schema: Option<JSONSchema>,
+    /// Whether the body schema itself describes an array; otherwise an array body
+    /// (a bulk write) is validated item by item
+    body_is_array: bool,
     parameters: Vec<ParameterValidator>,
 }
 
@@ -104,6 +108,7 @@ impl RequestValidator {
                         if let Some(op_obj) = operation.as_object() {
                             let mut route_val = RouteValidators {
                                 body_schema: None,
+                                body_is_array: false,
                                 parameters: Vec::new(),
                             };
 
@@ -121,6 +126,9 @@ impl RequestValidator {
                                     obj.insert("components".to_string(), components.clone());
                                 }
 
+                                route_val.body_is_array = Self::resolve_ref(spec, schema)
+                                    .and_then(|s| s.get("type"))
+                                    .is_some_and(|t| t == "array");
                                 match JSONSchema::options().compile(&schema_with_components) {
                                     Ok(compiled) => {
                                         route_val.body_schema = Some(compiled);
@@ -202,6 +210,43 @@ impl RequestValidator {
     }
 }
 
+/// `application/json`, or a structured `+json` type such as `application/merge-patch+json`
+fn is_json_media_type(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    essence == "application/json"
+        || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+fn is_json_patch(ctx: &RequestContext) -> bool {
+    ctx.headers
+        .get("content-type")
+        .and_then(|ct| ct.to_str().ok())
+        .and_then(|ct| ct.split(';').next())
+        .is_some_and(|ct| {
+            ct.trim()
+                .eq_ignore_ascii_case(crate::patch::JSON_PATCH_MEDIA_TYPE)
+        })
+}
+
+/// Field error for a schema violation, pointing at the offending member of the body.
+/// A missing required property is reported at the property rather than its parent.
+fn field_error(prefix: &str, error: &ValidationError) -> FieldError {
+    let mut pointer = format!("{}{}", prefix, error.instance_path);
+    if let ValidationErrorKind::Required {
+        property: Value::String(property),
+    } = &error.kind
+    {
+        pointer.push('/');
+        pointer.push_str(&property.replace('~', "~0").replace('/', "~1"));
+    }
+    FieldError::body(pointer, error.to_string())
+}
+
 impl Module for RequestValidator {
     fn name(&self) -> &str {
         "request_validator"
@@ -232,10 +277,10 @@ impl Module for RequestValidator {
         if self.config.enforce_content_type && ctx.json_body.is_some() {
             if let Some(content_type) = ctx.headers.get("content-type") {
                 let ct_str = content_type.to_str().unwrap_or("");
-                if !ct_str.contains("application/json") {
+                if !is_json_media_type(ct_str) {
                     return ModuleOutcome::Respond(error_response(
                         StatusCode::UNSUPPORTED_MEDIA_TYPE,
-                        "Content-Type must be application/json for JSON bodies",
+                        "Content-Type must be application/json (or a +json type) for JSON bodies",
                     ));
                 }
             } else {
@@ -253,28 +298,50 @@ impl Module for RequestValidator {
                 route.path_pattern.clone(),
             );
             if let Some(validators) = self.validators.get(&key) {
-                // 1. Validate Body
+                // 1. Validate Body (a JSON Patch document describes changes, not the record)
                 if let Some(ref schema) = validators.body_schema
                     && let Some(ref json_body) = ctx.json_body
-                    && let Err(errors) = schema.validate(json_body)
+                    && !is_json_patch(ctx)
                 {
-                    let error_msg = errors
-                        .map(|e| format!("Body validation error: {}", e))
-                        .collect::<Vec<_>>()
-                        .join("; ");
-
-                    tracing::warn!("Validation Error: {}", error_msg);
-                    // Debug: print the schema being used (if possible, JSONSchema doesn't implement Debug nicely usually, but let's try printing the key)
-                    tracing::debug!(
-                        "Validation failed for route: {} {}",
-                        ctx.method,
-                        route.path_pattern
-                    );
-
-                    return ModuleOutcome::Respond(error_response(
-                        StatusCode::BAD_REQUEST,
-                        &error_msg,
-                    ));
+                    let errors: Vec<FieldError> = match json_body {
+                        Value::Array(items) if !validators.body_is_array => items
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, item)| schema.validate(item).err().map(|e| (i, e)))
+                            .flat_map(|(i, errors)| {
+                                errors.map(move |e| field_error(&format!("/{}", i), &e))
+                            })
+                            .collect(),
+                        _ => match schema.validate(json_body) {
+                            Ok(()) => Vec::new(),
+                            Err(errors) => errors.map(|e| field_error("", &e)).collect(),
+                        },
+                    };
+                    if !errors.is_empty() {
+                        let error_msg = errors
+                            .iter()
+                            .map(|e| match e.pointer.as_deref() {
+                                Some("") | None => format!("Body validation error: {}", e.detail),
+                                Some(pointer) => {
+                                    format!("Body validation error at {}: {}", pointer, e.detail)
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                            .join("; ");
+                        tracing::warn!("Validation Error: {}", error_msg);
+                        tracing::debug!(
+                            "Validation failed for route: {} {}",
+                            ctx.method,
+                            route.path_pattern
+                        );
+
+                        return ModuleOutcome::Respond(
+                            Problem::new(StatusCode::BAD_REQUEST)
+                                .with_detail(error_msg)
+                                .with_errors(errors)
+                                .into_response(),
+                        );
+                    }
                 }
 
                 // 2. Validate Parameters
@@ -287,13 +354,16 @@ impl Module for RequestValidator {
                     };
 
                     if param.required && value_str.is_none() {
-                        return ModuleOutcome::Respond(error_response(
-                            StatusCode::BAD_REQUEST,
-                            &format!(
-                                "Missing required {} parameter: {}",
-                                param.location, param.name
-                            ),
-                        ));
+                        let detail = format!(
+                            "Missing required {} parameter: {}",
+                            param.location, param.name
+                        );
+                        return ModuleOutcome::Respond(
+                            Problem::new(StatusCode::BAD_REQUEST)
+                                .with_errors(vec![FieldError::parameter(&param.name, &detail)])
+                                .with_detail(detail)
+                                .into_response(),
+                        );
                     }
 
                     if let Some(val_str) = value_str
@@ -317,19 +387,28 @@ impl Module for RequestValidator {
                         };
 
                         if let Err(errors) = schema.validate(&json_val) {
+                            let errors: Vec<FieldError> = errors
+                                .map(|e| FieldError::parameter(&param.name, e.to_string()))
+                                .collect();
                             let error_msg = errors
+                                .iter()
                                 .map(|e| {
-                                    format!("Parameter '{}' validation error: {}", param.name, e)
+                                    format!(
+                                        "Parameter '{}' validation error: {}",
+                                        param.name, e.detail
+                                    )
                                 })
                                 .collect::<Vec<_>>()
                                 .join("; ");
 
                             tracing::warn!("Validation Error: {}", error_msg);
 
-                            return ModuleOutcome::Respond(error_response(
-                                StatusCode::BAD_REQUEST,
-                                &error_msg,
-                            ));
+                            return ModuleOutcome::Respond(
+                                Problem::new(StatusCode::BAD_REQUEST)
+                                    .with_detail(error_msg)
+                                    .with_errors(errors)
+                                    .into_response(),
+                            );
                         }
                     }
                 }
@@ -2,8 +2,11 @@
 
 use once_cell::sync::Lazy;
 use serde_json::{Value, json};
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
-use sqlx::{Column, Row};
+use sqlx::sqlite::{
+    SqliteArguments, SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteQueryResult,
+    SqliteRow,
+};
+use sqlx::{Column, Row, ValueRef};
 use sqlx::{QueryBuilder, Sqlite};
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -11,20 +14,44 @@ use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
 use tokio::sync::Mutex;
 
-use crate::database::{DatabaseBackend, DatabaseError, DatabaseRuntimeConfig};
-use crate::schema_generator::{ColumnDefinition, SchemaGenerator, TableSchema};
+use crate::database::{
+    AggregateQuery, BeginFuture, BulkInsert, DatabaseBackend, DatabaseError, DatabaseRuntimeConfig,
+    Filter, FilterOperator, Keyset, RELEVANCE_SORT, SelectQuery, SharedTransaction, SortKey,
+    TextSearch, nested_transaction, no_transaction, transaction_finished,
+};
+use crate::migration::{
+    HISTORY_TABLE, MigrationRun, TableAction, TablePlan, ensure_applicable, history_schema,
+    spec_hash,
+};
+use crate::schema_generator::{
+    CheckDefinition, ColumnDefinition, DropPolicy, ForeignKeyDefinition, IndexDefinition, OnDelete,
+    SchemaGenerator, TableSchema,
+};
 
 static MIGRATION_LOCKS: Lazy<StdMutex<HashMap<String, Arc<Mutex<()>>>>> =
     Lazy::new(|| StdMutex::new(HashMap::new()));
 
+type SqliteQuery<'q> = sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>>;
+
 #[derive(Debug, Clone)]
 pub struct SqliteBackend {
     pub pool: SqlitePool,
     pub migration_lock: Arc<Mutex<()>>,
+    /// Open transaction this handle runs its statements in (see `begin`)
+    tx: Option<SharedTransaction<Sqlite>>,
 }
 
 impl SqliteBackend {
     pub async fn connect(config: DatabaseRuntimeConfig) -> Result<Self, DatabaseError> {
+        Self::open(config, true).await
+    }
+
+    /// Connect without creating the database file, failing when it does not exist
+    pub async fn connect_existing(config: DatabaseRuntimeConfig) -> Result<Self, DatabaseError> {
+        Self::open(config, false).await
+    }
+
+    async fn open(config: DatabaseRuntimeConfig, create: bool) -> Result<Self, DatabaseError> {
         let (opts, filename_key) = if config.url == "sqlite::memory:" {
             (
                 SqliteConnectOptions::from_str(&config.url).map_err(DatabaseError::PoolError)?,
@@ -35,7 +62,7 @@ impl SqliteBackend {
             (
                 SqliteConnectOptions::new()
                     .filename(filename)
-                    .create_if_missing(true)
+                    .create_if_missing(create)
                     .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
                     .busy_timeout(std::time::Duration::from_secs(5)),
                 filename.to_string(),
@@ -65,26 +92,66 @@ impl SqliteBackend {
         Ok(Self {
             pool,
             migration_lock,
+            tx: None,
         })
     }
 
+    /// Run a statement inside the open transaction, or on the pool outside one
+    async fn fetch_all(&self, query: SqliteQuery<'_>) -> Result<Vec<SqliteRow>, sqlx::Error> {
+        match &self.tx {
+            Some(tx) => {
+                let mut guard = tx.lock().await;
+                let conn: &mut sqlx::SqliteConnection =
+                    guard.as_mut().ok_or_else(transaction_finished)?;
+                query.fetch_all(conn).await
+            }
+            None => query.fetch_all(&self.pool).await,
+        }
+    }
+
+    async fn fetch_one(&self, query: SqliteQuery<'_>) -> Result<SqliteRow, sqlx::Error> {
+        match &self.tx {
+            Some(tx) => {
+                let mut guard = tx.lock().await;
+                let conn: &mut sqlx::SqliteConnection =
+                    guard.as_mut().ok_or_else(transaction_finished)?;
+                query.fetch_one(conn).await
+            }
+            None => query.fetch_one(&self.pool).await,
+        }
+    }
+
+    async fn execute(&self, query: SqliteQuery<'_>) -> Result<SqliteQueryResult, sqlx::Error> {
+        match &self.tx {
+            Some(tx) => {
+                let mut guard = tx.lock().await;
+                let conn: &mut sqlx::SqliteConnection =
+                    guard.as_mut().ok_or_else(transaction_finished)?;
+                query.execute(conn).await
+            }
+            None => query.execute(&self.pool).await,
+        }
+    }
     async fn do_get_table_schema(&self, table: &str) -> Result<Option<TableSchema>, DatabaseError> {
         // Check if table exists first
-        let exists: bool = sqlx::query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type='table' AND name=$1)",
-        )
-        .bind(table)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(DatabaseError::QueryError)?;
+        let exists: bool = self
+            .fetch_one(
+                sqlx::query(
+                    "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type='table' AND name=$1)",
+                )
+                .bind(table),
+            )
+            .await
+            .map_err(DatabaseError::QueryError)?
+            .get(0);
 
         if !exists {
             return Ok(None);
         }
 
         let query = format!("PRAGMA table_info({})", table);
-        let rows = sqlx::query(&query)
-            .fetch_all(&self.pool)
+        let rows = self
+            .fetch_all(sqlx::query(&query))
             .await
             .map_err(DatabaseError::QueryError)?;
 
@@ -105,14 +172,82 @@ impl SqliteBackend {
                 auto_increment: false, // SQLite handles this implicitly for INTEGER PRIMARY KEY
                 default_value: dflt_value,
                 auto_field: false,
+                renamed_from: None,
             });
         }
 
+        // Indexes created with CREATE INDEX, leaving out those of UNIQUE and PRIMARY KEY
+        let mut indexes = Vec::new();
+        let index_rows = self
+            .fetch_all(sqlx::query(&format!("PRAGMA index_list({})", table)))
+            .await
+            .map_err(DatabaseError::QueryError)?;
+        for row in index_rows {
+            let origin: String = row.get("origin");
+            if origin != "c" {
+                continue;
+            }
+            let name: String = row.get("name");
+            let unique: i32 = row.get("unique");
+            let columns = self
+                .fetch_all(sqlx::query(&format!("PRAGMA index_info({})", name)))
+                .await
+                .map_err(DatabaseError::QueryError)?
+                .iter()
+                .filter_map(|row| row.get::<Option<String>, _>("name"))
+                .collect();
+            indexes.push(IndexDefinition {
+                name,
+                columns,
+                unique: unique != 0,
+            });
+        }
+
+        // Foreign keys, by their first column; SQLite does not keep constraint names
+        let foreign_keys = self
+            .fetch_all(sqlx::query(&format!("PRAGMA foreign_key_list({})", table)))
+            .await
+            .map_err(DatabaseError::QueryError)?
+            .iter()
+            .filter(|row| row.get::<i32, _>("seq") == 0)
+            .map(|row| {
+                let column: String = row.get("from");
+                ForeignKeyDefinition {
+                    name: SchemaGenerator::foreign_key_name(table, &column),
+                    column,
+                    references_table: row.get("table"),
+                    references_column: row
+                        .get::<Option<String>, _>("to")
+                        .unwrap_or_else(|| "id".to_string()),
+                    on_delete: OnDelete::from_sql(&row.get::<String, _>("on_delete")),
+                }
+            })
+            .collect();
+
+        // Named CHECK constraints, which SQLite only keeps in the table definition
+        let create_sql: Option<String> = self
+            .fetch_one(
+                sqlx::query("SELECT sql FROM sqlite_master WHERE type='table' AND name=$1")
+                    .bind(table),
+            )
+            .await
+            .map_err(DatabaseError::QueryError)?
+            .get(0);
+        let checks = create_sql.as_deref().map(checks_sqlite).unwrap_or_default();
+
         Ok(Some(TableSchema {
             table_name: table.to_string(),
             columns,
-            indexes: vec![],
+            indexes,
             relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys,
+            checks,
         }))
     }
 
@@ -125,50 +260,127 @@ impl SqliteBackend {
             "do_initialize_schema called with {} schemas",
             table_schemas.len()
         );
+        // BEGIN IMMEDIATE takes the database's write lock, so other processes migrating
+        // the same file wait until the schema is checked and migrated. The connection
+        // has foreign keys off, as SQLite requires to recreate referenced tables.
+        let options = (*self.pool.connect_options()).clone().foreign_keys(false);
+        let lock_pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .map_err(DatabaseError::PoolError)?;
+        let tx = lock_pool
+            .begin_with("BEGIN IMMEDIATE")
+            .await
+            .map_err(DatabaseError::PoolError)?;
+        let locked = Self {
+            tx: Some(SharedTransaction::new(tx)),
+            ..self.clone()
+        };
+        let result = locked.migrate(table_schemas).await;
+        // A failed migration is rolled back to its savepoint, keeping its history row
+        let committed = match &locked.tx {
+            Some(tx) => tx.commit().await,
+            None => Err(no_transaction()),
+        };
+        lock_pool.close().await;
+        result.and(committed)
+    }
+
+    async fn migrate(&self, table_schemas: Vec<TableSchema>) -> Result<(), DatabaseError> {
+        let spec_hash = spec_hash(&table_schemas);
+        let plans = self.do_plan_schema(table_schemas).await?;
+        let Some(run) = MigrationRun::start(spec_hash, &plans) else {
+            return Ok(());
+        };
+        let result = self.apply_plans(plans).await;
+        if let Err(e) = self.record_migration(run.finish(&result)).await {
+            tracing::error!(error = %e, "Failed to record migration history");
+        }
+        result
+    }
+
+    /// Run the plans on the migration transaction, then check the references of the
+    /// migrated tables
+    async fn apply_plans(&self, plans: Vec<TablePlan>) -> Result<(), DatabaseError> {
+        ensure_applicable(&plans)?;
+        let mut guard = self.tx.as_ref().ok_or_else(no_transaction)?.lock().await;
+        let conn: &mut sqlx::SqliteConnection = guard
+            .as_mut()
+            .ok_or_else(|| DatabaseError::QueryError(transaction_finished()))?;
+        apply_statements(conn, plans).await
+    }
+
+    /// Append a row to the migration history, creating the table on first use
+    async fn record_migration(&self, row: HashMap<String, Value>) -> Result<(), DatabaseError> {
+        let create = SchemaGenerator::generate_create_table_sql_sqlite(&history_schema());
+        {
+            let mut guard = self.tx.as_ref().ok_or_else(no_transaction)?.lock().await;
+            let conn: &mut sqlx::SqliteConnection = guard
+                .as_mut()
+                .ok_or_else(|| DatabaseError::QueryError(transaction_finished()))?;
+            sqlx::Executor::execute(conn, sqlx::raw_sql(&create))
+                .await
+                .map_err(DatabaseError::QueryError)?;
+        }
+        self.do_insert(HISTORY_TABLE, row).await.map(|_| ())
+    }
+
+    async fn do_plan_schema(
+        &self,
+        table_schemas: Vec<TableSchema>,
+    ) -> Result<Vec<TablePlan>, DatabaseError> {
+        let mut plans = Vec::new();
         for schema in table_schemas {
-            tracing::info!("Processing schema for table: {}", schema.table_name);
-            let current_schema = self.do_get_table_schema(&schema.table_name).await?;
-
-            if let Some(current) = current_schema {
-                // Table exists, migrate
-                let migration_sqls =
-                    SchemaGenerator::generate_migration_sql(&current, &schema, "sqlite")
-                        .map_err(DatabaseError::ValidationError)?;
-                for sql in migration_sqls {
-                    tracing::info!(sql = %sql, "Executing migration SQL");
-                    sqlx::raw_sql(&sql)
-                        .execute(&self.pool)
-                        .await
-                        .map_err(DatabaseError::QueryError)?;
-                }
-            } else {
-                tracing::info!("Table {} does not exist, creating...", schema.table_name);
-                let full_sql = SchemaGenerator::generate_create_table_sql_sqlite(&schema);
-                for stmt in full_sql.split(';') {
-                    let s = stmt.trim();
-                    if s.is_empty() {
-                        continue;
-                    }
-                    sqlx::raw_sql(s)
-                        .execute(&self.pool)
-                        .await
-                        .map_err(DatabaseError::QueryError)?;
-                }
-            }
+            tracing::info!("Planning schema for table: {}", schema.table_name);
+            let current = self.do_get_table_schema(&schema.table_name).await?;
+            let mut plan = TablePlan::new(current.as_ref(), &schema, "sqlite");
+            let table_changed = plan.action != TableAction::Unchanged;
+            let (statements, replaces) = self.search_index_sql(&schema, table_changed).await?;
+            plan.push_index_statements(statements, replaces);
+            plans.push(plan);
+        }
+        Ok(plans)
+    }
+
+    /// Statements creating, rebuilding or dropping the FTS5 index of a table to match
+    /// its `searchColumns`, and whether they replace an index built for other columns. The index is
+    /// rebuilt when the table is created or migrated, since recreating a SQLite table
+    /// drops its triggers and renumbers its rows.
+    async fn search_index_sql(
+        &self,
+        schema: &TableSchema,
+        table_changed: bool,
+    ) -> Result<(Vec<String>, bool), DatabaseError> {
+        let table = &schema.table_name;
+        let fts = SchemaGenerator::search_table_sqlite(table);
+        let indexed: Vec<String> = self
+            .fetch_all(sqlx::query(&format!("PRAGMA table_info({})", fts)))
+            .await
+            .map_err(DatabaseError::QueryError)?
+            .iter()
+            .map(|row| row.get("name"))
+            .collect();
+
+        let mut statements = Vec::new();
+        if !indexed.is_empty() && indexed != schema.search_columns {
+            statements.extend(SchemaGenerator::drop_search_sql_sqlite(table));
+        }
+        if !schema.search_columns.is_empty() && (table_changed || indexed != schema.search_columns)
+        {
+            statements.extend(SchemaGenerator::generate_search_sql_sqlite(schema));
         }
-        Ok(())
+        let replaces = !indexed.is_empty() && indexed != schema.search_columns;
+        Ok((statements, replaces))
     }
 
     async fn do_select(
         &self,
         table: &str,
-        columns: Option<Vec<String>>,
-        where_clause: Option<HashMap<String, Value>>,
-        limit: Option<u32>,
-        offset: Option<u32>,
+        query: SelectQuery,
     ) -> Result<Vec<Value>, DatabaseError> {
         let mut qb = QueryBuilder::<Sqlite>::new("SELECT ");
-        if let Some(cols) = columns {
+        if let Some(cols) = query.columns {
             if !cols.is_empty() {
                 qb.push(cols.join(", "));
             } else {
@@ -178,13 +390,22 @@ impl SqliteBackend {
             qb.push("*");
         }
         qb.push(" FROM ").push(table);
-        if let Some(conds) = where_clause {
-            push_where_sqlite(&mut qb, conds);
+        push_filters_sqlite(&mut qb, &query.filters);
+        if let Some(search) = &query.search {
+            push_search_sqlite(&mut qb, table, search, !query.filters.is_empty());
         }
-        if let Some(l) = limit {
+        if let Some(keyset) = &query.keyset {
+            push_keyset_sqlite(
+                &mut qb,
+                keyset,
+                !query.filters.is_empty() || query.search.is_some(),
+            );
+        }
+        push_order_by(&mut qb, table, &query.sort, query.search.as_ref());
+        if let Some(l) = query.limit {
             qb.push(" LIMIT ").push_bind(l as i64);
         }
-        if let Some(o) = offset {
+        if let Some(o) = query.offset {
             qb.push(" OFFSET ").push_bind(o as i64);
         }
 
@@ -200,7 +421,7 @@ impl SqliteBackend {
         }
 
         let start = std::time::Instant::now();
-        let rows = qb.build().fetch_all(&self.pool).await.map_err(|e| {
+        let rows = self.fetch_all(qb.build()).await.map_err(|e| {
             tracing::error!(
                 "SQLite select error on table {}: {:?}. Pool Size: {}, Idle: {}",
                 table,
@@ -225,6 +446,56 @@ impl SqliteBackend {
         Ok(rows.into_iter().map(|r| row_to_json_sqlite(&r)).collect())
     }
 
+    async fn do_aggregate(
+        &self,
+        table: &str,
+        query: AggregateQuery,
+    ) -> Result<Vec<Value>, DatabaseError> {
+        let mut columns = query.group_by.clone();
+        columns.extend(
+            query
+                .aggregates
+                .iter()
+                .map(|a| format!("{} AS {}", a.expression(), a.alias())),
+        );
+        let mut qb = QueryBuilder::<Sqlite>::new("SELECT ");
+        qb.push(columns.join(", ")).push(" FROM ").push(table);
+        push_filters_sqlite(&mut qb, &query.filters);
+        if let Some(search) = &query.search {
+            push_search_sqlite(&mut qb, table, search, !query.filters.is_empty());
+        }
+        if !query.group_by.is_empty() {
+            let groups = query.group_by.join(", ");
+            qb.push(" GROUP BY ").push(&groups);
+            qb.push(" ORDER BY ").push(&groups);
+        }
+        let rows = self.fetch_all(qb.build()).await.map_err(|e| {
+            tracing::error!("SQLite aggregate error on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+        Ok(rows.iter().map(row_to_json_sqlite).collect())
+    }
+
+    async fn do_count(
+        &self,
+        table: &str,
+        filters: Vec<Filter>,
+        search: Option<TextSearch>,
+    ) -> Result<u64, DatabaseError> {
+        let mut qb = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM ");
+        qb.push(table);
+        push_filters_sqlite(&mut qb, &filters);
+        if let Some(search) = &search {
+            push_search_sqlite(&mut qb, table, search, !filters.is_empty());
+        }
+        let row = self.fetch_one(qb.build()).await.map_err(|e| {
+            tracing::error!("SQLite count error on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+        let count: i64 = row.try_get(0).map_err(DatabaseError::QueryError)?;
+        Ok(count.max(0) as u64)
+    }
+
     async fn do_insert(
         &self,
         table: &str,
@@ -257,7 +528,7 @@ impl SqliteBackend {
         }
         let start = std::time::Instant::now();
 
-        let res = qb.build().execute(&self.pool).await.map_err(|e| {
+        let res = self.execute(qb.build()).await.map_err(|e| {
             tracing::error!(
                 "Insert query failed on table {}: {:?}. Pool Size: {}, Idle: {}",
                 table,
@@ -287,18 +558,55 @@ impl SqliteBackend {
         }))
     }
 
+    async fn do_insert_many(&self, table: &str, insert: BulkInsert) -> Result<u64, DatabaseError> {
+        let mut qb = QueryBuilder::<Sqlite>::new("INSERT INTO ");
+        qb.push(table).push(" (");
+        qb.push(insert.columns.join(", ")).push(") ");
+        qb.push_values(&insert.rows, |mut sep, row| {
+            for v in row {
+                push_bind_sqlite(&mut sep, v);
+            }
+        });
+        if let Some(conflict) = &insert.on_conflict {
+            qb.push(format!(" ON CONFLICT ({}) ", conflict.join(", ")));
+            if insert.update_columns.is_empty() {
+                qb.push("DO NOTHING");
+            } else {
+                let set: Vec<String> = insert
+                    .update_columns
+                    .iter()
+                    .map(|c| format!("{c} = excluded.{c}"))
+                    .chain(
+                        insert
+                            .increments
+                            .iter()
+                            .map(|c| format!("{c} = {table}.{c} + 1")),
+                    )
+                    .collect();
+                qb.push(format!("DO UPDATE SET {}", set.join(", ")));
+            }
+        }
+
+        let res = self.execute(qb.build()).await.map_err(|e| {
+            tracing::error!("Bulk insert failed on table {}: {:?}", table, e);
+            DatabaseError::QueryError(e)
+        })?;
+        Ok(res.rows_affected())
+    }
+
     async fn do_update(
         &self,
         table: &str,
         data: HashMap<String, Value>,
-        where_clause: HashMap<String, Value>,
+        filters: Vec<Filter>,
+        increments: Vec<String>,
     ) -> Result<Value, DatabaseError> {
-        if data.is_empty() {
+        if data.is_empty() && increments.is_empty() {
             return Err(DatabaseError::ValidationError(
                 "No data provided for update".to_string(),
             ));
         }
-        if where_clause.is_empty() {
+        if filters.is_empty() {
             return Err(DatabaseError::ValidationError(
                 "WHERE clause is required for update".to_string(),
             ));
@@ -336,38 +644,14 @@ impl SqliteBackend {
                 }
             }
         }
-        qb.push(" WHERE ");
-        let mut first = true;
-        for (k, v) in where_clause {
+        for column in &increments {
             if !first {
-                qb.push(" AND ");
+                qb.push(", ");
             }
             first = false;
-            qb.push(format!("{} = ", k));
-            match v {
-                Value::Null => {
-                    qb.push("NULL");
-                }
-                Value::Bool(b) => {
-                    qb.push_bind(b);
-                }
-                Value::Number(n) => {
-                    if let Some(i) = n.as_i64() {
-                        qb.push_bind(i);
-                    } else if let Some(f) = n.as_f64() {
-                        qb.push_bind(f);
-                    } else {
-                        qb.push_bind(n.to_string());
-                    }
-                }
-                Value::String(s) => {
-                    qb.push_bind(s);
-                }
-                Value::Array(_) | Value::Object(_) => {
-                    qb.push_bind(serde_json::to_string(&v).unwrap_or_default());
-                }
-            }
+            qb.push(column).push(" = ").push(column).push(" + 1");
         }
+        push_filters_sqlite(&mut qb, &filters);
 
         let pool_size = self.pool.size();
         let pool_idle = self.pool.num_idle();
@@ -381,7 +665,7 @@ impl SqliteBackend {
         }
         let start = std::time::Instant::now();
 
-        let res = qb.build().execute(&self.pool).await.map_err(|e| {
+        let res = self.execute(qb.build()).await.map_err(|e| {
             tracing::error!(
                 "Update query failed on table {}: {:?}. Pool Size: {}, Idle: {}",
                 table,
@@ -406,49 +690,15 @@ impl SqliteBackend {
         Ok(json!({"message": "Record updated", "affected_rows": res.rows_affected()}))
     }
 
-    async fn do_delete(
-        &self,
-        table: &str,
-        where_clause: HashMap<String, Value>,
-    ) -> Result<u64, DatabaseError> {
-        if where_clause.is_empty() {
+    async fn do_delete(&self, table: &str, filters: Vec<Filter>) -> Result<u64, DatabaseError> {
+        if filters.is_empty() {
             return Err(DatabaseError::ValidationError(
                 "WHERE clause is required for delete".to_string(),
             ));
         }
         let mut qb = QueryBuilder::<Sqlite>::new("DELETE FROM ");
-        qb.push(table).push(" WHERE ");
-        let mut first = true;
-        for (k, v) in where_clause {
-            if !first {
-                qb.push(" AND ");
-            }
-            first = false;
-            qb.push(format!("{} = ", k));
-            match v {
-                Value::Null => {
-                    qb.push("NULL");
-                }
-                Value::Bool(b) => {
-                    qb.push_bind(b);
-                }
-                Value::Number(n) => {
-                    if let Some(i) = n.as_i64() {
-                        qb.push_bind(i);
-                    } else if let Some(f) = n.as_f64() {
-                        qb.push_bind(f);
-                    } else {
-                        qb.push_bind(n.to_string());
-                    }
-                }
-                Value::String(s) => {
-                    qb.push_bind(s);
-                }
-                Value::Array(_) | Value::Object(_) => {
-                    qb.push_bind(serde_json::to_string(&v).unwrap_or_default());
-                }
-            }
-        }
+        qb.push(table);
+        push_filters_sqlite(&mut qb, &filters);
 
         let pool_size = self.pool.size();
         let pool_idle = self.pool.num_idle();
@@ -462,7 +712,7 @@ impl SqliteBackend {
         }
         let start = std::time::Instant::now();
 
-        let res = qb.build().execute(&self.pool).await.map_err(|e| {
+        let res = self.execute(qb.build()).await.map_err(|e| {
             tracing::error!(
                 "Delete query failed on table {}: {:?}. Pool Size: {}, Idle: {}",
                 table,
@@ -496,20 +746,41 @@ impl DatabaseBackend for SqliteBackend {
     {
         Box::pin(async move { self.do_initialize_schema(table_schemas).await })
     }
+    fn plan_schema<'a>(
+        &'a self,
+        table_schemas: Vec<TableSchema>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Vec<TablePlan>, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_plan_schema(table_schemas).await })
+    }
     fn select<'a>(
         &'a self,
         table: &'a str,
-        columns: Option<Vec<String>>,
-        where_clause: Option<HashMap<String, Value>>,
-        limit: Option<u32>,
-        offset: Option<u32>,
+        query: SelectQuery,
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<Vec<Value>, DatabaseError>> + Send + 'a>,
     > {
-        Box::pin(async move {
-            self.do_select(table, columns, where_clause, limit, offset)
-                .await
-        })
+        Box::pin(async move { self.do_select(table, query).await })
+    }
+    fn count<'a>(
+        &'a self,
+        table: &'a str,
+        filters: Vec<Filter>,
+        search: Option<TextSearch>,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_count(table, filters, search).await })
+    }
+    fn aggregate<'a>(
+        &'a self,
+        table: &'a str,
+        query: AggregateQuery,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<Vec<Value>, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_aggregate(table, query).await })
     }
     fn insert<'a>(
         &'a self,
@@ -520,24 +791,34 @@ impl DatabaseBackend for SqliteBackend {
     > {
         Box::pin(async move { self.do_insert(table, data).await })
     }
+    fn insert_many<'a>(
+        &'a self,
+        table: &'a str,
+        insert: BulkInsert,
+    ) -> core::pin::Pin<
+        Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
+    > {
+        Box::pin(async move { self.do_insert_many(table, insert).await })
+    }
     fn update<'a>(
         &'a self,
         table: &'a str,
         data: HashMap<String, Value>,
-        where_clause: HashMap<String, Value>,
+        filters: Vec<Filter>,
+        increments: Vec<String>,
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<Value, DatabaseError>> + Send + 'a>,
     > {
-        Box::pin(async move { self.do_update(table, data, where_clause).await })
+        Box::pin(async move { self.do_update(table, data, filters, increments).await })
     }
     fn delete<'a>(
         &'a self,
         table: &'a str,
-        where_clause: HashMap<String, Value>,
+        filters: Vec<Filter>,
     ) -> core::pin::Pin<
         Box<dyn core::future::Future<Output = Result<u64, DatabaseError>> + Send + 'a>,
     > {
-        Box::pin(async move { self.do_delete(table, where_clause).await })
+        Box::pin(async move { self.do_delete(table, filters).await })
     }
 
     fn get_table_schema<'a>(
@@ -570,12 +851,87 @@ impl DatabaseBackend for SqliteBackend {
             Ok(tables)
         })
     }
+
+    fn begin<'a>(&'a self) -> BeginFuture<'a> {
+        Box::pin(async move {
+            if self.tx.is_some() {
+                return Err(nested_transaction());
+            }
+            let tx = self
+                .pool
+                .begin_with("BEGIN IMMEDIATE")
+                .await
+                .map_err(DatabaseError::PoolError)?;
+            let backend: Arc<dyn DatabaseBackend> = Arc::new(Self {
+                tx: Some(SharedTransaction::new(tx)),
+                ..self.clone()
+            });
+            Ok(backend)
+        })
+    }
+
+    fn commit<'a>(
+        &'a self,
+    ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>
+    {
+        Box::pin(async move { self.tx.as_ref().ok_or_else(no_transaction)?.commit().await })
+    }
+
+    fn rollback<'a>(
+        &'a self,
+    ) -> core::pin::Pin<Box<dyn core::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>
+    {
+        Box::pin(async move {
+            self.tx
+                .as_ref()
+                .ok_or_else(no_transaction)?
+                .rollback()
+                .await
+        })
+    }
+}
+
+/// Named `CHECK` constraints of a `CREATE TABLE` statement
+fn checks_sqlite(create_sql: &str) -> Vec<CheckDefinition> {
+    static CHECK: Lazy<regex::Regex> =
+        Lazy::new(|| regex::Regex::new(r"(?i)\bCONSTRAINT\s+(\w+)\s+CHECK\s*\(").unwrap());
+    CHECK
+        .captures_iter(create_sql)
+        .filter_map(|caps| {
+            let start = caps.get(0)?.end();
+            // The expression ends at the parenthesis closing the one before it
+            let mut depth = 1;
+            let mut in_quotes = false;
+            for (i, c) in create_sql[start..].char_indices() {
+                match c {
+                    '\'' => in_quotes = !in_quotes,
+                    '(' if !in_quotes => depth += 1,
+                    ')' if !in_quotes => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(CheckDefinition {
+                                name: caps[1].to_string(),
+                                expression: create_sql[start..start + i].to_string(),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            None
+        })
+        .collect()
 }
 
 fn row_to_json_sqlite(row: &SqliteRow) -> Value {
     let mut obj = serde_json::Map::new();
     for (i, col) in row.columns().iter().enumerate() {
         let name = col.name().to_string();
+        // NULL decodes as an empty string, so check for it first
+        if row.try_get_raw(i).is_ok_and(|v| v.is_null()) {
+            obj.insert(name, Value::Null);
+            continue;
+        }
         if let Ok(v) = row.try_get::<String, _>(i) {
             obj.insert(name, Value::String(v));
             continue;
@@ -613,41 +969,214 @@ fn row_to_json_sqlite(row: &SqliteRow) -> Value {
     Value::Object(obj)
 }
 
-fn push_where_sqlite(qb: &mut QueryBuilder<Sqlite>, conds: HashMap<String, Value>) {
-    if conds.is_empty() {
+fn push_order_by(
+    qb: &mut QueryBuilder<Sqlite>,
+    table: &str,
+    sort: &[SortKey],
+    search: Option<&TextSearch>,
+) {
+    for (i, key) in sort.iter().enumerate() {
+        qb.push(if i == 0 { " ORDER BY " } else { ", " });
+        match search {
+            // FTS5 ranks better matches lower (bm25), so the direction flips
+            Some(search) if key.column == RELEVANCE_SORT => {
+                let fts = SchemaGenerator::search_table_sqlite(table);
+                qb.push(format!("(SELECT rank FROM {fts} WHERE {fts} MATCH "));
+                qb.push_bind(fts_match_query(search));
+                qb.push(format!(" AND rowid = {}.rowid)", table));
+                qb.push(if key.descending { " ASC" } else { " DESC" });
+            }
+            _ => {
+                qb.push(&key.column);
+                qb.push(if key.descending { " DESC" } else { " ASC" });
+            }
+        }
+    }
+}
+
+/// Restrict a query to the rows whose FTS5 index entry matches the search
+fn push_search_sqlite(
+    qb: &mut QueryBuilder<Sqlite>,
+    table: &str,
+    search: &TextSearch,
+    has_where: bool,
+) {
+    let fts = SchemaGenerator::search_table_sqlite(table);
+    qb.push(if has_where { " AND " } else { " WHERE " });
+    qb.push(format!(
+        "{}.rowid IN (SELECT rowid FROM {fts} WHERE {fts} MATCH ",
+        table
+    ));
+    qb.push_bind(fts_match_query(search));
+    qb.push(")");
+}
+
+/// FTS5 query requiring every term as a word prefix: `"ali"* "smith"*`
+fn fts_match_query(search: &TextSearch) -> String {
+    search
+        .terms
+        .iter()
+        .map(|t| format!("\"{}\"*", t.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn push_keyset_sqlite(qb: &mut QueryBuilder<Sqlite>, keyset: &Keyset, has_where: bool) {
+    qb.push(if has_where { " AND (" } else { " WHERE (" });
+    qb.push(keyset.columns.join(", "));
+    qb.push(if keyset.descending { ") < (" } else { ") > (" });
+    for (i, value) in keyset.values.iter().enumerate() {
+        if i > 0 {
+            qb.push(", ");
+        }
+        push_value_sqlite(qb, value);
+    }
+    qb.push(")");
+}
+
+/// Apply the statements of migration plans under a savepoint, undone when rows
+/// of the migrated tables reference missing rows
+async fn apply_statements(
+    conn: &mut sqlx::SqliteConnection,
+    plans: Vec<TablePlan>,
+) -> Result<(), DatabaseError> {
+    sqlx::Executor::execute(&mut *conn, sqlx::raw_sql("SAVEPOINT apify_migration"))
+        .await
+        .map_err(DatabaseError::QueryError)?;
+    let result = run_statements(conn, plans).await;
+    let end = if result.is_ok() {
+        "RELEASE apify_migration"
+    } else {
+        "ROLLBACK TO apify_migration; RELEASE apify_migration"
+    };
+    sqlx::Executor::execute(&mut *conn, sqlx::raw_sql(end))
+        .await
+        .map_err(DatabaseError::QueryError)?;
+    result
+}
+
+async fn run_statements(
+    conn: &mut sqlx::SqliteConnection,
+    plans: Vec<TablePlan>,
+) -> Result<(), DatabaseError> {
+    let mut migrated = Vec::new();
+    for plan in plans {
+        if !plan.statements.is_empty() {
+            migrated.push(plan.table.clone());
+        }
+        for sql in plan.statements {
+            tracing::info!(table = %plan.table, sql = %sql, "Executing migration SQL");
+            sqlx::Executor::execute(&mut *conn, sqlx::raw_sql(&sql))
+                .await
+                .map_err(DatabaseError::QueryError)?;
+        }
+    }
+    for table in migrated {
+        let violation = sqlx::query(&format!("PRAGMA foreign_key_check({})", table))
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(DatabaseError::QueryError)?;
+        if let Some(row) = violation {
+            let parent: String = row.get("parent");
+            return Err(DatabaseError::ValidationError(format!(
+                "Rows of table '{}' reference rows missing from '{}'; delete or fix them before migrating",
+                table, parent
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn push_filters_sqlite(qb: &mut QueryBuilder<Sqlite>, filters: &[Filter]) {
+    if filters.is_empty() {
         return;
     }
     qb.push(" WHERE ");
-    let mut first = true;
-    for (k, v) in conds {
-        if !first {
+    for (i, filter) in filters.iter().enumerate() {
+        if i > 0 {
             qb.push(" AND ");
         }
-        first = false;
-        qb.push(format!("{} = ", k));
-        match v {
-            Value::Null => {
-                qb.push("NULL");
+        let col = &filter.column;
+        match filter.operator {
+            FilterOperator::Eq if filter.value.is_null() => {
+                qb.push(format!("{} IS NULL", col));
+            }
+            FilterOperator::Ne if filter.value.is_null() => {
+                qb.push(format!("{} IS NOT NULL", col));
+            }
+            FilterOperator::IsNull => {
+                qb.push(format!("{} IS NULL", col));
+            }
+            FilterOperator::IsNotNull => {
+                qb.push(format!("{} IS NOT NULL", col));
             }
-            Value::Bool(b) => {
-                qb.push_bind(b);
+            // SQLite LIKE is already case-insensitive for ASCII
+            FilterOperator::Like | FilterOperator::ILike => {
+                qb.push(format!("{} LIKE ", col));
+                push_value_sqlite(qb, &filter.value);
             }
-            Value::Number(n) => {
-                if let Some(i) = n.as_i64() {
-                    qb.push_bind(i);
-                } else if let Some(f) = n.as_f64() {
-                    qb.push_bind(f);
+            FilterOperator::NotLike | FilterOperator::NotILike => {
+                qb.push(format!("{} NOT LIKE ", col));
+                push_value_sqlite(qb, &filter.value);
+            }
+            FilterOperator::In | FilterOperator::NotIn => {
+                let items = match &filter.value {
+                    Value::Array(items) => items.clone(),
+                    other => vec![other.clone()],
+                };
+                if items.is_empty() {
+                    // Empty IN list matches nothing, empty NOT IN matches everything
+                    qb.push(if filter.operator == FilterOperator::In {
+                        "1 = 0"
+                    } else {
+                        "1 = 1"
+                    });
+                    continue;
+                }
+                qb.push(col);
+                qb.push(if filter.operator == FilterOperator::In {
+                    " IN ("
                 } else {
-                    qb.push_bind(n.to_string());
+                    " NOT IN ("
+                });
+                let mut sep = qb.separated(", ");
+                for item in &items {
+                    push_bind_sqlite(&mut sep, item);
                 }
+                qb.push(")");
             }
-            Value::String(s) => {
-                qb.push_bind(s);
+            op => {
+                qb.push(col);
+                qb.push(op.comparison_sql().unwrap_or(" = "));
+                push_value_sqlite(qb, &filter.value);
             }
-            Value::Array(_) | Value::Object(_) => {
-                qb.push_bind(serde_json::to_string(&v).unwrap_or_default());
+        }
+    }
+}
+
+fn push_value_sqlite(qb: &mut QueryBuilder<Sqlite>, v: &Value) {
+    match v {
+        Value::Null => {
+            qb.push("NULL");
+        }
+        Value::Bool(b) => {
+            qb.push_bind(*b);
+        }
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                qb.push_bind(i);
+            } else if let Some(f) = n.as_f64() {
+                qb.push_bind(f);
+            } else {
+                qb.push_bind(n.to_string());
             }
         }
+        Value::String(s) => {
+            qb.push_bind(s.clone());
+        }
+        Value::Array(_) | Value::Object(_) => {
+            qb.push_bind(serde_json::to_string(v).unwrap_or_default());
+        }
     }
 }
 
@@ -0,0 +1,210 @@
+//! JSON Patch (RFC 6902) and JSON Merge Patch (RFC 7396) documents applied to records.
+//!
+//! `PATCH` requests with `Content-Type: application/json-patch+json` carry a list of
+//! operations against the record as returned by a GET, nested relations included:
+//!
+//! ```json
+//! [{"op": "replace", "path": "/status", "value": "shipped"},
+//!  {"op": "remove", "path": "/items/1"}]
+//! ```
+
+use serde_json::Value;
+
+/// Media type of JSON Patch request bodies
+pub const JSON_PATCH_MEDIA_TYPE: &str = "application/json-patch+json";
+
+/// Apply the operations of a JSON Patch document to `doc`.
+/// The patch is applied atomically: on error `doc` is left untouched.
+pub fn apply_json_patch(doc: &mut Value, patch: &Value) -> Result<(), String> {
+    let ops = patch
+        .as_array()
+        .ok_or_else(|| "A JSON Patch document must be an array of operations".to_string())?;
+    let mut patched = doc.clone();
+    for (i, op) in ops.iter().enumerate() {
+        apply_operation(&mut patched, op).map_err(|e| format!("Operation {}: {}", i, e))?;
+    }
+    *doc = patched;
+    Ok(())
+}
+
+fn apply_operation(doc: &mut Value, op: &Value) -> Result<(), String> {
+    let name = op.get("op").and_then(Value::as_str).ok_or("missing 'op'")?;
+    let path = op
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or("missing 'path'")?;
+    let value = || op.get("value").cloned().ok_or("missing 'value'");
+    let from = || {
+        op.get("from")
+            .and_then(Value::as_str)
+            .ok_or("missing 'from'")
+    };
+
+    match name {
+        "add" => add(doc, path, value()?),
+        "remove" => remove(doc, path).map(|_| ()),
+        "replace" => {
+            let target = doc
+                .pointer_mut(path)
+                .ok_or_else(|| format!("path '{}' does not exist", path))?;
+            *target = value()?;
+            Ok(())
+        }
+        "move" => {
+            let from = from()?;
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(format!("cannot move '{}' into itself", from));
+            }
+            let moved = remove(doc, from)?;
+            add(doc, path, moved)
+        }
+        "copy" => {
+            let from = from()?;
+            let copied = doc
+                .pointer(from)
+                .cloned()
+                .ok_or_else(|| format!("path '{}' does not exist", from))?;
+            add(doc, path, copied)
+        }
+        "test" => {
+            let expected = value()?;
+            match doc.pointer(path) {
+                Some(actual) if *actual == expected => Ok(()),
+                _ => Err(format!("test failed at '{}'", path)),
+            }
+        }
+        other => Err(format!("unknown operation '{}'", other)),
+    }
+}
+
+/// Split a JSON pointer into its parent pointer and unescaped last token
+fn split_pointer(path: &str) -> Result<(&str, String), String> {
+    let idx = path
+        .rfind('/')
+        .ok_or_else(|| format!("invalid path '{}'", path))?;
+    let token = path[idx + 1..].replace("~1", "/").replace("~0", "~");
+    Ok((&path[..idx], token))
+}
+
+fn array_index(token: &str, len: usize, path: &str) -> Result<usize, String> {
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|i| *i < len && (token == "0" || !token.starts_with('0')))
+        .ok_or_else(|| format!("invalid array index in '{}'", path))
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, token) = split_pointer(path)?;
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.insert(token, value);
+            Ok(())
+        }
+        Some(Value::Array(items)) => {
+            let idx = if token == "-" {
+                items.len()
+            } else {
+                // Inserting right after the last element is allowed
+                array_index(&token, items.len() + 1, path)?
+            };
+            items.insert(idx, value);
+            Ok(())
+        }
+        _ => Err(format!("path '{}' does not exist", parent)),
+    }
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value, String> {
+    let (parent, token) = split_pointer(path)?;
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(map)) => map
+            .remove(&token)
+            .ok_or_else(|| format!("path '{}' does not exist", path)),
+        Some(Value::Array(items)) => {
+            let idx = array_index(&token, items.len(), path)?;
+            Ok(items.remove(idx))
+        }
+        _ => Err(format!("path '{}' does not exist", parent)),
+    }
+}
+
+/// Merge `patch` into `target` following JSON Merge Patch: object members are merged
+/// recursively, `null` removes a member, and any other value replaces the target
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    if let Value::Object(map) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                map.remove(key);
+            } else {
+                merge_patch(map.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_json_patch() {
+        let mut doc = json!({"status": "new", "items": [{"id": 1}, {"id": 2}], "a/b": 1});
+        let patch = json!([
+            {"op": "test", "path": "/status", "value": "new"},
+            {"op": "replace", "path": "/status", "value": "shipped"},
+            {"op": "remove", "path": "/items/0"},
+            {"op": "add", "path": "/items/-", "value": {"qty": 3}},
+            {"op": "copy", "from": "/status", "path": "/previous"},
+            {"op": "move", "from": "/a~1b", "path": "/c"}
+        ]);
+        apply_json_patch(&mut doc, &patch).unwrap();
+        assert_eq!(
+            doc,
+            json!({
+                "status": "shipped",
+                "previous": "shipped",
+                "items": [{"id": 2}, {"qty": 3}],
+                "c": 1
+            })
+        );
+
+        // A failing operation leaves the document untouched
+        let before = doc.clone();
+        let patch = json!([
+            {"op": "replace", "path": "/status", "value": "lost"},
+            {"op": "test", "path": "/status", "value": "new"}
+        ]);
+        assert!(apply_json_patch(&mut doc, &patch).is_err());
+        assert_eq!(doc, before);
+
+        assert!(apply_json_patch(&mut doc, &json!([{"op": "remove", "path": "/nope"}])).is_err());
+        assert!(
+            apply_json_patch(&mut doc, &json!([{"op": "remove", "path": "/items/5"}])).is_err()
+        );
+        assert!(apply_json_patch(&mut doc, &json!([{"op": "jump", "path": "/status"}])).is_err());
+        assert!(apply_json_patch(&mut doc, &json!({"op": "remove"})).is_err());
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let mut target = json!({"a": "b", "c": {"d": "e", "f": "g"}});
+        merge_patch(&mut target, &json!({"a": "z", "c": {"f": null}}));
+        assert_eq!(target, json!({"a": "z", "c": {"d": "e"}}));
+
+        merge_patch(&mut target, &json!({"c": ["x"]}));
+        assert_eq!(target, json!({"a": "z", "c": ["x"]}));
+    }
+}
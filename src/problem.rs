@@ -0,0 +1,268 @@
+//! RFC 7807 problem details, the body of every error response
+
+use crate::hyper::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use crate::hyper::{Response, StatusCode};
+use crate::{http_body_util::Full, hyper::body::Bytes};
+use http_body_util::BodyExt;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+/// Media type of problem detail bodies
+pub const PROBLEM_MEDIA_TYPE: &str = "application/problem+json";
+
+/// Header carrying the id of a request, echoed on every response
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied request id that is reused instead of replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Id of the request being served, stored in the request context extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// Reuse the caller's `X-Request-Id` when it is a plain token, otherwise generate one
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let supplied = headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_REQUEST_ID_LEN
+                    && id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            });
+        match supplied {
+            Some(id) => RequestId(id.to_string()),
+            None => RequestId(uuid::Uuid::new_v4().to_string()),
+        }
+    }
+}
+
+/// One offending field of a request that failed validation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    /// JSON pointer to the field in the request body, e.g. `/items/0/qty`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    /// Name of the query, path or header parameter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<String>,
+    pub detail: String,
+}
+
+impl FieldError {
+    pub fn body(pointer: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            pointer: Some(pointer.into()),
+            parameter: None,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn parameter(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            pointer: None,
+            parameter: Some(name.into()),
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Problem details object (RFC 7807)
+#[derive(Debug, Clone, Serialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+    /// Members specific to the problem type, e.g. `constraint`
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+impl Problem {
+    /// Problem of the generic `about:blank` type, titled after the status
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail: None,
+            instance: None,
+            request_id: None,
+            errors: Vec::new(),
+            extensions: Map::new(),
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set a problem type URI and the title describing that type
+    pub fn with_type(mut self, problem_type: impl Into<String>, title: impl Into<String>) -> Self {
+        self.problem_type = problem_type.into();
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_errors(mut self, errors: Vec<FieldError>) -> Self {
+        self.errors = errors;
+        self
+    }
+
+    pub fn with_extension(mut self, name: &str, value: Value) -> Self {
+        self.extensions.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn into_response(self) -> Response<Full<Bytes>> {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let body = serde_json::to_string(&self).unwrap_or_else(|_| {
+            json!({ "type": "about:blank", "title": self.title, "status": self.status }).to_string()
+        });
+        let mut response = Response::new(Full::new(Bytes::from(body)));
+        *response.status_mut() = status;
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_MEDIA_TYPE));
+        response
+    }
+}
+
+/// Build a problem response with the given status and detail message
+pub fn error_response(status: StatusCode, detail: &str) -> Response<Full<Bytes>> {
+    Problem::new(status).with_detail(detail).into_response()
+}
+
+/// Finish a response on its way out: echo the request id and fill in the `instance`
+/// and `request_id` members of problem bodies built without access to the request
+pub async fn stamp_response(
+    response: Response<Full<Bytes>>,
+    instance: &str,
+    request_id: &RequestId,
+) -> Response<Full<Bytes>> {
+    let (mut parts, body) = response.into_parts();
+    if let Ok(value) = HeaderValue::from_str(&request_id.0) {
+        parts.headers.insert(REQUEST_ID_HEADER, value);
+    }
+
+    let is_problem = parts
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct == PROBLEM_MEDIA_TYPE);
+    let bytes = match body.collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(never) => match never {},
+    };
+    if !is_problem {
+        return Response::from_parts(parts, Full::new(bytes));
+    }
+
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(mut problem)) => {
+            problem
+                .entry("instance")
+                .or_insert_with(|| Value::String(instance.to_string()));
+            problem
+                .entry("request_id")
+                .or_insert_with(|| Value::String(request_id.0.clone()));
+            Bytes::from(Value::Object(problem).to_string())
+        }
+        _ => bytes,
+    };
+    Response::from_parts(parts, Full::new(body))
+}
+
+/// Schema of problem bodies, declared in generated OpenAPI documents
+pub fn openapi_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["type", "title", "status"],
+        "properties": {
+            "type": { "type": "string", "format": "uri-reference", "example": "about:blank" },
+            "title": { "type": "string", "example": "Bad Request" },
+            "status": { "type": "integer", "example": 400 },
+            "detail": { "type": "string" },
+            "instance": { "type": "string", "format": "uri-reference" },
+            "request_id": { "type": "string" },
+            "errors": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["detail"],
+                    "properties": {
+                        "pointer": { "type": "string", "example": "/items/0/qty" },
+                        "parameter": { "type": "string", "example": "limit" },
+                        "detail": { "type": "string" }
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_json(response: Response<Full<Bytes>>) -> Value {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn problems_are_stamped_with_instance_and_request_id() {
+        let response = Problem::new(StatusCode::BAD_REQUEST)
+            .with_detail("Body validation error")
+            .with_errors(vec![FieldError::body(
+                "/items/0/qty",
+                "\"x\" is not of type \"integer\"",
+            )])
+            .into_response();
+        assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_MEDIA_TYPE);
+
+        let id = RequestId("req-1".to_string());
+        let response = stamp_response(response, "/orders", &id).await;
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-1");
+        let body = body_json(response).await;
+        assert_eq!(body["type"], "about:blank");
+        assert_eq!(body["title"], "Bad Request");
+        assert_eq!(body["status"], 400);
+        assert_eq!(body["instance"], "/orders");
+        assert_eq!(body["request_id"], "req-1");
+        assert_eq!(body["errors"][0]["pointer"], "/items/0/qty");
+    }
+
+    #[tokio::test]
+    async fn other_bodies_are_left_alone() {
+        let response = Response::new(Full::new(Bytes::from("[1,2]")));
+        let id = RequestId("req-2".to_string());
+        let response = stamp_response(response, "/orders", &id).await;
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-2");
+        assert_eq!(body_json(response).await, json!([1, 2]));
+    }
+
+    #[test]
+    fn request_ids_are_reused_only_when_plain() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("abc-123"));
+        assert_eq!(RequestId::from_headers(&headers).0, "abc-123");
+
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("a b<script>"));
+        let generated = RequestId::from_headers(&headers).0;
+        assert!(uuid::Uuid::parse_str(&generated).is_ok());
+    }
+}
@@ -0,0 +1,1002 @@
+//! Parsing of list query parameters into backend-neutral filter conditions.
+//!
+//! Filters use a bracket syntax on the column name:
+//! `age[gte]=18`, `name[like]=Al%`, `status[in]=a,b`, `deleted_at[is]=null`,
+//! with `[not]` negating the operator that follows (`status[not][in]=a,b`).
+//! A bare `name=value` stays an equality filter.
+//!
+//! Ordering uses `sort=-created_at,name`, where a leading `-` sorts descending.
+//!
+//! Full-text search uses `q=words` over the table's `searchColumns`, and
+//! `sort=-relevance` ranks the matches.
+//!
+//! Sparse fieldsets use `fields=id,name` for the record itself and
+//! `fields[relation]=id,total` for nested relations.
+//!
+//! Cursor pagination uses an opaque `cursor` token produced by [`encode_cursor`].
+//!
+//! Aggregations use `group_by=status&sum=total&count=*` next to the filters.
+
+use crate::database::{
+    Aggregate, AggregateFunction, Filter, FilterOperator, RELEVANCE_SORT, SortKey, TextSearch,
+};
+use crate::schema_generator::TableSchema;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+
+/// Key signing pagination cursors. Set `APIFY_CURSOR_SECRET` to keep cursors valid
+/// across restarts and between replicas; otherwise a per-process key is used.
+static CURSOR_SECRET: Lazy<Vec<u8>> = Lazy::new(|| match std::env::var("APIFY_CURSOR_SECRET") {
+    Ok(secret) if !secret.is_empty() => secret.into_bytes(),
+    _ => uuid::Uuid::new_v4().as_bytes().to_vec(),
+});
+
+/// Query parameters that control the listing itself rather than filter it
+pub const RESERVED_PARAMS: &[&str] = &[
+    "limit",
+    "offset",
+    "sort",
+    "fields",
+    "cursor",
+    "include",
+    "with_deleted",
+    "on_conflict",
+    "q",
+];
+
+/// Query parameters of an aggregation: the grouping columns and one parameter per
+/// [`AggregateFunction`]. They are only reserved on aggregate operations.
+pub const AGGREGATE_PARAMS: &[&str] = &["group_by", "count", "sum", "avg", "min", "max"];
+
+/// Build filter conditions from list query parameters.
+///
+/// When a table schema is known, columns are resolved against it (case-insensitively)
+/// and values are coerced to the column type; unknown columns are rejected.
+pub fn parse_filters(
+    params: &HashMap<String, String>,
+    schema: Option<&TableSchema>,
+) -> Result<Vec<Filter>, String> {
+    let mut keys: Vec<&String> = params
+        .keys()
+        .filter(|k| !RESERVED_PARAMS.contains(&param_name(k)))
+        .collect();
+    keys.sort();
+
+    let mut filters = Vec::new();
+    for key in keys {
+        let raw = &params[key];
+        let (name, modifiers) = split_key(key)?;
+
+        let mut operator = FilterOperator::Eq;
+        let mut negated = false;
+        for (i, m) in modifiers.iter().enumerate() {
+            if m.eq_ignore_ascii_case("not") && i == 0 {
+                negated = true;
+                continue;
+            }
+            if i != modifiers.len() - 1 {
+                return Err(format!("Invalid filter '{}'", key));
+            }
+            operator = FilterOperator::from_name(m)
+                .ok_or_else(|| format!("Unknown filter operator '{}' in '{}'", m, key))?;
+        }
+        if negated && modifiers.len() == 1 {
+            operator = FilterOperator::Ne;
+            negated = false;
+        }
+
+        let (column, column_type) = resolve_column(name, schema, "filter")?;
+
+        let value = match operator {
+            FilterOperator::IsNull => {
+                if !raw.eq_ignore_ascii_case("null") {
+                    return Err(format!("Filter '{}' only accepts 'null'", key));
+                }
+                Value::Null
+            }
+            FilterOperator::In => Value::Array(
+                raw.split(',')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| coerce_value(s, column_type, &column))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            FilterOperator::Like | FilterOperator::ILike => Value::String(raw.clone()),
+            _ => coerce_value(raw, column_type, &column)?,
+        };
+        if let Value::Array(items) = &value
+            && items.is_empty()
+        {
+            return Err(format!("Filter '{}' requires at least one value", key));
+        }
+
+        filters.push(Filter {
+            column,
+            operator: if negated { operator.negate() } else { operator },
+            value,
+        });
+    }
+    Ok(filters)
+}
+
+/// Build the ORDER BY terms for a `sort=-created_at,name` parameter
+pub fn parse_sort(raw: &str, schema: Option<&TableSchema>) -> Result<Vec<SortKey>, String> {
+    let mut keys = Vec::new();
+    for part in split_list(raw) {
+        let (name, descending) = match part.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (part.strip_prefix('+').unwrap_or(part), false),
+        };
+        if !is_identifier(name) {
+            return Err(format!("Invalid sort column '{}'", name));
+        }
+        let (column, _) = match resolve_column(name, schema, "sort") {
+            Err(_) if is_relevance(name, schema) => (RELEVANCE_SORT.to_string(), None),
+            resolved => resolved?,
+        };
+        if keys.iter().any(|k: &SortKey| k.column == column) {
+            return Err(format!("Duplicate sort column '{}'", column));
+        }
+        keys.push(SortKey { column, descending });
+    }
+    Ok(keys)
+}
+
+/// Columns requested through `fields` / `fields[relation]`, and relations through `include`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldSelection {
+    /// Columns and relation names of the record itself (None = everything)
+    pub root: Option<Vec<String>>,
+    /// Columns per relation field name
+    pub relations: HashMap<String, Vec<String>>,
+    /// Relations to expand (None = every relation, one level deep)
+    pub include: Option<Include>,
+}
+
+/// Relation tree requested through `include=items,items.product`, keyed by field name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Include {
+    pub relations: HashMap<String, Include>,
+}
+
+impl FieldSelection {
+    /// Whether a relation should be loaded into the response
+    pub fn includes_relation(&self, field_name: &str) -> bool {
+        if let Some(include) = &self.include {
+            return include.relations.contains_key(field_name);
+        }
+        match &self.root {
+            None => true,
+            Some(root) => {
+                root.iter().any(|f| f == field_name) || self.relations.contains_key(field_name)
+            }
+        }
+    }
+
+    /// Columns to project for a relation (None = all)
+    pub fn relation_columns(&self, field_name: &str) -> Option<Vec<String>> {
+        self.relations.get(field_name).cloned()
+    }
+
+    /// Drop keys that were not requested from a top-level record
+    pub fn retain_root(&self, record: &mut Value) {
+        let (Some(root), Some(obj)) = (&self.root, record.as_object_mut()) else {
+            return;
+        };
+        obj.retain(|key, _| {
+            root.iter().any(|f| f.eq_ignore_ascii_case(key))
+                || self.relations.contains_key(key)
+                || self
+                    .include
+                    .as_ref()
+                    .is_some_and(|include| include.relations.contains_key(key))
+        });
+    }
+
+    /// Selection applied to the rows loaded for a relation: its `fields[relation]`
+    /// columns and the part of the include tree below it
+    pub fn nested(&self, field_name: &str) -> FieldSelection {
+        let include = self
+            .include
+            .as_ref()
+            .and_then(|include| include.relations.get(field_name))
+            .cloned()
+            .unwrap_or_default();
+        FieldSelection {
+            root: self.relation_columns(field_name),
+            relations: HashMap::new(),
+            include: Some(include),
+        }
+    }
+}
+
+/// Parse `include=items,items.product,customer` into a relation tree.
+///
+/// Each dotted path must follow relations declared on the tables it walks through,
+/// looked up with `target_schema`, and may be at most `max_depth` relations long.
+pub fn parse_include<'s>(
+    raw: &str,
+    schema: Option<&'s TableSchema>,
+    target_schema: impl Fn(&str) -> Option<&'s TableSchema>,
+    max_depth: usize,
+) -> Result<Include, String> {
+    let mut include = Include::default();
+    for path in split_list(raw) {
+        let segments: Vec<&str> = path.split('.').collect();
+        if segments.len() > max_depth {
+            return Err(format!(
+                "Include '{}' exceeds the maximum depth of {}",
+                path, max_depth
+            ));
+        }
+        let mut node = &mut include;
+        let mut current = schema;
+        for segment in segments {
+            let relation = current
+                .and_then(|s| s.relations.iter().find(|r| r.field_name == segment))
+                .ok_or_else(|| format!("Unknown relation '{}' in include '{}'", segment, path))?;
+            current = target_schema(&relation.target_table);
+            node = node
+                .relations
+                .entry(relation.field_name.clone())
+                .or_default();
+        }
+    }
+    Ok(include)
+}
+
+/// Parse `fields=` and `fields[relation]=` parameters.
+///
+/// Root fields may name columns or relations of `schema`; relation fields are checked
+/// against the target table, looked up through `target_schema`.
+pub fn parse_fields<'s>(
+    params: &HashMap<String, String>,
+    schema: Option<&'s TableSchema>,
+    target_schema: impl Fn(&str) -> Option<&'s TableSchema>,
+) -> Result<FieldSelection, String> {
+    let mut selection = FieldSelection::default();
+    for (key, raw) in params {
+        if param_name(key) != "fields" {
+            continue;
+        }
+        let (_, modifiers) = split_key(key)?;
+        match modifiers.as_slice() {
+            [] => {
+                let mut root = Vec::new();
+                for name in split_list(raw) {
+                    let is_relation = schema
+                        .map(|s| s.relations.iter().any(|r| r.field_name == name))
+                        .unwrap_or(false);
+                    if is_relation {
+                        root.push(name.to_string());
+                    } else {
+                        root.push(resolve_field(name, schema)?);
+                    }
+                }
+                selection.root = Some(root);
+            }
+            [relation] => {
+                let rel = schema
+                    .and_then(|s| s.relations.iter().find(|r| r.field_name == *relation))
+                    .ok_or_else(|| format!("Unknown relation '{}' in '{}'", relation, key))?;
+                let target = target_schema(&rel.target_table);
+                let columns = split_list(raw)
+                    .map(|name| resolve_field(name, target))
+                    .collect::<Result<Vec<_>, _>>()?;
+                selection.relations.insert(rel.field_name.clone(), columns);
+            }
+            _ => return Err(format!("Invalid fields parameter '{}'", key)),
+        }
+    }
+    Ok(selection)
+}
+
+/// Build the groups and aggregates of `group_by=status&sum=total&count=*`.
+/// `count=*` counts rows; without any aggregate parameter the rows are counted too.
+pub fn parse_aggregates(
+    params: &HashMap<String, String>,
+    schema: Option<&TableSchema>,
+) -> Result<(Vec<String>, Vec<Aggregate>), String> {
+    let mut group_by: Vec<String> = Vec::new();
+    for name in params
+        .get("group_by")
+        .map(|raw| split_list(raw))
+        .into_iter()
+        .flatten()
+    {
+        if !is_identifier(name) {
+            return Err(format!("Invalid group_by column '{}'", name));
+        }
+        let (column, _) = resolve_column(name, schema, "group_by")?;
+        if group_by.contains(&column) {
+            return Err(format!("Duplicate group_by column '{}'", column));
+        }
+        group_by.push(column);
+    }
+
+    let mut aggregates: Vec<Aggregate> = Vec::new();
+    for function in AggregateFunction::ALL {
+        for name in params
+            .get(function.name())
+            .map(|raw| split_list(raw))
+            .into_iter()
+            .flatten()
+        {
+            let aggregate = if name == "*" && function == AggregateFunction::Count {
+                Aggregate {
+                    function,
+                    column: None,
+                    integer: true,
+                }
+            } else {
+                if !is_identifier(name) {
+                    return Err(format!("Invalid {} column '{}'", function.name(), name));
+                }
+                let (column, column_type) = resolve_column(name, schema, function.name())?;
+                let numeric = column_type.is_none_or(is_numeric_type);
+                if matches!(function, AggregateFunction::Sum | AggregateFunction::Avg) && !numeric {
+                    return Err(format!(
+                        "Cannot compute {} of non-numeric column '{}'",
+                        function.name(),
+                        column
+                    ));
+                }
+                Aggregate {
+                    function,
+                    column: Some(column),
+                    integer: column_type.is_some_and(is_integer_type),
+                }
+            };
+            aggregates.push(aggregate);
+        }
+    }
+    if aggregates.is_empty() {
+        aggregates.push(Aggregate {
+            function: AggregateFunction::Count,
+            column: None,
+            integer: true,
+        });
+    }
+
+    let mut names: Vec<String> = group_by.clone();
+    for aggregate in &aggregates {
+        let alias = aggregate.alias();
+        if names.iter().any(|n| n.eq_ignore_ascii_case(&alias)) {
+            return Err(format!("Duplicate aggregate '{}'", alias));
+        }
+        names.push(alias);
+    }
+    Ok((group_by, aggregates))
+}
+
+/// Whether a sort name means search relevance: the table is searchable and has no
+/// column of that name
+fn is_relevance(name: &str, schema: Option<&TableSchema>) -> bool {
+    name.eq_ignore_ascii_case(RELEVANCE_SORT)
+        && schema.is_some_and(|s| !s.search_columns.is_empty())
+}
+
+/// Build the full-text search of a `q` parameter over the table's `searchColumns`.
+/// A query without any word is no search at all.
+pub fn parse_search(
+    raw: Option<&str>,
+    schema: Option<&TableSchema>,
+) -> Result<Option<TextSearch>, String> {
+    let Some(raw) = raw else {
+        return Ok(None);
+    };
+    let columns = schema
+        .map(|s| s.search_columns.clone())
+        .filter(|columns| !columns.is_empty())
+        .ok_or("Full-text search is not enabled for this table")?;
+    let search = TextSearch::new(columns, raw);
+    Ok((!search.terms.is_empty()).then_some(search))
+}
+
+fn resolve_field(name: &str, schema: Option<&TableSchema>) -> Result<String, String> {
+    if !is_identifier(name) {
+        return Err(format!("Invalid field '{}'", name));
+    }
+    resolve_column(name, schema, "field").map(|(column, _)| column)
+}
+
+fn split_list(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split(',').map(str::trim).filter(|p| !p.is_empty())
+}
+
+/// Name of a query parameter without its bracketed modifiers
+fn param_name(key: &str) -> &str {
+    key.split('[').next().unwrap_or(key)
+}
+
+/// Ordering for a list request: the requested `sort`, else best matches first when
+/// searching, else the schema's `defaultSort`, followed by the primary key as a
+/// tie-breaker so pagination is deterministic.
+pub fn list_sort(
+    requested: Option<&str>,
+    schema: Option<&TableSchema>,
+    search: Option<&TextSearch>,
+) -> Result<Vec<SortKey>, String> {
+    let default = match search {
+        Some(_) => Some("-relevance"),
+        None => schema.and_then(|s| s.default_sort.as_deref()),
+    };
+    let mut keys = match requested.or(default) {
+        Some(raw) => parse_sort(raw, schema)?,
+        None => Vec::new(),
+    };
+    if search.is_none() && keys.iter().any(|k| k.column == RELEVANCE_SORT) {
+        return Err("Sorting by relevance requires a q search".to_string());
+    }
+    if let Some(schema) = schema {
+        for pk in schema.columns.iter().filter(|c| c.primary_key) {
+            if !keys.iter().any(|k| k.column == pk.name) {
+                keys.push(SortKey {
+                    column: pk.name.clone(),
+                    descending: false,
+                });
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Columns ordering a cursor-paginated list: the schema's `cursorColumn` (if any)
+/// followed by the primary key. Returns the columns and whether they sort descending.
+/// The cursor column must be NOT NULL, as rows after a cursor are found by comparison.
+pub fn keyset_order(schema: Option<&TableSchema>) -> Result<(Vec<String>, bool), String> {
+    let schema = schema.ok_or("Cursor pagination requires a table schema")?;
+    let mut columns = Vec::new();
+    let mut descending = false;
+    if let Some(raw) = schema.cursor_column.as_deref() {
+        let (name, desc) = match raw.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (raw, false),
+        };
+        let (column, _) = resolve_column(name, Some(schema), "cursor")?;
+        if schema
+            .columns
+            .iter()
+            .any(|c| c.name == column && c.nullable)
+        {
+            return Err(format!(
+                "cursorColumn '{}' of '{}' must be NOT NULL",
+                column, schema.table_name
+            ));
+        }
+        columns.push(column);
+        descending = desc;
+    }
+    for pk in schema.columns.iter().filter(|c| c.primary_key) {
+        if !columns.contains(&pk.name) {
+            columns.push(pk.name.clone());
+        }
+    }
+    if columns.len() == usize::from(schema.cursor_column.is_some()) {
+        return Err(format!(
+            "Cursor pagination requires a primary key on '{}'",
+            schema.table_name
+        ));
+    }
+    Ok((columns, descending))
+}
+
+/// Encode the ordering values of the last row of a page as an opaque, signed cursor.
+/// `scope` ties the cursor to the table and filters of the request that produced it.
+pub fn encode_cursor(values: &[Value], scope: &str) -> String {
+    let payload = Value::Array(values.to_vec()).to_string();
+    format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(&payload),
+        URL_SAFE_NO_PAD.encode(cursor_signature(&payload, scope))
+    )
+}
+
+/// Verify and decode a cursor from [`encode_cursor`] issued for the same `scope`
+pub fn decode_cursor(cursor: &str, scope: &str, expected_len: usize) -> Result<Vec<Value>, String> {
+    let invalid = || "Invalid or expired cursor".to_string();
+    let (payload, signature) = cursor.split_once('.').ok_or_else(invalid)?;
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .ok()
+        .and_then(|p| String::from_utf8(p).ok())
+        .ok_or_else(invalid)?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+
+    cursor_mac(&payload, scope)
+        .verify_slice(&signature)
+        .map_err(|_| invalid())?;
+
+    match serde_json::from_str::<Value>(&payload) {
+        Ok(Value::Array(values)) if values.len() == expected_len => Ok(values),
+        _ => Err(invalid()),
+    }
+}
+
+/// Scope string binding a cursor to a table, its filter conditions and search terms
+pub fn cursor_scope(table: &str, filters: &[Filter], search: Option<&TextSearch>) -> String {
+    let mut scope = table.to_string();
+    for f in filters {
+        scope.push_str(&format!("|{}:{:?}:{}", f.column, f.operator, f.value));
+    }
+    if let Some(search) = search {
+        scope.push_str(&format!("|q:{}", search.terms.join(" ")));
+    }
+    scope
+}
+
+fn cursor_mac(payload: &str, scope: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(&CURSOR_SECRET).expect("HMAC accepts keys of any size");
+    mac.update(scope.as_bytes());
+    mac.update(b"\n");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+fn cursor_signature(payload: &str, scope: &str) -> Vec<u8> {
+    cursor_mac(payload, scope).finalize().into_bytes().to_vec()
+}
+
+/// Map a requested column to its schema name and SQL type.
+/// Without a schema the name is used as-is.
+fn resolve_column<'s>(
+    name: &str,
+    schema: Option<&'s TableSchema>,
+    usage: &str,
+) -> Result<(String, Option<&'s str>), String> {
+    match schema {
+        Some(schema) => {
+            let col = schema
+                .columns
+                .iter()
+                .find(|c| c.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("Unknown {} column '{}'", usage, name))?;
+            Ok((col.name.clone(), Some(col.column_type.as_str())))
+        }
+        None => Ok((name.to_string(), None)),
+    }
+}
+
+/// Split `name[op1][op2]` into the column name and its bracketed modifiers
+fn split_key(key: &str) -> Result<(&str, Vec<&str>), String> {
+    let (name, mut rest) = match key.find('[') {
+        Some(pos) => (&key[..pos], &key[pos..]),
+        None => (key, ""),
+    };
+    if !is_identifier(name) {
+        return Err(format!("Invalid filter column '{}'", name));
+    }
+    let mut modifiers = Vec::new();
+    while !rest.is_empty() {
+        let inner = rest
+            .strip_prefix('[')
+            .and_then(|r| r.split_once(']'))
+            .ok_or_else(|| format!("Invalid filter '{}'", key))?;
+        modifiers.push(inner.0);
+        rest = inner.1;
+    }
+    if modifiers.len() > 2 {
+        return Err(format!("Invalid filter '{}'", key));
+    }
+    Ok((name, modifiers))
+}
+
+/// Column names are spliced into SQL, so only plain identifiers are accepted
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Convert a raw query value to the JSON type matching the column's SQL type.
+/// Without a known type the value is kept as a string.
+fn coerce_value(raw: &str, column_type: Option<&str>, column: &str) -> Result<Value, String> {
+    let Some(ty) = column_type.map(|t| t.to_uppercase()) else {
+        return Ok(Value::String(raw.to_string()));
+    };
+    // Text columns can hold the string "null"; `[is]=null` matches NULL there
+    if raw.eq_ignore_ascii_case("null") && !is_text_type(&ty) {
+        return Ok(Value::Null);
+    }
+    let invalid = || format!("Invalid value '{}' for column '{}'", raw, column);
+    if is_integer_type(&ty) {
+        return raw
+            .parse::<i64>()
+            .map(|i| Value::Number(i.into()))
+            .map_err(|_| invalid());
+    }
+    if is_numeric_type(&ty) {
+        return raw
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(invalid);
+    }
+    if ty.contains("BOOL") {
+        return match raw.to_ascii_lowercase().as_str() {
+            "true" | "1" => Ok(Value::Bool(true)),
+            "false" | "0" => Ok(Value::Bool(false)),
+            _ => Err(invalid()),
+        };
+    }
+    Ok(Value::String(raw.to_string()))
+}
+
+fn is_integer_type(column_type: &str) -> bool {
+    let ty = column_type.to_uppercase();
+    ty.contains("INT") || ty == "SERIAL" || ty == "BIGSERIAL"
+}
+
+fn is_text_type(column_type: &str) -> bool {
+    let ty = column_type.to_uppercase();
+    ["CHAR", "TEXT", "CLOB", "STRING", "UUID"]
+        .iter()
+        .any(|t| ty.contains(t))
+}
+
+fn is_numeric_type(column_type: &str) -> bool {
+    let ty = column_type.to_uppercase();
+    is_integer_type(&ty)
+        || ["REAL", "FLOAT", "DOUBLE", "NUMERIC", "DECIMAL"]
+            .iter()
+            .any(|t| ty.contains(t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema_generator::{ColumnDefinition, DropPolicy};
+
+    fn column(name: &str, column_type: &str) -> ColumnDefinition {
+        ColumnDefinition {
+            name: name.to_string(),
+            column_type: column_type.to_string(),
+            nullable: true,
+            primary_key: false,
+            unique: false,
+            auto_increment: false,
+            default_value: None,
+            auto_field: false,
+            renamed_from: None,
+        }
+    }
+
+    fn users_schema() -> TableSchema {
+        TableSchema {
+            table_name: "users".to_string(),
+            columns: vec![
+                column("id", "INTEGER"),
+                column("name", "TEXT"),
+                column("age", "INTEGER"),
+                column("status", "TEXT"),
+                column("deleted_at", "TEXT"),
+            ],
+            indexes: vec![],
+            relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
+        }
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_operators() {
+        let schema = users_schema();
+        let filters = parse_filters(
+            &params(&[
+                ("age[gte]", "18"),
+                ("name[like]", "Al%"),
+                ("status[in]", "a,b"),
+                ("deleted_at[is]", "null"),
+                ("limit", "10"),
+            ]),
+            Some(&schema),
+        )
+        .unwrap();
+
+        assert_eq!(filters.len(), 4);
+        assert_eq!(filters[0].column, "age");
+        assert_eq!(filters[0].operator, FilterOperator::Gte);
+        assert_eq!(filters[0].value, Value::from(18));
+        assert_eq!(filters[1].operator, FilterOperator::IsNull);
+        assert_eq!(filters[2].operator, FilterOperator::Like);
+        assert_eq!(filters[2].value, Value::from("Al%"));
+        assert_eq!(filters[3].operator, FilterOperator::In);
+        assert_eq!(filters[3].value, serde_json::json!(["a", "b"]));
+    }
+
+    #[test]
+    fn test_parse_negation() {
+        let schema = users_schema();
+        let filters = parse_filters(
+            &params(&[
+                ("status[not]", "a"),
+                ("age[not][in]", "1,2"),
+                ("deleted_at[not][is]", "null"),
+            ]),
+            Some(&schema),
+        )
+        .unwrap();
+
+        assert_eq!(filters[0].operator, FilterOperator::NotIn);
+        assert_eq!(filters[0].value, serde_json::json!([1, 2]));
+        assert_eq!(filters[1].operator, FilterOperator::IsNotNull);
+        assert_eq!(filters[2].operator, FilterOperator::Ne);
+    }
+
+    #[test]
+    fn test_null_literal_on_text_column() {
+        let schema = users_schema();
+        let filters =
+            parse_filters(&params(&[("name", "null"), ("age", "null")]), Some(&schema)).unwrap();
+
+        assert_eq!(filters[0].column, "age");
+        assert_eq!(filters[0].value, Value::Null);
+        assert_eq!(filters[1].column, "name");
+        assert_eq!(filters[1].value, Value::from("null"));
+    }
+
+    #[test]
+    fn test_reject_invalid_filters() {
+        let schema = users_schema();
+        for (key, value) in [
+            ("nope", "1"),
+            ("age[between]", "1"),
+            ("age[gte]", "abc"),
+            ("deleted_at[is]", "yes"),
+            ("age;drop[eq]", "1"),
+            ("age[gte", "1"),
+        ] {
+            assert!(
+                parse_filters(&params(&[(key, value)]), Some(&schema)).is_err(),
+                "{key}={value} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        let schema = users_schema();
+        let keys = parse_sort("-age, Name", Some(&schema)).unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                SortKey {
+                    column: "age".to_string(),
+                    descending: true
+                },
+                SortKey {
+                    column: "name".to_string(),
+                    descending: false
+                },
+            ]
+        );
+        assert!(parse_sort("-password", Some(&schema)).is_err());
+        assert!(parse_sort("age,-age", Some(&schema)).is_err());
+        assert!(parse_sort("age desc", None).is_err());
+    }
+
+    #[test]
+    fn test_parse_aggregates() {
+        let schema = users_schema();
+        let (group_by, aggregates) = parse_aggregates(
+            &params(&[("group_by", "Status"), ("sum", "age"), ("count", "*,name")]),
+            Some(&schema),
+        )
+        .unwrap();
+        assert_eq!(group_by, vec!["status"]);
+        let aliases: Vec<String> = aggregates.iter().map(|a| a.alias()).collect();
+        assert_eq!(aliases, vec!["count", "count_name", "sum_age"]);
+        assert_eq!(aggregates[0].expression(), "COUNT(*)");
+        assert!(aggregates[2].integer);
+
+        // Rows are counted when no aggregate is requested
+        let (_, aggregates) = parse_aggregates(&params(&[]), Some(&schema)).unwrap();
+        assert_eq!(aggregates[0].alias(), "count");
+
+        for (key, value) in [
+            ("sum", "name"),
+            ("avg", "*"),
+            ("max", "password"),
+            ("group_by", "status,status"),
+            ("min", "age,age"),
+        ] {
+            assert!(
+                parse_aggregates(&params(&[(key, value)]), Some(&schema)).is_err(),
+                "{key}={value} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_relevance_sort() {
+        let mut schema = users_schema();
+        // Tables without search columns have no relevance
+        assert!(parse_sort("-relevance", Some(&schema)).is_err());
+        assert_eq!(
+            parse_search(Some("ada"), Some(&schema)),
+            Err("Full-text search is not enabled for this table".to_string())
+        );
+
+        schema.search_columns = vec!["name".to_string()];
+        let search = parse_search(Some("Ada  Love-"), Some(&schema))
+            .unwrap()
+            .unwrap();
+        assert_eq!(search.terms, vec!["ada", "love"]);
+        assert_eq!(parse_search(Some(" - "), Some(&schema)), Ok(None));
+
+        // Searching ranks by relevance unless another order is requested
+        let keys = list_sort(None, Some(&schema), Some(&search)).unwrap();
+        assert_eq!(keys[0].column, RELEVANCE_SORT);
+        assert!(keys[0].descending);
+        let keys = list_sort(Some("name"), Some(&schema), Some(&search)).unwrap();
+        assert_eq!(keys[0].column, "name");
+        assert!(list_sort(Some("-relevance"), Some(&schema), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        let mut orders = users_schema();
+        orders.table_name = "orders".to_string();
+        orders
+            .relations
+            .push(crate::schema_generator::RelationDefinition {
+                field_name: "owner".to_string(),
+                relation_type: crate::schema_generator::RelationType::BelongsTo,
+                target_table: "users".to_string(),
+                foreign_key: "id".to_string(),
+                local_key: None,
+                through: None,
+                other_key: None,
+                on_delete: None,
+            });
+        let users = users_schema();
+        let lookup = |table: &str| (table == "users").then_some(&users);
+
+        let selection = parse_fields(
+            &params(&[
+                ("fields", "ID,name"),
+                ("fields[owner]", "name"),
+                ("age", "3"),
+            ]),
+            Some(&orders),
+            lookup,
+        )
+        .unwrap();
+        assert_eq!(
+            selection.root,
+            Some(vec!["id".to_string(), "name".to_string()])
+        );
+        assert_eq!(
+            selection.relation_columns("owner"),
+            Some(vec!["name".to_string()])
+        );
+        assert!(selection.includes_relation("owner"));
+
+        let mut record = serde_json::json!({"id": 1, "name": "a", "age": 3, "owner": {}});
+        selection.retain_root(&mut record);
+        assert_eq!(
+            record,
+            serde_json::json!({"id": 1, "name": "a", "owner": {}})
+        );
+
+        assert!(parse_fields(&params(&[("fields", "secret")]), Some(&orders), lookup).is_err());
+        assert!(parse_fields(&params(&[("fields[nope]", "id")]), Some(&orders), lookup).is_err());
+        assert!(
+            parse_fields(
+                &params(&[("fields[owner]", "secret")]),
+                Some(&orders),
+                lookup
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_include() {
+        let relation = |field: &str, target: &str| crate::schema_generator::RelationDefinition {
+            field_name: field.to_string(),
+            relation_type: crate::schema_generator::RelationType::HasMany,
+            target_table: target.to_string(),
+            foreign_key: "order_id".to_string(),
+            local_key: None,
+            through: None,
+            other_key: None,
+            on_delete: None,
+        };
+        let mut orders = users_schema();
+        orders.table_name = "orders".to_string();
+        orders.relations.push(relation("items", "order_items"));
+        let mut items = users_schema();
+        items.table_name = "order_items".to_string();
+        items.relations.push(relation("product", "products"));
+        let lookup = |table: &str| (table == "order_items").then_some(&items);
+
+        let include = parse_include("items.product, items", Some(&orders), lookup, 3).unwrap();
+        let items_node = &include.relations["items"];
+        assert_eq!(include.relations.len(), 1);
+        assert!(items_node.relations["product"].relations.is_empty());
+
+        let selection = FieldSelection {
+            include: Some(include.clone()),
+            ..Default::default()
+        };
+        assert!(selection.includes_relation("items"));
+        assert!(!selection.includes_relation("customer"));
+        assert_eq!(selection.nested("items").include.as_ref(), Some(items_node));
+
+        assert!(parse_include("items.product", Some(&orders), lookup, 1).is_err());
+        assert!(parse_include("customer", Some(&orders), lookup, 3).is_err());
+        assert!(parse_include("items.nope", Some(&orders), lookup, 3).is_err());
+        assert_eq!(
+            parse_include("", Some(&orders), lookup, 3).unwrap(),
+            Include::default()
+        );
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let values = vec![Value::from("2024-01-01"), Value::from(42)];
+        let cursor = encode_cursor(&values, "users");
+        assert_eq!(decode_cursor(&cursor, "users", 2).unwrap(), values);
+
+        // Reused with other filters, tampered with or truncated
+        assert!(decode_cursor(&cursor, "users|age:Gte:18", 2).is_err());
+        let (payload, sig) = cursor.split_once('.').unwrap();
+        let forged = URL_SAFE_NO_PAD.encode(r#"["2024-01-01",1]"#);
+        assert!(decode_cursor(&format!("{forged}.{sig}"), "users", 2).is_err());
+        assert!(decode_cursor(payload, "users", 2).is_err());
+        assert!(decode_cursor(&cursor, "users", 1).is_err());
+    }
+
+    #[test]
+    fn test_keyset_order() {
+        let mut schema = users_schema();
+        schema.columns[0].primary_key = true;
+        assert_eq!(
+            keyset_order(Some(&schema)).unwrap(),
+            (vec!["id".to_string()], false)
+        );
+        schema.cursor_column = Some("-Age".to_string());
+        // Rows with a NULL cursor value could never be reached
+        assert!(keyset_order(Some(&schema)).is_err());
+        schema
+            .columns
+            .iter_mut()
+            .find(|c| c.name == "age")
+            .unwrap()
+            .nullable = false;
+        assert_eq!(
+            keyset_order(Some(&schema)).unwrap(),
+            (vec!["age".to_string(), "id".to_string()], true)
+        );
+        schema.columns[0].primary_key = false;
+        assert!(keyset_order(Some(&schema)).is_err());
+        assert!(keyset_order(None).is_err());
+    }
+
+    #[test]
+    fn test_without_schema_keeps_strings() {
+        let filters = parse_filters(&params(&[("age[gt]", "5")]), None).unwrap();
+        assert_eq!(filters[0].column, "age");
+        assert_eq!(filters[0].value, Value::from("5"));
+    }
+}
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 
 /// Table schema definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +15,64 @@ pub struct TableSchema {
     pub indexes: Vec<IndexDefinition>,
     #[serde(default)]
     pub relations: Vec<RelationDefinition>,
+    #[serde(default)]
+    #[serde(alias = "default_sort")]
+    pub default_sort: Option<String>, // Default list ordering (e.g., "-created_at,name")
+    #[serde(default)]
+    #[serde(alias = "cursor_column")]
+    pub cursor_column: Option<String>, // Ordering column for cursor pagination (e.g., "-created_at")
+    #[serde(default)]
+    #[serde(alias = "soft_delete")]
+    pub soft_delete: Option<String>, // Timestamp column marking deleted rows (e.g., "deleted_at")
+    #[serde(default)]
+    #[serde(alias = "version_column")]
+    pub version_column: Option<String>, // Row version bumped on every update (e.g., "version")
+    #[serde(default)]
+    #[serde(alias = "search_columns")]
+    pub search_columns: Vec<String>, // Text columns matched by the `q` list parameter
+    #[serde(default)]
+    #[serde(alias = "drop_columns")]
+    pub drop_columns: DropPolicy, // What migrations do with columns no longer declared
+    #[serde(default)]
+    #[serde(alias = "foreign_keys")]
+    pub foreign_keys: Vec<ForeignKeyDefinition>, // Constraints derived from relations
+    #[serde(default)]
+    pub checks: Vec<CheckDefinition>, // CHECK constraints (enums, ranges)
+}
+
+/// What migrations do with existing columns that are no longer declared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DropPolicy {
+    /// Leave them in place, dropping their `NOT NULL` constraint when they have no default
+    #[default]
+    Keep,
+    /// Drop them with their data
+    Drop,
+    /// Refuse the migration
+    Fail,
+}
+
+impl TableSchema {
+    /// Primary key columns in declaration order (`id` when none is declared)
+    pub fn primary_key(&self) -> Vec<String> {
+        let keys: Vec<String> = self
+            .columns
+            .iter()
+            .filter(|c| c.primary_key)
+            .map(|c| c.name.clone())
+            .collect();
+        if keys.is_empty() {
+            vec!["id".to_string()]
+        } else {
+            keys
+        }
+    }
+
+    /// Whether the primary key spans several columns
+    pub fn has_composite_key(&self) -> bool {
+        self.columns.iter().filter(|c| c.primary_key).count() > 1
+    }
 }
 
 /// Relation definition for nested object support
@@ -30,6 +89,97 @@ pub struct RelationDefinition {
     pub foreign_key: String, // Foreign key column name
     #[serde(alias = "local_key")]
     pub local_key: Option<String>, // Local key (default: "id")
+    #[serde(default)]
+    pub through: Option<String>, // Join table of a belongsToMany relation (e.g., "post_tags")
+    #[serde(default)]
+    #[serde(alias = "other_key")]
+    pub other_key: Option<String>, // Join table column referencing the target (e.g., "tag_id")
+    #[serde(default)]
+    #[serde(alias = "on_delete")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_delete: Option<OnDelete>, // What deleting the parent row does to related rows
+}
+
+impl RelationDefinition {
+    /// Action applied to related rows when the parent row is deleted: related rows of
+    /// `hasMany`/`hasOne` relations are deleted unless `onDelete` says otherwise,
+    /// and `belongsTo` parents with children cannot be deleted
+    pub fn delete_action(&self) -> OnDelete {
+        self.on_delete.unwrap_or(match self.relation_type {
+            RelationType::HasMany | RelationType::HasOne | RelationType::BelongsToMany => {
+                OnDelete::Cascade
+            }
+            RelationType::BelongsTo => OnDelete::Restrict,
+        })
+    }
+}
+
+/// Referential action of a foreign key when the referenced row is deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnDelete {
+    /// Delete the referencing rows
+    Cascade,
+    /// Refuse to delete referenced rows
+    #[default]
+    Restrict,
+    /// Set the referencing column to `NULL`
+    #[serde(rename = "set null", alias = "setNull", alias = "set_null")]
+    SetNull,
+}
+
+impl OnDelete {
+    /// `ON DELETE` clause keyword
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            OnDelete::Cascade => "CASCADE",
+            OnDelete::Restrict => "RESTRICT",
+            OnDelete::SetNull => "SET NULL",
+        }
+    }
+
+    /// Parse a delete rule as reported by the database (`NO ACTION` counts as restrict)
+    pub fn from_sql(rule: &str) -> Self {
+        match rule.trim().to_ascii_uppercase().as_str() {
+            "CASCADE" | "C" => OnDelete::Cascade,
+            "SET NULL" | "N" => OnDelete::SetNull,
+            _ => OnDelete::Restrict,
+        }
+    }
+}
+
+/// Foreign key constraint of a single column
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ForeignKeyDefinition {
+    pub name: String,
+    pub column: String,
+    #[serde(alias = "references_table")]
+    pub references_table: String,
+    #[serde(alias = "references_column")]
+    pub references_column: String,
+    #[serde(default)]
+    #[serde(alias = "on_delete")]
+    pub on_delete: OnDelete,
+}
+
+impl ForeignKeyDefinition {
+    /// Whether both constraints enforce the same reference, whatever their names
+    pub fn same_constraint(&self, other: &ForeignKeyDefinition) -> bool {
+        self.column == other.column
+            && self.references_table == other.references_table
+            && self.references_column == other.references_column
+            && self.on_delete == other.on_delete
+    }
+}
+
+/// `CHECK` constraint; unnamed checks are named after a hash of their expression
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckDefinition {
+    #[serde(default)]
+    pub name: String,
+    pub expression: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -38,7 +188,7 @@ pub enum RelationType {
     HasMany,
     BelongsTo,
     HasOne,
-    BelongsToMany, // For future many-to-many support
+    BelongsToMany, // Many-to-many through a join table
 }
 
 /// Column definition
@@ -63,6 +213,10 @@ pub struct ColumnDefinition {
     #[serde(default)]
     #[serde(alias = "auto_field")]
     pub auto_field: bool, // For audit fields like createdBy, updatedBy
+    #[serde(default)]
+    #[serde(alias = "renamed_from")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>, // Previous name of the column, renamed by migrations
 }
 
 /// Index definition
@@ -77,6 +231,10 @@ pub struct IndexDefinition {
 
 pub struct SchemaGenerator;
 
+/// Text search configuration of Postgres search indexes: words are lowercased but
+/// not stemmed, which suits names, emails and codes
+pub const SEARCH_CONFIG_POSTGRES: &str = "simple";
+
 impl SchemaGenerator {
     /// Extract table schemas from OpenAPI specification
     pub fn extract_schemas_from_openapi(
@@ -130,19 +288,359 @@ impl SchemaGenerator {
 
         // Fallback: derive from components.schemas if no explicit schemas found
         if schemas.is_empty()
-            && let Some(derived) = Self::derive_from_components(spec)
+            && let Some(mut derived) = Self::derive_from_components(spec)
             && !derived.is_empty()
         {
             tracing::info!(
                 derived_count = derived.len(),
                 "Derived schemas from components"
             );
+            Self::add_join_tables(&mut derived);
+            Self::add_managed_columns(&mut derived);
+            Self::add_foreign_keys(&mut derived);
+            Self::name_checks(&mut derived);
             return Ok(derived);
         }
 
+        Self::add_join_tables(&mut schemas);
+        Self::add_managed_columns(&mut schemas);
+        Self::add_foreign_keys(&mut schemas);
+        Self::name_checks(&mut schemas);
         Ok(schemas)
     }
 
+    /// Declare the `softDelete` column (a nullable timestamp, indexed) and the
+    /// `versionColumn` (an integer starting at 1) of tables that do not list them,
+    /// and drop `searchColumns` that are not columns of the table
+    fn add_managed_columns(schemas: &mut [TableSchema]) {
+        for schema in schemas.iter_mut() {
+            let columns = &schema.columns;
+            schema.search_columns.retain(|name| {
+                let known = columns.iter().any(|c| &c.name == name);
+                if !known {
+                    tracing::warn!(
+                        table = %schema.table_name,
+                        column = %name,
+                        "Ignoring unknown search column"
+                    );
+                }
+                known
+            });
+
+            if let Some(column) = schema.version_column.clone()
+                && !schema.columns.iter().any(|c| c.name == column)
+            {
+                schema.columns.push(ColumnDefinition {
+                    name: column,
+                    column_type: "INTEGER".to_string(),
+                    nullable: false,
+                    primary_key: false,
+                    unique: false,
+                    auto_increment: false,
+                    default_value: Some("1".to_string()),
+                    auto_field: true,
+                    renamed_from: None,
+                });
+            }
+
+            let Some(column) = schema.soft_delete.clone() else {
+                continue;
+            };
+            if !schema.columns.iter().any(|c| c.name == column) {
+                schema.columns.push(ColumnDefinition {
+                    name: column.clone(),
+                    column_type: "TIMESTAMP".to_string(),
+                    nullable: true,
+                    primary_key: false,
+                    unique: false,
+                    auto_increment: false,
+                    default_value: None,
+                    auto_field: true,
+                    renamed_from: None,
+                });
+            }
+            if !schema.indexes.iter().any(|i| i.columns == [column.clone()]) {
+                schema.indexes.push(IndexDefinition {
+                    name: format!("idx_{}_{}", schema.table_name, column),
+                    columns: vec![column],
+                    unique: false,
+                });
+            }
+        }
+    }
+
+    /// `through` and `otherKey` of a belongsToMany `x-relation`
+    fn join_table_keys(
+        relation_obj: &serde_json::Map<String, Value>,
+    ) -> (Option<String>, Option<String>) {
+        let key = |name: &str| {
+            relation_obj
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+        (key("through"), key("otherKey"))
+    }
+
+    /// `onDelete` of an `x-relation`; unknown actions are ignored with a warning
+    fn relation_on_delete(
+        relation_obj: &serde_json::Map<String, Value>,
+        table: &str,
+        field: &str,
+    ) -> Option<OnDelete> {
+        let value = relation_obj.get("onDelete")?;
+        match serde_json::from_value(value.clone()) {
+            Ok(action) => Some(action),
+            Err(_) => {
+                tracing::warn!(
+                    table = %table,
+                    field = %field,
+                    on_delete = %value,
+                    "Invalid onDelete, expected cascade, restrict or set null"
+                );
+                None
+            }
+        }
+    }
+
+    /// Join table column referencing `table` (posts -> post_id)
+    fn join_table_key(table: &str) -> String {
+        format!("{}_id", table.strip_suffix('s').unwrap_or(table))
+    }
+
+    /// Add a table schema for every belongsToMany join table that is not declared
+    /// explicitly: an id, the two key columns and a unique index over the pair.
+    /// `otherKey` defaults to the target's join column (target "tags" -> "tag_id").
+    fn add_join_tables(schemas: &mut Vec<TableSchema>) {
+        let mut join_tables = Vec::new();
+        for schema in schemas.iter_mut() {
+            let table_name = schema.table_name.clone();
+            for relation in &mut schema.relations {
+                if relation.relation_type != RelationType::BelongsToMany {
+                    continue;
+                }
+                let Some(through) = relation.through.clone() else {
+                    tracing::warn!(
+                        table = %table_name,
+                        field = %relation.field_name,
+                        "belongsToMany relation without 'through' join table is ignored"
+                    );
+                    continue;
+                };
+                let other_key = relation
+                    .other_key
+                    .get_or_insert_with(|| Self::join_table_key(&relation.target_table))
+                    .clone();
+                join_tables.push((through, relation.foreign_key.clone(), other_key));
+            }
+        }
+
+        for (through, foreign_key, other_key) in join_tables {
+            if schemas.iter().any(|s| s.table_name == through) {
+                continue;
+            }
+            let key_column = |name: &str| ColumnDefinition {
+                name: name.to_string(),
+                column_type: "INTEGER".to_string(),
+                nullable: false,
+                primary_key: false,
+                unique: false,
+                auto_increment: false,
+                default_value: None,
+                auto_field: false,
+                renamed_from: None,
+            };
+            schemas.push(TableSchema {
+                table_name: through.clone(),
+                columns: vec![
+                    ColumnDefinition {
+                        primary_key: true,
+                        auto_increment: true,
+                        ..key_column("id")
+                    },
+                    key_column(&foreign_key),
+                    key_column(&other_key),
+                ],
+                indexes: vec![
+                    IndexDefinition {
+                        name: format!("uq_{}_{}_{}", through, foreign_key, other_key),
+                        columns: vec![foreign_key.clone(), other_key.clone()],
+                        unique: true,
+                    },
+                    IndexDefinition {
+                        name: format!("idx_{}_{}", through, other_key),
+                        columns: vec![other_key],
+                        unique: false,
+                    },
+                ],
+                relations: vec![],
+                default_sort: None,
+                cursor_column: None,
+                soft_delete: None,
+                version_column: None,
+                search_columns: Vec::new(),
+                drop_columns: DropPolicy::Keep,
+                foreign_keys: vec![],
+                checks: vec![],
+            });
+        }
+    }
+
+    /// Declare a foreign key for every relation between two tables of the spec, and
+    /// index foreign key columns that no index or primary key starts with. When both
+    /// sides of a relation are declared, an explicit `onDelete` wins over the default
+    /// of the `hasMany`/`hasOne` side. Join table rows are always deleted with either
+    /// linked record.
+    fn add_foreign_keys(schemas: &mut [TableSchema]) {
+        // (table, column, referenced table, referenced column, action, explicit)
+        let mut references = Vec::new();
+        for schema in schemas.iter() {
+            let local_key = |relation: &RelationDefinition| {
+                relation
+                    .local_key
+                    .clone()
+                    .unwrap_or_else(|| schema.primary_key().remove(0))
+            };
+            for relation in &schema.relations {
+                let explicit = relation.on_delete.is_some();
+                let action = relation.delete_action();
+                match relation.relation_type {
+                    RelationType::HasMany | RelationType::HasOne => references.push((
+                        relation.target_table.clone(),
+                        relation.foreign_key.clone(),
+                        schema.table_name.clone(),
+                        local_key(relation),
+                        action,
+                        explicit,
+                    )),
+                    RelationType::BelongsTo => {
+                        let Some(target) = schemas
+                            .iter()
+                            .find(|s| s.table_name == relation.target_table)
+                        else {
+                            continue;
+                        };
+                        references.push((
+                            schema.table_name.clone(),
+                            relation.foreign_key.clone(),
+                            target.table_name.clone(),
+                            target.primary_key().remove(0),
+                            action,
+                            explicit,
+                        ));
+                    }
+                    RelationType::BelongsToMany => {
+                        let (Some(through), Some(other_key)) =
+                            (&relation.through, &relation.other_key)
+                        else {
+                            continue;
+                        };
+                        references.push((
+                            through.clone(),
+                            relation.foreign_key.clone(),
+                            schema.table_name.clone(),
+                            local_key(relation),
+                            OnDelete::Cascade,
+                            true,
+                        ));
+                        if let Some(target) = schemas
+                            .iter()
+                            .find(|s| s.table_name == relation.target_table)
+                        {
+                            references.push((
+                                through.clone(),
+                                other_key.clone(),
+                                target.table_name.clone(),
+                                target.primary_key().remove(0),
+                                OnDelete::Cascade,
+                                true,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Explicit actions first, then declaration order
+        references.sort_by_key(|reference| !reference.5);
+        for (table, column, references_table, references_column, mut on_delete, _) in references {
+            let Some(referenced) = schemas
+                .iter()
+                .find(|s| s.table_name == references_table)
+                .and_then(|s| s.columns.iter().find(|c| c.name == references_column))
+            else {
+                continue;
+            };
+            let referenced_unique = (referenced.primary_key
+                && schemas
+                    .iter()
+                    .find(|s| s.table_name == references_table)
+                    .is_some_and(|s| !s.has_composite_key()))
+                || referenced.unique;
+            let referenced_type = Self::map_type_to_postgres(&referenced.column_type);
+            let Some(schema) = schemas.iter_mut().find(|s| s.table_name == table) else {
+                continue;
+            };
+            let Some(col) = schema.columns.iter().find(|c| c.name == column) else {
+                continue;
+            };
+            if schema.foreign_keys.iter().any(|fk| fk.column == column) {
+                continue;
+            }
+            if !referenced_unique || Self::map_type_to_postgres(&col.column_type) != referenced_type
+            {
+                tracing::warn!(
+                    table = %table,
+                    column = %column,
+                    references = %format!("{}.{}", references_table, references_column),
+                    "No foreign key: the referenced column is not a unique key of the same type"
+                );
+                continue;
+            }
+            if on_delete == OnDelete::SetNull && !col.nullable {
+                tracing::warn!(
+                    table = %table,
+                    column = %column,
+                    "onDelete 'set null' needs a nullable column, using restrict"
+                );
+                on_delete = OnDelete::Restrict;
+            }
+            if on_delete == OnDelete::Cascade && schema.soft_delete.is_some() {
+                tracing::warn!(
+                    table = %table,
+                    column = %column,
+                    "Rows of soft-delete tables are never removed by the database, using restrict"
+                );
+                on_delete = OnDelete::Restrict;
+            }
+
+            let indexed = schema.primary_key().first() == Some(&column)
+                || schema
+                    .indexes
+                    .iter()
+                    .any(|index| index.columns.first() == Some(&column));
+            if !indexed {
+                schema.indexes.push(IndexDefinition {
+                    name: format!("idx_{}_{}", table, column),
+                    columns: vec![column.clone()],
+                    unique: false,
+                });
+            }
+            schema.foreign_keys.push(ForeignKeyDefinition {
+                name: Self::foreign_key_name(&table, &column),
+                column,
+                references_table,
+                references_column,
+                on_delete,
+            });
+        }
+    }
+
+    /// Name of the foreign key constraint of a column
+    pub fn foreign_key_name(table: &str, column: &str) -> String {
+        format!("fk_{}_{}", table, column)
+    }
+
     /// Extract relation definitions from API paths and merge into table schemas
     fn extract_relations_from_paths(
         schemas: &mut [TableSchema],
@@ -333,6 +831,11 @@ impl SchemaGenerator {
                         .and_then(|lk| lk.as_str())
                         .map(|s| s.to_string());
 
+                    let (through, other_key) = Self::join_table_keys(relation_obj);
+                    let foreign_key = foreign_key
+                        .or_else(|| through.as_ref().map(|_| Self::join_table_key(table_name)));
+                    let on_delete = Self::relation_on_delete(relation_obj, table_name, prop_name);
+
                     if let (Some(rel_type), Some(target), Some(fk)) =
                         (relation_type, target_table, foreign_key)
                     {
@@ -351,6 +854,9 @@ impl SchemaGenerator {
                             target_table: target,
                             foreign_key: fk,
                             local_key,
+                            through,
+                            other_key,
+                            on_delete,
                         });
                     }
                 }
@@ -403,7 +909,7 @@ impl SchemaGenerator {
     /// - Properties map to columns; required => NOT NULL; infer basic SQL types
     /// - Ensure an 'id INTEGER PRIMARY KEY AUTOINCREMENT' if not present
     /// - Special cases: created_at -> DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
-    /// - Extensions: x-unique => UNIQUE; x-index => create index
+    /// - Extensions: x-unique => UNIQUE; x-index => create index; x-renamed-from => column rename
     fn derive_from_components(spec: &Value) -> Option<Vec<TableSchema>> {
         tracing::info!("Attempting to derive schemas from components");
         let components = spec.get("components")?.get("schemas")?.as_object()?;
@@ -454,6 +960,8 @@ impl SchemaGenerator {
 
             let mut columns: Vec<ColumnDefinition> = Vec::new();
             let mut indexes: Vec<IndexDefinition> = Vec::new();
+            let mut search_columns: Vec<String> = Vec::new();
+            let mut checks: Vec<CheckDefinition> = Vec::new();
 
             let required: std::collections::HashSet<String> = obj
                 .get("required")
@@ -478,6 +986,7 @@ impl SchemaGenerator {
                             auto_increment: true,
                             default_value: None,
                             auto_field: false,
+                            renamed_from: None,
                         });
                         continue;
                     }
@@ -486,6 +995,16 @@ impl SchemaGenerator {
                     let (col_type, default_value) =
                         Self::infer_sql_type_and_default(prop_name, prop_schema);
                     let nullable = !required.contains(prop_name);
+                    if let Some(expression) = Self::infer_check(prop_name, prop_schema) {
+                        checks.push(CheckDefinition {
+                            name: Self::check_name(
+                                &Self::to_table_name(schema_name),
+                                Some(prop_name),
+                                &expression,
+                            ),
+                            expression,
+                        });
+                    }
 
                     let unique = prop_schema
                         .as_object()
@@ -518,6 +1037,14 @@ impl SchemaGenerator {
                             .unwrap_or(false)
                         || is_audit_field;
 
+                    if prop_schema
+                        .get("x-searchable")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false)
+                    {
+                        search_columns.push(prop_name.clone());
+                    }
+
                     // indexes via x-index
                     let index = prop_schema
                         .as_object()
@@ -525,6 +1052,11 @@ impl SchemaGenerator {
                         .and_then(|v| v.as_bool())
                         .unwrap_or(false);
 
+                    let renamed_from = prop_schema
+                        .get("x-renamed-from")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+
                     columns.push(ColumnDefinition {
                         name: prop_name.clone(),
                         column_type: col_type,
@@ -534,6 +1066,7 @@ impl SchemaGenerator {
                         auto_increment: false,
                         default_value,
                         auto_field,
+                        renamed_from,
                     });
 
                     if index {
@@ -559,6 +1092,7 @@ impl SchemaGenerator {
                         auto_increment: true,
                         default_value: None,
                         auto_field: false,
+                        renamed_from: None,
                     },
                 );
             }
@@ -597,6 +1131,18 @@ impl SchemaGenerator {
                             .and_then(|lk| lk.as_str())
                             .map(|s| s.to_string());
 
+                        let (through, other_key) = Self::join_table_keys(relation_obj);
+                        let foreign_key = foreign_key.or_else(|| {
+                            through
+                                .as_ref()
+                                .map(|_| Self::join_table_key(&Self::to_table_name(schema_name)))
+                        });
+                        let on_delete = Self::relation_on_delete(
+                            relation_obj,
+                            &Self::to_table_name(schema_name),
+                            prop_name,
+                        );
+
                         if let (Some(rel_type), Some(target), Some(fk)) =
                             (relation_type, target_table, foreign_key)
                         {
@@ -606,6 +1152,9 @@ impl SchemaGenerator {
                                 target_table: target,
                                 foreign_key: fk,
                                 local_key,
+                                through,
+                                other_key,
+                                on_delete,
                             });
                         }
                     }
@@ -613,11 +1162,42 @@ impl SchemaGenerator {
             }
 
             let table_name = Self::to_table_name(schema_name);
+            let default_sort = obj
+                .get("x-default-sort")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            let cursor_column = obj
+                .get("x-cursor-column")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            let soft_delete = obj
+                .get("x-soft-delete")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            let version_column = obj
+                .get("x-version-column")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            let drop_columns = match obj.get("x-drop-columns") {
+                Some(v) => serde_json::from_value(v.clone()).unwrap_or_else(|_| {
+                    tracing::warn!(schema_name = %schema_name, value = %v, "Invalid x-drop-columns, keeping columns");
+                    DropPolicy::Keep
+                }),
+                None => DropPolicy::Keep,
+            };
             tables.push(TableSchema {
                 table_name,
                 columns,
                 indexes,
                 relations,
+                default_sort,
+                cursor_column,
+                soft_delete,
+                version_column,
+                search_columns,
+                drop_columns,
+                foreign_keys: vec![],
+                checks,
             });
         }
 
@@ -674,16 +1254,138 @@ impl SchemaGenerator {
             .and_then(|v| v.as_str())
             .unwrap_or("");
 
-        match (t, format) {
-            ("string", "date-time") => ("TIMESTAMP".to_string(), None),
-            ("string", "date") => ("DATE".to_string(), None),
-            ("string", _) => ("TEXT".to_string(), None),
-            ("integer", _) => ("INTEGER".to_string(), None),
-            ("number", _) => ("REAL".to_string(), None),
-            ("boolean", _) => ("INTEGER".to_string(), None),
-            ("array", _) => ("TEXT".to_string(), None),
-            ("object", _) => ("TEXT".to_string(), None),
-            _ => ("TEXT".to_string(), None),
+        let max_length = prop_schema.get("maxLength").and_then(|v| v.as_u64());
+
+        let sql_type = match (t, format) {
+            ("string", "date-time") => "TIMESTAMP".to_string(),
+            ("string", "date") => "DATE".to_string(),
+            ("string", "uuid") => "UUID".to_string(),
+            ("string", _) => match max_length {
+                Some(n) if n > 0 => format!("VARCHAR({})", n),
+                _ => "TEXT".to_string(),
+            },
+            ("integer", _) => "INTEGER".to_string(),
+            ("number", _) => match Self::numeric_scale(prop_schema) {
+                Some(scale) => {
+                    let precision = prop_schema
+                        .get("x-precision")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(18)
+                        .max(scale);
+                    format!("NUMERIC({},{})", precision, scale)
+                }
+                None => "REAL".to_string(),
+            },
+            ("boolean", _) => "BOOLEAN".to_string(),
+            ("array", _) | ("object", _) => "JSON".to_string(),
+            _ => "TEXT".to_string(),
+        };
+        let default = prop_schema.get("default").and_then(Self::sql_literal);
+        (sql_type, default)
+    }
+
+    /// Digits after the decimal point of a number whose `multipleOf` is a power of ten
+    /// below one (0.01 gives 2), for which an exact NUMERIC type is used
+    fn numeric_scale(prop_schema: &Value) -> Option<u64> {
+        let step = prop_schema.get("multipleOf")?.as_f64()?;
+        if step <= 0.0 || step >= 1.0 {
+            return None;
+        }
+        let scale = -step.log10().round();
+        ((10f64.powf(-scale) - step).abs() < step * 1e-9).then_some(scale as u64)
+    }
+
+    /// SQL literal of a scalar JSON value, used for column defaults and CHECK lists
+    fn sql_literal(value: &Value) -> Option<String> {
+        match value {
+            Value::String(s) => Some(format!("'{}'", s.replace('\'', "''"))),
+            Value::Number(n) => Some(n.to_string()),
+            Value::Bool(b) => Some(if *b { "TRUE" } else { "FALSE" }.to_string()),
+            _ => None,
+        }
+    }
+
+    /// `CHECK` expression enforcing the `enum`, `minimum`, `maximum`, `exclusiveMinimum`
+    /// and `exclusiveMaximum` of a property, so rows written by other clients obey them too.
+    /// Enums are checks on the plain column type rather than Postgres `CREATE TYPE ... AS
+    /// ENUM`: every backend supports them, and a changed value list is migrated by
+    /// replacing the constraint, where an enum type cannot drop or rename its values.
+    fn infer_check(prop_name: &str, prop_schema: &Value) -> Option<String> {
+        let mut conditions = Vec::new();
+        if let Some(values) = prop_schema.get("enum").and_then(|v| v.as_array()) {
+            // NULL passes a CHECK anyway, so a null member only matters to nullability
+            let literals: Option<Vec<String>> = values
+                .iter()
+                .filter(|v| !v.is_null())
+                .map(|v| match v {
+                    Value::String(_) | Value::Number(_) => Self::sql_literal(v),
+                    _ => None,
+                })
+                .collect();
+            match literals {
+                Some(literals) if !literals.is_empty() => {
+                    conditions.push(format!("{} IN ({})", prop_name, literals.join(", ")))
+                }
+                _ => tracing::warn!(
+                    column = %prop_name,
+                    "Only string and number enums are enforced by the database"
+                ),
+            }
+        }
+
+        // OpenAPI 3.0 flags the bound as exclusive, 3.1 gives the bound itself
+        let bound =
+            |key: &str, exclusive_key: &str, inclusive: &str, exclusive: &str| match prop_schema
+                .get(exclusive_key)
+            {
+                Some(Value::Number(n)) => Some(format!("{} {} {}", prop_name, exclusive, n)),
+                Some(Value::Bool(true)) => prop_schema
+                    .get(key)
+                    .and_then(|v| v.as_number())
+                    .map(|n| format!("{} {} {}", prop_name, exclusive, n)),
+                _ => prop_schema
+                    .get(key)
+                    .and_then(|v| v.as_number())
+                    .map(|n| format!("{} {} {}", prop_name, inclusive, n)),
+            };
+        conditions.extend(bound("minimum", "exclusiveMinimum", ">=", ">"));
+        conditions.extend(bound("maximum", "exclusiveMaximum", "<=", "<"));
+
+        (!conditions.is_empty()).then(|| conditions.join(" AND "))
+    }
+
+    /// Name of a `CHECK` constraint: the table and column, then a hash of the expression
+    /// so that changing the expression replaces the constraint
+    pub fn check_name(table: &str, column: Option<&str>, expression: &str) -> String {
+        let hash: String = Sha256::digest(expression.as_bytes())
+            .iter()
+            .take(4)
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let name = match column {
+            Some(column) => format!("ck_{}_{}_{}", table, column, hash),
+            None => format!("ck_{}_{}", table, hash),
+        };
+        // Longest identifier Postgres keeps
+        if name.len() <= 63 {
+            return name;
+        }
+        let hash: String = Sha256::digest(name.as_bytes())
+            .iter()
+            .take(12)
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        format!("ck_{}", hash)
+    }
+
+    /// Name the checks of `x-table-schemas` given without a name
+    fn name_checks(schemas: &mut [TableSchema]) {
+        for schema in schemas.iter_mut() {
+            for check in &mut schema.checks {
+                if check.name.is_empty() {
+                    check.name = Self::check_name(&schema.table_name, None, &check.expression);
+                }
+            }
         }
     }
 
@@ -691,22 +1393,24 @@ impl SchemaGenerator {
     pub fn generate_create_table_sql_sqlite(schema: &TableSchema) -> String {
         let mut sql = format!("CREATE TABLE IF NOT EXISTS {} (\n", schema.table_name);
 
+        let composite = schema.has_composite_key();
         let mut column_defs = Vec::new();
         for col in &schema.columns {
+            let primary_key = col.primary_key && !composite;
             let mut col_def = format!(
                 "    {} {}",
                 col.name,
                 Self::map_type_to_sqlite(&col.column_type)
             );
 
-            if col.primary_key {
+            if primary_key {
                 col_def.push_str(" PRIMARY KEY");
                 if col.auto_increment {
                     col_def.push_str(" AUTOINCREMENT");
                 }
             }
 
-            if !col.nullable && !col.primary_key {
+            if !col.nullable && !primary_key {
                 col_def.push_str(" NOT NULL");
             }
 
@@ -720,39 +1424,134 @@ impl SchemaGenerator {
 
             column_defs.push(col_def);
         }
+        column_defs.extend(Self::composite_key_sql(schema));
+        column_defs.extend(schema.foreign_keys.iter().map(Self::foreign_key_sql));
+        column_defs.extend(schema.checks.iter().map(Self::check_sql));
 
         sql.push_str(&column_defs.join(",\n"));
         sql.push_str("\n);\n");
 
         // Generate index statements
         for index in &schema.indexes {
-            let index_type = if index.unique {
-                "UNIQUE INDEX"
-            } else {
-                "INDEX"
-            };
-            sql.push_str(&format!(
-                "CREATE {} IF NOT EXISTS {} ON {} ({});\n",
-                index_type,
-                index.name,
-                schema.table_name,
-                index.columns.join(", ")
-            ));
+            sql.push_str(&Self::create_index_sql(&schema.table_name, index, true));
+            sql.push_str(";\n");
         }
 
         sql
     }
 
+    /// FTS5 table indexing the `searchColumns` of a SQLite table
+    pub fn search_table_sqlite(table: &str) -> String {
+        format!("{}_fts", table)
+    }
+
+    /// Create the FTS5 index of a SQLite table: an external-content table over the
+    /// search columns, triggers keeping it in sync with the table, and a rebuild
+    /// indexing the rows already stored. One statement per element.
+    pub fn generate_search_sql_sqlite(schema: &TableSchema) -> Vec<String> {
+        let table = &schema.table_name;
+        let fts = Self::search_table_sqlite(table);
+        let columns = schema.search_columns.join(", ");
+        let values = |row: &str| {
+            schema
+                .search_columns
+                .iter()
+                .map(|c| format!("{}.{}", row, c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let insert = format!(
+            "INSERT INTO {fts}(rowid, {columns}) VALUES (new.rowid, {});",
+            values("new")
+        );
+        let delete = format!(
+            "INSERT INTO {fts}({fts}, rowid, {columns}) VALUES ('delete', old.rowid, {});",
+            values("old")
+        );
+        vec![
+            format!(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS {fts} USING fts5({columns}, content='{table}', content_rowid='rowid')"
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS {fts}_ai AFTER INSERT ON {table} BEGIN {insert} END"
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS {fts}_ad AFTER DELETE ON {table} BEGIN {delete} END"
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS {fts}_au AFTER UPDATE ON {table} BEGIN {delete} {insert} END"
+            ),
+            format!("INSERT INTO {fts}({fts}) VALUES ('rebuild')"),
+        ]
+    }
+
+    /// Remove the FTS5 index of a SQLite table and its triggers
+    pub fn drop_search_sql_sqlite(table: &str) -> Vec<String> {
+        let fts = Self::search_table_sqlite(table);
+        vec![
+            format!("DROP TRIGGER IF EXISTS {fts}_ai"),
+            format!("DROP TRIGGER IF EXISTS {fts}_ad"),
+            format!("DROP TRIGGER IF EXISTS {fts}_au"),
+            format!("DROP TABLE IF EXISTS {fts}"),
+        ]
+    }
+
+    /// GIN (Postgres) or FULLTEXT (MySQL) index over the `searchColumns` of a table
+    pub fn search_index_name(table: &str) -> String {
+        format!("idx_{}_search", table)
+    }
+
+    /// The searchable columns of a Postgres table as one `tsvector`. Queries must use
+    /// the same expression as the index to be served by it.
+    pub fn search_document_postgres(columns: &[String]) -> String {
+        let text = columns
+            .iter()
+            .map(|c| format!("coalesce({}, '')", c))
+            .collect::<Vec<_>>()
+            .join(" || ' ' || ");
+        format!("to_tsvector('{}', {})", SEARCH_CONFIG_POSTGRES, text)
+    }
+
+    /// Create the GIN index of a Postgres table, commented with its columns so a
+    /// change of `searchColumns` can be detected
+    pub fn generate_search_sql_postgres(schema: &TableSchema) -> Vec<String> {
+        let index = Self::search_index_name(&schema.table_name);
+        vec![
+            format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} USING GIN ({})",
+                index,
+                schema.table_name,
+                Self::search_document_postgres(&schema.search_columns)
+            ),
+            format!(
+                "COMMENT ON INDEX {} IS '{}'",
+                index,
+                schema.search_columns.join(",")
+            ),
+        ]
+    }
+
+    /// Create the FULLTEXT index of a MySQL table
+    pub fn generate_search_sql_mysql(schema: &TableSchema) -> Vec<String> {
+        vec![format!(
+            "CREATE FULLTEXT INDEX {} ON {} ({})",
+            Self::search_index_name(&schema.table_name),
+            schema.table_name,
+            schema.search_columns.join(", ")
+        )]
+    }
+
     /// Generate CREATE TABLE SQL statement for PostgreSQL
     pub fn generate_create_table_sql_postgres(schema: &TableSchema) -> String {
         let mut sql = format!("CREATE TABLE IF NOT EXISTS {} (\n", schema.table_name);
 
+        let composite = schema.has_composite_key();
         let mut column_defs = Vec::new();
         for col in &schema.columns {
+            let primary_key = col.primary_key && !composite;
             // Special handling: INTEGER primary key with auto_increment -> SERIAL PRIMARY KEY
             let col_def =
-                if col.primary_key && col.auto_increment && Self::is_integer_type(&col.column_type)
-                {
+                if primary_key && col.auto_increment && Self::is_integer_type(&col.column_type) {
                     format!("    {} SERIAL PRIMARY KEY", col.name)
                 } else {
                     let mut tmp = format!(
@@ -761,11 +1560,11 @@ impl SchemaGenerator {
                         Self::map_type_to_postgres(&col.column_type)
                     );
 
-                    if col.primary_key {
+                    if primary_key {
                         tmp.push_str(" PRIMARY KEY");
                     }
 
-                    if !col.nullable && !col.primary_key {
+                    if !col.nullable && !primary_key {
                         tmp.push_str(" NOT NULL");
                     }
 
@@ -782,106 +1581,169 @@ impl SchemaGenerator {
 
             column_defs.push(col_def);
         }
+        column_defs.extend(Self::composite_key_sql(schema));
+        column_defs.extend(schema.foreign_keys.iter().map(Self::foreign_key_sql));
+        column_defs.extend(schema.checks.iter().map(Self::check_sql));
 
         sql.push_str(&column_defs.join(",\n"));
         sql.push_str("\n);\n");
 
         // Generate index statements
         for index in &schema.indexes {
-            let index_type = if index.unique {
-                "UNIQUE INDEX"
-            } else {
-                "INDEX"
-            };
-            sql.push_str(&format!(
-                "CREATE {} IF NOT EXISTS {} ON {} ({});\n",
-                index_type,
-                index.name,
-                schema.table_name,
-                index.columns.join(", ")
-            ));
+            sql.push_str(&Self::create_index_sql(&schema.table_name, index, true));
+            sql.push_str(";\n");
         }
 
         sql
     }
 
-    /// Generate migration SQL statements to update current schema to desired schema
-    pub fn generate_migration_sql(
-        current: &TableSchema,
-        desired: &TableSchema,
-        driver: &str,
-    ) -> Result<Vec<String>, String> {
-        let mut sqls = Vec::new();
+    /// Generate CREATE TABLE SQL statement for MySQL/MariaDB
+    pub fn generate_create_table_sql_mysql(schema: &TableSchema) -> String {
+        let mut sql = format!("CREATE TABLE IF NOT EXISTS {} (\n", schema.table_name);
 
-        if driver == "sqlite" {
-            // Check if we need full table recreation
-            // Recreation is needed if:
-            // 1. Columns are removed
-            // 2. Columns are modified (type, nullability, etc.)
-            // 3. Primary key changes (not supported yet but good to know)
+        let composite = schema.has_composite_key();
+        let mut column_defs = Vec::new();
+        for col in &schema.columns {
+            let mut col_def = format!(
+                "    {} {}",
+                col.name,
+                Self::map_type_to_mysql(&col.column_type, Self::is_keyed_mysql(schema, col))
+            );
 
-            let mut needs_recreation = false;
+            if !col.nullable || col.primary_key {
+                col_def.push_str(" NOT NULL");
+            }
 
-            // Check for removed columns
-            for curr_col in &current.columns {
-                if !desired.columns.iter().any(|c| c.name == curr_col.name) {
-                    needs_recreation = true;
-                    break;
+            if col.primary_key && !composite {
+                if col.auto_increment && Self::is_integer_type(&col.column_type) {
+                    col_def.push_str(" AUTO_INCREMENT");
                 }
+                col_def.push_str(" PRIMARY KEY");
             }
 
-            // Check for modified columns
-            if !needs_recreation {
-                for col in &desired.columns {
-                    if let Some(curr_col) = current.columns.iter().find(|c| c.name == col.name) {
-                        // Compare attributes relevant for SQLite
-                        // Note: SQLite types are loose, but we check if the definition changed
-                        let desired_type = Self::map_type_to_sqlite(&col.column_type);
-                        // Current type comes from PRAGMA table_info, which might be normalized differently.
-                        // We do a loose comparison.
-                        let curr_type_norm = curr_col.column_type.to_uppercase();
-                        let desired_type_norm = desired_type.to_uppercase();
-
-                        tracing::info!(
-                            "Checking column '{}' compatibility: {} -> {}",
-                            col.name,
-                            curr_type_norm,
-                            desired_type_norm
-                        );
+            if col.unique && !col.primary_key {
+                col_def.push_str(" UNIQUE");
+            }
 
-                        if curr_type_norm != desired_type_norm {
-                            // Check compatibility before allowing recreation
-                            if !Self::is_type_compatible(&curr_type_norm, &desired_type_norm) {
-                                return Err(format!(
-                                    "Incompatible type change for column '{}': {} -> {}",
-                                    col.name, curr_type_norm, desired_type_norm
-                                ));
-                            }
-                            needs_recreation = true;
-                            // Continue checking other columns for incompatible changes
-                        }
+            if let Some(default) = &col.default_value {
+                col_def.push_str(&format!(" DEFAULT {}", default));
+            }
 
-                        if curr_col.nullable != col.nullable
-                            || curr_col.primary_key != col.primary_key
-                            || !Self::are_defaults_equal(
-                                &curr_col.default_value,
-                                &col.default_value,
-                            )
-                        {
-                            tracing::info!(
-                                "Column attribute mismatch for {}: nullable: {} vs {}, pk: {} vs {}, default: {:?} vs {:?}",
-                                col.name,
-                                curr_col.nullable,
-                                col.nullable,
-                                curr_col.primary_key,
-                                col.primary_key,
-                                curr_col.default_value,
-                                col.default_value
-                            );
-                            needs_recreation = true;
-                            // Continue checking other columns for incompatible changes
-                        }
+            column_defs.push(col_def);
+        }
+        column_defs.extend(Self::composite_key_sql(schema));
+        column_defs.extend(schema.foreign_keys.iter().map(Self::foreign_key_sql));
+        column_defs.extend(schema.checks.iter().map(Self::check_sql));
+
+        sql.push_str(&column_defs.join(",\n"));
+        sql.push_str("\n);\n");
+
+        // MySQL has no CREATE INDEX IF NOT EXISTS; indexes are only created with the table
+        for index in &schema.indexes {
+            sql.push_str(&Self::create_index_sql(&schema.table_name, index, false));
+            sql.push_str(";\n");
+        }
+
+        sql
+    }
+
+    /// Generate migration SQL statements to update current schema to desired schema
+    pub fn generate_migration_sql(
+        current: &TableSchema,
+        desired: &TableSchema,
+        driver: &str,
+    ) -> Result<Vec<String>, String> {
+        let mut sqls = Vec::new();
+        let sources = Self::column_sources(current, desired);
+        let undeclared = Self::undeclared_columns(current, desired);
+
+        if desired.drop_columns == DropPolicy::Fail
+            && let Some(col) = undeclared.first()
+        {
+            return Err(format!(
+                "Column '{}' of table '{}' is no longer declared; declare it, rename it with \
+                 x-renamed-from or set x-drop-columns to keep or drop it",
+                col.name, desired.table_name
+            ));
+        }
+
+        if driver == "sqlite" {
+            // Check if we need full table recreation
+            // Recreation is needed if:
+            // 1. Columns are dropped, or kept columns must accept NULL
+            // 2. Columns are modified (type, nullability, etc.)
+            // 3. Primary key changes (not supported yet but good to know)
+            // 4. Foreign keys or checks change, since SQLite cannot alter constraints
+
+            let mut needs_recreation = match desired.drop_columns {
+                DropPolicy::Drop => !undeclared.is_empty(),
+                _ => undeclared.iter().any(|c| Self::blocks_inserts(c)),
+            };
+            let same_keys = |a: &TableSchema, b: &TableSchema| {
+                a.foreign_keys
+                    .iter()
+                    .all(|fk| b.foreign_keys.iter().any(|other| other.same_constraint(fk)))
+            };
+            if !same_keys(current, desired) || !same_keys(desired, current) {
+                needs_recreation = true;
+            }
+            let same_checks = |a: &TableSchema, b: &TableSchema| {
+                a.checks
+                    .iter()
+                    .all(|check| b.checks.iter().any(|other| other.name == check.name))
+            };
+            if !same_checks(current, desired) || !same_checks(desired, current) {
+                needs_recreation = true;
+            }
+
+            // Check for modified columns
+            for (col, curr_col) in &sources {
+                let Some(curr_col) = curr_col else {
+                    continue;
+                };
+                // Compare attributes relevant for SQLite
+                // Note: SQLite types are loose, but we check if the definition changed
+                let desired_type = Self::map_type_to_sqlite(&col.column_type);
+                // Current type comes from PRAGMA table_info, which might be normalized differently.
+                // We do a loose comparison.
+                let curr_type_norm = curr_col.column_type.to_uppercase();
+                let desired_type_norm = desired_type.to_uppercase();
+
+                tracing::info!(
+                    "Checking column '{}' compatibility: {} -> {}",
+                    col.name,
+                    curr_type_norm,
+                    desired_type_norm
+                );
+
+                if curr_type_norm != desired_type_norm {
+                    // Check compatibility before allowing recreation
+                    if !Self::is_type_compatible(&curr_type_norm, &desired_type_norm) {
+                        return Err(format!(
+                            "Incompatible type change for column '{}': {} -> {}",
+                            col.name, curr_type_norm, desired_type_norm
+                        ));
                     }
+                    needs_recreation = true;
+                    // Continue checking other columns for incompatible changes
+                }
+
+                if curr_col.nullable != col.nullable
+                    || curr_col.primary_key != col.primary_key
+                    || !Self::are_defaults_equal(&curr_col.default_value, &col.default_value)
+                {
+                    tracing::info!(
+                        "Column attribute mismatch for {}: nullable: {} vs {}, pk: {} vs {}, default: {:?} vs {:?}",
+                        col.name,
+                        curr_col.nullable,
+                        col.nullable,
+                        curr_col.primary_key,
+                        col.primary_key,
+                        curr_col.default_value,
+                        col.default_value
+                    );
+                    needs_recreation = true;
+                    // Continue checking other columns for incompatible changes
                 }
             }
 
@@ -890,22 +1752,63 @@ impl SchemaGenerator {
             }
         }
 
-        // 1. Add missing columns
-        for col in &desired.columns {
-            if !current.columns.iter().any(|c| c.name == col.name) {
-                let sql = if driver == "postgres" {
-                    Self::generate_add_column_sql_postgres(&desired.table_name, col)
-                } else {
-                    Self::generate_add_column_sql_sqlite(&desired.table_name, col)
+        // 1. Rename columns declared with x-renamed-from
+        for (col, curr_col) in &sources {
+            if let Some(curr_col) = curr_col
+                && curr_col.name != col.name
+            {
+                sqls.push(format!(
+                    "ALTER TABLE {} RENAME COLUMN {} TO {}",
+                    desired.table_name, curr_col.name, col.name
+                ));
+            }
+        }
+
+        // Drop the foreign keys of relations that changed or were removed before their
+        // columns change. Constraints not named after a relation are left alone.
+        for fk in &current.foreign_keys {
+            if fk.name.starts_with("fk_")
+                && !desired.foreign_keys.iter().any(|d| d.same_constraint(fk))
+            {
+                sqls.push(match driver {
+                    "mysql" => format!(
+                        "ALTER TABLE {} DROP FOREIGN KEY {}",
+                        desired.table_name, fk.name
+                    ),
+                    _ => format!(
+                        "ALTER TABLE {} DROP CONSTRAINT {}",
+                        desired.table_name, fk.name
+                    ),
+                });
+            }
+        }
+        // Likewise checks whose expression changed, which changes their name
+        for check in &current.checks {
+            if check.name.starts_with("ck_") && !desired.checks.iter().any(|d| d.name == check.name)
+            {
+                sqls.push(format!(
+                    "ALTER TABLE {} DROP CONSTRAINT {}",
+                    desired.table_name, check.name
+                ));
+            }
+        }
+
+        // 2. Add missing columns
+        for (col, curr_col) in &sources {
+            if curr_col.is_none() {
+                let sql = match driver {
+                    "postgres" => Self::generate_add_column_sql_postgres(&desired.table_name, col),
+                    "mysql" => Self::generate_add_column_sql_mysql(desired, col),
+                    _ => Self::generate_add_column_sql_sqlite(&desired.table_name, col),
                 };
                 sqls.push(sql);
             }
         }
 
-        // 2. Modify existing columns (Type changes, Nullability) - Postgres only mostly
+        // 3. Modify existing columns (Type changes, Nullability) - Postgres only mostly
         if driver == "postgres" {
-            for col in &desired.columns {
-                if let Some(curr_col) = current.columns.iter().find(|c| c.name == col.name) {
+            for (col, curr_col) in &sources {
+                if let Some(curr_col) = curr_col {
                     // Check type compatibility
                     let curr_type = Self::map_type_to_postgres(&curr_col.column_type);
                     let new_type = Self::map_type_to_postgres(&col.column_type);
@@ -936,28 +1839,208 @@ impl SchemaGenerator {
                         );
                         sqls.push(sql);
                     }
+
+                    // Check default; serial columns keep the default of their sequence
+                    if !curr_col.auto_increment
+                        && !col.auto_increment
+                        && !Self::are_defaults_equal(&curr_col.default_value, &col.default_value)
+                    {
+                        sqls.push(match &col.default_value {
+                            Some(default) => format!(
+                                "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {}",
+                                desired.table_name, col.name, default
+                            ),
+                            None => format!(
+                                "ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT",
+                                desired.table_name, col.name
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+
+        // MySQL changes type, nullability and default together with MODIFY COLUMN
+        if driver == "mysql" {
+            for (col, curr_col) in &sources {
+                if let Some(curr_col) = curr_col {
+                    let keyed = Self::is_keyed_mysql(desired, col);
+                    let curr_type = Self::map_type_to_mysql(&curr_col.column_type, keyed);
+                    let new_type = Self::map_type_to_mysql(&col.column_type, keyed);
+
+                    if curr_type != new_type && !Self::is_type_compatible(&curr_type, &new_type) {
+                        return Err(format!(
+                            "Incompatible type change for column '{}': {} -> {}",
+                            col.name, curr_type, new_type
+                        ));
+                    }
+
+                    // Primary key columns are always NOT NULL in MySQL
+                    let nullability_changed = !col.primary_key && curr_col.nullable != col.nullable;
+                    let default_changed = !col.auto_increment
+                        && !Self::are_defaults_equal(&curr_col.default_value, &col.default_value);
+                    if curr_type != new_type || nullability_changed || default_changed {
+                        sqls.push(format!(
+                            "ALTER TABLE {} MODIFY COLUMN {}",
+                            desired.table_name,
+                            Self::mysql_column_sql(desired, col)
+                        ));
+                    }
+                }
+            }
+        }
+
+        // 4. Drop undeclared columns, or let kept ones accept NULL so inserts still succeed
+        for col in undeclared {
+            match desired.drop_columns {
+                DropPolicy::Drop => sqls.push(format!(
+                    "ALTER TABLE {} DROP COLUMN {}",
+                    desired.table_name, col.name
+                )),
+                _ if !Self::blocks_inserts(col) => {}
+                _ if driver == "postgres" => sqls.push(format!(
+                    "ALTER TABLE {} ALTER COLUMN {} DROP NOT NULL",
+                    desired.table_name, col.name
+                )),
+                _ if driver == "mysql" => {
+                    let mut relaxed = col.clone();
+                    relaxed.nullable = true;
+                    sqls.push(format!(
+                        "ALTER TABLE {} MODIFY COLUMN {}",
+                        desired.table_name,
+                        Self::mysql_column_sql(current, &relaxed)
+                    ));
                 }
+                _ => {}
+            }
+        }
+
+        // 5. Create declared indexes the table lacks
+        for index in &desired.indexes {
+            if !current.indexes.iter().any(|i| i.name == index.name) {
+                sqls.push(Self::create_index_sql(
+                    &desired.table_name,
+                    index,
+                    driver != "mysql",
+                ));
+            }
+        }
+
+        // 6. Add foreign keys of new or changed relations
+        for fk in &desired.foreign_keys {
+            if !current.foreign_keys.iter().any(|c| c.same_constraint(fk)) {
+                sqls.push(format!(
+                    "ALTER TABLE {} ADD {}",
+                    desired.table_name,
+                    Self::foreign_key_sql(fk).trim_start()
+                ));
+            }
+        }
+
+        // 7. Add checks the table lacks
+        for check in &desired.checks {
+            if !current.checks.iter().any(|c| c.name == check.name) {
+                sqls.push(format!(
+                    "ALTER TABLE {} ADD {}",
+                    desired.table_name,
+                    Self::check_sql(check).trim_start()
+                ));
             }
         }
 
         Ok(sqls)
     }
 
+    /// Existing column each declared column is migrated from: the column of the same
+    /// name, or the one named by `renamed_from` when that name is no longer declared
+    fn column_sources<'a>(
+        current: &'a TableSchema,
+        desired: &'a TableSchema,
+    ) -> Vec<(&'a ColumnDefinition, Option<&'a ColumnDefinition>)> {
+        let existing = |name: &str| current.columns.iter().find(|c| c.name == name);
+        desired
+            .columns
+            .iter()
+            .map(|col| {
+                let source = existing(&col.name).or_else(|| {
+                    let old = col.renamed_from.as_deref()?;
+                    if desired.columns.iter().any(|c| c.name == old) {
+                        return None;
+                    }
+                    existing(old)
+                });
+                (col, source)
+            })
+            .collect()
+    }
+
+    /// Existing columns that no declared column is migrated from
+    pub fn undeclared_columns<'a>(
+        current: &'a TableSchema,
+        desired: &TableSchema,
+    ) -> Vec<&'a ColumnDefinition> {
+        let sources = Self::column_sources(current, desired);
+        current
+            .columns
+            .iter()
+            .filter(|c| {
+                !sources
+                    .iter()
+                    .any(|(_, source)| source.is_some_and(|s| s.name == c.name))
+            })
+            .collect()
+    }
+
+    /// Whether a column left out of inserts makes them fail
+    fn blocks_inserts(col: &ColumnDefinition) -> bool {
+        !col.nullable && col.default_value.is_none() && !col.primary_key && !col.auto_increment
+    }
+
     fn are_defaults_equal(d1: &Option<String>, d2: &Option<String>) -> bool {
         match (d1, d2) {
             (None, None) => true,
             (Some(v1), Some(v2)) => {
-                // Normalize SQLite defaults
-                // 1. Remove surrounding single quotes
-                // 2. Case insensitive
-                let v1_norm = v1.trim_matches('\'').to_uppercase();
-                let v2_norm = v2.trim_matches('\'').to_uppercase();
-                v1_norm == v2_norm
+                let (v1_norm, v2_norm) = (Self::normalize_default(v1), Self::normalize_default(v2));
+                match (v1_norm.parse::<f64>(), v2_norm.parse::<f64>()) {
+                    // DECIMAL defaults come back padded to their scale (0 is 0.00)
+                    (Ok(n1), Ok(n2)) => n1 == n2,
+                    _ => v1_norm == v2_norm,
+                }
             }
             _ => false,
         }
     }
 
+    /// Default as comparable text, whichever database reported it: without Postgres
+    /// casts (`'draft'::character varying`), surrounding quotes or the parentheses of
+    /// `current_timestamp()`, uppercased, with booleans as 1 and 0
+    fn normalize_default(default: &str) -> String {
+        let mut in_quotes = false;
+        let mut end = default.len();
+        for (i, c) in default.char_indices() {
+            match c {
+                '\'' => in_quotes = !in_quotes,
+                ':' if !in_quotes && default[i..].starts_with("::") => {
+                    end = i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let value = default[..end]
+            .trim()
+            .trim_matches('\'')
+            .to_uppercase()
+            .trim_end_matches("()")
+            .to_string();
+        match value.as_str() {
+            "TRUE" => "1".to_string(),
+            "FALSE" => "0".to_string(),
+            "NOW" => "CURRENT_TIMESTAMP".to_string(),
+            _ => value,
+        }
+    }
+
     fn is_type_compatible(from: &str, to: &str) -> bool {
         let from = from.to_uppercase();
         let to = to.to_uppercase();
@@ -995,6 +2078,25 @@ impl SchemaGenerator {
             return true;
         }
 
+        // Native types of what used to be stored as integers and text: booleans,
+        // uuids, JSON documents and exact numbers. Rows that do not convert make
+        // the migration fail rather than lose data.
+        let integer = matches!(from.as_str(), "INTEGER" | "SMALLINT" | "INT" | "BIGINT");
+        let text = from == "TEXT" || from.starts_with("VARCHAR") || from.starts_with("CHAR");
+        let number = integer
+            || matches!(
+                from.as_str(),
+                "REAL" | "FLOAT" | "DOUBLE" | "DOUBLE PRECISION"
+            )
+            || from.starts_with("NUMERIC")
+            || from.starts_with("DECIMAL");
+        if (to == "BOOLEAN" && integer)
+            || (text && (to == "UUID" || to == "JSON" || to == "JSONB" || to.starts_with("CHAR")))
+            || (number && (to.starts_with("NUMERIC") || to.starts_with("DECIMAL")))
+        {
+            return true;
+        }
+
         // Disallow others (e.g. TEXT -> INTEGER, INTEGER -> BOOLEAN)
         false
     }
@@ -1002,33 +2104,57 @@ impl SchemaGenerator {
     fn generate_sqlite_recreate_sql(current: &TableSchema, desired: &TableSchema) -> Vec<String> {
         let mut sqls = Vec::new();
         let table = &desired.table_name;
-        let temp_table = format!("{}_old_{}", table, uuid::Uuid::new_v4().simple());
-
-        // 1. Rename current table
-        sqls.push(format!("ALTER TABLE {} RENAME TO {}", table, temp_table));
+        let temp_table = format!("{}_new_{}", table, uuid::Uuid::new_v4().simple());
+
+        // Columns copied into the new table and the columns they are copied from,
+        // following renames. Kept columns are carried over, accepting NULL when
+        // inserts would otherwise fail.
+        let mut target = desired.clone();
+        let mut copies: Vec<(String, String)> = Self::column_sources(current, desired)
+            .into_iter()
+            .filter_map(|(col, source)| Some((col.name.clone(), source?.name.clone())))
+            .collect();
+        if desired.drop_columns == DropPolicy::Keep {
+            for col in Self::undeclared_columns(current, desired) {
+                let mut kept = col.clone();
+                if Self::blocks_inserts(&kept) {
+                    kept.nullable = true;
+                }
+                copies.push((kept.name.clone(), kept.name.clone()));
+                target.columns.push(kept);
+            }
+        }
 
-        // 2. Create new table
-        sqls.push(Self::generate_create_table_sql_sqlite(desired));
+        // The new table is built under a temporary name and renamed once the old one is
+        // gone: renaming the old table instead would repoint the foreign keys of other
+        // tables to it, and keep its indexes from being created on the new one
 
-        // 3. Copy data
-        // Only copy columns that exist in both schemas
-        let common_columns: Vec<String> = desired
-            .columns
-            .iter()
-            .filter(|col| current.columns.iter().any(|c| c.name == col.name))
-            .map(|col| col.name.clone())
-            .collect();
+        // 1. Create new table
+        let mut created = target.clone();
+        created.table_name = temp_table.clone();
+        created.indexes.clear();
+        sqls.push(Self::generate_create_table_sql_sqlite(&created));
 
-        if !common_columns.is_empty() {
-            let cols = common_columns.join(", ");
+        // 2. Copy data
+        if !copies.is_empty() {
+            let (columns, sources): (Vec<String>, Vec<String>) = copies.into_iter().unzip();
             sqls.push(format!(
                 "INSERT INTO {} ({}) SELECT {} FROM {}",
-                table, cols, cols, temp_table
+                temp_table,
+                columns.join(", "),
+                sources.join(", "),
+                table
             ));
         }
 
-        // 4. Drop old table
-        sqls.push(format!("DROP TABLE {}", temp_table));
+        // 3. Drop old table and take its name
+        sqls.push(format!("DROP TABLE {}", table));
+        sqls.push(format!("ALTER TABLE {} RENAME TO {}", temp_table, table));
+
+        // 4. Recreate indexes
+        for index in &target.indexes {
+            sqls.push(Self::create_index_sql(table, index, true));
+        }
 
         sqls
     }
@@ -1068,11 +2194,66 @@ impl SchemaGenerator {
         sql
     }
 
-    /// Map generic type to SQLite type
-    fn map_type_to_sqlite(type_name: &str) -> &str {
-        match type_name.to_lowercase().as_str() {
+    fn generate_add_column_sql_mysql(schema: &TableSchema, col: &ColumnDefinition) -> String {
+        format!(
+            "ALTER TABLE {} ADD COLUMN {}",
+            schema.table_name,
+            Self::mysql_column_sql(schema, col)
+        )
+    }
+
+    /// Column definition used by MySQL ADD/MODIFY COLUMN (keys are left untouched)
+    fn mysql_column_sql(schema: &TableSchema, col: &ColumnDefinition) -> String {
+        let mut sql = format!(
+            "{} {}",
+            col.name,
+            Self::map_type_to_mysql(&col.column_type, Self::is_keyed_mysql(schema, col))
+        );
+        if !col.nullable || col.primary_key {
+            sql.push_str(" NOT NULL");
+        }
+        if col.primary_key && col.auto_increment && Self::is_integer_type(&col.column_type) {
+            sql.push_str(" AUTO_INCREMENT");
+        }
+        if let Some(default) = &col.default_value {
+            sql.push_str(&format!(" DEFAULT {}", default));
+        }
+        sql
+    }
+
+    /// Whether a MySQL column needs a bounded type: TEXT cannot be used in keys
+    /// or indexes without a prefix length, nor (before 8.0.13) carry a DEFAULT
+    fn is_keyed_mysql(schema: &TableSchema, col: &ColumnDefinition) -> bool {
+        col.primary_key
+            || col.unique
+            || col.default_value.is_some()
+            || schema
+                .indexes
+                .iter()
+                .any(|index| index.columns.contains(&col.name))
+    }
+
+    /// Lowercase name and arguments of a type: `VARCHAR(50)` is `("varchar", [50])`
+    fn split_type(type_name: &str) -> (String, Vec<u64>) {
+        let type_name = type_name.trim().to_lowercase();
+        match type_name.split_once('(') {
+            Some((base, args)) => {
+                let args = args
+                    .trim_end_matches(')')
+                    .split(',')
+                    .filter_map(|arg| arg.trim().parse().ok())
+                    .collect();
+                (base.trim().to_string(), args)
+            }
+            None => (type_name, Vec::new()),
+        }
+    }
+
+    /// Map generic type to SQLite type; lengths and precisions are not enforced
+    fn map_type_to_sqlite(type_name: &str) -> &'static str {
+        match Self::split_type(type_name).0.as_str() {
             "integer" | "int" | "bigint" | "smallint" => "INTEGER",
-            "text" | "string" | "varchar" | "char" => "TEXT",
+            "text" | "string" | "varchar" | "char" | "uuid" | "json" | "jsonb" => "TEXT",
             "real" | "float" | "double" | "decimal" | "numeric" => "REAL",
             "boolean" | "bool" => "INTEGER", // SQLite uses INTEGER for boolean
             "blob" | "binary" => "BLOB",
@@ -1085,25 +2266,96 @@ impl SchemaGenerator {
 
     /// Map generic type to PostgreSQL type
     fn map_type_to_postgres(type_name: &str) -> String {
-        match type_name.to_lowercase().as_str() {
-            "integer" | "int" => "INTEGER".to_string(),
-            "bigint" => "BIGINT".to_string(),
-            "smallint" => "SMALLINT".to_string(),
-            "text" | "string" => "TEXT".to_string(),
-            "varchar" => "VARCHAR(255)".to_string(),
-            "char" => "CHAR(1)".to_string(),
-            "real" | "float" => "REAL".to_string(),
-            "double" => "DOUBLE PRECISION".to_string(),
-            "decimal" | "numeric" => "NUMERIC".to_string(),
-            "boolean" | "bool" => "BOOLEAN".to_string(),
-            "blob" | "binary" => "BYTEA".to_string(),
-            "datetime" | "timestamp" => "TIMESTAMPTZ".to_string(),
-            "date" => "DATE".to_string(),
-            "time" => "TIME".to_string(),
+        let (base, args) = Self::split_type(type_name);
+        match (base.as_str(), args.as_slice()) {
+            ("integer" | "int", _) => "INTEGER".to_string(),
+            ("bigint", _) => "BIGINT".to_string(),
+            ("smallint", _) => "SMALLINT".to_string(),
+            ("text" | "string", _) => "TEXT".to_string(),
+            ("varchar", [n]) => format!("VARCHAR({})", n),
+            ("varchar", _) => "VARCHAR(255)".to_string(),
+            ("char", [n]) => format!("CHAR({})", n),
+            ("char", _) => "CHAR(1)".to_string(),
+            ("real" | "float", _) => "REAL".to_string(),
+            ("double", _) => "DOUBLE PRECISION".to_string(),
+            ("decimal" | "numeric", [p, s]) => format!("NUMERIC({},{})", p, s),
+            ("decimal" | "numeric", [p]) => format!("NUMERIC({})", p),
+            ("decimal" | "numeric", _) => "NUMERIC".to_string(),
+            ("boolean" | "bool", _) => "BOOLEAN".to_string(),
+            ("uuid", _) => "UUID".to_string(),
+            ("json" | "jsonb", _) => "JSONB".to_string(),
+            ("blob" | "binary", _) => "BYTEA".to_string(),
+            ("datetime" | "timestamp", _) => "TIMESTAMPTZ".to_string(),
+            ("date", _) => "DATE".to_string(),
+            ("time", _) => "TIME".to_string(),
             _ => "TEXT".to_string(), // Default to TEXT for unknown types
         }
     }
 
+    /// Map generic type to MySQL type; `keyed` text columns become VARCHAR(255)
+    fn map_type_to_mysql(type_name: &str, keyed: bool) -> String {
+        let (base, args) = Self::split_type(type_name);
+        match (base.as_str(), args.as_slice()) {
+            ("integer" | "int", _) => "INTEGER".to_string(),
+            ("bigint", _) => "BIGINT".to_string(),
+            ("smallint", _) => "SMALLINT".to_string(),
+            ("varchar", [n]) => format!("VARCHAR({})", n),
+            ("varchar", _) => "VARCHAR(255)".to_string(),
+            ("char", [n]) => format!("CHAR({})", n),
+            ("char", _) => "CHAR(1)".to_string(),
+            ("real" | "float" | "double", _) => "DOUBLE".to_string(),
+            ("decimal" | "numeric", [p, s]) => format!("DECIMAL({},{})", p, s),
+            ("decimal" | "numeric", [p]) => format!("DECIMAL({})", p),
+            ("decimal" | "numeric", _) => "DECIMAL(38,10)".to_string(),
+            ("boolean" | "bool", _) => "BOOLEAN".to_string(),
+            ("uuid", _) => "CHAR(36)".to_string(),
+            ("json" | "jsonb", _) => "JSON".to_string(),
+            ("blob" | "binary", _) => "BLOB".to_string(),
+            ("datetime" | "timestamp", _) => "DATETIME".to_string(),
+            ("date", _) => "DATE".to_string(),
+            ("time", _) => "TIME".to_string(),
+            // TEXT for "text", "string" and unknown types
+            _ if keyed => "VARCHAR(255)".to_string(),
+            _ => "TEXT".to_string(),
+        }
+    }
+
+    /// `CREATE INDEX` statement of an index, without the terminating semicolon
+    fn create_index_sql(table: &str, index: &IndexDefinition, if_not_exists: bool) -> String {
+        format!(
+            "CREATE {}INDEX {}{} ON {} ({})",
+            if index.unique { "UNIQUE " } else { "" },
+            if if_not_exists { "IF NOT EXISTS " } else { "" },
+            index.name,
+            table,
+            index.columns.join(", ")
+        )
+    }
+
+    /// Table-level `FOREIGN KEY` constraint
+    fn foreign_key_sql(fk: &ForeignKeyDefinition) -> String {
+        format!(
+            "    CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}) ON DELETE {}",
+            fk.name,
+            fk.column,
+            fk.references_table,
+            fk.references_column,
+            fk.on_delete.as_sql()
+        )
+    }
+
+    /// Table-level `CHECK` constraint
+    fn check_sql(check: &CheckDefinition) -> String {
+        format!("    CONSTRAINT {} CHECK ({})", check.name, check.expression)
+    }
+
+    /// Table-level `PRIMARY KEY (a, b)` constraint for composite keys
+    fn composite_key_sql(schema: &TableSchema) -> Option<String> {
+        schema
+            .has_composite_key()
+            .then(|| format!("    PRIMARY KEY ({})", schema.primary_key().join(", ")))
+    }
+
     /// Check if a type is an integer type
     fn is_integer_type(type_name: &str) -> bool {
         matches!(
@@ -1130,9 +2382,18 @@ mod tests {
                 unique: false,
                 default_value: None,
                 auto_field: false,
+                renamed_from: None,
             }],
             indexes: vec![],
             relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
         };
 
         let desired = TableSchema {
@@ -1146,9 +2407,18 @@ mod tests {
                 unique: false,
                 default_value: None,
                 auto_field: false,
+                renamed_from: None,
             }],
             indexes: vec![],
             relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
         };
 
         let sqls = SchemaGenerator::generate_migration_sql(&current, &desired, "postgres").unwrap();
@@ -1169,9 +2439,18 @@ mod tests {
                 unique: false,
                 default_value: None,
                 auto_field: false,
+                renamed_from: None,
             }],
             indexes: vec![],
             relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
         };
 
         let desired = TableSchema {
@@ -1185,9 +2464,18 @@ mod tests {
                 unique: false,
                 default_value: None,
                 auto_field: false,
+                renamed_from: None,
             }],
             indexes: vec![],
             relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
         };
 
         let result = SchemaGenerator::generate_migration_sql(&current, &desired, "postgres");
@@ -1209,6 +2497,7 @@ mod tests {
                     auto_increment: true,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "name".to_string(),
@@ -1219,6 +2508,7 @@ mod tests {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
                 ColumnDefinition {
                     name: "email".to_string(),
@@ -1229,6 +2519,7 @@ mod tests {
                     auto_increment: false,
                     default_value: None,
                     auto_field: false,
+                    renamed_from: None,
                 },
             ],
             indexes: vec![IndexDefinition {
@@ -1237,6 +2528,14 @@ mod tests {
                 unique: false,
             }],
             relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
         };
 
         let sql = SchemaGenerator::generate_create_table_sql_sqlite(&schema);
@@ -1247,4 +2546,435 @@ mod tests {
         assert!(sql.contains("email TEXT NOT NULL UNIQUE"));
         assert!(sql.contains("CREATE INDEX IF NOT EXISTS idx_users_email"));
     }
+
+    fn mysql_column(name: &str, column_type: &str) -> ColumnDefinition {
+        ColumnDefinition {
+            name: name.to_string(),
+            column_type: column_type.to_string(),
+            nullable: true,
+            primary_key: false,
+            unique: false,
+            auto_increment: false,
+            default_value: None,
+            auto_field: false,
+            renamed_from: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_create_table_sql_mysql() {
+        let schema = TableSchema {
+            table_name: "users".to_string(),
+            columns: vec![
+                ColumnDefinition {
+                    primary_key: true,
+                    auto_increment: true,
+                    ..mysql_column("id", "INTEGER")
+                },
+                mysql_column("bio", "TEXT"),
+                ColumnDefinition {
+                    unique: true,
+                    nullable: false,
+                    ..mysql_column("email", "TEXT")
+                },
+                mysql_column("active", "BOOLEAN"),
+            ],
+            indexes: vec![IndexDefinition {
+                name: "idx_users_active".to_string(),
+                columns: vec!["active".to_string()],
+                unique: false,
+            }],
+            relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
+        };
+
+        let sql = SchemaGenerator::generate_create_table_sql_mysql(&schema);
+
+        assert!(sql.contains("id INTEGER NOT NULL AUTO_INCREMENT PRIMARY KEY"));
+        assert!(sql.contains("bio TEXT,"));
+        assert!(sql.contains("email VARCHAR(255) NOT NULL UNIQUE"));
+        assert!(sql.contains("active BOOLEAN"));
+        assert!(sql.contains("CREATE INDEX idx_users_active ON users (active)"));
+    }
+
+    #[test]
+    fn test_generate_create_table_sql_composite_key() {
+        let schema = TableSchema {
+            table_name: "enrollments".to_string(),
+            columns: vec![
+                ColumnDefinition {
+                    primary_key: true,
+                    ..mysql_column("student_id", "INTEGER")
+                },
+                ColumnDefinition {
+                    primary_key: true,
+                    ..mysql_column("course_id", "TEXT")
+                },
+                mysql_column("grade", "TEXT"),
+            ],
+            indexes: vec![],
+            relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
+        };
+        assert_eq!(schema.primary_key(), vec!["student_id", "course_id"]);
+
+        let sqlite = SchemaGenerator::generate_create_table_sql_sqlite(&schema);
+        assert!(sqlite.contains("PRIMARY KEY (student_id, course_id)"));
+        assert!(!sqlite.contains("student_id INTEGER PRIMARY KEY"));
+
+        let mysql = SchemaGenerator::generate_create_table_sql_mysql(&schema);
+        assert!(mysql.contains("PRIMARY KEY (student_id, course_id)"));
+        assert!(!mysql.contains("AUTO_INCREMENT"));
+    }
+
+    #[test]
+    fn test_mysql_migration() {
+        let current = TableSchema {
+            table_name: "users".to_string(),
+            columns: vec![mysql_column("age", "integer")],
+            indexes: vec![],
+            relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
+        };
+        let desired = TableSchema {
+            columns: vec![
+                ColumnDefinition {
+                    nullable: false,
+                    ..mysql_column("age", "bigint")
+                },
+                mysql_column("name", "TEXT"),
+            ],
+            ..current.clone()
+        };
+
+        let sqls = SchemaGenerator::generate_migration_sql(&current, &desired, "mysql").unwrap();
+        assert_eq!(
+            sqls,
+            [
+                "ALTER TABLE users ADD COLUMN name TEXT",
+                "ALTER TABLE users MODIFY COLUMN age BIGINT NOT NULL",
+            ]
+        );
+
+        let incompatible = TableSchema {
+            columns: vec![mysql_column("age", "date")],
+            ..current.clone()
+        };
+        assert!(SchemaGenerator::generate_migration_sql(&current, &incompatible, "mysql").is_err());
+    }
+
+    #[test]
+    fn test_renames_and_drop_policy() {
+        let current = TableSchema {
+            table_name: "users".to_string(),
+            columns: vec![
+                mysql_column("login", "TEXT"),
+                ColumnDefinition {
+                    nullable: false,
+                    ..mysql_column("nickname", "TEXT")
+                },
+            ],
+            indexes: vec![],
+            relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: Vec::new(),
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
+        };
+        let mut desired = TableSchema {
+            columns: vec![ColumnDefinition {
+                renamed_from: Some("login".to_string()),
+                ..mysql_column("username", "TEXT")
+            }],
+            ..current.clone()
+        };
+
+        let sqls = SchemaGenerator::generate_migration_sql(&current, &desired, "postgres").unwrap();
+        assert_eq!(
+            sqls,
+            [
+                "ALTER TABLE users RENAME COLUMN login TO username",
+                "ALTER TABLE users ALTER COLUMN nickname DROP NOT NULL",
+            ]
+        );
+        let sqls = SchemaGenerator::generate_migration_sql(&current, &desired, "mysql").unwrap();
+        assert_eq!(sqls[1], "ALTER TABLE users MODIFY COLUMN nickname TEXT");
+
+        desired.drop_columns = DropPolicy::Drop;
+        let sqls = SchemaGenerator::generate_migration_sql(&current, &desired, "postgres").unwrap();
+        assert_eq!(sqls[1], "ALTER TABLE users DROP COLUMN nickname");
+
+        desired.drop_columns = DropPolicy::Fail;
+        let err = SchemaGenerator::generate_migration_sql(&current, &desired, "mysql").unwrap_err();
+        assert!(err.contains("'nickname'"));
+
+        // Once renamed, the column is left alone
+        let renamed = TableSchema {
+            drop_columns: DropPolicy::Keep,
+            ..desired.clone()
+        };
+        assert!(
+            SchemaGenerator::generate_migration_sql(&renamed, &desired, "postgres")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_foreign_keys_from_relations() {
+        let ok = serde_json::json!({ "responses": { "200": { "description": "ok" } } });
+        let spec = serde_json::json!({
+            "x-table-schemas": [{
+                "tableName": "authors",
+                "columns": [{ "name": "id", "columnType": "INTEGER", "primaryKey": true }]
+            }, {
+                "tableName": "books",
+                "columns": [
+                    { "name": "id", "columnType": "INTEGER", "primaryKey": true },
+                    { "name": "author_id", "columnType": "INTEGER", "nullable": true },
+                    { "name": "editor", "columnType": "TEXT" }
+                ]
+            }],
+            "paths": {
+                "/authors": {
+                    "post": {
+                        "requestBody": { "content": { "application/json": { "schema": {
+                            "type": "object",
+                            "properties": {
+                                "books": {
+                                    "type": "array",
+                                    "x-relation": {
+                                        "type": "hasMany", "target": "Book",
+                                        "foreignKey": "author_id", "onDelete": "set null"
+                                    }
+                                },
+                                "editor": {
+                                    "type": "object",
+                                    "x-relation": {
+                                        "type": "belongsTo", "target": "Book", "foreignKey": "editor"
+                                    }
+                                }
+                            }
+                        } } } },
+                        "responses": { "200": { "description": "ok" } }
+                    }
+                },
+                "/books": { "get": ok }
+            }
+        });
+        let schemas = SchemaGenerator::extract_schemas_from_openapi(&spec).unwrap();
+        let books = schemas.iter().find(|s| s.table_name == "books").unwrap();
+        assert_eq!(
+            books.foreign_keys,
+            [ForeignKeyDefinition {
+                name: "fk_books_author_id".to_string(),
+                column: "author_id".to_string(),
+                references_table: "authors".to_string(),
+                references_column: "id".to_string(),
+                on_delete: OnDelete::SetNull,
+            }]
+        );
+        assert_eq!(books.indexes[0].name, "idx_books_author_id");
+        // authors.editor does not exist, so the belongsTo relation declares no key
+        let authors = schemas.iter().find(|s| s.table_name == "authors").unwrap();
+        assert!(authors.foreign_keys.is_empty());
+
+        let sql = SchemaGenerator::generate_create_table_sql_postgres(books);
+        assert!(sql.contains(
+            "CONSTRAINT fk_books_author_id FOREIGN KEY (author_id) REFERENCES authors (id) ON DELETE SET NULL"
+        ));
+        assert!(
+            sql.contains("CREATE INDEX IF NOT EXISTS idx_books_author_id ON books (author_id)")
+        );
+
+        // Changing the action replaces the constraint; adding one also adds its index
+        let mut current = books.clone();
+        current.foreign_keys[0].on_delete = OnDelete::Cascade;
+        let sqls = SchemaGenerator::generate_migration_sql(&current, books, "mysql").unwrap();
+        assert_eq!(
+            sqls,
+            [
+                "ALTER TABLE books DROP FOREIGN KEY fk_books_author_id",
+                "ALTER TABLE books ADD CONSTRAINT fk_books_author_id FOREIGN KEY (author_id) REFERENCES authors (id) ON DELETE SET NULL",
+            ]
+        );
+        current.foreign_keys.clear();
+        current.indexes.clear();
+        let sqls = SchemaGenerator::generate_migration_sql(&current, books, "postgres").unwrap();
+        assert_eq!(
+            sqls,
+            [
+                "CREATE INDEX IF NOT EXISTS idx_books_author_id ON books (author_id)",
+                "ALTER TABLE books ADD CONSTRAINT fk_books_author_id FOREIGN KEY (author_id) REFERENCES authors (id) ON DELETE SET NULL",
+            ]
+        );
+        // SQLite cannot alter constraints and recreates the table
+        let sqls = SchemaGenerator::generate_migration_sql(&current, books, "sqlite").unwrap();
+        assert!(sqls.contains(&"DROP TABLE books".to_string()));
+    }
+
+    #[test]
+    fn test_constraints_from_schema_keywords() {
+        let spec = serde_json::json!({
+            "components": { "schemas": { "Product": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "id": { "type": "integer" },
+                    "name": { "type": "string", "maxLength": 80 },
+                    "status": { "type": "string", "enum": ["draft", "live"], "default": "draft" },
+                    "price": { "type": "number", "multipleOf": 0.01, "minimum": 0 },
+                    "rating": { "type": "integer", "minimum": 1, "exclusiveMaximum": 6 },
+                    "active": { "type": "boolean", "default": true },
+                    "sku": { "type": "string", "format": "uuid" },
+                    "tags": { "type": "array", "items": { "type": "string" } }
+                }
+            } } }
+        });
+        let schemas = SchemaGenerator::extract_schemas_from_openapi(&spec).unwrap();
+        let products = &schemas[0];
+        let column = |name: &str| products.columns.iter().find(|c| c.name == name).unwrap();
+        assert_eq!(column("name").column_type, "VARCHAR(80)");
+        assert_eq!(column("status").default_value.as_deref(), Some("'draft'"));
+        assert_eq!(column("price").column_type, "NUMERIC(18,2)");
+        assert_eq!(column("active").column_type, "BOOLEAN");
+        assert_eq!(column("active").default_value.as_deref(), Some("TRUE"));
+        assert_eq!(column("sku").column_type, "UUID");
+        assert_eq!(column("tags").column_type, "JSON");
+        let expressions: Vec<&str> = products
+            .checks
+            .iter()
+            .map(|c| c.expression.as_str())
+            .collect();
+        assert_eq!(
+            expressions,
+            [
+                "price >= 0",
+                "rating >= 1 AND rating < 6",
+                "status IN ('draft', 'live')"
+            ]
+        );
+        let status = products.checks[2].clone();
+        assert!(status.name.starts_with("ck_products_status_"));
+
+        let sql = SchemaGenerator::generate_create_table_sql_postgres(products);
+        assert!(sql.contains("    sku UUID,"));
+        assert!(sql.contains("    tags JSONB,"));
+        assert!(sql.contains("    price NUMERIC(18,2),"));
+        assert!(sql.contains(&format!(
+            "CONSTRAINT {} CHECK (status IN ('draft', 'live'))",
+            status.name
+        )));
+        let sql = SchemaGenerator::generate_create_table_sql_mysql(products);
+        assert!(sql.contains("    sku CHAR(36),"));
+        assert!(sql.contains("    price DECIMAL(18,2),"));
+        let sql = SchemaGenerator::generate_create_table_sql_sqlite(products);
+        assert!(sql.contains("    active INTEGER DEFAULT TRUE,"));
+
+        // A table from before: integer booleans, no default and another enum. Defaults
+        // as Postgres reports them are left alone.
+        let mut current = products.clone();
+        current
+            .columns
+            .iter_mut()
+            .for_each(|c| match c.name.as_str() {
+                "active" => {
+                    c.column_type = "integer".to_string();
+                    c.default_value = None;
+                }
+                "status" => c.default_value = Some("'draft'::character varying".to_string()),
+                _ => {}
+            });
+        current.checks[2] = CheckDefinition {
+            name: "ck_products_status_00000000".to_string(),
+            expression: "status IN ('draft')".to_string(),
+        };
+        let sqls = SchemaGenerator::generate_migration_sql(&current, products, "postgres").unwrap();
+        assert_eq!(
+            sqls,
+            [
+                "ALTER TABLE products DROP CONSTRAINT ck_products_status_00000000".to_string(),
+                "ALTER TABLE products ALTER COLUMN active TYPE BOOLEAN USING active::BOOLEAN"
+                    .to_string(),
+                "ALTER TABLE products ALTER COLUMN active SET DEFAULT TRUE".to_string(),
+                format!(
+                    "ALTER TABLE products ADD CONSTRAINT {} CHECK (status IN ('draft', 'live'))",
+                    status.name
+                ),
+            ]
+        );
+        // SQLite cannot alter constraints and recreates the table
+        current.columns.iter_mut().for_each(|c| {
+            c.column_type = SchemaGenerator::map_type_to_sqlite(&c.column_type).to_string()
+        });
+        let sqls = SchemaGenerator::generate_migration_sql(&current, products, "sqlite").unwrap();
+        assert!(sqls.contains(&"DROP TABLE products".to_string()));
+    }
+
+    #[test]
+    fn test_search_index_sql() {
+        let schema = TableSchema {
+            table_name: "articles".to_string(),
+            columns: vec![mysql_column("title", "TEXT"), mysql_column("body", "TEXT")],
+            indexes: vec![],
+            relations: vec![],
+            default_sort: None,
+            cursor_column: None,
+            soft_delete: None,
+            version_column: None,
+            search_columns: vec!["title".to_string(), "body".to_string()],
+            drop_columns: DropPolicy::Keep,
+            foreign_keys: vec![],
+            checks: vec![],
+        };
+
+        assert_eq!(
+            SchemaGenerator::generate_search_sql_postgres(&schema),
+            [
+                "CREATE INDEX IF NOT EXISTS idx_articles_search ON articles USING GIN \
+                 (to_tsvector('simple', coalesce(title, '') || ' ' || coalesce(body, '')))",
+                "COMMENT ON INDEX idx_articles_search IS 'title,body'",
+            ]
+        );
+        assert_eq!(
+            SchemaGenerator::generate_search_sql_mysql(&schema),
+            ["CREATE FULLTEXT INDEX idx_articles_search ON articles (title, body)"]
+        );
+
+        let sqlite = SchemaGenerator::generate_search_sql_sqlite(&schema);
+        assert_eq!(
+            sqlite[0],
+            "CREATE VIRTUAL TABLE IF NOT EXISTS articles_fts USING fts5(title, body, \
+             content='articles', content_rowid='rowid')"
+        );
+        assert!(sqlite[3].contains(
+            "INSERT INTO articles_fts(articles_fts, rowid, title, body) \
+             VALUES ('delete', old.rowid, old.title, old.body);"
+        ));
+    }
 }
@@ -0,0 +1,97 @@
+//! Helpers shared by the integration tests that run APIs against a SQLite file
+
+#![allow(dead_code)]
+
+use apify::app_state::{AppState, AppStateConfig, OpenApiStateConfig};
+use apify::config::{DatabaseSettings, OpenAPIConfig, OpenAPISettings};
+use apify::crud_handler::{CRUDError, CRUDHandler};
+use apify::hyper::{HeaderMap, Method, Uri};
+use apify::phases::RequestContext;
+use serde_json::Value;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+/// State serving the APIs of `spec` from the SQLite database `file` in `temp`
+pub async fn sqlite_state(temp: &TempDir, file: &str, spec: Value) -> AppState {
+    let mut datasources = HashMap::new();
+    datasources.insert(
+        "main".to_string(),
+        DatabaseSettings {
+            driver: "sqlite".to_string(),
+            host: None,
+            port: None,
+            user: None,
+            password: None,
+            database: temp.path().join(file).to_string_lossy().to_string(),
+            ssl_mode: None,
+            max_pool_size: Some(2),
+            migrations: None,
+        },
+    );
+    AppState::new_with_crud(AppStateConfig {
+        routes: None,
+        datasources: Some(datasources),
+        openapi_configs: vec![OpenApiStateConfig {
+            config: OpenAPIConfig {
+                openapi: OpenAPISettings {
+                    spec,
+                    validation: None,
+                },
+            },
+            modules: None,
+            datasource: Some("main".to_string()),
+            access_log: None,
+            listeners: None,
+        }],
+        listener_modules: None,
+        auth_config: None,
+        public_url: None,
+        access_log_config: None,
+        control_plane_db: None,
+        control_plane_config: None,
+    })
+    .await
+    .unwrap()
+}
+
+/// Context of a request for `path` (which may carry a query string)
+pub fn context(method: &str, path: &str, headers: HeaderMap) -> RequestContext {
+    RequestContext::new(
+        method.parse::<Method>().unwrap(),
+        path.parse::<Uri>().unwrap(),
+        headers,
+        None,
+    )
+}
+
+/// Dispatch a request without headers
+pub async fn request(
+    crud: &CRUDHandler,
+    method: &str,
+    path: &str,
+    body: Option<Value>,
+) -> Result<Value, CRUDError> {
+    dispatch(crud, &context(method, path, HeaderMap::new()), body).await
+}
+
+/// Dispatch like the server does: path parameters come from the matched route and
+/// query parameters from the URI
+pub async fn dispatch(
+    crud: &CRUDHandler,
+    ctx: &RequestContext,
+    body: Option<Value>,
+) -> Result<Value, CRUDError> {
+    let method = ctx.method.as_str();
+    let path = ctx.uri.path();
+    let path_params = crud
+        .api_generator
+        .match_operation(method, path)
+        .map(|pattern| crud.api_generator.extract_path_params(&pattern, path))
+        .unwrap_or_default();
+    let query_params: HashMap<String, String> =
+        form_urlencoded::parse(ctx.uri.query().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+    crud.handle_request(method, path, path_params, query_params, body, ctx)
+        .await
+}
@@ -0,0 +1,366 @@
+//! Bulk inserts, upserts and filtered bulk updates/deletes against a SQLite datasource
+
+mod common;
+
+use apify::app_state::AppState;
+use apify::crud_handler::{CRUDError, CRUDHandler};
+use apify::hyper::HeaderMap;
+use apify::hyper::header::{HeaderValue, IF_MATCH};
+use common::request;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+async fn catalog_state(temp: &TempDir) -> AppState {
+    let product_ref = json!({
+        "type": "array",
+        "items": { "type": "object" },
+        "x-relation": { "type": "hasMany", "target": "Product", "foreignKey": "category_id" }
+    });
+    let ok = json!({ "200": { "description": "ok" } });
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Catalog", "version": "1.0.0" },
+        "x-table-schemas": [{
+            "tableName": "categories",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "name", "columnType": "TEXT" }
+            ]
+        }, {
+            "tableName": "products",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "sku", "columnType": "TEXT", "unique": true },
+                { "name": "name", "columnType": "TEXT", "nullable": true },
+                { "name": "price", "columnType": "REAL" },
+                { "name": "active", "columnType": "INTEGER", "nullable": true, "defaultValue": "1" },
+                { "name": "category_id", "columnType": "INTEGER", "nullable": true }
+            ]
+        }],
+        "paths": {
+            "/categories": {
+                "post": {
+                    "requestBody": { "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" }, "products": product_ref }
+                    } } } },
+                    "responses": ok
+                },
+                "delete": { "responses": ok }
+            },
+            "/products": {
+                "get": { "responses": ok },
+                "post": { "responses": ok },
+                "put": { "responses": ok },
+                "patch": { "responses": ok },
+                "delete": { "responses": ok }
+            }
+        }
+    });
+
+    common::sqlite_state(temp, "bulk.sqlite", spec).await
+}
+
+/// `sku -> (price, active)` of every product
+async fn products(crud: &CRUDHandler) -> Vec<(String, f64, i64)> {
+    let rows = request(crud, "GET", "/products?sort=sku", None)
+        .await
+        .unwrap();
+    rows.as_array()
+        .unwrap()
+        .iter()
+        .map(|r| {
+            (
+                r["sku"].as_str().unwrap().to_string(),
+                r["price"].as_f64().unwrap(),
+                r["active"].as_i64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn array_body_inserts_every_row() {
+    let temp = TempDir::new().unwrap();
+    let state = catalog_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    let rows: Vec<Value> = (1..=250)
+        .map(|i| json!({ "sku": format!("SKU-{:03}", i), "name": "Widget", "price": i }))
+        .chain([json!({ "sku": "SKU-999", "name": "Retired", "price": 1, "active": 0 })])
+        .collect();
+    let result = request(crud, "POST", "/products", Some(Value::Array(rows)))
+        .await
+        .unwrap();
+    assert_eq!(result["affected_rows"], 251);
+
+    let stored = products(crud).await;
+    assert_eq!(stored.len(), 251);
+    // Omitted columns keep their defaults
+    assert_eq!(stored[0], ("SKU-001".to_string(), 1.0, 1));
+    assert_eq!(stored[250], ("SKU-999".to_string(), 1.0, 0));
+
+    // One bad row rolls back the whole batch
+    let rows = json!([{ "sku": "SKU-A", "price": 1 }, { "sku": "SKU-001", "price": 2 }]);
+    assert!(
+        request(crud, "POST", "/products", Some(rows))
+            .await
+            .is_err()
+    );
+    assert_eq!(products(crud).await.len(), 251);
+
+    let unknown = json!([{ "sku": "SKU-B", "colour": "red" }]);
+    assert!(matches!(
+        request(crud, "POST", "/products", Some(unknown)).await,
+        Err(CRUDError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn on_conflict_updates_existing_rows() {
+    let temp = TempDir::new().unwrap();
+    let state = catalog_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    let rows = json!([
+        { "sku": "A", "name": "Apple", "price": 1.0 },
+        { "sku": "B", "name": "Banana", "price": 2.0 }
+    ]);
+    request(crud, "POST", "/products", Some(rows))
+        .await
+        .unwrap();
+
+    let rows = json!([
+        { "sku": "B", "name": "Banana", "price": 2.5 },
+        { "sku": "C", "name": "Cherry", "price": 3.0 }
+    ]);
+    request(crud, "POST", "/products?on_conflict=sku", Some(rows))
+        .await
+        .unwrap();
+    assert_eq!(
+        products(crud).await,
+        vec![
+            ("A".to_string(), 1.0, 1),
+            ("B".to_string(), 2.5, 1),
+            ("C".to_string(), 3.0, 1),
+        ]
+    );
+
+    // A single object comes back as the stored record
+    let record = request(
+        crud,
+        "POST",
+        "/products?on_conflict=sku",
+        Some(json!({ "sku": "A", "price": 1.5 })),
+    )
+    .await
+    .unwrap();
+    assert_eq!(record["name"], "Apple");
+    assert_eq!(record["price"], 1.5);
+
+    // PUT on the collection upserts on the primary key
+    let id = record["id"].clone();
+    request(
+        crud,
+        "PUT",
+        "/products",
+        Some(json!([{ "id": id, "sku": "A", "name": "Apricot", "price": 1.5 }])),
+    )
+    .await
+    .unwrap();
+    let rows = request(crud, "GET", "/products?sku=A", None).await.unwrap();
+    assert_eq!(rows[0]["name"], "Apricot");
+    assert_eq!(products(crud).await.len(), 3);
+
+    // Conflicts can only be resolved on unique columns
+    assert!(matches!(
+        request(
+            crud,
+            "POST",
+            "/products?on_conflict=name",
+            Some(json!([{ "sku": "D", "name": "Apple" }]))
+        )
+        .await,
+        Err(CRUDError::InvalidParameterError(_))
+    ));
+}
+
+#[tokio::test]
+async fn filtered_patch_and_delete() {
+    let temp = TempDir::new().unwrap();
+    let state = catalog_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    let rows: Vec<Value> = (1..=5)
+        .map(|i| json!({ "sku": format!("S{}", i), "name": "Item", "price": i }))
+        .collect();
+    request(crud, "POST", "/products", Some(Value::Array(rows)))
+        .await
+        .unwrap();
+
+    let result = request(
+        crud,
+        "PATCH",
+        "/products?price[lte]=2",
+        Some(json!({ "active": 0 })),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["affected_rows"], 2);
+    let inactive: Vec<String> = products(crud)
+        .await
+        .into_iter()
+        .filter(|(_, _, active)| *active == 0)
+        .map(|(sku, _, _)| sku)
+        .collect();
+    assert_eq!(inactive, vec!["S1", "S2"]);
+
+    let result = request(crud, "DELETE", "/products?active=0", None)
+        .await
+        .unwrap();
+    assert_eq!(result["affected_rows"], 2);
+    assert_eq!(products(crud).await.len(), 3);
+
+    // Without a filter nothing is touched
+    assert!(matches!(
+        request(crud, "DELETE", "/products", None).await,
+        Err(CRUDError::InvalidParameterError(_))
+    ));
+    assert!(matches!(
+        request(crud, "PATCH", "/products", Some(json!({ "active": 0 }))).await,
+        Err(CRUDError::InvalidParameterError(_))
+    ));
+    assert_eq!(products(crud).await.len(), 3);
+}
+
+#[tokio::test]
+async fn bulk_delete_cascades_to_children() {
+    let temp = TempDir::new().unwrap();
+    let state = catalog_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    // Rows with nested relations are created one by one
+    let categories = json!([
+        { "name": "fruit", "products": [{ "sku": "F1", "price": 1 }, { "sku": "F2", "price": 2 }] },
+        { "name": "tools", "products": [{ "sku": "T1", "price": 3 }] }
+    ]);
+    let result = request(crud, "POST", "/categories", Some(categories))
+        .await
+        .unwrap();
+    assert_eq!(result["affected_rows"], 2);
+    assert_eq!(products(crud).await.len(), 3);
+
+    request(crud, "DELETE", "/categories?name=fruit", None)
+        .await
+        .unwrap();
+    let remaining: Vec<String> = products(crud)
+        .await
+        .into_iter()
+        .map(|(sku, _, _)| sku)
+        .collect();
+    assert_eq!(remaining, vec!["T1"]);
+}
+
+#[tokio::test]
+async fn upserts_bump_the_version() {
+    let temp = TempDir::new().unwrap();
+    let ok = json!({ "200": { "description": "ok" } });
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Documents", "version": "1.0.0" },
+        "x-table-schemas": [{
+            "tableName": "documents",
+            "versionColumn": "version",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "slug", "columnType": "TEXT", "unique": true },
+                { "name": "title", "columnType": "TEXT" }
+            ]
+        }],
+        "paths": {
+            "/documents": { "post": { "responses": ok } },
+            "/documents/{id}": { "get": { "responses": ok }, "patch": { "responses": ok } }
+        }
+    });
+    let state = common::sqlite_state(&temp, "versions.sqlite", spec).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    let created = request(
+        crud,
+        "POST",
+        "/documents?on_conflict=slug",
+        Some(json!({ "slug": "intro", "title": "Intro", "version": 9 })),
+    )
+    .await
+    .unwrap();
+    assert_eq!(created["version"], 1);
+    let stale = crud.record_etag("documents", &created).unwrap();
+
+    // The conflicting row is updated with its version incremented, whatever the client sent
+    let upserted = request(
+        crud,
+        "POST",
+        "/documents?on_conflict=slug",
+        Some(json!({ "slug": "intro", "title": "Introduction", "version": 1 })),
+    )
+    .await
+    .unwrap();
+    assert_eq!(upserted["title"], "Introduction");
+    assert_eq!(upserted["version"], 2);
+
+    let path = format!("/documents/{}", created["id"]);
+    let mut headers = HeaderMap::new();
+    headers.insert(IF_MATCH, HeaderValue::from_str(&stale).unwrap());
+    let result = common::dispatch(
+        crud,
+        &common::context("PATCH", &path, headers),
+        Some(json!({ "title": "Lost update" })),
+    )
+    .await;
+    assert!(matches!(result, Err(CRUDError::PreconditionFailedError(_))));
+    let record = request(crud, "GET", &path, None).await.unwrap();
+    assert_eq!(record["title"], "Introduction");
+}
+
+#[tokio::test]
+async fn boolean_columns_read_back_as_booleans() {
+    let temp = TempDir::new().unwrap();
+    let ok = json!({ "200": { "description": "ok" } });
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Flags", "version": "1.0.0" },
+        "x-table-schemas": [{
+            "tableName": "flags",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "name", "columnType": "TEXT" },
+                { "name": "enabled", "columnType": "BOOLEAN", "nullable": true }
+            ]
+        }],
+        "paths": {
+            "/flags": { "get": { "responses": ok }, "post": { "responses": ok } },
+            "/flags/{id}": { "get": { "responses": ok } }
+        }
+    });
+    let state = common::sqlite_state(&temp, "flags.sqlite", spec).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    let rows = json!([
+        { "name": "on", "enabled": true },
+        { "name": "off", "enabled": false },
+        { "name": "unset" }
+    ]);
+    request(crud, "POST", "/flags", Some(rows)).await.unwrap();
+    let flags = request(crud, "GET", "/flags?sort=id", None).await.unwrap();
+    let enabled: Vec<&Value> = flags
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| &f["enabled"])
+        .collect();
+    assert_eq!(enabled, [&json!(true), &json!(false), &Value::Null]);
+    let flag = request(crud, "GET", &format!("/flags/{}", flags[0]["id"]), None)
+        .await
+        .unwrap();
+    assert_eq!(flag["enabled"], true);
+}
@@ -50,6 +50,7 @@ async fn users_crud_flow() -> Result<(), Box<dyn std::error::Error>> {
         columns:
           - { name: "id", columnType: "INTEGER", nullable: false, primaryKey: true, unique: false, autoIncrement: true, defaultValue: null }
           - { name: "name", columnType: "TEXT", nullable: false, primaryKey: false, unique: false, autoIncrement: false, defaultValue: null }
+          - { name: "email", columnType: "TEXT", nullable: true, primaryKey: false, unique: true, autoIncrement: false, defaultValue: null }
         indexes: []
     paths:
       /users:
@@ -68,7 +69,13 @@ async fn users_crud_flow() -> Result<(), Box<dyn std::error::Error>> {
           responses: { "200": { description: "ok" } }
         put:
           operationId: updateUser
-          x-modules: { access: ["key_auth"] }
+          requestBody:
+            content:
+              application/json:
+                schema:
+                  type: object
+                  required: ["name"]
+                  properties: { name: { type: "string" }, email: { type: "string" } }
           responses: { "200": { description: "ok" } }
         delete:
           operationId: deleteUser
@@ -127,10 +134,40 @@ apis:
     let r = client
         .post(format!("{}/users", base))
         .header(key.0, key.1)
-        .json(&serde_json::json!({"name":"Alice"}))
+        .json(&serde_json::json!({"name":"Alice", "email": "alice@example.com"}))
         .send()
         .await?;
-    assert_eq!(r.status(), 200);
+    assert_eq!(r.status(), 201);
+    let location = r.headers()["location"].to_str()?.to_string();
+    let created: Value = r.json().await?;
+    assert_eq!(created["name"], "Alice");
+    assert_eq!(location, format!("/users/{}", created["id"]));
+
+    // Constraint violations are client errors, reported as problem details
+    let r = client
+        .post(format!("{}/users", base))
+        .header(key.0, key.1)
+        .header("X-Request-Id", "dup-email-1")
+        .json(&serde_json::json!({"name":"Alicia", "email": "alice@example.com"}))
+        .send()
+        .await?;
+    assert_eq!(r.status(), 409);
+    assert_eq!(r.headers()["content-type"], "application/problem+json");
+    assert_eq!(r.headers()["x-request-id"], "dup-email-1");
+    let body: Value = r.json().await?;
+    assert_eq!(body["type"], "about:blank");
+    assert_eq!(body["title"], "Conflict");
+    assert_eq!(body["status"], 409);
+    assert_eq!(body["instance"], "/users");
+    assert_eq!(body["request_id"], "dup-email-1");
+    assert_eq!(body["constraint"]["type"], "unique");
+    let r = client
+        .post(format!("{}/users", base))
+        .header(key.0, key.1)
+        .json(&serde_json::json!({"email": "anon@example.com"}))
+        .send()
+        .await?;
+    assert_eq!(r.status(), 422);
 
     // List and pick the inserted id
     let r = client
@@ -170,6 +207,26 @@ apis:
         .await?;
     assert_eq!(r.status(), 200);
 
+    // Schema violations point at the offending fields
+    let r = client
+        .put(format!("{}/users/{}", base, id))
+        .header(key.0, key.1)
+        .json(&serde_json::json!({"email": 7}))
+        .send()
+        .await?;
+    assert_eq!(r.status(), 400);
+    assert!(r.headers().contains_key("x-request-id"));
+    let body: Value = r.json().await?;
+    let mut pointers: Vec<&str> = body["errors"]
+        .as_array()
+        .expect("errors array")
+        .iter()
+        .filter_map(|e| e["pointer"].as_str())
+        .collect();
+    pointers.sort();
+    assert_eq!(pointers, vec!["/email", "/name"]);
+    assert!(body["request_id"].is_string());
+
     // Verify
     let r = client
         .get(format!("{}/users/{}", base, id))
@@ -186,7 +243,8 @@ apis:
         .header(key.0, key.1)
         .send()
         .await?;
-    assert_eq!(r.status(), 200);
+    assert_eq!(r.status(), 204);
+    assert!(r.bytes().await?.is_empty());
 
     // Ensure gone
     let r = client
@@ -199,3 +257,248 @@ apis:
     let _ = child.kill().await;
     Ok(())
 }
+
+#[tokio::test]
+#[serial]
+async fn users_list_pagination_headers() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    let dir = temp.path();
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let port = listener.local_addr()?.port();
+    drop(listener);
+    let db_file = dir.join("paging.sqlite");
+
+    let users_spec = r#"openapi:
+  spec:
+    openapi: "3.0.0"
+    info: { title: "Users", version: "1.0.0" }
+    x-pagination: headers
+    x-table-schemas:
+      - tableName: "users"
+        columns:
+          - { name: "id", columnType: "INTEGER", nullable: false, primaryKey: true, autoIncrement: true }
+          - { name: "name", columnType: "TEXT", nullable: false }
+    paths:
+      /users:
+        get:
+          operationId: listUsers
+          responses: { "200": { description: "ok" } }
+        post:
+          operationId: createUser
+          responses: { "200": { description: "ok" } }
+"#;
+    fs::write(dir.join("users.yaml"), users_spec)?;
+
+    let cfg = format!(
+        r#"datasource:
+  test_db:
+    driver: sqlite
+    database: {}
+    max_pool_size: 5
+listeners:
+  - name: default
+    port: {port}
+    ip: 127.0.0.1
+    protocol: HTTP
+apis:
+  - path: ./users.yaml
+    datasource: test_db
+    listeners: [default]
+"#,
+        db_file.display()
+    );
+    let cfg_path = dir.join("config.yaml");
+    fs::write(&cfg_path, cfg)?;
+
+    let bin = assert_cmd::cargo::cargo_bin!("apify");
+    let db_url = format!("sqlite:{}", db_file.display());
+    let mut child = TokioCommand::new(bin)
+        .env("APIFY_DB_URL", &db_url)
+        .env("APIFY_THREADS", "1")
+        .arg("-c")
+        .arg(cfg_path.to_string_lossy().to_string())
+        .spawn()?;
+
+    wait_for_ready("127.0.0.1", port, Duration::from_secs(8)).await?;
+    let client = Client::builder().no_proxy().build()?;
+    let base = format!("http://127.0.0.1:{}", port);
+
+    for name in ["Alice", "Bob", "Carol"] {
+        let r = client
+            .post(format!("{}/users", base))
+            .json(&serde_json::json!({ "name": name }))
+            .send()
+            .await?;
+        assert!(r.status().is_success());
+    }
+
+    let r = client
+        .get(format!("{}/users?limit=2&sort=name", base))
+        .send()
+        .await?;
+    assert_eq!(r.status(), 200);
+    assert_eq!(r.headers()["x-total-count"], "3");
+    assert_eq!(
+        r.headers()["link"],
+        "</users?limit=2&sort=name&offset=2>; rel=\"next\""
+    );
+    let arr: Value = r.json().await?;
+    assert_eq!(arr.as_array().map(|a| a.len()), Some(2));
+
+    let _ = child.kill().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn users_conditional_requests() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    let dir = temp.path();
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let port = listener.local_addr()?.port();
+    drop(listener);
+    let db_file = dir.join("etag.sqlite");
+
+    let users_spec = r#"openapi:
+  spec:
+    openapi: "3.0.0"
+    info: { title: "Users", version: "1.0.0" }
+    x-table-schemas:
+      - tableName: "users"
+        versionColumn: "version"
+        columns:
+          - { name: "id", columnType: "INTEGER", nullable: false, primaryKey: true, autoIncrement: true }
+          - { name: "name", columnType: "TEXT", nullable: false }
+    paths:
+      /users:
+        get:
+          operationId: listUsers
+          responses: { "200": { description: "ok" } }
+        post:
+          operationId: createUser
+          responses: { "200": { description: "ok" } }
+      /users/{id}:
+        get:
+          operationId: getUser
+          responses: { "200": { description: "ok" } }
+        patch:
+          operationId: updateUser
+          responses: { "200": { description: "ok" } }
+        delete:
+          operationId: deleteUser
+          responses: { "200": { description: "ok" } }
+"#;
+    fs::write(dir.join("users.yaml"), users_spec)?;
+
+    let cfg = format!(
+        r#"datasource:
+  test_db:
+    driver: sqlite
+    database: {}
+    max_pool_size: 5
+listeners:
+  - name: default
+    port: {port}
+    ip: 127.0.0.1
+    protocol: HTTP
+apis:
+  - path: ./users.yaml
+    datasource: test_db
+    listeners: [default]
+"#,
+        db_file.display()
+    );
+    let cfg_path = dir.join("config.yaml");
+    fs::write(&cfg_path, cfg)?;
+
+    let bin = assert_cmd::cargo::cargo_bin!("apify");
+    let db_url = format!("sqlite:{}", db_file.display());
+    let mut child = TokioCommand::new(bin)
+        .env("APIFY_DB_URL", &db_url)
+        .env("APIFY_THREADS", "1")
+        .arg("-c")
+        .arg(cfg_path.to_string_lossy().to_string())
+        .spawn()?;
+
+    wait_for_ready("127.0.0.1", port, Duration::from_secs(8)).await?;
+    let client = Client::builder().no_proxy().build()?;
+    let base = format!("http://127.0.0.1:{}", port);
+
+    let r = client
+        .post(format!("{}/users", base))
+        .json(&serde_json::json!({ "name": "Alice" }))
+        .send()
+        .await?;
+    assert!(r.status().is_success());
+    let created: Value = r.json().await?;
+    let user_url = format!("{}/users/{}", base, created["id"]);
+
+    // Versioned records are tagged with their version, starting at 1
+    let r = client.get(&user_url).send().await?;
+    assert_eq!(r.status(), 200);
+    assert_eq!(r.headers()["etag"], "\"1\"");
+    let r = client
+        .get(&user_url)
+        .header("If-None-Match", "\"1\"")
+        .send()
+        .await?;
+    assert_eq!(r.status(), 304);
+    assert_eq!(r.headers()["etag"], "\"1\"");
+
+    // A matching If-Match updates the record and bumps the version
+    let r = client
+        .patch(&user_url)
+        .header("If-Match", "\"1\"")
+        .json(&serde_json::json!({ "name": "Alicia", "version": 7 }))
+        .send()
+        .await?;
+    assert_eq!(r.status(), 200);
+    let r = client.get(&user_url).send().await?;
+    assert_eq!(r.headers()["etag"], "\"2\"");
+    let body: Value = r.json().await?;
+    assert_eq!(body["name"], "Alicia");
+    assert_eq!(body["version"], 2);
+
+    // A stale version is rejected and leaves the record alone
+    let r = client
+        .patch(&user_url)
+        .header("If-Match", "\"1\"")
+        .json(&serde_json::json!({ "name": "Mallory" }))
+        .send()
+        .await?;
+    assert_eq!(r.status(), 412);
+    let r = client
+        .delete(&user_url)
+        .header("If-Match", "\"1\"")
+        .send()
+        .await?;
+    assert_eq!(r.status(), 412);
+    let body: Value = client.get(&user_url).send().await?.json().await?;
+    assert_eq!(body["name"], "Alicia");
+
+    // Lists are tagged by content
+    let r = client.get(format!("{}/users", base)).send().await?;
+    let list_etag = r.headers()["etag"].to_str()?.to_string();
+    let r = client
+        .get(format!("{}/users", base))
+        .header("If-None-Match", &list_etag)
+        .send()
+        .await?;
+    assert_eq!(r.status(), 304);
+
+    let r = client
+        .delete(&user_url)
+        .header("If-Match", "\"2\"")
+        .send()
+        .await?;
+    assert_eq!(r.status(), 204);
+    let r = client
+        .get(format!("{}/users", base))
+        .header("If-None-Match", &list_etag)
+        .send()
+        .await?;
+    assert_eq!(r.status(), 200);
+
+    let _ = child.kill().await;
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Verifies that APIs sharing a listener are served by their own datasources
+
+use apify::app_state::{AppState, AppStateConfig, OpenApiStateConfig};
+use apify::config::{DatabaseSettings, OpenAPIConfig, OpenAPISettings};
+use apify::database::{DatabaseManager, DatabaseRuntimeConfig};
+use apify::hyper::{HeaderMap, Method, Uri};
+use apify::phases::RequestContext;
+use serde_json::json;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+fn sqlite_settings(path: &std::path::Path) -> DatabaseSettings {
+    DatabaseSettings {
+        driver: "sqlite".to_string(),
+        host: None,
+        port: None,
+        user: None,
+        password: None,
+        database: path.to_string_lossy().to_string(),
+        ssl_mode: None,
+        max_pool_size: Some(2),
+        migrations: None,
+    }
+}
+
+fn api_config(table: &str, datasource: &str) -> OpenApiStateConfig {
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": table, "version": "1.0.0" },
+        "x-table-schemas": [{
+            "tableName": table,
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "name", "columnType": "TEXT", "nullable": true }
+            ]
+        }],
+        "paths": {
+            format!("/{}", table): {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "post": { "responses": { "200": { "description": "ok" } } }
+            }
+        }
+    });
+    OpenApiStateConfig {
+        config: OpenAPIConfig {
+            openapi: OpenAPISettings {
+                spec,
+                validation: None,
+            },
+        },
+        modules: None,
+        datasource: Some(datasource.to_string()),
+        access_log: None,
+        listeners: None,
+    }
+}
+
+fn ctx(method: Method, path: &str) -> RequestContext {
+    RequestContext::new(method, path.parse::<Uri>().unwrap(), HeaderMap::new(), None)
+}
+
+async fn open(path: &std::path::Path) -> DatabaseManager {
+    DatabaseManager::new(DatabaseRuntimeConfig {
+        driver: "sqlite".to_string(),
+        url: format!("sqlite:{}", path.display()),
+        max_size: 1,
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn apis_on_one_listener_use_their_own_datasources() {
+    let temp = TempDir::new().unwrap();
+    let billing_db = temp.path().join("billing.sqlite");
+    let flags_db = temp.path().join("flags.sqlite");
+
+    let mut datasources = HashMap::new();
+    datasources.insert("billing".to_string(), sqlite_settings(&billing_db));
+    datasources.insert("flags".to_string(), sqlite_settings(&flags_db));
+
+    let state = AppState::new_with_crud(AppStateConfig {
+        routes: None,
+        datasources: Some(datasources),
+        openapi_configs: vec![
+            api_config("invoices", "billing"),
+            api_config("features", "flags"),
+        ],
+        listener_modules: None,
+        auth_config: None,
+        public_url: None,
+        access_log_config: None,
+        control_plane_db: None,
+        control_plane_config: None,
+    })
+    .await
+    .unwrap();
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    crud.handle_request(
+        "POST",
+        "/invoices",
+        HashMap::new(),
+        HashMap::new(),
+        Some(json!({"name": "INV-1"})),
+        &ctx(Method::POST, "/invoices"),
+    )
+    .await
+    .unwrap();
+    crud.handle_request(
+        "POST",
+        "/features",
+        HashMap::new(),
+        HashMap::new(),
+        Some(json!({"name": "dark-mode"})),
+        &ctx(Method::POST, "/features"),
+    )
+    .await
+    .unwrap();
+
+    // Each table only exists in the datasource of its API
+    let billing_tables = open(&billing_db).await.list_tables().await.unwrap();
+    let flags_tables = open(&flags_db).await.list_tables().await.unwrap();
+    assert!(billing_tables.contains(&"invoices".to_string()));
+    assert!(!billing_tables.contains(&"features".to_string()));
+    assert!(flags_tables.contains(&"features".to_string()));
+    assert!(!flags_tables.contains(&"invoices".to_string()));
+
+    let features = crud
+        .handle_request(
+            "GET",
+            "/features",
+            HashMap::new(),
+            HashMap::new(),
+            None,
+            &ctx(Method::GET, "/features"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(features.as_array().unwrap().len(), 1);
+    assert_eq!(features[0]["name"], "dark-mode");
+}
+
+#[tokio::test]
+async fn apis_of_different_datasources_cannot_share_a_path() {
+    let temp = TempDir::new().unwrap();
+    let mut datasources = HashMap::new();
+    datasources.insert(
+        "billing".to_string(),
+        sqlite_settings(&temp.path().join("billing.sqlite")),
+    );
+    datasources.insert(
+        "flags".to_string(),
+        sqlite_settings(&temp.path().join("flags.sqlite")),
+    );
+
+    let err = AppState::new_with_crud(AppStateConfig {
+        routes: None,
+        datasources: Some(datasources),
+        openapi_configs: vec![
+            api_config("invoices", "billing"),
+            api_config("invoices", "flags"),
+        ],
+        listener_modules: None,
+        auth_config: None,
+        public_url: None,
+        access_log_config: None,
+        control_plane_db: None,
+        control_plane_config: None,
+    })
+    .await
+    .err()
+    .unwrap()
+    .to_string();
+    assert!(err.contains("Path '/invoices'"), "{}", err);
+    assert!(
+        err.contains("'billing'") && err.contains("'flags'"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn apis_without_datasource_need_an_unambiguous_default() {
+    let temp = TempDir::new().unwrap();
+    let state_with = |names: &[&str]| {
+        let datasources: HashMap<String, DatabaseSettings> = names
+            .iter()
+            .map(|name| {
+                let path = temp.path().join(format!("{}.sqlite", name));
+                (name.to_string(), sqlite_settings(&path))
+            })
+            .collect();
+        let mut api = api_config("notes", "unused");
+        api.datasource = None;
+        AppState::new_with_crud(AppStateConfig {
+            routes: None,
+            datasources: Some(datasources),
+            openapi_configs: vec![api],
+            listener_modules: None,
+            auth_config: None,
+            public_url: None,
+            access_log_config: None,
+            control_plane_db: None,
+            control_plane_config: None,
+        })
+    };
+
+    let err = state_with(&["billing", "flags"])
+        .await
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("(billing, flags)"), "{}", err);
+
+    state_with(&["billing", "default"]).await.unwrap();
+    let tables = open(&temp.path().join("default.sqlite"))
+        .await
+        .list_tables()
+        .await
+        .unwrap();
+    assert!(tables.contains(&"notes".to_string()));
+}
@@ -0,0 +1,466 @@
+//! List endpoint query parameters (filters, sorting, fields, pagination) and
+//! aggregations against a SQLite datasource
+
+use apify::app_state::{AppState, AppStateConfig, OpenApiStateConfig};
+use apify::config::{DatabaseSettings, OpenAPIConfig, OpenAPISettings};
+use apify::crud_handler::{CRUDError, CRUDHandler};
+use apify::hyper::{HeaderMap, Method, Uri};
+use apify::phases::RequestContext;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+async fn users_state(temp: &TempDir) -> AppState {
+    users_state_with(temp, json!({})).await
+}
+
+/// Users API whose spec root is extended with `extra` (e.g. `x-pagination`)
+async fn users_state_with(temp: &TempDir, extra: Value) -> AppState {
+    let mut spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Users", "version": "1.0.0" },
+        "x-table-schemas": [{
+            "tableName": "users",
+            "defaultSort": "-age",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "name", "columnType": "TEXT" },
+                { "name": "age", "columnType": "INTEGER", "nullable": true },
+                { "name": "status", "columnType": "TEXT", "nullable": true },
+                { "name": "deleted_at", "columnType": "TEXT", "nullable": true }
+            ],
+            "relations": [
+                { "fieldName": "posts", "relationType": "hasMany", "targetTable": "posts", "foreignKey": "user_id" }
+            ]
+        }, {
+            "tableName": "posts",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "user_id", "columnType": "INTEGER" },
+                { "name": "title", "columnType": "TEXT" },
+                { "name": "body", "columnType": "TEXT", "nullable": true }
+            ]
+        }],
+        "paths": {
+            "/users": {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "post": { "responses": { "200": { "description": "ok" } } }
+            },
+            "/users/{id}": {
+                "get": { "responses": { "200": { "description": "ok" } } }
+            },
+            "/users/_aggregate": {
+                "get": { "responses": { "200": { "description": "ok" } } }
+            }
+        }
+    });
+    for (key, value) in extra.as_object().unwrap() {
+        spec[key] = value.clone();
+    }
+    let mut datasources = HashMap::new();
+    datasources.insert(
+        "main".to_string(),
+        DatabaseSettings {
+            driver: "sqlite".to_string(),
+            host: None,
+            port: None,
+            user: None,
+            password: None,
+            database: temp
+                .path()
+                .join("list.sqlite")
+                .to_string_lossy()
+                .to_string(),
+            ssl_mode: None,
+            max_pool_size: Some(2),
+            migrations: None,
+        },
+    );
+    AppState::new_with_crud(AppStateConfig {
+        routes: None,
+        datasources: Some(datasources),
+        openapi_configs: vec![OpenApiStateConfig {
+            config: OpenAPIConfig {
+                openapi: OpenAPISettings {
+                    spec,
+                    validation: None,
+                },
+            },
+            modules: None,
+            datasource: Some("main".to_string()),
+            access_log: None,
+            listeners: None,
+        }],
+        listener_modules: None,
+        auth_config: None,
+        public_url: None,
+        access_log_config: None,
+        control_plane_db: None,
+        control_plane_config: None,
+    })
+    .await
+    .unwrap()
+}
+
+fn ctx(method: Method, path: &str) -> RequestContext {
+    RequestContext::new(method, path.parse::<Uri>().unwrap(), HeaderMap::new(), None)
+}
+
+async fn seed(crud: &CRUDHandler) {
+    for user in [
+        json!({"name": "Alice", "age": 31, "status": "active"}),
+        json!({"name": "Albert", "age": 17, "status": "pending"}),
+        json!({"name": "Bob", "age": 45, "status": "banned", "deleted_at": "2024-01-01"}),
+        json!({"name": "Carol", "age": 18, "status": "active"}),
+    ] {
+        crud.handle_request(
+            "POST",
+            "/users",
+            HashMap::new(),
+            HashMap::new(),
+            Some(user),
+            &ctx(Method::POST, "/users"),
+        )
+        .await
+        .unwrap();
+    }
+}
+
+async fn list(crud: &CRUDHandler, params: &[(&str, &str)]) -> Result<Value, CRUDError> {
+    let query_params = params
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    crud.handle_request(
+        "GET",
+        "/users",
+        HashMap::new(),
+        query_params,
+        None,
+        &ctx(Method::GET, "/users"),
+    )
+    .await
+}
+
+fn names(result: &Value) -> Vec<String> {
+    let mut names: Vec<String> = result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|u| u["name"].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[tokio::test]
+async fn list_filters_with_operators() {
+    let temp = TempDir::new().unwrap();
+    let state = users_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    seed(crud).await;
+
+    let r = list(crud, &[("age[gte]", "18")]).await.unwrap();
+    assert_eq!(names(&r), ["Alice", "Bob", "Carol"]);
+
+    let r = list(crud, &[("name[like]", "Al%")]).await.unwrap();
+    assert_eq!(names(&r), ["Albert", "Alice"]);
+
+    let r = list(crud, &[("status[in]", "active,pending")])
+        .await
+        .unwrap();
+    assert_eq!(names(&r), ["Albert", "Alice", "Carol"]);
+
+    let r = list(crud, &[("deleted_at[is]", "null"), ("age[lt]", "30")])
+        .await
+        .unwrap();
+    assert_eq!(names(&r), ["Albert", "Carol"]);
+
+    let r = list(crud, &[("status[not]", "active")]).await.unwrap();
+    assert_eq!(names(&r), ["Albert", "Bob"]);
+
+    let r = list(crud, &[("deleted_at[not][is]", "null")])
+        .await
+        .unwrap();
+    assert_eq!(names(&r), ["Bob"]);
+
+    // Plain equality keeps working
+    let r = list(crud, &[("age", "45")]).await.unwrap();
+    assert_eq!(names(&r), ["Bob"]);
+}
+
+#[tokio::test]
+async fn list_rejects_invalid_filters() {
+    let temp = TempDir::new().unwrap();
+    let state = users_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    for (key, value) in [
+        ("password", "x"),
+        ("age[between]", "1"),
+        ("age[gte]", "old"),
+        ("sort", "-password"),
+    ] {
+        let err = list(crud, &[(key, value)]).await.unwrap_err();
+        assert!(
+            matches!(err, CRUDError::InvalidParameterError(_)),
+            "{key}={value} returned {err:?}"
+        );
+    }
+}
+
+fn ordered_names(result: &Value) -> Vec<&str> {
+    result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|u| u["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn list_sorting() {
+    let temp = TempDir::new().unwrap();
+    let state = users_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    seed(crud).await;
+
+    // defaultSort from the table schema
+    let r = list(crud, &[]).await.unwrap();
+    assert_eq!(ordered_names(&r), ["Bob", "Alice", "Carol", "Albert"]);
+
+    let r = list(crud, &[("sort", "name")]).await.unwrap();
+    assert_eq!(ordered_names(&r), ["Albert", "Alice", "Bob", "Carol"]);
+
+    let r = list(crud, &[("sort", "status,-name")]).await.unwrap();
+    assert_eq!(ordered_names(&r), ["Carol", "Alice", "Bob", "Albert"]);
+
+    // Sorting composes with filters and pagination
+    let r = list(
+        crud,
+        &[
+            ("sort", "age"),
+            ("age[gte]", "18"),
+            ("limit", "2"),
+            ("offset", "1"),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(ordered_names(&r), ["Alice", "Bob"]);
+}
+
+#[tokio::test]
+async fn sparse_fieldsets() {
+    let temp = TempDir::new().unwrap();
+    let state = users_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    crud.handle_request(
+        "POST",
+        "/users",
+        HashMap::new(),
+        HashMap::new(),
+        Some(json!({
+            "name": "Alice",
+            "age": 31,
+            "posts": [{ "title": "Hello", "body": "long text" }]
+        })),
+        &ctx(Method::POST, "/users"),
+    )
+    .await
+    .unwrap();
+
+    // Only the requested columns; relations are skipped unless requested
+    let r = list(crud, &[("fields", "name,age")]).await.unwrap();
+    assert_eq!(r, json!([{ "name": "Alice", "age": 31 }]));
+
+    // Relation named in `fields` and projected through `fields[posts]`
+    let r = list(crud, &[("fields", "name"), ("fields[posts]", "title")])
+        .await
+        .unwrap();
+    assert_eq!(
+        r,
+        json!([{ "name": "Alice", "posts": [{ "title": "Hello" }] }])
+    );
+
+    let mut path_params = HashMap::new();
+    path_params.insert("id".to_string(), "1".to_string());
+    let mut query_params = HashMap::new();
+    query_params.insert("fields".to_string(), "id,posts".to_string());
+    let r = crud
+        .handle_request(
+            "GET",
+            "/users/1",
+            path_params,
+            query_params,
+            None,
+            &ctx(Method::GET, "/users/1"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(r["id"], 1);
+    assert!(r.get("name").is_none());
+    assert_eq!(r["posts"][0]["body"], "long text");
+
+    for (key, value) in [("fields", "password"), ("fields[posts]", "secret")] {
+        let err = list(crud, &[(key, value)]).await.unwrap_err();
+        assert!(matches!(err, CRUDError::InvalidParameterError(_)));
+    }
+}
+
+#[tokio::test]
+async fn list_envelope_with_total() {
+    let temp = TempDir::new().unwrap();
+    let state = users_state_with(&temp, json!({ "x-pagination": "envelope" })).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    seed(crud).await;
+
+    let r = list(crud, &[("age[gte]", "18"), ("sort", "age"), ("limit", "2")])
+        .await
+        .unwrap();
+    assert_eq!(r["total"], 3);
+    assert_eq!(r["limit"], 2);
+    assert_eq!(r["offset"], 0);
+    assert_eq!(ordered_names(&r["data"]), ["Carol", "Alice"]);
+    assert_eq!(
+        r["next"],
+        "/users?age%5Bgte%5D=18&limit=2&sort=age&offset=2"
+    );
+
+    let r = list(
+        crud,
+        &[
+            ("age[gte]", "18"),
+            ("sort", "age"),
+            ("limit", "2"),
+            ("offset", "2"),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(ordered_names(&r["data"]), ["Bob"]);
+    assert_eq!(r["next"], Value::Null);
+
+    // The served spec documents the envelope
+    let spec = state
+        .crud_handler
+        .as_ref()
+        .unwrap()
+        .api_generator
+        .get_spec();
+    let schema = &spec["paths"]["/users"]["get"]["responses"]["200"]["content"]["application/json"]
+        ["schema"];
+    assert_eq!(schema["properties"]["total"]["type"], "integer");
+}
+
+#[tokio::test]
+async fn list_cursor_pagination() {
+    let temp = TempDir::new().unwrap();
+    let state = users_state_with(&temp, json!({ "x-pagination": "cursor" })).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    seed(crud).await;
+
+    let r = list(crud, &[("age[gte]", "18"), ("limit", "2")])
+        .await
+        .unwrap();
+    assert_eq!(ordered_names(&r["data"]), ["Alice", "Bob"]);
+    let cursor = r["next_cursor"].as_str().unwrap().to_string();
+
+    // Rows inserted before the cursor position do not shift the next page
+    crud.handle_request(
+        "POST",
+        "/users",
+        HashMap::new(),
+        HashMap::new(),
+        Some(json!({"name": "Dave", "age": 50})),
+        &ctx(Method::POST, "/users"),
+    )
+    .await
+    .unwrap();
+    let r = list(
+        crud,
+        &[("age[gte]", "18"), ("limit", "2"), ("cursor", &cursor)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(ordered_names(&r["data"]), ["Carol", "Dave"]);
+    assert_eq!(r["next_cursor"], Value::Null);
+
+    // Pages are bounded even without a limit
+    let r = list(crud, &[]).await.unwrap();
+    assert_eq!(r["limit"], 50);
+    assert_eq!(r["data"].as_array().unwrap().len(), 5);
+    let r = list(crud, &[("limit", "100000")]).await.unwrap();
+    assert_eq!(r["limit"], 1000);
+
+    // Cursors are bound to their filters and cannot be forged
+    for params in [
+        vec![("cursor", cursor.as_str())],
+        vec![("age[gte]", "18"), ("cursor", "W10.AAAA")],
+        vec![("sort", "name")],
+    ] {
+        let err = list(crud, &params).await.unwrap_err();
+        assert!(matches!(err, CRUDError::InvalidParameterError(_)));
+    }
+}
+
+async fn aggregate(crud: &CRUDHandler, params: &[(&str, &str)]) -> Result<Value, CRUDError> {
+    let query_params = params
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    crud.handle_request(
+        "GET",
+        "/users/_aggregate",
+        HashMap::new(),
+        query_params,
+        None,
+        &ctx(Method::GET, "/users/_aggregate"),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn aggregates_per_group_with_filters() {
+    let temp = TempDir::new().unwrap();
+    let state = users_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    seed(crud).await;
+
+    let r = aggregate(
+        crud,
+        &[
+            ("group_by", "status"),
+            ("count", "*"),
+            ("sum", "age"),
+            ("max", "name"),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        r,
+        json!([
+            { "status": "active", "count": 2, "sum_age": 49, "max_name": "Carol" },
+            { "status": "banned", "count": 1, "sum_age": 45, "max_name": "Bob" },
+            { "status": "pending", "count": 1, "sum_age": 17, "max_name": "Albert" }
+        ])
+    );
+
+    // Same filters as lists; without grouping there is a single row
+    let r = aggregate(crud, &[("age[gte]", "18"), ("avg", "age"), ("min", "age")])
+        .await
+        .unwrap();
+    assert_eq!(r, json!([{ "avg_age": 31.333333333333332, "min_age": 18 }]));
+    let r = aggregate(crud, &[("status", "active")]).await.unwrap();
+    assert_eq!(r, json!([{ "count": 2 }]));
+
+    for params in [
+        vec![("sum", "name")],
+        vec![("group_by", "password")],
+        vec![("count", "*"), ("nickname", "x")],
+    ] {
+        let err = aggregate(crud, &params).await.unwrap_err();
+        assert!(matches!(err, CRUDError::InvalidParameterError(_)));
+    }
+}
@@ -109,7 +109,7 @@ apis:
         .json(&small_body)
         .send()
         .await?;
-    assert_eq!(resp.status(), 200, "Small body should be accepted");
+    assert_eq!(resp.status(), 201, "Small body should be accepted");
 
     // Test 2: Very large body should be rejected (if request_validator is active)
     // Note: This test assumes request_validator with 1MB limit is configured
@@ -206,7 +206,7 @@ apis:
         .await?;
     assert_eq!(
         resp.status(),
-        200,
+        201,
         "Valid JSON with Content-Type should work"
     );
 
@@ -327,7 +327,7 @@ apis:
         .json(&serde_json::json!({"message": "test log"}))
         .send()
         .await?;
-    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.status(), 201);
 
     let _ = child.kill().await;
     Ok(())
@@ -453,11 +453,14 @@ apis:
         }))
         .send()
         .await?;
-    assert_eq!(create_resp.status(), 200);
+    assert_eq!(create_resp.status(), 201);
     let created: serde_json::Value = create_resp.json().await?;
 
     // Verify create response
-    assert_eq!(created["affected_rows"], 1, "Should insert one row");
+    assert_eq!(
+        created["name"], "Test Product",
+        "Should return the stored record"
+    );
 
     // 4. List all products to find our created one
     let list_resp = client
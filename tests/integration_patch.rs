@@ -0,0 +1,263 @@
+//! PUT replacement, PATCH merges and JSON Patch documents against a SQLite datasource
+
+mod common;
+
+use apify::app_state::AppState;
+use apify::crud_handler::{CRUDError, CRUDHandler};
+use apify::hyper::HeaderMap;
+use apify::hyper::header::{CONTENT_TYPE, HeaderValue};
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+async fn orders_state(temp: &TempDir) -> AppState {
+    let line_ref = json!({
+        "type": "array",
+        "items": { "type": "object" },
+        "x-relation": { "type": "hasMany", "target": "Line", "foreignKey": "order_id" }
+    });
+    let ok = json!({ "200": { "description": "ok" } });
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Orders", "version": "1.0.0" },
+        "x-table-schemas": [{
+            "tableName": "orders",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "customer", "columnType": "TEXT" },
+                { "name": "status", "columnType": "TEXT", "defaultValue": "'new'" },
+                { "name": "note", "columnType": "TEXT", "nullable": true },
+                { "name": "meta", "columnType": "TEXT", "nullable": true }
+            ]
+        }, {
+            "tableName": "lines",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "order_id", "columnType": "INTEGER" },
+                { "name": "product", "columnType": "TEXT" },
+                { "name": "qty", "columnType": "INTEGER" }
+            ]
+        }],
+        "paths": {
+            "/orders": {
+                "post": {
+                    "requestBody": { "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": { "customer": { "type": "string" }, "lines": line_ref }
+                    } } } },
+                    "responses": ok
+                }
+            },
+            "/orders/{id}": {
+                "get": { "responses": ok },
+                "put": { "responses": ok },
+                "patch": { "responses": ok }
+            }
+        }
+    });
+
+    common::sqlite_state(temp, "patch.sqlite", spec).await
+}
+
+/// Dispatch with the given `Content-Type`
+async fn request(
+    crud: &CRUDHandler,
+    method: &str,
+    path: &str,
+    content_type: &'static str,
+    body: Option<Value>,
+) -> Result<Value, CRUDError> {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    common::dispatch(crud, &common::context(method, path, headers), body).await
+}
+
+async fn create_order(crud: &CRUDHandler) -> i64 {
+    let body = json!({
+        "customer": "Ada",
+        "status": "paid",
+        "note": "leave at door",
+        "lines": [{ "product": "pen", "qty": 1 }, { "product": "ink", "qty": 2 }]
+    });
+    let order = request(crud, "POST", "/orders", "application/json", Some(body))
+        .await
+        .unwrap();
+    order["id"].as_i64().unwrap()
+}
+
+/// `(id, product, qty)` of every line of an order
+fn lines(order: &Value) -> Vec<(i64, String, i64)> {
+    order["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| {
+            (
+                l["id"].as_i64().unwrap(),
+                l["product"].as_str().unwrap().to_string(),
+                l["qty"].as_i64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn put_replaces_and_patch_merges() {
+    let temp = TempDir::new().unwrap();
+    let state = orders_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    let id = create_order(crud).await;
+    let path = format!("/orders/{}", id);
+
+    // PATCH only touches the given fields; null clears a column
+    let body = json!({ "note": null, "meta": { "gift": true, "wrap": "red" } });
+    request(crud, "PATCH", &path, "application/json", Some(body))
+        .await
+        .unwrap();
+    let order = request(crud, "GET", &path, "application/json", None)
+        .await
+        .unwrap();
+    assert_eq!(order["customer"], "Ada");
+    assert_eq!(order["status"], "paid");
+    assert_eq!(order["note"], Value::Null);
+
+    // Objects are merged into JSON columns
+    let body = json!({ "meta": { "wrap": null, "card": "hi" } });
+    request(
+        crud,
+        "PATCH",
+        &path,
+        "application/merge-patch+json",
+        Some(body),
+    )
+    .await
+    .unwrap();
+    let order = request(crud, "GET", &path, "application/json", None)
+        .await
+        .unwrap();
+    let meta: Value = match &order["meta"] {
+        Value::String(text) => serde_json::from_str(text).unwrap(),
+        meta => meta.clone(),
+    };
+    assert_eq!(meta, json!({ "gift": true, "card": "hi" }));
+
+    // PUT resets what the body leaves out to the column default
+    let body = json!({ "customer": "Grace" });
+    request(crud, "PUT", &path, "application/json", Some(body))
+        .await
+        .unwrap();
+    let order = request(crud, "GET", &path, "application/json", None)
+        .await
+        .unwrap();
+    assert_eq!(order["id"], id);
+    assert_eq!(order["customer"], "Grace");
+    assert_eq!(order["status"], "new");
+    assert_eq!(order["meta"], Value::Null);
+    // Relations left out of the body are untouched
+    assert_eq!(order["lines"].as_array().unwrap().len(), 2);
+
+    // A replacement must carry every required column
+    let err = request(crud, "PUT", &path, "application/json", Some(json!({})))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, CRUDError::ValidationError(m) if m.contains("customer")));
+}
+
+#[tokio::test]
+async fn nested_children_are_updated_by_id() {
+    let temp = TempDir::new().unwrap();
+    let state = orders_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    let id = create_order(crud).await;
+    let path = format!("/orders/{}", id);
+    let order = request(crud, "GET", &path, "application/json", None)
+        .await
+        .unwrap();
+    let (pen, ink) = (lines(&order)[0].0, lines(&order)[1].0);
+
+    // A merge updates listed children in place and keeps the others
+    let body = json!({ "lines": [{ "id": pen, "qty": 5 }, { "product": "pad", "qty": 1 }] });
+    request(crud, "PATCH", &path, "application/json", Some(body))
+        .await
+        .unwrap();
+    let order = request(crud, "GET", &path, "application/json", None)
+        .await
+        .unwrap();
+    let current = lines(&order);
+    assert_eq!(current.len(), 3);
+    assert_eq!(current[0], (pen, "pen".to_string(), 5));
+    assert_eq!(current[1], (ink, "ink".to_string(), 2));
+    assert_eq!(current[2].1, "pad");
+
+    // A replacement keeps child ids and deletes unlisted children
+    let body = json!({
+        "customer": "Ada",
+        "lines": [{ "id": ink, "product": "ink", "qty": 3 }]
+    });
+    request(crud, "PUT", &path, "application/json", Some(body))
+        .await
+        .unwrap();
+    let order = request(crud, "GET", &path, "application/json", None)
+        .await
+        .unwrap();
+    assert_eq!(lines(&order), vec![(ink, "ink".to_string(), 3)]);
+}
+
+#[tokio::test]
+async fn json_patch_applies_operations() {
+    let temp = TempDir::new().unwrap();
+    let state = orders_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    let id = create_order(crud).await;
+    let path = format!("/orders/{}", id);
+    let order = request(crud, "GET", &path, "application/json", None)
+        .await
+        .unwrap();
+    let ink = lines(&order)[1].0;
+
+    let patch = json!([
+        { "op": "test", "path": "/status", "value": "paid" },
+        { "op": "replace", "path": "/status", "value": "shipped" },
+        { "op": "remove", "path": "/note" },
+        { "op": "remove", "path": "/lines/0" },
+        { "op": "replace", "path": "/lines/0/qty", "value": 4 },
+        { "op": "add", "path": "/lines/-", "value": { "product": "nib", "qty": 9 } }
+    ]);
+    request(
+        crud,
+        "PATCH",
+        &path,
+        "application/json-patch+json",
+        Some(patch),
+    )
+    .await
+    .unwrap();
+    let order = request(crud, "GET", &path, "application/json", None)
+        .await
+        .unwrap();
+    assert_eq!(order["status"], "shipped");
+    assert_eq!(order["note"], Value::Null);
+    let current = lines(&order);
+    assert_eq!(current.len(), 2);
+    assert_eq!(current[0], (ink, "ink".to_string(), 4));
+    assert_eq!(current[1].1, "nib");
+
+    // A failed test leaves the record as it was
+    let patch = json!([
+        { "op": "replace", "path": "/customer", "value": "Eve" },
+        { "op": "test", "path": "/status", "value": "paid" }
+    ]);
+    let err = request(
+        crud,
+        "PATCH",
+        &path,
+        "application/json-patch+json",
+        Some(patch),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, CRUDError::ValidationError(_)));
+    let order = request(crud, "GET", &path, "application/json", None)
+        .await
+        .unwrap();
+    assert_eq!(order["customer"], "Ada");
+}
@@ -0,0 +1,238 @@
+//! Composite and non-`id` primary keys against a SQLite datasource
+
+mod common;
+
+use apify::app_state::AppState;
+use apify::crud_handler::CRUDError;
+use common::request;
+use serde_json::json;
+use tempfile::TempDir;
+
+async fn school_state(temp: &TempDir) -> AppState {
+    let town_ref = json!({
+        "type": "array",
+        "items": { "type": "object" },
+        "x-relation": {
+            "type": "hasMany",
+            "target": "Town",
+            "foreignKey": "country_code",
+            "localKey": "code"
+        }
+    });
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "School", "version": "1.0.0" },
+        "x-table-schemas": [{
+            "tableName": "courses",
+            "columns": [
+                { "name": "code", "columnType": "TEXT", "primaryKey": true },
+                { "name": "title", "columnType": "TEXT" }
+            ]
+        }, {
+            "tableName": "enrollments",
+            "columns": [
+                { "name": "student_id", "columnType": "INTEGER", "primaryKey": true },
+                { "name": "course_id", "columnType": "TEXT", "primaryKey": true },
+                { "name": "grade", "columnType": "TEXT" }
+            ]
+        }, {
+            "tableName": "countries",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "code", "columnType": "TEXT" },
+                { "name": "name", "columnType": "TEXT" }
+            ]
+        }, {
+            "tableName": "towns",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "country_code", "columnType": "TEXT" },
+                { "name": "name", "columnType": "TEXT" }
+            ]
+        }],
+        "paths": {
+            "/courses": {
+                "post": { "responses": { "200": { "description": "ok" } } }
+            },
+            "/courses/{id}": {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "put": { "responses": { "200": { "description": "ok" } } },
+                "delete": { "responses": { "200": { "description": "ok" } } }
+            },
+            "/enrollments": {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "post": { "responses": { "200": { "description": "ok" } } }
+            },
+            "/enrollments/{student_id}/{course_id}": {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "put": { "responses": { "200": { "description": "ok" } } },
+                "delete": { "responses": { "200": { "description": "ok" } } }
+            },
+            "/countries": {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "post": {
+                    "requestBody": { "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": {
+                            "code": { "type": "string" },
+                            "name": { "type": "string" },
+                            "towns": town_ref
+                        }
+                    } } } },
+                    "responses": { "200": { "description": "ok" } }
+                }
+            },
+            "/countries/{id}": {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "delete": { "responses": { "200": { "description": "ok" } } }
+            },
+            "/towns": {
+                "get": { "responses": { "200": { "description": "ok" } } }
+            }
+        }
+    });
+
+    common::sqlite_state(temp, "keys.sqlite", spec).await
+}
+
+#[tokio::test]
+async fn composite_key_routes_address_one_row() {
+    let temp = TempDir::new().unwrap();
+    let state = school_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    for (student, course) in [(1, "RUST101"), (1, "SQL201"), (2, "RUST101")] {
+        let body = json!({ "student_id": student, "course_id": course, "grade": "B" });
+        request(crud, "POST", "/enrollments", Some(body))
+            .await
+            .unwrap();
+    }
+
+    let enrollment = request(crud, "GET", "/enrollments/1/RUST101", None)
+        .await
+        .unwrap();
+    assert_eq!(enrollment["course_id"], "RUST101");
+    assert_eq!(enrollment["student_id"], 1);
+
+    request(
+        crud,
+        "PUT",
+        "/enrollments/1/RUST101",
+        Some(json!({ "grade": "A" })),
+    )
+    .await
+    .unwrap();
+    request(crud, "DELETE", "/enrollments/1/SQL201", None)
+        .await
+        .unwrap();
+
+    let rows = request(crud, "GET", "/enrollments", None).await.unwrap();
+    let mut grades: Vec<(i64, String, String)> = rows
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| {
+            (
+                r["student_id"].as_i64().unwrap(),
+                r["course_id"].as_str().unwrap().to_string(),
+                r["grade"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    grades.sort();
+    assert_eq!(
+        grades,
+        vec![
+            (1, "RUST101".to_string(), "A".to_string()),
+            (2, "RUST101".to_string(), "B".to_string()),
+        ]
+    );
+
+    let missing = request(crud, "GET", "/enrollments/1/SQL201", None).await;
+    assert!(
+        matches!(&missing, Err(CRUDError::NotFoundError(m)) if m.contains("course_id = SQL201")),
+        "{:?}",
+        missing
+    );
+}
+
+#[tokio::test]
+async fn text_primary_key_is_matched_by_position() {
+    let temp = TempDir::new().unwrap();
+    let state = school_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    request(
+        crud,
+        "POST",
+        "/courses",
+        Some(json!({ "code": "42", "title": "Answers" })),
+    )
+    .await
+    .unwrap();
+
+    // `{id}` fills the `code` key and stays text even though it looks numeric
+    let course = request(crud, "GET", "/courses/42", None).await.unwrap();
+    assert_eq!(course["code"], "42");
+    assert_eq!(course["title"], "Answers");
+
+    request(
+        crud,
+        "PUT",
+        "/courses/42",
+        Some(json!({ "title": "More answers" })),
+    )
+    .await
+    .unwrap();
+    let course = request(crud, "GET", "/courses/42", None).await.unwrap();
+    assert_eq!(course["title"], "More answers");
+
+    request(crud, "DELETE", "/courses/42", None).await.unwrap();
+    assert!(matches!(
+        request(crud, "GET", "/courses/42", None).await,
+        Err(CRUDError::NotFoundError(_))
+    ));
+}
+
+#[tokio::test]
+async fn relations_join_on_local_key() {
+    let temp = TempDir::new().unwrap();
+    let state = school_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    let body = json!({
+        "code": "NL",
+        "name": "Netherlands",
+        "towns": [{ "name": "Utrecht" }, { "name": "Delft" }]
+    });
+    let country = request(crud, "POST", "/countries", Some(body))
+        .await
+        .unwrap();
+    let id = country["id"].as_i64().unwrap();
+
+    let towns = request(crud, "GET", "/towns", None).await.unwrap();
+    for town in towns.as_array().unwrap() {
+        assert_eq!(town["country_code"], "NL");
+    }
+
+    let country = request(crud, "GET", &format!("/countries/{}", id), None)
+        .await
+        .unwrap();
+    let names: Vec<&str> = country["towns"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Utrecht", "Delft"]);
+
+    let list = request(crud, "GET", "/countries", None).await.unwrap();
+    assert_eq!(list[0]["towns"].as_array().unwrap().len(), 2);
+
+    // Deleting the country removes the towns keyed on its code
+    request(crud, "DELETE", &format!("/countries/{}", id), None)
+        .await
+        .unwrap();
+    let towns = request(crud, "GET", "/towns", None).await.unwrap();
+    assert!(towns.as_array().unwrap().is_empty());
+}
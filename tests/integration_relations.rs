@@ -0,0 +1,511 @@
+//! Nested relation writes against a SQLite datasource
+
+use apify::app_state::{AppState, AppStateConfig, OpenApiStateConfig};
+use apify::config::{DatabaseSettings, OpenAPIConfig, OpenAPISettings};
+use apify::crud_handler::{CRUDError, CRUDHandler};
+use apify::hyper::{HeaderMap, Method, Uri};
+use apify::modules::metrics::DB_QUERIES_TOTAL;
+use apify::phases::RequestContext;
+use serde_json::{Value, json};
+use serial_test::serial;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+async fn posts_state(temp: &TempDir) -> AppState {
+    let tag_ref = json!({
+        "type": "array",
+        "items": { "type": "object" },
+        "x-relation": { "type": "belongsToMany", "target": "Tag", "through": "post_tags" }
+    });
+    let comment_ref = json!({
+        "type": "array",
+        "items": { "type": "object" },
+        "x-relation": { "type": "hasMany", "target": "Comment", "foreignKey": "post_id" }
+    });
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Blog", "version": "1.0.0" },
+        "x-table-schemas": [{
+            "tableName": "posts",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "title", "columnType": "TEXT" }
+            ]
+        }, {
+            "tableName": "comments",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "post_id", "columnType": "INTEGER" },
+                { "name": "body", "columnType": "TEXT" }
+            ]
+        }, {
+            "tableName": "tags",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "name", "columnType": "TEXT" }
+            ]
+        }],
+        "paths": {
+            "/posts": {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "post": {
+                    "requestBody": { "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": {
+                            "title": { "type": "string" },
+                            "tags": tag_ref,
+                            "comments": comment_ref
+                        }
+                    } } } },
+                    "responses": { "200": { "description": "ok" } }
+                }
+            },
+            "/posts/{id}": {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "put": { "responses": { "200": { "description": "ok" } } },
+                "patch": { "responses": { "200": { "description": "ok" } } },
+                "delete": { "responses": { "200": { "description": "ok" } } }
+            },
+            "/tags": {
+                "get": { "responses": { "200": { "description": "ok" } } },
+                "post": { "responses": { "200": { "description": "ok" } } }
+            }
+        }
+    });
+    sqlite_state(temp, spec).await
+}
+
+async fn sqlite_state(temp: &TempDir, spec: Value) -> AppState {
+    let mut datasources = HashMap::new();
+    datasources.insert(
+        "main".to_string(),
+        DatabaseSettings {
+            driver: "sqlite".to_string(),
+            host: None,
+            port: None,
+            user: None,
+            password: None,
+            database: temp
+                .path()
+                .join("relations.sqlite")
+                .to_string_lossy()
+                .to_string(),
+            ssl_mode: None,
+            max_pool_size: Some(2),
+            migrations: None,
+        },
+    );
+    AppState::new_with_crud(AppStateConfig {
+        routes: None,
+        datasources: Some(datasources),
+        openapi_configs: vec![OpenApiStateConfig {
+            config: OpenAPIConfig {
+                openapi: OpenAPISettings {
+                    spec,
+                    validation: None,
+                },
+            },
+            modules: None,
+            datasource: Some("main".to_string()),
+            access_log: None,
+            listeners: None,
+        }],
+        listener_modules: None,
+        auth_config: None,
+        public_url: None,
+        access_log_config: None,
+        control_plane_db: None,
+        control_plane_config: None,
+    })
+    .await
+    .unwrap()
+}
+
+async fn request(
+    crud: &CRUDHandler,
+    method: &str,
+    path: &str,
+    body: Option<Value>,
+) -> Result<Value, CRUDError> {
+    let uri = path.parse::<Uri>().unwrap();
+    let path = uri.path();
+    let mut path_params = HashMap::new();
+    if let Some((_, id)) = path.trim_start_matches('/').split_once('/') {
+        path_params.insert("id".to_string(), id.to_string());
+    }
+    let query_params: HashMap<String, String> =
+        form_urlencoded::parse(uri.query().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+    let ctx = RequestContext::new(
+        method.parse::<Method>().unwrap(),
+        uri.clone(),
+        HeaderMap::new(),
+        None,
+    );
+    crud.handle_request(method, path, path_params, query_params, body, &ctx)
+        .await
+}
+
+fn selects(table: &str) -> f64 {
+    DB_QUERIES_TOTAL
+        .with_label_values(&["select", table, "success"])
+        .get()
+}
+
+fn tag_names(post: &Value) -> Vec<&str> {
+    post["tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn belongs_to_many_through_join_table() {
+    let temp = TempDir::new().unwrap();
+    let state = posts_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    for name in ["rust", "sql"] {
+        request(crud, "POST", "/tags", Some(json!({ "name": name })))
+            .await
+            .unwrap();
+    }
+
+    // Link existing tags by id and create a new one inline
+    let post = request(
+        crud,
+        "POST",
+        "/posts",
+        Some(json!({ "title": "Hello", "tags": [1, { "name": "web" }] })),
+    )
+    .await
+    .unwrap();
+    assert_eq!(tag_names(&post), ["rust", "web"]);
+
+    let posts = request(crud, "GET", "/posts", None).await.unwrap();
+    assert_eq!(tag_names(&posts[0]), ["rust", "web"]);
+
+    // Updating replaces the links without touching the tags
+    request(
+        crud,
+        "PATCH",
+        "/posts/1",
+        Some(json!({ "tags": [{ "id": 2 }, 3] })),
+    )
+    .await
+    .unwrap();
+    let post = request(crud, "GET", "/posts/1", None).await.unwrap();
+    assert_eq!(post["title"], "Hello");
+    assert_eq!(tag_names(&post), ["sql", "web"]);
+
+    let err = request(crud, "PATCH", "/posts/1", Some(json!({ "tags": [42] })))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, CRUDError::ValidationError(_)));
+
+    // Deleting the post removes its join rows only
+    request(crud, "DELETE", "/posts/1", None).await.unwrap();
+    let tags = request(crud, "GET", "/tags", None).await.unwrap();
+    assert_eq!(tags.as_array().unwrap().len(), 3);
+
+    // The join table comes from the relation's `through`
+    let join = crud.api_generator.get_table_schema("post_tags").unwrap();
+    let columns: Vec<&str> = join.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(columns, ["id", "post_id", "tag_id"]);
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_nested_write_leaves_no_partial_state() {
+    let temp = TempDir::new().unwrap();
+    let state = posts_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    // The second comment has an unknown column, so the whole create fails
+    let err = request(
+        crud,
+        "POST",
+        "/posts",
+        Some(json!({ "title": "Broken", "comments": [{ "body": "ok" }, { "bogus": 1 }] })),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, CRUDError::DatabaseError(_)));
+    let posts = request(crud, "GET", "/posts", None).await.unwrap();
+    assert_eq!(posts, json!([]));
+
+    let post = request(
+        crud,
+        "POST",
+        "/posts",
+        Some(json!({ "title": "Hello", "comments": [{ "body": "first" }] })),
+    )
+    .await
+    .unwrap();
+    let id = post["id"].as_i64().unwrap();
+
+    // A failing update keeps the record and its old children
+    request(
+        crud,
+        "PUT",
+        &format!("/posts/{id}"),
+        Some(json!({ "title": "Changed", "comments": [{ "bogus": 1 }] })),
+    )
+    .await
+    .unwrap_err();
+    let post = request(crud, "GET", &format!("/posts/{id}"), None)
+        .await
+        .unwrap();
+    assert_eq!(post["title"], "Hello");
+    assert_eq!(post["comments"].as_array().unwrap().len(), 1);
+    assert_eq!(post["comments"][0]["body"], "first");
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_relation_lookup_fails_the_create() {
+    let temp = TempDir::new().unwrap();
+    let state = posts_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    // Loading the comments of the created post fails, which must undo the insert
+    let db = temp.path().join("relations.sqlite");
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", db.display()))
+        .await
+        .unwrap();
+    sqlx::query("DROP TABLE comments")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let err = request(crud, "POST", "/posts", Some(json!({ "title": "Lost" })))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, CRUDError::DatabaseError(_)));
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM posts")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+#[serial]
+async fn list_loads_relations_in_batches() {
+    let temp = TempDir::new().unwrap();
+    let state = posts_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    for name in ["rust", "sql"] {
+        request(crud, "POST", "/tags", Some(json!({ "name": name })))
+            .await
+            .unwrap();
+    }
+    for i in 1..=3 {
+        request(
+            crud,
+            "POST",
+            "/posts",
+            Some(json!({
+                "title": format!("Post {i}"),
+                "tags": [i % 2 + 1],
+                "comments": [{ "body": format!("{i}a") }, { "body": format!("{i}b") }]
+            })),
+        )
+        .await
+        .unwrap();
+    }
+
+    let before: Vec<f64> = ["comments", "post_tags", "tags"].map(selects).to_vec();
+    let posts = request(crud, "GET", "/posts", None).await.unwrap();
+    let after: Vec<f64> = ["comments", "post_tags", "tags"].map(selects).to_vec();
+    // One query per relation for the whole page, not one per post
+    for (before, after) in before.iter().zip(&after) {
+        assert_eq!(after - before, 1.0);
+    }
+
+    let posts = posts.as_array().unwrap();
+    assert_eq!(posts.len(), 3);
+    for (i, post) in posts.iter().enumerate() {
+        let n = i + 1;
+        let bodies: Vec<&str> = post["comments"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["body"].as_str().unwrap())
+            .collect();
+        assert_eq!(bodies, [format!("{n}a"), format!("{n}b")]);
+        let expected = if n % 2 == 0 { "rust" } else { "sql" };
+        assert_eq!(tag_names(post), [expected]);
+    }
+
+    // Sparse relation fields still omit the foreign key
+    let posts = request(crud, "GET", "/posts?fields%5Bcomments%5D=body", None)
+        .await
+        .unwrap();
+    assert_eq!(posts[0]["comments"][0], json!({ "body": "1a" }));
+}
+
+#[tokio::test]
+#[serial]
+async fn large_integer_keys_keep_their_children() {
+    let temp = TempDir::new().unwrap();
+    let state = posts_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    // Both ids round to the same f64
+    let ids = [9_007_199_254_740_992_i64, 9_007_199_254_740_993];
+    for id in ids {
+        request(
+            crud,
+            "POST",
+            "/posts",
+            Some(json!({ "id": id, "title": "Big", "comments": [{ "body": id.to_string() }] })),
+        )
+        .await
+        .unwrap();
+    }
+
+    let posts = request(crud, "GET", "/posts", None).await.unwrap();
+    for post in posts.as_array().unwrap() {
+        let comments = post["comments"].as_array().unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(
+            comments[0]["body"],
+            post["id"].as_i64().unwrap().to_string()
+        );
+    }
+}
+
+async fn orders_state(temp: &TempDir) -> AppState {
+    let relation = |kind: &str, target: &str, foreign_key: &str| {
+        json!({
+            "type": if kind == "hasMany" { "array" } else { "object" },
+            "x-relation": { "type": kind, "target": target, "foreignKey": foreign_key }
+        })
+    };
+    let table = |name: &str, columns: &[&str]| {
+        let mut cols = vec![json!({
+            "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true
+        })];
+        cols.extend(
+            columns
+                .iter()
+                .map(|c| json!({ "name": c, "columnType": "TEXT" })),
+        );
+        json!({ "tableName": name, "columns": cols })
+    };
+    let create = |properties: Value| {
+        json!({
+            "requestBody": { "content": { "application/json": { "schema": {
+                "type": "object", "properties": properties
+            } } } },
+            "responses": { "200": { "description": "ok" } }
+        })
+    };
+    let ok = json!({ "responses": { "200": { "description": "ok" } } });
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Shop", "version": "1.0.0" },
+        "x-include-max-depth": 2,
+        "x-table-schemas": [
+            table("customers", &["name"]),
+            table("products", &["name"]),
+            table("orders", &["status", "customer_id"]),
+            table("order_items", &["order_id", "product_id", "quantity"])
+        ],
+        "paths": {
+            "/customers": { "post": ok },
+            "/products": { "post": ok },
+            "/orders": {
+                "get": ok,
+                "post": create(json!({
+                    "items": relation("hasMany", "OrderItem", "order_id"),
+                    "customer": relation("belongsTo", "Customer", "customer_id")
+                }))
+            },
+            "/orders/{id}": { "get": ok },
+            "/order_items": {
+                "post": create(json!({
+                    "product": relation("belongsTo", "Product", "product_id")
+                }))
+            }
+        }
+    });
+    sqlite_state(temp, spec).await
+}
+
+#[tokio::test]
+#[serial]
+async fn include_selects_nested_relations() {
+    let temp = TempDir::new().unwrap();
+    let state = orders_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+
+    request(crud, "POST", "/customers", Some(json!({ "name": "Alice" })))
+        .await
+        .unwrap();
+    request(crud, "POST", "/products", Some(json!({ "name": "Laptop" })))
+        .await
+        .unwrap();
+    request(
+        crud,
+        "POST",
+        "/orders",
+        Some(json!({
+            "status": "paid",
+            "customer_id": "1",
+            "items": [{ "product_id": "1", "quantity": "2" }]
+        })),
+    )
+    .await
+    .unwrap();
+
+    // Without include, every relation one level deep
+    let order = request(crud, "GET", "/orders/1", None).await.unwrap();
+    assert_eq!(order["customer"]["name"], "Alice");
+    assert!(order["items"][0].get("product").is_none());
+
+    // Nested relations under line items, nothing else
+    let order = request(crud, "GET", "/orders/1?include=items.product", None)
+        .await
+        .unwrap();
+    assert!(order.get("customer").is_none());
+    assert_eq!(order["items"][0]["quantity"], "2");
+    assert_eq!(order["items"][0]["product"]["name"], "Laptop");
+
+    // Nested includes respect sparse relation fields
+    let order = request(
+        crud,
+        "GET",
+        "/orders/1?include=items.product,customer&fields%5Bitems%5D=quantity",
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(order["customer"]["name"], "Alice");
+    assert_eq!(
+        order["items"][0],
+        json!({ "quantity": "2", "product": { "id": 1, "name": "Laptop" } })
+    );
+
+    // An empty include loads no relations at all
+    let orders = request(crud, "GET", "/orders?include=", None)
+        .await
+        .unwrap();
+    assert_eq!(
+        orders,
+        json!([{ "id": 1, "status": "paid", "customer_id": "1" }])
+    );
+
+    for query in ["include=items.product.orders", "include=nope"] {
+        let err = request(crud, "GET", &format!("/orders?{query}"), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CRUDError::InvalidParameterError(_)));
+    }
+}
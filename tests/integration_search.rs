@@ -0,0 +1,157 @@
+//! Full-text search of lists (`q=`) against a SQLite datasource
+
+mod common;
+
+use apify::app_state::AppState;
+use apify::crud_handler::{CRUDError, CRUDHandler};
+use common::request;
+use serde_json::json;
+use tempfile::TempDir;
+
+async fn articles_state(temp: &TempDir) -> AppState {
+    let ok = json!({ "200": { "description": "ok" } });
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Articles", "version": "1.0.0" },
+        "x-pagination": "envelope",
+        "components": { "schemas": {
+            "Article": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer" },
+                    "title": { "type": "string", "x-searchable": true },
+                    "body": { "type": "string", "x-searchable": true },
+                    "author": { "type": "string" }
+                }
+            },
+            "Tag": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer" },
+                    "name": { "type": "string" }
+                }
+            }
+        } },
+        "paths": {
+            "/articles": {
+                "get": { "responses": ok },
+                "post": { "responses": ok }
+            },
+            "/articles/{id}": {
+                "patch": { "responses": ok },
+                "delete": { "responses": ok }
+            },
+            "/tags": {
+                "get": { "responses": ok }
+            }
+        }
+    });
+
+    common::sqlite_state(temp, "search.sqlite", spec).await
+}
+
+/// Titles of the articles listed at `path`, in response order
+async fn titles(crud: &CRUDHandler, path: &str) -> Vec<String> {
+    let page = request(crud, "GET", path, None).await.unwrap();
+    page["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["title"].as_str().unwrap().to_string())
+        .collect()
+}
+
+async fn create_article(crud: &CRUDHandler, title: &str, body: &str, author: &str) -> i64 {
+    let article = json!({ "title": title, "body": body, "author": author });
+    let created = request(crud, "POST", "/articles", Some(article))
+        .await
+        .unwrap();
+    created["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn search_matches_word_prefixes_and_ranks_by_relevance() {
+    let temp = TempDir::new().unwrap();
+    let state = articles_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    create_article(
+        crud,
+        "Cooking",
+        "Pasta, and a short note on rust stains",
+        "ada",
+    )
+    .await;
+    create_article(crud, "Rust", "Rust ownership and rust lifetimes", "grace").await;
+    create_article(crud, "Gardening", "Programming the sprinklers", "ada").await;
+
+    // Best matches first by default, every term must match as a word prefix
+    assert_eq!(
+        titles(crud, "/articles?q=rust").await,
+        vec!["Rust", "Cooking"]
+    );
+    assert_eq!(titles(crud, "/articles?q=RUST+own").await, vec!["Rust"]);
+    assert_eq!(titles(crud, "/articles?q=program").await, vec!["Gardening"]);
+    assert!(titles(crud, "/articles?q=rust+sprinkler").await.is_empty());
+    assert_eq!(
+        titles(crud, "/articles?q=rust&sort=relevance").await,
+        vec!["Cooking", "Rust"]
+    );
+
+    // Search combines with filters, other orderings and totals
+    let page = request(crud, "GET", "/articles?q=rust&author=ada", None)
+        .await
+        .unwrap();
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["data"][0]["title"], "Cooking");
+    assert_eq!(
+        titles(crud, "/articles?q=rust&sort=title").await,
+        vec!["Cooking", "Rust"]
+    );
+    // A query without words searches nothing
+    assert_eq!(titles(crud, "/articles?q=%20-").await.len(), 3);
+
+    for path in [
+        "/articles?sort=-relevance",
+        "/tags?q=rust",
+        "/tags?sort=relevance",
+    ] {
+        assert!(
+            matches!(
+                request(crud, "GET", path, None).await,
+                Err(CRUDError::InvalidParameterError(_))
+            ),
+            "{path} should be rejected"
+        );
+    }
+}
+
+#[tokio::test]
+async fn index_follows_writes_and_restarts() {
+    let temp = TempDir::new().unwrap();
+    let state = articles_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    let id = create_article(crud, "Draft", "Nothing yet", "ada").await;
+    let other = create_article(crud, "Notes", "Compilers", "grace").await;
+
+    request(
+        crud,
+        "PATCH",
+        &format!("/articles/{}", id),
+        Some(json!({ "body": "All about compilers" })),
+    )
+    .await
+    .unwrap();
+    assert!(titles(crud, "/articles?q=nothing").await.is_empty());
+    assert_eq!(titles(crud, "/articles?q=compilers").await.len(), 2);
+
+    request(crud, "DELETE", &format!("/articles/{}", other), None)
+        .await
+        .unwrap();
+    assert_eq!(titles(crud, "/articles?q=compilers").await, vec!["Draft"]);
+
+    // Starting again on the same database keeps the existing index
+    drop(state);
+    let state = articles_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    assert_eq!(titles(crud, "/articles?q=compil").await, vec!["Draft"]);
+}
@@ -0,0 +1,370 @@
+//! Soft-delete tables against a SQLite datasource
+
+mod common;
+
+use apify::app_state::AppState;
+use apify::crud_handler::{CRUDError, CRUDHandler};
+use apify::hyper::HeaderMap;
+use apify::modules::ConsumerIdentity;
+use apify::schema_generator::OnDelete;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+async fn orders_state(temp: &TempDir) -> AppState {
+    let has_many = |target: &str, on_delete: &str| {
+        json!({
+            "type": "array",
+            "items": { "type": "object" },
+            "x-relation": {
+                "type": "hasMany", "target": target, "foreignKey": "order_id", "onDelete": on_delete
+            }
+        })
+    };
+    let ok = json!({ "200": { "description": "ok" } });
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Orders", "version": "1.0.0" },
+        "x-table-schemas": [{
+            "tableName": "orders",
+            "softDelete": "deleted_at",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "customer", "columnType": "TEXT" },
+                { "name": "deleted_by", "columnType": "TEXT", "nullable": true, "autoField": true }
+            ]
+        }, {
+            "tableName": "lines",
+            "softDelete": "deleted_at",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "order_id", "columnType": "INTEGER" },
+                { "name": "product", "columnType": "TEXT" }
+            ]
+        }, {
+            "tableName": "notes",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "order_id", "columnType": "INTEGER" },
+                { "name": "body", "columnType": "TEXT" }
+            ]
+        }, {
+            "tableName": "invoices",
+            "softDelete": "deleted_at",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "order_id", "columnType": "INTEGER" }
+            ]
+        }, {
+            "tableName": "reviews",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "order_id", "columnType": "INTEGER", "nullable": true },
+                { "name": "stars", "columnType": "INTEGER" }
+            ]
+        }, {
+            "tableName": "carts",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "token", "columnType": "TEXT" }
+            ]
+        }, {
+            "tableName": "cart_items",
+            "softDelete": "deleted_at",
+            "columns": [
+                { "name": "id", "columnType": "INTEGER", "primaryKey": true, "autoIncrement": true },
+                { "name": "cart_token", "columnType": "TEXT" },
+                { "name": "product", "columnType": "TEXT" }
+            ]
+        }],
+        "paths": {
+            "/orders": {
+                "get": { "responses": ok },
+                "post": {
+                    "requestBody": { "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": {
+                            "customer": { "type": "string" },
+                            "lines": has_many("Line", "cascade"),
+                            "notes": has_many("Note", "cascade"),
+                            "invoices": has_many("Invoice", "restrict"),
+                            "reviews": has_many("Review", "set null")
+                        }
+                    } } } },
+                    "responses": ok
+                },
+                "delete": { "responses": ok }
+            },
+            "/orders/{id}": {
+                "get": { "responses": ok },
+                "put": { "responses": ok },
+                "delete": { "responses": ok }
+            },
+            "/orders/{id}/restore": {
+                "post": { "responses": ok }
+            },
+            "/lines": {
+                "get": { "responses": ok }
+            },
+            "/lines/{id}": {
+                "delete": { "responses": ok }
+            },
+            "/invoices/{id}": {
+                "delete": { "responses": ok }
+            },
+            "/reviews": {
+                "get": { "responses": ok }
+            },
+            "/notes": {
+                "get": { "responses": ok }
+            },
+            "/carts": {
+                "post": {
+                    "requestBody": { "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": {
+                            "token": { "type": "string" },
+                            "items": {
+                                "type": "array",
+                                "items": { "type": "object" },
+                                "x-relation": {
+                                    "type": "hasMany", "target": "CartItem",
+                                    "foreignKey": "cart_token", "localKey": "token"
+                                }
+                            }
+                        }
+                    } } } },
+                    "responses": ok
+                }
+            },
+            "/carts/{id}": {
+                "delete": { "responses": ok }
+            },
+            "/cart_items": {
+                "get": { "responses": ok }
+            }
+        }
+    });
+
+    common::sqlite_state(temp, "soft.sqlite", spec).await
+}
+
+/// Dispatch like the server does, on behalf of the consumer `auditor`
+async fn request(
+    crud: &CRUDHandler,
+    method: &str,
+    path: &str,
+    body: Option<Value>,
+) -> Result<Value, CRUDError> {
+    let mut ctx = common::context(method, path, HeaderMap::new());
+    ctx.extensions.insert(ConsumerIdentity {
+        name: "auditor".to_string(),
+    });
+    common::dispatch(crud, &ctx, body).await
+}
+
+async fn count(crud: &CRUDHandler, path: &str) -> usize {
+    request(crud, "GET", path, None)
+        .await
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .len()
+}
+
+async fn create_order(crud: &CRUDHandler, customer: &str) -> i64 {
+    let body = json!({
+        "customer": customer,
+        "lines": [{ "product": "pen" }, { "product": "ink" }],
+        "notes": [{ "body": "fragile" }]
+    });
+    let order = request(crud, "POST", "/orders", Some(body)).await.unwrap();
+    order["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn delete_hides_rows_and_restore_brings_them_back() {
+    let temp = TempDir::new().unwrap();
+    let state = orders_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    let id = create_order(crud, "Ada").await;
+    create_order(crud, "Grace").await;
+    let path = format!("/orders/{}", id);
+
+    request(crud, "DELETE", &path, None).await.unwrap();
+
+    // Gone from lists and fetches
+    assert_eq!(count(crud, "/orders").await, 1);
+    assert_eq!(count(crud, "/lines").await, 2);
+    assert!(matches!(
+        request(crud, "GET", &path, None).await,
+        Err(CRUDError::NotFoundError(_))
+    ));
+    assert!(matches!(
+        request(crud, "DELETE", &path, None).await,
+        Err(CRUDError::NotFoundError(_))
+    ));
+
+    // ...but still stored, with the deleting consumer
+    assert_eq!(count(crud, "/orders?with_deleted=true").await, 2);
+    assert_eq!(count(crud, "/lines?with_deleted=true").await, 4);
+    let order = request(crud, "GET", &format!("{}?with_deleted=true", path), None)
+        .await
+        .unwrap();
+    assert!(order["deleted_at"].is_string());
+    assert_eq!(order["deleted_by"], "auditor");
+    // Children of tables without softDelete are never removed
+    assert_eq!(count(crud, "/notes").await, 2);
+
+    let order = request(crud, "POST", &format!("{}/restore", path), None)
+        .await
+        .unwrap();
+    assert_eq!(order["customer"], "Ada");
+    assert_eq!(order["deleted_at"], Value::Null);
+    assert_eq!(order["deleted_by"], Value::Null);
+    assert_eq!(order["lines"].as_array().unwrap().len(), 2);
+    assert_eq!(count(crud, "/orders").await, 2);
+
+    // Only deleted records can be restored
+    assert!(matches!(
+        request(crud, "POST", &format!("{}/restore", path), None).await,
+        Err(CRUDError::NotFoundError(_))
+    ));
+    assert!(matches!(
+        request(crud, "GET", "/orders?with_deleted=maybe", None).await,
+        Err(CRUDError::InvalidParameterError(_))
+    ));
+}
+
+#[tokio::test]
+async fn restore_keeps_children_deleted_earlier() {
+    let temp = TempDir::new().unwrap();
+    let state = orders_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    let id = create_order(crud, "Ada").await;
+    let order = request(crud, "GET", &format!("/orders/{}", id), None)
+        .await
+        .unwrap();
+    let ink = order["lines"][1]["id"].as_i64().unwrap();
+
+    // One line is removed on its own before the whole order is deleted
+    request(crud, "DELETE", &format!("/lines/{}", ink), None)
+        .await
+        .unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+    // Bulk deletes are soft too
+    let result = request(crud, "DELETE", "/orders?customer=Ada", None)
+        .await
+        .unwrap();
+    assert_eq!(result["affected_rows"], 1);
+    assert_eq!(count(crud, "/orders").await, 0);
+    assert_eq!(count(crud, "/lines").await, 0);
+
+    // Restoring the order brings back only the lines deleted along with it
+    let order = request(crud, "POST", &format!("/orders/{}/restore", id), None)
+        .await
+        .unwrap();
+    let products: Vec<&str> = order["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| l["product"].as_str().unwrap())
+        .collect();
+    assert_eq!(products, vec!["pen"]);
+    assert_eq!(count(crud, "/lines?with_deleted=true").await, 2);
+}
+
+#[tokio::test]
+async fn replacing_children_soft_deletes_the_dropped_ones() {
+    let temp = TempDir::new().unwrap();
+    let state = orders_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    let id = create_order(crud, "Ada").await;
+    let path = format!("/orders/{}", id);
+    let order = request(crud, "GET", &path, None).await.unwrap();
+    let ink = order["lines"][1]["id"].as_i64().unwrap();
+    request(crud, "DELETE", &format!("/lines/{}", ink), None)
+        .await
+        .unwrap();
+
+    // "pen" is dropped by the replacement; the deleted "ink" line is left as it is
+    let body = json!({ "customer": "Ada", "lines": [{ "product": "paper" }] });
+    request(crud, "PUT", &path, Some(body)).await.unwrap();
+    let order = request(crud, "GET", &path, None).await.unwrap();
+    let products: Vec<&str> = order["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| l["product"].as_str().unwrap())
+        .collect();
+    assert_eq!(products, vec!["paper"]);
+
+    let lines = request(crud, "GET", "/lines?with_deleted=true", None)
+        .await
+        .unwrap();
+    let deleted: Vec<&str> = lines
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|l| l["deleted_at"].is_string())
+        .map(|l| l["product"].as_str().unwrap())
+        .collect();
+    assert_eq!(deleted, vec!["pen", "ink"]);
+}
+
+#[tokio::test]
+async fn hard_deletes_cascade_softly_into_soft_delete_tables() {
+    let temp = TempDir::new().unwrap();
+    let state = orders_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    let body = json!({
+        "token": "t-1",
+        "items": [{ "product": "pen" }, { "product": "ink" }]
+    });
+    let cart = request(crud, "POST", "/carts", Some(body)).await.unwrap();
+
+    // The cart row goes, its items are only stamped
+    request(crud, "DELETE", &format!("/carts/{}", cart["id"]), None)
+        .await
+        .unwrap();
+    assert_eq!(count(crud, "/cart_items").await, 0);
+    let items = request(crud, "GET", "/cart_items?with_deleted=true", None)
+        .await
+        .unwrap();
+    let items = items.as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(|item| item["deleted_at"].is_string()));
+
+    // The database never removes rows of soft-delete tables on its own
+    let lines = crud.api_generator.get_table_schema("lines").unwrap();
+    assert_eq!(lines.foreign_keys[0].on_delete, OnDelete::Restrict);
+}
+
+#[tokio::test]
+async fn soft_deletes_apply_restrict_and_set_null() {
+    let temp = TempDir::new().unwrap();
+    let state = orders_state(&temp).await;
+    let crud = state.crud_handler.as_ref().unwrap();
+    let body = json!({ "customer": "Ada", "invoices": [{}] });
+    let order = request(crud, "POST", "/orders", Some(body)).await.unwrap();
+    let path = format!("/orders/{}", order["id"]);
+
+    // A live invoice keeps the order from being deleted
+    assert!(matches!(
+        request(crud, "DELETE", &path, None).await,
+        Err(CRUDError::ConflictError(_))
+    ));
+    assert_eq!(count(crud, "/orders").await, 1);
+
+    // Deleted invoices no longer do; reviews are unlinked rather than stamped
+    let invoice = request(crud, "GET", &path, None).await.unwrap()["invoices"][0]["id"].clone();
+    request(crud, "DELETE", &format!("/invoices/{}", invoice), None)
+        .await
+        .unwrap();
+    let body = json!({ "customer": "Ada", "reviews": [{ "stars": 5 }] });
+    request(crud, "PUT", &path, Some(body)).await.unwrap();
+    request(crud, "DELETE", &path, None).await.unwrap();
+    let reviews = request(crud, "GET", "/reviews", None).await.unwrap();
+    assert_eq!(reviews[0]["stars"], 5);
+    assert_eq!(reviews[0]["order_id"], Value::Null);
+}
@@ -1,5 +1,5 @@
 use apify::database::{DatabaseManager, DatabaseRuntimeConfig};
-use apify::schema_generator::{ColumnDefinition, TableSchema};
+use apify::schema_generator::{ColumnDefinition, DropPolicy, TableSchema};
 use serde_json::json;
 use std::collections::HashMap;
 
@@ -26,6 +26,7 @@ async fn test_schema_migration_sqlite() {
                 auto_increment: true,
                 default_value: None,
                 auto_field: false,
+                renamed_from: None,
             },
             ColumnDefinition {
                 name: "name".to_string(),
@@ -36,10 +37,19 @@ async fn test_schema_migration_sqlite() {
                 auto_increment: false,
                 default_value: None,
                 auto_field: false,
+                renamed_from: None,
             },
         ],
         indexes: vec![],
         relations: vec![],
+        default_sort: None,
+        cursor_column: None,
+        soft_delete: None,
+        version_column: None,
+        search_columns: Vec::new(),
+        drop_columns: DropPolicy::Keep,
+        foreign_keys: vec![],
+        checks: vec![],
     };
 
     // 3. Initialize schema v1
@@ -61,6 +71,7 @@ async fn test_schema_migration_sqlite() {
         auto_increment: false,
         default_value: None,
         auto_field: false,
+        renamed_from: None,
     });
 
     // 6. Initialize schema v2 (should migrate)
@@ -90,7 +101,7 @@ async fn test_schema_migration_sqlite() {
 
 #[test]
 fn test_postgres_compatibility_check() {
-    use apify::schema_generator::{ColumnDefinition, SchemaGenerator, TableSchema};
+    use apify::schema_generator::{ColumnDefinition, DropPolicy, SchemaGenerator, TableSchema};
 
     // Case 1: Compatible (Integer -> BigInt)
     let current = TableSchema {
@@ -104,9 +115,18 @@ fn test_postgres_compatibility_check() {
             unique: false,
             default_value: None,
             auto_field: false,
+            renamed_from: None,
         }],
         indexes: vec![],
         relations: vec![],
+        default_sort: None,
+        cursor_column: None,
+        soft_delete: None,
+        version_column: None,
+        search_columns: Vec::new(),
+        drop_columns: DropPolicy::Keep,
+        foreign_keys: vec![],
+        checks: vec![],
     };
     let desired = TableSchema {
         table_name: "test".to_string(),
@@ -119,9 +139,18 @@ fn test_postgres_compatibility_check() {
             unique: false,
             default_value: None,
             auto_field: false,
+            renamed_from: None,
         }],
         indexes: vec![],
         relations: vec![],
+        default_sort: None,
+        cursor_column: None,
+        soft_delete: None,
+        version_column: None,
+        search_columns: Vec::new(),
+        drop_columns: DropPolicy::Keep,
+        foreign_keys: vec![],
+        checks: vec![],
     };
     let result = SchemaGenerator::generate_migration_sql(&current, &desired, "postgres");
     assert!(result.is_ok());
@@ -141,9 +170,18 @@ fn test_postgres_compatibility_check() {
             unique: false,
             default_value: None,
             auto_field: false,
+            renamed_from: None,
         }],
         indexes: vec![],
         relations: vec![],
+        default_sort: None,
+        cursor_column: None,
+        soft_delete: None,
+        version_column: None,
+        search_columns: Vec::new(),
+        drop_columns: DropPolicy::Keep,
+        foreign_keys: vec![],
+        checks: vec![],
     };
     let desired_int = TableSchema {
         table_name: "test".to_string(),
@@ -156,11 +194,611 @@ fn test_postgres_compatibility_check() {
             unique: false,
             default_value: None,
             auto_field: false,
+            renamed_from: None,
         }],
         indexes: vec![],
         relations: vec![],
+        default_sort: None,
+        cursor_column: None,
+        soft_delete: None,
+        version_column: None,
+        search_columns: Vec::new(),
+        drop_columns: DropPolicy::Keep,
+        foreign_keys: vec![],
+        checks: vec![],
     };
     let result = SchemaGenerator::generate_migration_sql(&current_text, &desired_int, "postgres");
     assert!(result.is_err());
     assert!(result.unwrap_err().contains("Incompatible type change"));
 }
+
+fn users_api(properties: serde_json::Value) -> apify::app_state::OpenApiStateConfig {
+    use apify::config::{OpenAPIConfig, OpenAPISettings};
+
+    let ok = json!({ "200": { "description": "ok" } });
+    let spec = json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Users", "version": "1.0.0" },
+        "components": { "schemas": {
+            "User": { "type": "object", "properties": properties }
+        } },
+        "paths": { "/users": { "get": { "responses": ok } } }
+    });
+    apify::app_state::OpenApiStateConfig {
+        config: OpenAPIConfig {
+            openapi: OpenAPISettings {
+                spec,
+                validation: None,
+            },
+        },
+        modules: None,
+        datasource: Some("main".to_string()),
+        access_log: None,
+        listeners: None,
+    }
+}
+
+#[tokio::test]
+async fn test_migration_plan_is_not_applied() {
+    use apify::config::DatabaseSettings;
+    use apify::migration::{MigrationRisk, TableAction, apply_migrations, plan_migrations};
+
+    let temp = tempfile::TempDir::new().unwrap();
+    let path = temp
+        .path()
+        .join("plan.sqlite")
+        .to_string_lossy()
+        .to_string();
+    let mut datasources = HashMap::new();
+    datasources.insert(
+        "main".to_string(),
+        DatabaseSettings {
+            driver: "sqlite".to_string(),
+            host: None,
+            port: None,
+            user: None,
+            password: None,
+            database: path.clone(),
+            ssl_mode: None,
+            max_pool_size: Some(1),
+            migrations: None,
+        },
+    );
+    let db = DatabaseManager::new(DatabaseRuntimeConfig {
+        driver: "sqlite".to_string(),
+        url: format!("sqlite:{}", path),
+        max_size: 1,
+    })
+    .await
+    .unwrap();
+
+    let v1 = vec![users_api(json!({
+        "id": { "type": "integer" },
+        "name": { "type": "string" },
+        "nickname": { "type": "string" }
+    }))];
+    let plans = plan_migrations(&datasources, &v1).await.unwrap();
+    assert_eq!(plans.len(), 1);
+    assert_eq!(plans[0].datasource, "main");
+    assert_eq!(plans[0].risk, MigrationRisk::Additive);
+    let users = plans[0].tables.iter().find(|t| t.table == "users").unwrap();
+    assert_eq!(users.action, TableAction::Create);
+    assert!(users.statements[0].starts_with("CREATE TABLE"));
+    assert!(db.get_table_schema("users").await.unwrap().is_none());
+
+    // Plans computed for another spec are not applied
+    let v0 = vec![users_api(json!({ "id": { "type": "integer" } }))];
+    let stale = plan_migrations(&datasources, &v0).await.unwrap();
+    assert!(apply_migrations(&datasources, &v1, &stale).await.is_err());
+    assert!(db.get_table_schema("users").await.unwrap().is_none());
+
+    apply_migrations(&datasources, &v1, &plans).await.unwrap();
+    assert!(db.get_table_schema("users").await.unwrap().is_some());
+    let plans = plan_migrations(&datasources, &v1).await.unwrap();
+    assert!(!plans[0].has_changes());
+    assert_eq!(plans[0].risk, MigrationRisk::None);
+
+    // A property removed from the spec keeps its column
+    let v2 = vec![users_api(json!({
+        "id": { "type": "integer" },
+        "name": { "type": "string" },
+        "email": { "type": "string" }
+    }))];
+    let plans = plan_migrations(&datasources, &v2).await.unwrap();
+    let users = plans[0].tables.iter().find(|t| t.table == "users").unwrap();
+    assert_eq!(users.action, TableAction::Alter);
+    assert_eq!(users.risk, MigrationRisk::Additive);
+    assert_eq!(
+        users.statements,
+        vec!["ALTER TABLE users ADD COLUMN email TEXT"]
+    );
+    let columns = db.get_table_schema("users").await.unwrap().unwrap().columns;
+    assert!(columns.iter().any(|c| c.name == "nickname"));
+    assert!(!columns.iter().any(|c| c.name == "email"));
+}
+
+#[tokio::test]
+async fn test_migration_plan_does_not_create_sqlite_file() {
+    use apify::config::DatabaseSettings;
+    use apify::migration::{TableAction, plan_migrations};
+
+    let temp = tempfile::TempDir::new().unwrap();
+    let path = temp.path().join("missing.sqlite");
+    let mut datasources = HashMap::new();
+    datasources.insert(
+        "main".to_string(),
+        DatabaseSettings {
+            driver: "sqlite".to_string(),
+            host: None,
+            port: None,
+            user: None,
+            password: None,
+            database: path.to_string_lossy().to_string(),
+            ssl_mode: None,
+            max_pool_size: Some(1),
+            migrations: None,
+        },
+    );
+
+    let apis = vec![users_api(json!({ "id": { "type": "integer" } }))];
+    let plans = plan_migrations(&datasources, &apis).await.unwrap();
+    let users = plans[0].tables.iter().find(|t| t.table == "users").unwrap();
+    assert_eq!(users.action, TableAction::Create);
+    assert!(!path.exists());
+
+    // Datasources with migrations off are not planned
+    datasources.get_mut("main").unwrap().migrations = Some(apify::config::MigrationMode::Off);
+    assert!(
+        plan_migrations(&datasources, &apis)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_migration_history_and_modes() {
+    use apify::config::MigrationMode;
+    use apify::migration::{HISTORY_TABLE, migrate_datasource};
+    use apify::schema_generator::SchemaGenerator;
+
+    let temp = tempfile::TempDir::new().unwrap();
+    let url = format!("sqlite:{}", temp.path().join("history.sqlite").display());
+    let connect = || {
+        DatabaseManager::new(DatabaseRuntimeConfig {
+            driver: "sqlite".to_string(),
+            url: url.clone(),
+            max_size: 2,
+        })
+    };
+    let db = connect().await.unwrap();
+    let schemas_of = |properties| {
+        SchemaGenerator::extract_schemas_from_openapi(&users_api(properties).config.openapi.spec)
+            .unwrap()
+    };
+    let v1 = schemas_of(json!({
+        "id": { "type": "integer" },
+        "name": { "type": "string" }
+    }));
+
+    // Nothing is created while migrations are off, and manual mode refuses the drift
+    migrate_datasource(&db, "main", MigrationMode::Off, v1.clone())
+        .await
+        .unwrap();
+    assert!(db.get_table_schema("users").await.unwrap().is_none());
+    let err = migrate_datasource(&db, "main", MigrationMode::Manual, v1.clone())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("pending migrations for tables users")
+    );
+    assert!(db.get_table_schema(HISTORY_TABLE).await.unwrap().is_none());
+
+    // Concurrent startups migrate once
+    let mut tasks = Vec::new();
+    for _ in 0..4 {
+        let db = connect().await.unwrap();
+        let schemas = v1.clone();
+        tasks.push(tokio::spawn(async move {
+            migrate_datasource(&db, "main", MigrationMode::Auto, schemas).await
+        }));
+    }
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+    migrate_datasource(&db, "main", MigrationMode::Manual, v1.clone())
+        .await
+        .unwrap();
+
+    let history = db
+        .select(HISTORY_TABLE, None, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["outcome"], "applied");
+    assert_eq!(history[0]["spec_hash"], apify::migration::spec_hash(&v1));
+    assert!(history[0]["error"].is_null());
+    let statements: Vec<String> =
+        serde_json::from_str(history[0]["statements"].as_str().unwrap()).unwrap();
+    assert!(statements[0].starts_with("CREATE TABLE IF NOT EXISTS users"));
+
+    // A refused migration is recorded without running anything
+    let mut retyped = v1.clone();
+    let name = retyped[0]
+        .columns
+        .iter_mut()
+        .find(|c| c.name == "name")
+        .unwrap();
+    name.column_type = "INTEGER".to_string();
+    name.nullable = false;
+    assert!(db.initialize_schema(retyped).await.is_err());
+    let history = db
+        .select(HISTORY_TABLE, None, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1]["outcome"], "refused");
+    assert!(
+        history[1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("Incompatible type change")
+    );
+}
+
+#[tokio::test]
+async fn test_migrators_of_one_file_migrate_once() {
+    use apify::migration::HISTORY_TABLE;
+    use apify::schema_generator::SchemaGenerator;
+
+    // Two spellings of the path get separate in-process locks, like two processes would
+    let temp = tempfile::TempDir::new().unwrap();
+    let urls = [
+        format!("sqlite:{}", temp.path().join("shared.sqlite").display()),
+        format!(
+            "sqlite:{}",
+            temp.path().join(".").join("shared.sqlite").display()
+        ),
+    ];
+    let schemas = SchemaGenerator::extract_schemas_from_openapi(
+        &users_api(json!({
+            "id": { "type": "integer" },
+            "name": { "type": "string" }
+        }))
+        .config
+        .openapi
+        .spec,
+    )
+    .unwrap();
+
+    let mut tasks = Vec::new();
+    for url in urls {
+        let db = DatabaseManager::new(DatabaseRuntimeConfig {
+            driver: "sqlite".to_string(),
+            url,
+            max_size: 1,
+        })
+        .await
+        .unwrap();
+        let schemas = schemas.clone();
+        tasks.push(tokio::spawn(async move {
+            db.initialize_schema(schemas).await?;
+            Ok::<_, apify::database::DatabaseError>(db)
+        }));
+    }
+    let mut dbs = Vec::new();
+    for task in tasks {
+        dbs.push(task.await.unwrap().unwrap());
+    }
+
+    let history = dbs[0]
+        .select(HISTORY_TABLE, None, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["outcome"], "applied");
+}
+
+#[tokio::test]
+async fn test_renamed_and_undeclared_columns() {
+    use apify::schema_generator::SchemaGenerator;
+
+    let db = DatabaseManager::new(DatabaseRuntimeConfig {
+        driver: "sqlite".to_string(),
+        url: "sqlite::memory:".to_string(),
+        max_size: 1,
+    })
+    .await
+    .unwrap();
+    let schemas_of = |component: serde_json::Value| {
+        SchemaGenerator::extract_schemas_from_openapi(&json!({
+            "openapi": "3.0.0",
+            "components": { "schemas": { "User": component } }
+        }))
+        .unwrap()
+    };
+
+    db.initialize_schema(schemas_of(json!({
+        "type": "object",
+        "required": ["login", "nickname"],
+        "properties": {
+            "id": { "type": "integer" },
+            "login": { "type": "string" },
+            "nickname": { "type": "string" },
+            "age": { "type": "integer" }
+        }
+    })))
+    .await
+    .unwrap();
+    let mut data = HashMap::new();
+    data.insert("login".to_string(), json!("ada"));
+    data.insert("nickname".to_string(), json!("Countess"));
+    data.insert("age".to_string(), json!(36));
+    db.insert("users", data).await.unwrap();
+
+    // login is renamed, age becomes required and nickname is left out: the table is
+    // recreated with the data copied to the renamed column and nickname kept
+    let v2 = schemas_of(json!({
+        "type": "object",
+        "required": ["username", "age"],
+        "properties": {
+            "id": { "type": "integer" },
+            "username": { "type": "string", "x-renamed-from": "login" },
+            "age": { "type": "integer" }
+        }
+    }));
+    db.initialize_schema(v2.clone()).await.unwrap();
+    let users = db.select("users", None, None, None, None).await.unwrap();
+    assert_eq!(users[0]["username"], "ada");
+    assert_eq!(users[0]["nickname"], "Countess");
+    assert_eq!(users[0]["age"], 36);
+    let columns = db.get_table_schema("users").await.unwrap().unwrap().columns;
+    assert!(!columns.iter().any(|c| c.name == "login"));
+    // The kept column no longer blocks inserts leaving it out
+    let mut data = HashMap::new();
+    data.insert("username".to_string(), json!("grace"));
+    data.insert("age".to_string(), json!(45));
+    db.insert("users", data).await.unwrap();
+    assert!(db.plan_schema(v2).await.unwrap()[0].statements.is_empty());
+
+    // A rename alone does not recreate the table
+    let current = db.get_table_schema("users").await.unwrap().unwrap();
+    let mut renamed = current.clone();
+    let age = renamed
+        .columns
+        .iter_mut()
+        .find(|c| c.name == "age")
+        .unwrap();
+    age.name = "years".to_string();
+    age.renamed_from = Some("age".to_string());
+    let plan = SchemaGenerator::generate_migration_sql(&current, &renamed, "sqlite").unwrap();
+    assert_eq!(plan, vec!["ALTER TABLE users RENAME COLUMN age TO years"]);
+
+    let mut undeclared = current.clone();
+    undeclared.columns.retain(|c| c.name != "nickname");
+    undeclared.drop_columns = DropPolicy::Fail;
+    let err = db
+        .initialize_schema(vec![undeclared.clone()])
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Column 'nickname' of table 'users' is no longer declared")
+    );
+
+    undeclared.drop_columns = DropPolicy::Drop;
+    db.initialize_schema(vec![undeclared]).await.unwrap();
+    let columns = db.get_table_schema("users").await.unwrap().unwrap().columns;
+    assert!(!columns.iter().any(|c| c.name == "nickname"));
+    let users = db
+        .select(
+            "users",
+            Some(vec!["username".to_string()]),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        users,
+        vec![json!({"username": "ada"}), json!({"username": "grace"})]
+    );
+}
+
+#[tokio::test]
+async fn test_foreign_key_constraints() {
+    use apify::schema_generator::{OnDelete, SchemaGenerator};
+
+    let db = DatabaseManager::new(DatabaseRuntimeConfig {
+        driver: "sqlite".to_string(),
+        url: "sqlite::memory:".to_string(),
+        max_size: 1,
+    })
+    .await
+    .unwrap();
+    let schemas_of = |books: serde_json::Value| {
+        SchemaGenerator::extract_schemas_from_openapi(&json!({
+            "openapi": "3.0.0",
+            "components": { "schemas": {
+                "Author": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "name": { "type": "string" },
+                        "books": books
+                    }
+                },
+                "Book": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "author_id": { "type": "integer" }
+                    }
+                }
+            } }
+        }))
+        .unwrap()
+    };
+    let book = |author_id: i64| HashMap::from([("author_id".to_string(), json!(author_id))]);
+
+    db.initialize_schema(schemas_of(json!({ "type": "array" })))
+        .await
+        .unwrap();
+    db.insert(
+        "authors",
+        HashMap::from([("name".to_string(), json!("Ada"))]),
+    )
+    .await
+    .unwrap();
+    db.insert("books", book(1)).await.unwrap();
+    db.insert("books", book(2)).await.unwrap();
+
+    // Declaring the relation adds the constraint, refused while a book has no author
+    let v2 = schemas_of(json!({
+        "type": "array",
+        "x-relation": {
+            "type": "hasMany", "target": "Book", "foreignKey": "author_id", "onDelete": "set null"
+        }
+    }));
+    let err = db.initialize_schema(v2.clone()).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Rows of table 'books' reference rows missing from 'authors'")
+    );
+    assert!(
+        db.get_table_schema("books")
+            .await
+            .unwrap()
+            .unwrap()
+            .foreign_keys
+            .is_empty()
+    );
+    // The check runs while applying, so the migration failed rather than being refused
+    let history = db
+        .select(apify::migration::HISTORY_TABLE, None, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.last().unwrap()["outcome"], "failed");
+
+    let mut orphan = HashMap::new();
+    orphan.insert("author_id".to_string(), json!(2));
+    db.delete("books", orphan).await.unwrap();
+    db.initialize_schema(v2.clone()).await.unwrap();
+    let books = db.get_table_schema("books").await.unwrap().unwrap();
+    assert_eq!(books.foreign_keys[0].references_table, "authors");
+    assert_eq!(books.foreign_keys[0].on_delete, OnDelete::SetNull);
+    assert_eq!(books.indexes[0].name, "idx_books_author_id");
+    assert!(
+        db.plan_schema(v2)
+            .await
+            .unwrap()
+            .iter()
+            .all(|plan| plan.statements.is_empty())
+    );
+
+    // The database enforces the constraint on its own, without the CRUD handler
+    assert!(db.insert("books", book(99)).await.is_err());
+    let mut author = HashMap::new();
+    author.insert("id".to_string(), json!(1));
+    db.delete("authors", author).await.unwrap();
+    let books = db
+        .select(
+            "books",
+            Some(vec!["author_id".to_string()]),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(books, vec![json!({"author_id": null})]);
+}
+
+#[tokio::test]
+async fn test_check_constraints_and_defaults() {
+    use apify::schema_generator::SchemaGenerator;
+
+    let db = DatabaseManager::new(DatabaseRuntimeConfig {
+        driver: "sqlite".to_string(),
+        url: "sqlite::memory:".to_string(),
+        max_size: 1,
+    })
+    .await
+    .unwrap();
+    let schemas_of = |statuses: serde_json::Value| {
+        SchemaGenerator::extract_schemas_from_openapi(&json!({
+            "openapi": "3.0.0",
+            "components": { "schemas": { "Ticket": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer" },
+                    "title": { "type": "string" },
+                    "status": { "type": "string", "enum": statuses, "default": "open" },
+                    "priority": { "type": "integer", "minimum": 1, "maximum": 5 }
+                }
+            } } }
+        }))
+        .unwrap()
+    };
+    let ticket = |field: &str, value: serde_json::Value| {
+        HashMap::from([
+            ("title".to_string(), json!("Printer on fire")),
+            (field.to_string(), value),
+        ])
+    };
+
+    let v1 = schemas_of(json!(["open", "closed"]));
+    db.initialize_schema(v1.clone()).await.unwrap();
+    assert!(
+        db.plan_schema(v1)
+            .await
+            .unwrap()
+            .iter()
+            .all(|plan| plan.statements.is_empty())
+    );
+
+    // Rows written past the API obey the enum and range too; the default applies
+    db.insert("tickets", ticket("priority", json!(2)))
+        .await
+        .unwrap();
+    assert!(
+        db.insert("tickets", ticket("priority", json!(9)))
+            .await
+            .is_err()
+    );
+    assert!(
+        db.insert("tickets", ticket("status", json!("archived")))
+            .await
+            .is_err()
+    );
+    let select = || {
+        db.select(
+            "tickets",
+            Some(vec!["status".to_string(), "priority".to_string()]),
+            None,
+            None,
+            None,
+        )
+    };
+    assert_eq!(
+        select().await.unwrap(),
+        vec![json!({"status": "open", "priority": 2})]
+    );
+
+    // Changing the enum replaces the constraint, keeping the rows
+    let v2 = schemas_of(json!(["open", "closed", "archived"]));
+    db.initialize_schema(v2.clone()).await.unwrap();
+    db.insert("tickets", ticket("status", json!("archived")))
+        .await
+        .unwrap();
+    assert_eq!(select().await.unwrap().len(), 2);
+    assert!(
+        db.plan_schema(v2)
+            .await
+            .unwrap()
+            .iter()
+            .all(|plan| plan.statements.is_empty())
+    );
+}